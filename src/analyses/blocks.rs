@@ -0,0 +1,753 @@
+use bitcoincore_rpc::bitcoin::{Block, OutPoint, Script, Transaction};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+};
+
+use crate::clusters::ScriptClusters;
+use crate::custom::{Aggregate, CustomExpression, CustomScope};
+use crate::pools::{get_pool, PoolSignature};
+use crate::scripts::{
+    get_input_type, get_output_type, get_spent_script, get_taproot_spend, ScriptType, TaprootSpend,
+};
+use crate::signet::SignetChallenge;
+use crate::utils::*;
+use crate::{BlockHeight, BlockSampleFullData, Result, HALVING_INTERVAL, RETARGET_INTERVAL};
+
+use super::{
+    get_rolling_tables, CoinbaseRevenueReport, CoinbaseRevenueTable, CorrelationReport,
+    CorrelationTable, CustomReport, CustomTable, EmptyBlocksReport, EmptyBlocksTable,
+    EntityClusterTable, EntityClustersReport, PoolLuckReport, PoolLuckTable, PoolShareTable,
+    PoolsReport, RollingReport, SignetProducerTable, SignetProducersReport, TaprootAdoptionReport,
+    TaprootAdoptionTable, WitnessVersionFlowReport, WitnessVersionFlowTable,
+};
+
+/// Subsidy of the blocks of the first halving era in satoshis.
+const INITIAL_SUBSIDY: u64 = 50 * 100_000_000;
+
+/// Possible statistical analysis that require the full block data. Every
+/// analysis returns its report, see the render module for its table.
+pub trait FullBlockStatistics {
+    /// Run an analysis of which signet signers produced the sampled blocks.
+    /// The signer is identified by verifying the signet solution in the
+    /// coinbase against the keys of the challenge. The mining time of a block
+    /// is only known when its parent is within the same window.
+    ///
+    /// See https://github.com/bitcoin/bips/blob/master/bip-0325.mediawiki
+    fn fetch_signet_producers(
+        self,
+        challenge: &SignetChallenge,
+        window: u64,
+    ) -> SignetProducersReport;
+
+    /// Run a correlation analysis of the size and weight of blocks against
+    /// the time taken to mine them. A positive correlation indicates larger
+    /// blocks follow longer intervals as the mempool accumulates
+    /// transactions. Only blocks with their parent in the same window are
+    /// considered.
+    fn fetch_size_interval_correlation(self, window: u64) -> CorrelationReport;
+
+    /// Get the rolling mean and median block size over the number of blocks
+    /// as a time series. Only contiguous blocks are rolled over, so the
+    /// window needs to reach the number of blocks.
+    fn fetch_rolling_sizes(self, blocks: usize) -> RollingReport;
+
+    /// Run an analysis attributing every sampled block to a mining pool by
+    /// the tags of its coinbase scriptSig or the payout addresses of its
+    /// coinbase outputs, with the first matching signature winning. The
+    /// share of a pool is estimated with the Wilson score interval of the
+    /// given z-score.
+    fn fetch_pools(self, pools: &[PoolSignature], z_score: f64) -> PoolsReport;
+
+    /// Run an analysis of the empty blocks of the sample, blocks with only a
+    /// coinbase transaction, reporting the rate of empty blocks of every
+    /// mining pool with the Wilson score interval of the given z-score. The
+    /// mining times of the empty blocks with their parent in the sample are
+    /// compared to the other blocks, as pools mine empty blocks on a new tip
+    /// before validating it.
+    fn fetch_empty_blocks(self, pools: &[PoolSignature], z_score: f64) -> EmptyBlocksReport;
+
+    /// Run an analysis of the luck of every mining pool comparing the blocks
+    /// observed against the expected share of the pool (e.g. its share of
+    /// the hashrate) where known. The confidence interval of the observed
+    /// share is the Wilson score interval of the given z-score, an expected
+    /// share outside of it indicates luck beyond chance.
+    fn fetch_pool_luck(
+        self,
+        pools: &[PoolSignature],
+        expected: &BTreeMap<String, f64>,
+        z_score: f64,
+        window: u64,
+    ) -> PoolLuckReport;
+
+    /// Run an analysis of the script types of the inputs spent against the
+    /// outputs created for every period of the given number of blocks. The
+    /// net flow of a script type is the outputs created less the inputs
+    /// spent, quantifying the migration between legacy, SegWit v0 and
+    /// taproot. Coinbase inputs and unspendable outputs are not counted.
+    ///
+    /// Note: Blocks do not include the outputs being spent so the type of
+    /// an input is inferred from its scriptSig and witness.
+    fn fetch_witness_version_flow(self, period: u64) -> WitnessVersionFlowReport;
+
+    /// Run an analysis of the adoption of taproot since its activation
+    /// height, reporting the P2TR outputs created and the taproot outputs
+    /// spent by key path or script path for every retarget epoch. Blocks
+    /// before the activation height are excluded.
+    ///
+    /// Note: The path of a spend is inferred from its witness, see
+    /// get_input_type for the inputs mistaken for taproot.
+    fn fetch_taproot_adoption(self, activation_height: u64) -> TaprootAdoptionReport;
+
+    /// Run an analysis of the miner revenue claimed by the coinbase of every
+    /// sampled block for every halving era. The output value of a coinbase
+    /// is split into the subsidy scheduled for its height and the fees as
+    /// the remainder, reporting the share of the fees of the revenue.
+    ///
+    /// Note: A coinbase may claim less than the subsidy and fees, the
+    /// unclaimed value is lost and the fees of such blocks are undercounted.
+    fn fetch_coinbase_revenue(self) -> CoinbaseRevenueReport;
+
+    /// Run a heuristic clustering of the scripts spent by the sample into
+    /// entities and report the distribution of the cluster sizes. Using
+    /// the common-input-ownership heuristic, all inputs of a transaction are
+    /// assumed to be controlled by the same entity. The script spent by an
+    /// input is known when its output was created within the sample or it
+    /// reveals a single public key, other inputs are unattributed. Likely
+    /// CoinJoins (3 or more outputs of equal value) are skipped as they
+    /// break the heuristic. At most max_scripts scripts are tracked to bound
+    /// the memory used.
+    ///
+    /// Note: This is a heuristic and only meaningful over contiguous blocks,
+    /// clusters do not identify real world entities.
+    fn fetch_entity_clusters(self, max_scripts: usize) -> EntityClustersReport;
+
+    /// Run a custom expression over every block or transaction of the
+    /// sample and report the requested aggregates of its values. Fails when
+    /// the expression cannot be evaluated.
+    fn fetch_custom(
+        self,
+        expression: &CustomExpression,
+        scope: CustomScope,
+        aggregates: &[Aggregate],
+        window: u64,
+    ) -> Result<CustomReport>;
+
+    /// Run an analysis compiled to WASM over the sample returning the report
+    /// of the plugin. See the plugins module for the host ABI.
+    #[cfg(feature = "plugins")]
+    fn fetch_plugin(self, plugin: &crate::plugins::WasmPlugin) -> Result<String>;
+}
+
+impl FullBlockStatistics for BlockSampleFullData {
+    fn fetch_signet_producers(
+        self,
+        challenge: &SignetChallenge,
+        window: u64,
+    ) -> SignetProducersReport {
+        let window = window as usize;
+        // Blocks and mining times keyed by the index of the signer
+        let mut producers: BTreeMap<Option<usize>, (usize, Vec<f64>)> = BTreeMap::new();
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            let mut prev: Option<&Block> = None;
+
+            blocks.iter().for_each(|(_, block)| {
+                let producer = producers
+                    .entry(challenge.get_signer(block))
+                    .or_insert_with(|| (0, vec![]));
+                producer.0 += 1;
+
+                if let Some(parent) = prev {
+                    let drift = (block.header.time as i64) - (parent.header.time as i64);
+                    producer.1.push(drift as f64 / 60.0);
+                }
+
+                prev = Some(block);
+            })
+        });
+
+        let total = producers.values().map(|(blocks, _)| blocks).sum::<usize>();
+        let entries = producers
+            .into_iter()
+            .map(|(signer, (blocks, deltas))| SignetProducerTable {
+                signer: match signer {
+                    Some(index) => challenge.signers()[index].to_string(),
+                    None => String::from("Unidentified"),
+                },
+                blocks,
+                share: blocks as f64 / total as f64,
+                mean_time: get_rounded_by(get_mean(&deltas), 2),
+            })
+            .collect();
+
+        SignetProducersReport {
+            entries,
+            blocks: total,
+            signers: challenge.signers().len(),
+        }
+    }
+
+    fn fetch_size_interval_correlation(self, window: u64) -> CorrelationReport {
+        let window = window as usize;
+        let mut intervals = vec![];
+        let mut sizes = vec![];
+        let mut weights = vec![];
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            blocks.windows(2).for_each(|pair| {
+                let (parent, block) = (&pair[0].1, &pair[1].1);
+                let drift = (block.header.time as i64) - (parent.header.time as i64);
+                intervals.push(drift as f64 / 60.0);
+                sizes.push(block.size() as f64);
+                weights.push(block.weight() as f64);
+            })
+        });
+
+        CorrelationReport {
+            entries: vec![
+                CorrelationTable::new("Size", &intervals, &sizes),
+                CorrelationTable::new("Weight", &intervals, &weights),
+            ],
+            pairs: intervals.len(),
+            mean_size: get_mean(&sizes),
+            mean_weight: get_mean(&weights),
+        }
+    }
+
+    fn fetch_rolling_sizes(self, blocks: usize) -> RollingReport {
+        let sizes = self
+            .0
+            .iter()
+            .map(|(height, block)| (*height, block.header.time, block.size() as f64))
+            .collect::<Vec<_>>();
+
+        RollingReport {
+            entries: get_rolling_tables(&sizes, blocks),
+            metric: String::from("Size (bytes)"),
+            blocks,
+        }
+    }
+
+    fn fetch_pool_luck(
+        self,
+        pools: &[PoolSignature],
+        expected: &BTreeMap<String, f64>,
+        z_score: f64,
+        window: u64,
+    ) -> PoolLuckReport {
+        let window = window as usize;
+        // Blocks and mining times keyed by the pool name
+        let mut producers: BTreeMap<String, (usize, Vec<f64>)> = BTreeMap::new();
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            let mut prev: Option<&Block> = None;
+
+            blocks.iter().for_each(|(_, block)| {
+                let pool = get_pool(block, pools).unwrap_or("Unknown");
+                let producer = producers
+                    .entry(pool.to_string())
+                    .or_insert_with(|| (0, vec![]));
+                producer.0 += 1;
+
+                if let Some(parent) = prev {
+                    let drift = (block.header.time as i64) - (parent.header.time as i64);
+                    producer.1.push(drift as f64 / 60.0);
+                }
+
+                prev = Some(block);
+            })
+        });
+
+        let total = producers.values().map(|(blocks, _)| blocks).sum::<usize>();
+        let mut entries = producers
+            .into_iter()
+            .map(|(pool, (blocks, deltas))| {
+                let (low, high) = get_wilson_interval(blocks, total, z_score);
+                let expected_share = expected.get(&pool).copied();
+                let share = blocks as f64 / total as f64;
+
+                PoolLuckTable {
+                    blocks,
+                    share,
+                    interval: format!("{:.2}% - {:.2}%", low * 100.0, high * 100.0),
+                    expected_share,
+                    luck: expected_share.map(|expected| share / expected),
+                    mean_time: get_rounded_by(get_mean(&deltas), 2),
+                    pool,
+                }
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|row| Reverse(row.blocks));
+
+        PoolLuckReport {
+            entries,
+            blocks: total,
+            z_score,
+            expected_pools: expected.len(),
+        }
+    }
+
+    fn fetch_pools(self, pools: &[PoolSignature], z_score: f64) -> PoolsReport {
+        // Blocks attributed by tag and by address keyed by the pool name
+        let mut attributions: BTreeMap<&str, (usize, usize, usize)> = BTreeMap::new();
+
+        self.0.iter().for_each(|(_, block)| {
+            let pool = pools.iter().find(|pool| pool.matches(block));
+            let attribution = attributions
+                .entry(pool.map_or("Unknown", |pool| pool.name()))
+                .or_default();
+            attribution.0 += 1;
+
+            if let Some(pool) = pool {
+                attribution.1 += pool.matches_tag(block) as usize;
+                attribution.2 += pool.matches_payout(block) as usize;
+            }
+        });
+
+        let total = self.0.len();
+        let mut entries = attributions
+            .into_iter()
+            .map(|(pool, (blocks, by_tag, by_address))| {
+                let (low, high) = get_wilson_interval(blocks, total, z_score);
+
+                PoolShareTable {
+                    pool: pool.to_string(),
+                    blocks,
+                    share: blocks as f64 / total as f64,
+                    interval: format!("{:.2}% - {:.2}%", low * 100.0, high * 100.0),
+                    by_tag,
+                    by_address,
+                }
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|row| Reverse(row.blocks));
+
+        PoolsReport {
+            attributed: entries
+                .iter()
+                .filter(|row| row.pool != "Unknown")
+                .map(|row| row.blocks)
+                .sum(),
+            entries,
+            blocks: total,
+            z_score,
+        }
+    }
+
+    fn fetch_empty_blocks(self, pools: &[PoolSignature], z_score: f64) -> EmptyBlocksReport {
+        // Blocks and empty blocks keyed by the pool name
+        let mut producers: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        let (mut empty_times, mut times) = (vec![], vec![]);
+        let mut prev: Option<(BlockHeight, &Block)> = None;
+
+        self.0.iter().for_each(|(height, block)| {
+            let empty = block.txdata.len() == 1;
+            let producer = producers
+                .entry(get_pool(block, pools).unwrap_or("Unknown"))
+                .or_default();
+            producer.0 += 1;
+            producer.1 += empty as usize;
+
+            if let Some((_, parent)) =
+                prev.filter(|(parent_height, _)| *parent_height + 1 == *height)
+            {
+                let drift = (block.header.time as i64) - (parent.header.time as i64);
+                match empty {
+                    true => empty_times.push(drift as f64 / 60.0),
+                    false => times.push(drift as f64 / 60.0),
+                }
+            }
+
+            prev = Some((*height, block));
+        });
+
+        let mut entries = producers
+            .into_iter()
+            .map(|(pool, (blocks, empty))| {
+                let (low, high) = get_wilson_interval(empty, blocks, z_score);
+
+                EmptyBlocksTable {
+                    pool: pool.to_string(),
+                    blocks,
+                    empty,
+                    rate: empty as f64 / blocks as f64,
+                    interval: format!("{:.2}% - {:.2}%", low * 100.0, high * 100.0),
+                }
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|row| Reverse(row.empty));
+
+        let empty = entries.iter().map(|row| row.empty).sum::<usize>();
+        let mean = |times: &[f64]| (!times.is_empty()).then(|| get_rounded_by(get_mean(times), 2));
+
+        EmptyBlocksReport {
+            entries,
+            blocks: self.0.len(),
+            empty,
+            rate: empty as f64 / self.0.len().max(1) as f64,
+            mean_empty_time: mean(&empty_times),
+            mean_time: mean(&times),
+            z_score,
+        }
+    }
+
+    fn fetch_witness_version_flow(self, period: u64) -> WitnessVersionFlowReport {
+        let period = period.max(1);
+        // Inputs and outputs by script type keyed by the period
+        let mut periods: BTreeMap<u64, BTreeMap<ScriptType, (usize, usize)>> = BTreeMap::new();
+
+        self.0.iter().for_each(|(height, block)| {
+            let flows = periods.entry(height.0 / period).or_default();
+
+            block.txdata.iter().skip(1).for_each(|tx| {
+                tx.input.iter().for_each(|input| {
+                    flows.entry(get_input_type(input)).or_default().0 += 1;
+                });
+            });
+            block.txdata.iter().for_each(|tx| {
+                tx.output
+                    .iter()
+                    .filter_map(|output| get_output_type(&output.script_pubkey))
+                    .for_each(|script_type| flows.entry(script_type).or_default().1 += 1);
+            });
+        });
+
+        let mut totals: BTreeMap<ScriptType, i64> = BTreeMap::new();
+        let mut entries = vec![];
+
+        periods.into_iter().for_each(|(index, flows)| {
+            let inputs = flows.values().map(|(inputs, _)| inputs).sum::<usize>();
+            let outputs = flows.values().map(|(_, outputs)| outputs).sum::<usize>();
+
+            flows
+                .into_iter()
+                .for_each(|(script_type, (type_inputs, type_outputs))| {
+                    let net = type_outputs as i64 - type_inputs as i64;
+                    *totals.entry(script_type).or_default() += net;
+
+                    entries.push(WitnessVersionFlowTable {
+                        period: format!("{}-{}", index * period, (index + 1) * period - 1),
+                        script_type: script_type.to_string(),
+                        inputs: type_inputs,
+                        input_share: type_inputs as f64 / inputs.max(1) as f64,
+                        outputs: type_outputs,
+                        output_share: type_outputs as f64 / outputs.max(1) as f64,
+                        net,
+                    });
+                });
+        });
+
+        WitnessVersionFlowReport {
+            entries,
+            blocks: self.0.len(),
+            net_flows: totals
+                .iter()
+                .map(|(script_type, net)| (script_type.to_string(), *net))
+                .collect(),
+        }
+    }
+
+    fn fetch_taproot_adoption(self, activation_height: u64) -> TaprootAdoptionReport {
+        #[derive(Default)]
+        struct Tally {
+            blocks: usize,
+            outputs: usize,
+            p2tr: usize,
+            inputs: usize,
+            key_path: usize,
+            script_path: usize,
+        }
+
+        let mut epochs: BTreeMap<u64, Tally> = BTreeMap::new();
+        let (activated, excluded): (Vec<_>, Vec<_>) = self
+            .0
+            .iter()
+            .partition(|(height, _)| height.0 >= activation_height);
+
+        activated.iter().for_each(|(height, block)| {
+            let tally = epochs.entry(height.0 / RETARGET_INTERVAL).or_default();
+            tally.blocks += 1;
+
+            block.txdata.iter().skip(1).for_each(|tx| {
+                tx.input.iter().for_each(|input| {
+                    tally.inputs += 1;
+                    match get_taproot_spend(input) {
+                        Some(TaprootSpend::KeyPath) => tally.key_path += 1,
+                        Some(TaprootSpend::ScriptPath) => tally.script_path += 1,
+                        None => {}
+                    }
+                });
+            });
+            block.txdata.iter().for_each(|tx| {
+                tx.output
+                    .iter()
+                    .filter_map(|output| get_output_type(&output.script_pubkey))
+                    .for_each(|script_type| {
+                        tally.outputs += 1;
+                        tally.p2tr += (script_type == ScriptType::Taproot) as usize;
+                    });
+            });
+        });
+
+        let entries = epochs
+            .iter()
+            .map(|(epoch, tally)| TaprootAdoptionTable {
+                epoch: *epoch,
+                blocks: tally.blocks,
+                outputs: tally.p2tr,
+                output_share: tally.p2tr as f64 / tally.outputs.max(1) as f64,
+                key_path: tally.key_path,
+                script_path: tally.script_path,
+                input_share: (tally.key_path + tally.script_path) as f64
+                    / tally.inputs.max(1) as f64,
+            })
+            .collect();
+        let total = |count: fn(&Tally) -> usize| epochs.values().map(count).sum::<usize>();
+        let (key_path, script_path) = (
+            total(|tally| tally.key_path),
+            total(|tally| tally.script_path),
+        );
+
+        TaprootAdoptionReport {
+            entries,
+            activation_height,
+            blocks: activated.len(),
+            excluded: excluded.len(),
+            outputs: total(|tally| tally.p2tr),
+            output_share: total(|tally| tally.p2tr) as f64
+                / total(|tally| tally.outputs).max(1) as f64,
+            key_path,
+            script_path,
+            key_path_share: key_path as f64 / (key_path + script_path).max(1) as f64,
+        }
+    }
+
+    fn fetch_coinbase_revenue(self) -> CoinbaseRevenueReport {
+        // Subsidy and fees in satoshis of the blocks keyed by the era
+        let mut eras: BTreeMap<u64, Vec<(u64, u64)>> = BTreeMap::new();
+        let mut underclaimed = 0;
+
+        self.0.iter().for_each(|(height, block)| {
+            let subsidy = get_block_subsidy(height.0);
+            let claimed = block.txdata.first().map_or(0, |coinbase| {
+                coinbase.output.iter().map(|output| output.value).sum()
+            });
+            underclaimed += (claimed < subsidy) as usize;
+            eras.entry(height.0 / HALVING_INTERVAL)
+                .or_default()
+                .push((subsidy.min(claimed), claimed.saturating_sub(subsidy)));
+        });
+
+        let btc = |sats: u64| get_rounded_by(sats as f64 / 100_000_000.0, 8);
+        let entries = eras
+            .into_iter()
+            .map(|(era, blocks)| {
+                let subsidy = blocks.iter().map(|(subsidy, _)| subsidy).sum::<u64>();
+                let fees = blocks.iter().map(|(_, fees)| fees).sum::<u64>();
+                let start = era * HALVING_INTERVAL;
+
+                CoinbaseRevenueTable {
+                    era,
+                    heights: format!("{}-{}", start, start + HALVING_INTERVAL - 1),
+                    blocks: blocks.len(),
+                    subsidy: btc(subsidy),
+                    fees: btc(fees),
+                    mean_fees: btc(fees / blocks.len() as u64),
+                    fee_share: fees as f64 / (subsidy + fees).max(1) as f64,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let total_subsidy = entries.iter().map(|row| row.subsidy).sum::<f64>();
+        let total_fees = entries.iter().map(|row| row.fees).sum::<f64>();
+
+        CoinbaseRevenueReport {
+            entries,
+            blocks: self.0.len(),
+            total_subsidy: get_rounded_by(total_subsidy, 8),
+            total_fees: get_rounded_by(total_fees, 8),
+            fee_share: total_fees / (total_subsidy + total_fees).max(f64::MIN_POSITIVE),
+            underclaimed,
+        }
+    }
+
+    fn fetch_entity_clusters(self, max_scripts: usize) -> EntityClustersReport {
+        // Size ranges of the reported clusters
+        const SIZES: [(usize, usize); 6] = [
+            (1, 1),
+            (2, 4),
+            (5, 9),
+            (10, 99),
+            (100, 999),
+            (1000, usize::MAX),
+        ];
+        let mut clusters = ScriptClusters::new(max_scripts);
+        // Scripts of the outputs created within the sample
+        let mut created: HashMap<OutPoint, Script> = HashMap::new();
+        let (mut transactions, mut coinjoins, mut unattributed) = (0, 0, 0);
+
+        self.0.iter().for_each(|(_, block)| {
+            block.txdata.iter().skip(1).for_each(|tx| {
+                transactions += 1;
+
+                if is_likely_coinjoin(tx) {
+                    coinjoins += 1;
+                    return;
+                }
+
+                let spent = tx
+                    .input
+                    .iter()
+                    .filter_map(|input| {
+                        let script = created
+                            .remove(&input.previous_output)
+                            .or_else(|| get_spent_script(input))
+                            .and_then(|script| clusters.insert(&script));
+
+                        if script.is_none() {
+                            unattributed += 1;
+                        }
+                        script
+                    })
+                    .collect::<Vec<_>>();
+
+                if let Some((first, rest)) = spent.split_first() {
+                    rest.iter().for_each(|index| clusters.union(*first, *index));
+                }
+
+                tx.output
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, output)| get_output_type(&output.script_pubkey).is_some())
+                    .for_each(|(vout, output)| {
+                        if created.len() < max_scripts {
+                            created.insert(
+                                OutPoint::new(tx.txid(), vout as u32),
+                                output.script_pubkey.clone(),
+                            );
+                        }
+                    });
+            })
+        });
+
+        let sizes = clusters.cluster_sizes();
+        let entries = SIZES
+            .iter()
+            .map(|(min, max)| {
+                let matching = sizes
+                    .iter()
+                    .filter(|size| (min..=max).contains(size))
+                    .collect::<Vec<_>>();
+                let scripts = matching.iter().copied().sum::<usize>();
+
+                EntityClusterTable {
+                    size: match *max {
+                        usize::MAX => format!("{}+", min),
+                        max if max == *min => min.to_string(),
+                        max => format!("{}-{}", min, max),
+                    },
+                    clusters: matching.len(),
+                    scripts,
+                    share: scripts as f64 / clusters.len().max(1) as f64,
+                }
+            })
+            .collect();
+
+        EntityClustersReport {
+            entries,
+            scripts: clusters.len(),
+            clusters: sizes.len(),
+            largest_cluster: sizes.iter().max().copied().unwrap_or_default(),
+            transactions,
+            coinjoins,
+            unattributed,
+            script_limit: clusters.is_full().then_some(max_scripts),
+        }
+    }
+
+    fn fetch_custom(
+        self,
+        expression: &CustomExpression,
+        scope: CustomScope,
+        aggregates: &[Aggregate],
+        window: u64,
+    ) -> Result<CustomReport> {
+        let window = window as usize;
+        let mut values = vec![];
+        let mut evaluated = 0;
+
+        for blocks in self.0.windows(window).step_by(window) {
+            let mut prev: Option<&Block> = None;
+
+            for (height, block) in blocks {
+                match scope {
+                    CustomScope::Block => {
+                        evaluated += 1;
+                        values.extend(expression.eval_block(height.0, block, prev)?);
+                    }
+                    CustomScope::Transaction => {
+                        for tx in block.txdata.iter() {
+                            evaluated += 1;
+                            values.extend(expression.eval_transaction(height.0, block, tx)?);
+                        }
+                    }
+                }
+
+                prev = Some(block);
+            }
+        }
+
+        Ok(CustomReport {
+            entries: aggregates
+                .iter()
+                .map(|aggregate| CustomTable {
+                    aggregate: aggregate.to_string(),
+                    value: get_rounded_by(aggregate.apply(&values), 4),
+                })
+                .collect(),
+            evaluated,
+            values: values.len(),
+        })
+    }
+
+    #[cfg(feature = "plugins")]
+    fn fetch_plugin(self, plugin: &crate::plugins::WasmPlugin) -> Result<String> {
+        plugin.run(&self.0)
+    }
+}
+
+/// Check whether the transaction is likely a CoinJoin, having 3 or more
+/// outputs of an equal value.
+fn is_likely_coinjoin(tx: &Transaction) -> bool {
+    let mut values: HashMap<u64, usize> = HashMap::new();
+
+    tx.output.iter().any(|output| {
+        let count = values.entry(output.value).or_default();
+        *count += 1;
+        *count >= 3
+    })
+}
+
+/// Get the subsidy in satoshis scheduled for a block at the height, halved
+/// every halving era until it is gone after 64 halvings.
+fn get_block_subsidy(height: u64) -> u64 {
+    INITIAL_SUBSIDY
+        .checked_shr((height / HALVING_INTERVAL) as u32)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_block_subsidy() {
+        assert_eq!(get_block_subsidy(0), 5_000_000_000);
+        assert_eq!(get_block_subsidy(209_999), 5_000_000_000);
+        assert_eq!(get_block_subsidy(210_000), 2_500_000_000);
+        assert_eq!(get_block_subsidy(840_000), 312_500_000);
+        assert_eq!(get_block_subsidy(6_930_000), 0);
+        assert_eq!(get_block_subsidy(64 * 210_000), 0);
+    }
+}
@@ -0,0 +1,1527 @@
+use bitcoincore_rpc::bitcoin::{blockdata::constants::genesis_block, BlockHeader, Network};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap},
+    str::FromStr,
+};
+
+use crate::records::{BlockGap, RecordBook};
+use crate::utils::*;
+use crate::{
+    BlockHeight, BlockSampleData, HALVING_INTERVAL, MTP_BLOCKS, POW_LIMIT_BITS, RETARGET_INTERVAL,
+    TARGET_SPACING,
+};
+
+use super::tables::display_relative_error;
+use super::{
+    get_rolling_tables, AutocorrelationReport, AutocorrelationTable, BackwardJumpTable,
+    BlockTimeDriftReport, BlockTimeDriftTable, ChiSquareTest, CumulativeDriftReport,
+    CumulativeDriftTable, DifficultyEpochTable, DifficultyEpochsReport, DifficultyTrendReport,
+    DifficultyTrendTable, EpochDurationTable, EpochDurationsReport, FutureTimestampTable,
+    FutureTimestampsReport, HalvingEraSpeedReport, HalvingEraTable, HashrateReport, HashrateTable,
+    IntervalPercentileTable, KolmogorovSmirnovTest, MonotonicityStreaksReport, NegativeDriftTable,
+    NegativeDriftsReport, OutOfOrderReport, OutOfOrderTable, RecordTable, RecordsReport,
+    ReorgRiskReport, ReorgRiskTable, RollingReport, StreakTable, TargetShareTable, TimeWarpReport,
+    TimeWarpTable, VersionBitsReport, VersionBitsTable, WindowTable,
+};
+
+/// Targets in minutes of the headline share of mining times within them.
+const TARGET_MINS: [i64; 3] = [10, 20, 60];
+/// Percentiles of the mining times reported by the drift analysis.
+const INTERVAL_PERCENTILES: [f64; 4] = [50.0, 90.0, 95.0, 99.0];
+/// Most bins of the chi-square test of the mining times being exponential.
+const CHI_SQUARE_BINS: usize = 10;
+/// Seconds a block may be timestamped ahead of the clock of a node.
+const MAX_FUTURE_BLOCK_TIME: i64 = 2 * 60 * 60;
+/// Seconds the median time past lags the time of a block mined on target.
+const MTP_LAG: i64 = (MTP_BLOCKS as i64 + 1) / 2 * TARGET_SPACING;
+/// Seconds without a block after which testnet allows minimum difficulty.
+const TESTNET_MIN_DIFFICULTY_SPACING: i64 = 20 * 60;
+/// Mask of the top bits of a block version signaling version bits, see BIP9.
+const VERSIONBITS_TOP_MASK: u32 = 0xe000_0000;
+/// Top bits of a block version signaling version bits.
+const VERSIONBITS_TOP_BITS: u32 = 0x2000_0000;
+/// Number of version bits available to deployments.
+pub const VERSIONBITS_NUM_BITS: u8 = 29;
+/// First version bit rolled by overt ASICBoost, see BIP320.
+const VERSIONBITS_ROLLED_BIT: u8 = 13;
+/// Maximum factor of a difficulty retarget in either direction.
+const MAX_RETARGET_FACTOR: f64 = 4.0;
+/// Relative standard error beyond which an estimate is warned of as
+/// unstable, the threshold statistical agencies suppress estimates at.
+const MAX_RELATIVE_ERROR: f64 = 0.3;
+
+pub type BlockHeap = BinaryHeap<BlockTimeDriftTable>;
+
+/// Handling of testnet minimum difficulty blocks in interval analyses.
+/// Testnet allows a block at the minimum difficulty when no block has been
+/// found for 20 minutes, which produces pathological interval data.
+#[derive(Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MinDifficultyBlocks {
+    /// Keep the blocks as is
+    Keep,
+    /// Keep the blocks but mark them within the results
+    Tag,
+    /// Exclude the blocks from the results
+    Filter,
+}
+
+/// Possible statistical analysis that can be run on sampled data. Every
+/// analysis returns its report, see the render module for its table.
+pub trait BlockStatistics {
+    /// Run a statistical analysis of two contiguous blocks having a specified
+    /// drift time between mining. The window specifies the number of
+    /// contiguous blocks occur in the sample data.
+    ///
+    /// Note: This will not be totally accurate as blocks depends on miner
+    /// provided timestamps which must be within a 2-hour window of network
+    /// adjusted time and be greater than the median of the past 11 blocks.
+    /// See https://github.com/bitcoin/bips/blob/master/bip-0113.mediawiki
+    /// See https://arxiv.org//pdf/1803.09028.pdf
+    ///
+    /// On testnet, minimum difficulty blocks are detected and handled as
+    /// specified, on any other network they are always kept.
+    ///
+    /// The share of the mining times within 10, 20 and 60 minutes is
+    /// reported along with its Wilson score interval of the given z-score.
+    /// Windows beyond 2 blocks are also reported by their span, the mean
+    /// and variance of their mining times and their longest mining time.
+    /// The p50, p90, p95 and p99 of the mining times tell e.g. the time 95%
+    /// of the blocks are mined within. The fit of the mining times to the
+    /// exponential distribution of their mean is tested by chi-square.
+    fn fetch_block_time_drift(
+        self,
+        drift_time: i64,
+        window: u64,
+        sample: bool,
+        network: Network,
+        min_difficulty: MinDifficultyBlocks,
+        z_score: f64,
+    ) -> BlockTimeDriftReport;
+
+    /// Update the record book with the longest and shortest gaps between
+    /// contiguous blocks of the sample and report the records. The number
+    /// of records kept for both the longest and shortest gaps is the count.
+    fn fetch_records(self, book: &mut RecordBook, count: usize, window: u64) -> RecordsReport;
+
+    /// Run an analysis of the runs of strictly increasing timestamps
+    /// (streaks) over contiguous blocks and of the backward jumps breaking
+    /// them for every period of the given number of blocks. The number of
+    /// longest streaks reported is the count. Streaks are cut at the end of
+    /// a window so wider windows are needed to observe long streaks.
+    ///
+    /// Note: Timestamps only need to exceed the median of the past 11
+    /// blocks, so backward jumps are valid, see fetch_block_time_drift.
+    fn fetch_monotonicity_streaks(
+        self,
+        period: u64,
+        count: usize,
+        window: u64,
+    ) -> MonotonicityStreaksReport;
+
+    /// Run an analysis of the pairs of contiguous blocks where the child is
+    /// timestamped before its parent (negative drifts), listing every pair
+    /// along with the frequency of negative drifts and their magnitude. The
+    /// frequency interval is the Wilson score interval at the z-score.
+    ///
+    /// Note: A timestamp only needs to exceed the median of the past 11
+    /// blocks, so a child may be timestamped up to about an hour before
+    /// its parent, see fetch_monotonicity_streaks.
+    fn fetch_negative_drifts(self, window: u64, z_score: f64) -> NegativeDriftsReport;
+
+    /// Run an analysis of how often the child of a pair of contiguous
+    /// blocks is timestamped before its parent, grouped by the year of the
+    /// parent timestamp, quantifying the noise of miner timestamps over
+    /// time. The frequency interval of every year is the Wilson score
+    /// interval at the z-score, see fetch_negative_drifts for the pairs.
+    fn fetch_out_of_order(self, window: u64, z_score: f64) -> OutOfOrderReport;
+
+    /// Run an analysis of the timestamp of every block against the limits
+    /// of the protocol. A timestamp must exceed the median time past of the
+    /// block, the median of the 11 blocks before it, and may not be more
+    /// than 2 hours ahead of the clock of the node receiving it. The margin
+    /// over the median time past is exact, while the lead over the clock is
+    /// estimated as the margin less the hour the median time past lags a
+    /// block mined on target. The given number of blocks closest to either
+    /// limit are listed, along with the blocks within the given seconds of
+    /// them. Blocks without all 11 blocks before them in the sample are
+    /// skipped, so every window needs MTP_BLOCKS of context.
+    ///
+    /// Note: The clock of a node is not recorded on chain, the estimated
+    /// lead is off by the deviation of the 6 latest mining times from the
+    /// hour targeted.
+    fn fetch_future_timestamps(self, count: usize, near: i64) -> FutureTimestampsReport;
+
+    /// Run an analysis of the sampled mining times grouped by difficulty
+    /// retarget epoch of 2016 blocks. The duration of an epoch is estimated
+    /// from its mean interval and implies the difficulty change retargeting
+    /// should make, which is compared to the change observed in the headers
+    /// of the next epoch when it is sampled too.
+    ///
+    /// Note: Retargeting measures the timestamps of the first and last block
+    /// of an epoch, so the implied change only approximates it. The highest
+    /// difficulty of an epoch is used to skip testnet minimum difficulty
+    /// blocks.
+    fn fetch_difficulty_epochs(self, window: u64) -> DifficultyEpochsReport;
+
+    /// Run an analysis of the signaling of soft fork deployments by the
+    /// version bits of the headers, see BIP9 and BIP8. The rate of every bit
+    /// signaled within the sample is reported for every retarget epoch with
+    /// the Wilson score interval of the given z-score, reaching the
+    /// threshold when the rate is at least the threshold, e.g. 0.95 for
+    /// BIP9 or 0.9 for taproot. Without bits given, the bits below those
+    /// rolled by overt ASICBoost (BIP320) are reported.
+    ///
+    /// Note: Deployments lock in by the signaling of every block of an
+    /// epoch, a sampled rate only estimates it.
+    fn fetch_version_bits(self, bits: &[u8], threshold: f64, z_score: f64) -> VersionBitsReport;
+
+    /// Run an analysis of the difficulty of the sampled headers converted
+    /// from their bits, by retarget epoch along with the date of its first
+    /// sampled header. The retarget of an epoch is observed when the epoch
+    /// before it is sampled too, marking the given number of largest
+    /// upward and downward retargets. The growth per epoch is fitted by a
+    /// least squares line through the log of the difficulties.
+    ///
+    /// Note: Testnet minimum difficulty blocks are ignored, the difficulty
+    /// of an epoch is the highest of its sampled headers.
+    fn fetch_difficulty_trend(self, count: usize) -> DifficultyTrendReport;
+
+    /// Get the rolling mean and median mining time over the number of blocks
+    /// as a time series. Only contiguous blocks are rolled over, so the
+    /// window needs to exceed the number of blocks.
+    fn fetch_rolling_intervals(self, blocks: usize, window: u64) -> RollingReport;
+
+    /// Estimate the network hashrate as the difficulty times 2^32 hashes
+    /// over the mean mining time, rolling over the number of blocks as a
+    /// time series. Only contiguous blocks are rolled over, so the window
+    /// needs to exceed the number of blocks.
+    ///
+    /// Note: The estimates inherit the inaccuracy of the miner provided
+    /// timestamps, see fetch_block_time_drift.
+    fn fetch_hashrate(self, blocks: usize, window: u64) -> HashrateReport;
+
+    /// Run an analysis of the autocorrelation of the mining times of
+    /// contiguous blocks at every lag up to the given lag, testing whether
+    /// the mining times are independent as assumed by the Poisson model of
+    /// block production. The pairs of mining times the lag apart are pooled
+    /// over the windows, so the window needs to exceed the lag by a block.
+    /// An autocorrelation is significant beyond the z-score over the root of
+    /// its pairs, and all lags are tested at once by a Box-Pierce test.
+    ///
+    /// Note: The hashrate changing between retargets correlates the mining
+    /// times, while miner timestamps anticorrelate them at lag 1, see
+    /// fetch_negative_drifts.
+    fn fetch_autocorrelation(self, lags: usize, window: u64, z_score: f64)
+        -> AutocorrelationReport;
+
+    /// Run an analysis of the duration of every completed retarget epoch of
+    /// 2016 blocks, from the timestamp of its first block to its last as
+    /// retargeting measures it, against the 14 days targeted. The
+    /// adjustment is the difficulty change the duration implies, limited
+    /// to a factor of 4, and observed from the first block of the next
+    /// epoch when collected. The fastest and slowest number of epochs are
+    /// marked, see BlockSample::collect_epoch_boundaries.
+    ///
+    /// Note: Retargeting measures 2015 mining times, a known off-by-one.
+    fn fetch_epoch_durations(self, count: usize) -> EpochDurationsReport;
+
+    /// Run an analysis of the timestamps at the boundaries of every
+    /// completed retarget epoch for the time warp attack, where the first
+    /// block of an epoch is timestamped far behind the last block of the
+    /// epoch before, down to its median time past, stretching the duration
+    /// retargeting measures. The first block is flagged as backdated when
+    /// its gap behind the block before it exceeds the maximum gap in
+    /// seconds, and as at MTP when timestamped a second after its median
+    /// time past, the lowest consensus allows. The influence is the change
+    /// of the difficulty retargeting set against the first block
+    /// timestamped no further than the maximum gap behind, see
+    /// BlockSample::collect_epoch_boundaries with MTP_BLOCKS of context.
+    ///
+    /// See https://github.com/bitcoin/bips/blob/master/bip-0054.md
+    fn fetch_time_warp(self, max_gap: i64) -> TimeWarpReport;
+
+    /// Run an analysis of the cumulative time of the chain against the
+    /// schedule of a block every 10 minutes since the genesis block of the
+    /// network. A block is ahead of schedule when timestamped before its
+    /// height times 10 minutes after genesis, as the growth of the hashrate
+    /// outpaces retargeting. See BlockSample::collect_checkpoints.
+    fn fetch_cumulative_drift(self, network: Network) -> CumulativeDriftReport;
+
+    /// Run an analysis of the block production speed of every completed
+    /// halving era. The duration, mean interval and blocks per day of an era
+    /// are exact from its first and last block. The variance of the mining
+    /// times within an era is estimated from the sampled intervals, testing
+    /// whether the mean interval differs from the era before it beyond
+    /// chance at the z-score with a two sided z-test over all the intervals
+    /// of both eras. See BlockSample::collect_halving_eras.
+    ///
+    /// Note: The test assumes independent mining times, while the hashrate
+    /// growing within an era correlates them.
+    fn fetch_halving_era_speed(self, z_score: f64) -> HalvingEraSpeedReport;
+
+    /// Estimate the probability of a transaction being reversed by a natural
+    /// reorganization after 1 up to the given number of confirmations. The
+    /// stale rate implies the propagation delay within which two blocks of
+    /// a Poisson process fork the chain. A fork reverses N confirmations
+    /// when the race continues for N - 1 more blocks, each found within the
+    /// delay with the probability of a gamma distribution matching the mean
+    /// and variance of the sampled mining times. The Poisson probability
+    /// assumes exponential mining times instead.
+    ///
+    /// Note: Attackers are not modeled, see the Bitcoin whitepaper for the
+    /// probability of an attacker with a share of the hashrate catching up.
+    fn fetch_reorg_risk(self, stale_rate: f64, confirmations: u64, window: u64) -> ReorgRiskReport;
+}
+
+impl FromStr for MinDifficultyBlocks {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(MinDifficultyBlocks::Keep),
+            "tag" => Ok(MinDifficultyBlocks::Tag),
+            "filter" => Ok(MinDifficultyBlocks::Filter),
+            _ => Err(format!("Unknown minimum difficulty handling: {}", s)),
+        }
+    }
+}
+
+impl BlockStatistics for BlockSampleData {
+    fn fetch_block_time_drift(
+        self,
+        drift_time: i64,
+        window: u64,
+        sample: bool,
+        network: Network,
+        min_difficulty: MinDifficultyBlocks,
+        z_score: f64,
+    ) -> BlockTimeDriftReport {
+        let window = window as usize;
+        let detect = network == Network::Testnet && min_difficulty != MinDifficultyBlocks::Keep;
+        let mut min_difficulty_blocks = 0;
+        // Store the data in a binary heap to bubble up the longest drifts
+        let mut heap: BlockHeap = BinaryHeap::new();
+        // Drifts reaching the threshold
+        let mut entries = vec![];
+        // Result formatting for probability data
+        let mut block_deltas = vec![];
+        let mut windows = vec![];
+
+        // Add the blocks to the heap by their timestamp difference
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            let mut prev = &blocks[0].1;
+            let first = block_deltas.len();
+
+            blocks.iter().skip(1).for_each(|(_, block)| {
+                let drift = (block.time as i64).checked_sub(prev.time as i64);
+                if let Some(time) = drift {
+                    let is_min_difficulty = detect && is_min_difficulty_block(prev, block);
+
+                    if is_min_difficulty {
+                        min_difficulty_blocks += 1;
+                    }
+
+                    if !is_min_difficulty || min_difficulty == MinDifficultyBlocks::Tag {
+                        block_deltas.push(time as f64 / 60.0);
+
+                        // Only the drifts reaching the threshold are reported
+                        // so there is no need to retain the rows of others
+                        if time / 60 >= drift_time / 60 {
+                            // Pushing to the heap after iterating blocks gives
+                            // us O(n log n) time
+                            heap.push(BlockTimeDriftTable::new(
+                                time / 60,
+                                prev.block_hash(),
+                                block.block_hash(),
+                                is_min_difficulty,
+                            ));
+                        }
+                    }
+                    prev = block;
+                }
+            });
+
+            let deltas = &block_deltas[first..];
+
+            if window > 2 && !deltas.is_empty() {
+                let ((start_height, start), (end_height, end)) =
+                    (&blocks[0], &blocks[blocks.len() - 1]);
+
+                windows.push(WindowTable {
+                    start_height: *start_height,
+                    end_height: *end_height,
+                    span: (end.time as i64) - (start.time as i64),
+                    mean: get_rounded_by(get_mean(deltas), 2),
+                    variance: get_rounded_by(get_variance(deltas, sample), 2),
+                    max_gap: deltas.iter().fold(f64::MIN, |max, delta| max.max(*delta)) as i64,
+                });
+            }
+        });
+
+        // Popping from the heap will give us the highest drifts descending
+        while let Some(leaf) = heap.pop() {
+            if leaf.drift >= drift_time / 60 {
+                entries.push(leaf);
+            } else {
+                break;
+            }
+        }
+
+        let hours = -(drift_time as f64 / 60.0_f64.powf(2.0));
+        // Get the mean block minting time
+        let mean = get_mean(&block_deltas);
+        let (mean_low, mean_high) = get_mean_interval(&block_deltas, z_score);
+        let std_deviation = get_standard_deviation(&block_deltas, sample);
+        let intervals = block_deltas.len();
+
+        // Warn of the mean, the occurrences and the shares within target
+        // estimated from too few mining times
+        let mean_error = std_deviation / (mean.abs() * (intervals as f64).sqrt());
+        let mut warnings = match mean_error > MAX_RELATIVE_ERROR || intervals < 2 {
+            true => vec![format!(
+                "Mean of only {} mining times; estimate unstable (relative standard error {})",
+                intervals,
+                display_relative_error(mean_error)
+            )],
+            false => vec![],
+        };
+        warnings.extend(get_estimate_warning(
+            entries.len(),
+            intervals,
+            &format!("mining times of {} minutes or more", drift_time / 60),
+        ));
+        TARGET_MINS.iter().for_each(|target| {
+            let within = block_deltas
+                .iter()
+                .filter(|delta| **delta <= *target as f64)
+                .count();
+            warnings.extend(get_estimate_warning(
+                within,
+                intervals,
+                &format!("mining times within {} minutes", target),
+            ));
+        });
+
+        let within_target = TARGET_MINS
+            .iter()
+            .map(|target| {
+                let within = block_deltas
+                    .iter()
+                    .filter(|delta| **delta <= *target as f64)
+                    .count();
+                let (low, high) = get_wilson_interval(within, block_deltas.len(), z_score);
+
+                TargetShareTable {
+                    target: *target,
+                    intervals: within,
+                    share: within as f64 / block_deltas.len().max(1) as f64,
+                    interval: format!("{:.2}% - {:.2}%", low * 100.0, high * 100.0),
+                }
+            })
+            .collect();
+        let percentiles = get_percentiles(&block_deltas, &INTERVAL_PERCENTILES)
+            .into_iter()
+            .zip(INTERVAL_PERCENTILES)
+            .map(|(minutes, percentile)| IntervalPercentileTable {
+                percentile,
+                minutes: get_rounded_by(minutes, 2),
+            })
+            .collect();
+
+        BlockTimeDriftReport {
+            entries,
+            mean,
+            mean_low,
+            mean_high,
+            // Get the standard deviation
+            std_deviation,
+            // Get the poisson probability using the sample data
+            poisson_probability: get_poisson_probability(60.0 / mean, hours),
+            within_target,
+            percentiles,
+            windows,
+            min_difficulty,
+            min_difficulty_blocks: detect.then_some(min_difficulty_blocks),
+            histogram: vec![],
+            exponential_fit: get_exponential_chi_square(&block_deltas, CHI_SQUARE_BINS).map(
+                |(statistic, degrees, p_value)| ChiSquareTest {
+                    statistic,
+                    degrees,
+                    p_value,
+                },
+            ),
+            exponential_ks: get_exponential_kolmogorov_smirnov(&block_deltas)
+                .map(|(statistic, p_value)| KolmogorovSmirnovTest { statistic, p_value }),
+            strata: vec![],
+            stratified_error: None,
+            deltas: block_deltas,
+            warnings,
+        }
+    }
+
+    fn fetch_records(self, book: &mut RecordBook, count: usize, window: u64) -> RecordsReport {
+        let window = window as usize;
+        let mut gaps = 0;
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            blocks.windows(2).for_each(|pair| {
+                let ((parent_height, parent), (child_height, child)) = (&pair[0], &pair[1]);
+                gaps += 1;
+                book.observe(
+                    BlockGap {
+                        seconds: (child.time as i64) - (parent.time as i64),
+                        parent_height: *parent_height,
+                        child_height: *child_height,
+                        time: child.time,
+                        difficulty: get_difficulty(child.bits),
+                    },
+                    count,
+                );
+            })
+        });
+
+        let longest = book
+            .longest()
+            .iter()
+            .enumerate()
+            .map(|(i, gap)| RecordTable::new(format!("Longest #{}", i + 1), gap));
+        let shortest = book
+            .shortest()
+            .iter()
+            .enumerate()
+            .map(|(i, gap)| RecordTable::new(format!("Shortest #{}", i + 1), gap));
+
+        RecordsReport {
+            entries: longest.chain(shortest).collect(),
+            gaps,
+        }
+    }
+
+    fn fetch_monotonicity_streaks(
+        self,
+        period: u64,
+        count: usize,
+        window: u64,
+    ) -> MonotonicityStreaksReport {
+        let period = period.max(1);
+        let window = window as usize;
+        // Pairs and backward jump magnitudes keyed by the period
+        let mut periods: BTreeMap<u64, (usize, Vec<i64>)> = BTreeMap::new();
+        // Length, start and end heights and what ended every streak
+        let mut streaks: Vec<(usize, BlockHeight, BlockHeight, &str)> = vec![];
+        let mut equal_timestamps = 0;
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            let mut start = blocks[0].0;
+            let mut length = 1;
+
+            blocks.windows(2).for_each(|pair| {
+                let ((parent_height, parent), (child_height, child)) = (&pair[0], &pair[1]);
+                let seconds = (child.time as i64) - (parent.time as i64);
+                let jumps = periods.entry(child_height.0 / period).or_default();
+                jumps.0 += 1;
+
+                if seconds > 0 {
+                    length += 1;
+                    return;
+                }
+
+                let ended_by = match seconds {
+                    0 => {
+                        equal_timestamps += 1;
+                        "Equal Timestamp"
+                    }
+                    _ => {
+                        jumps.1.push(-seconds);
+                        "Backward Jump"
+                    }
+                };
+                streaks.push((length, start, *parent_height, ended_by));
+                start = *child_height;
+                length = 1;
+            });
+
+            if let Some((end, _)) = blocks.last() {
+                streaks.push((length, start, *end, "Window End"));
+            }
+        });
+
+        let lengths = streaks
+            .iter()
+            .map(|(length, ..)| *length as f64)
+            .collect::<Vec<_>>();
+        // Longest first, earliest first among equal streaks
+        streaks.sort_by_key(|(length, start, ..)| (Reverse(*length), *start));
+
+        let entries = periods
+            .into_iter()
+            .map(|(index, (pairs, magnitudes))| {
+                let total = magnitudes.iter().sum::<i64>();
+
+                BackwardJumpTable {
+                    period: format!("{}-{}", index * period, (index + 1) * period - 1),
+                    pairs,
+                    jumps: magnitudes.len(),
+                    frequency: magnitudes.len() as f64 / pairs as f64,
+                    mean_magnitude: total / magnitudes.len().max(1) as i64,
+                    max_magnitude: magnitudes.iter().max().copied().unwrap_or_default(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        MonotonicityStreaksReport {
+            pairs: entries.iter().map(|row| row.pairs).sum(),
+            backward_jumps: entries.iter().map(|row| row.jumps).sum(),
+            entries,
+            streaks: streaks
+                .into_iter()
+                .take(count)
+                .enumerate()
+                .map(
+                    |(i, (blocks, start_height, end_height, ended_by))| StreakTable {
+                        streak: format!("Longest #{}", i + 1),
+                        blocks,
+                        start_height,
+                        end_height,
+                        ended_by: ended_by.to_string(),
+                    },
+                )
+                .collect(),
+            equal_timestamps,
+            mean_streak: get_mean(&lengths),
+        }
+    }
+
+    fn fetch_negative_drifts(self, window: u64, z_score: f64) -> NegativeDriftsReport {
+        let window = window as usize;
+        let mut pairs = 0;
+        let mut entries = vec![];
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            blocks.windows(2).for_each(|pair| {
+                let ((parent_height, parent), (child_height, child)) = (&pair[0], &pair[1]);
+                let seconds = (child.time as i64) - (parent.time as i64);
+                pairs += 1;
+
+                if seconds < 0 {
+                    entries.push(NegativeDriftTable {
+                        parent_height: *parent_height,
+                        child_height: *child_height,
+                        parent_date: get_date(parent.time),
+                        child_date: get_date(child.time),
+                        seconds,
+                    });
+                }
+            })
+        });
+
+        // Largest drift first, earliest first among equal drifts
+        entries.sort_by_key(|row| (row.seconds, row.child_height));
+
+        let magnitudes = entries
+            .iter()
+            .map(|row| -row.seconds as f64)
+            .collect::<Vec<_>>();
+        let (low, high) = get_wilson_interval(entries.len(), pairs, z_score);
+        let (mean, median) = match magnitudes.is_empty() {
+            true => (0.0, 0.0),
+            false => (get_mean(&magnitudes), get_median(&magnitudes)),
+        };
+
+        NegativeDriftsReport {
+            warnings: get_estimate_warning(entries.len(), pairs, "negative drifts")
+                .into_iter()
+                .collect(),
+            pairs,
+            frequency: entries.len() as f64 / pairs.max(1) as f64,
+            frequency_interval: (get_rounded_by(low, 6), get_rounded_by(high, 6)),
+            mean_magnitude: get_rounded_by(mean, 2),
+            median_magnitude: median,
+            max_magnitude: entries.first().map(|row| -row.seconds).unwrap_or_default(),
+            entries,
+        }
+    }
+
+    fn fetch_out_of_order(self, window: u64, z_score: f64) -> OutOfOrderReport {
+        let window = window as usize;
+        // Pairs and the magnitudes of the pairs out of order keyed by year
+        let mut years: BTreeMap<i32, (usize, Vec<i64>)> = BTreeMap::new();
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            blocks.windows(2).for_each(|pair| {
+                let ((_, parent), (_, child)) = (&pair[0], &pair[1]);
+                let seconds = (child.time as i64) - (parent.time as i64);
+                let year = years.entry(get_year(parent.time)).or_default();
+                year.0 += 1;
+
+                if seconds < 0 {
+                    year.1.push(-seconds);
+                }
+            })
+        });
+
+        let entries = years
+            .iter()
+            .map(|(year, (pairs, magnitudes))| {
+                let (low, high) = get_wilson_interval(magnitudes.len(), *pairs, z_score);
+                let sum = magnitudes.iter().sum::<i64>();
+
+                OutOfOrderTable {
+                    year: *year,
+                    pairs: *pairs,
+                    out_of_order: magnitudes.len(),
+                    frequency: magnitudes.len() as f64 / *pairs as f64,
+                    interval: format!("{:.2}% - {:.2}%", low * 100.0, high * 100.0),
+                    mean_magnitude: sum / magnitudes.len().max(1) as i64,
+                    max_magnitude: magnitudes.iter().copied().max().unwrap_or_default(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let pairs = entries.iter().map(|row| row.pairs).sum::<usize>();
+        let out_of_order = entries.iter().map(|row| row.out_of_order).sum::<usize>();
+        let (low, high) = get_wilson_interval(out_of_order, pairs, z_score);
+
+        OutOfOrderReport {
+            entries,
+            pairs,
+            out_of_order,
+            frequency: out_of_order as f64 / pairs.max(1) as f64,
+            frequency_interval: (get_rounded_by(low, 6), get_rounded_by(high, 6)),
+            warnings: get_estimate_warning(out_of_order, pairs, "out of order pairs")
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn fetch_future_timestamps(self, count: usize, near: i64) -> FutureTimestampsReport {
+        let times = self
+            .0
+            .iter()
+            .map(|(height, header)| (*height, header.time))
+            .collect::<HashMap<_, _>>();
+        // Height, time and median time past of every block with its past
+        let mut blocks = self
+            .0
+            .iter()
+            .filter_map(|(height, header)| {
+                let mut past = (height.0.checked_sub(MTP_BLOCKS)?..height.0)
+                    .map(|height| times.get(&BlockHeight(height)).copied())
+                    .collect::<Option<Vec<_>>>()?;
+                past.sort_unstable();
+
+                Some((*height, header.time, past[past.len() / 2]))
+            })
+            .collect::<Vec<_>>();
+        blocks.sort_by_key(|(height, time, median)| (*time as i64 - *median as i64, *height));
+
+        let row = |extreme: &str, (height, time, median): &(BlockHeight, u32, u32)| {
+            let margin = *time as i64 - *median as i64;
+
+            FutureTimestampTable {
+                extreme: extreme.to_string(),
+                height: *height,
+                date: get_date(*time),
+                median_time_past: get_date(*median),
+                margin,
+                lead: margin - MTP_LAG,
+            }
+        };
+        // Closest to the past limit and then to the future limit, without
+        // listing a block twice
+        let closest = count.min(blocks.len());
+        let furthest = count.min(blocks.len() - closest);
+        let entries = blocks
+            .iter()
+            .take(closest)
+            .map(|block| row("Closest to MTP", block))
+            .chain(
+                blocks
+                    .iter()
+                    .rev()
+                    .take(furthest)
+                    .map(|block| row("Furthest Ahead", block)),
+            )
+            .collect();
+
+        let margins = blocks
+            .iter()
+            .map(|(_, time, median)| *time as f64 - *median as f64)
+            .collect::<Vec<_>>();
+        let (mean_margin, median_margin) = match margins.is_empty() {
+            true => (0.0, 0.0),
+            false => (get_mean(&margins), get_median(&margins)),
+        };
+
+        FutureTimestampsReport {
+            entries,
+            blocks: blocks.len(),
+            mean_margin: get_rounded_by(mean_margin, 2),
+            median_margin,
+            near,
+            near_past: margins
+                .iter()
+                .filter(|margin| **margin <= near as f64)
+                .count(),
+            near_future: margins
+                .iter()
+                .filter(|margin| **margin - MTP_LAG as f64 >= (MAX_FUTURE_BLOCK_TIME - near) as f64)
+                .count(),
+        }
+    }
+
+    fn fetch_version_bits(self, bits: &[u8], threshold: f64, z_score: f64) -> VersionBitsReport {
+        let bits = match bits.is_empty() {
+            true => (0..VERSIONBITS_ROLLED_BIT).collect::<Vec<_>>(),
+            false => bits.to_vec(),
+        };
+        // Blocks and blocks signaling every bit keyed by the epoch
+        let mut epochs: BTreeMap<u64, (usize, BTreeMap<u8, usize>)> = BTreeMap::new();
+        let mut versionbits_blocks = 0;
+
+        self.0.iter().for_each(|(height, header)| {
+            let epoch = epochs.entry(height.0 / RETARGET_INTERVAL).or_default();
+            epoch.0 += 1;
+            let version = header.version as u32;
+
+            if version & VERSIONBITS_TOP_MASK == VERSIONBITS_TOP_BITS {
+                versionbits_blocks += 1;
+                bits.iter()
+                    .filter(|bit| version & (1 << **bit) != 0)
+                    .for_each(|bit| *epoch.1.entry(*bit).or_default() += 1);
+            }
+        });
+
+        // Every bit signaled within the sample is reported for every epoch
+        let signaled = epochs
+            .values()
+            .flat_map(|(_, signals)| signals.keys().copied())
+            .collect::<BTreeSet<_>>();
+        let entries = epochs
+            .iter()
+            .flat_map(|(epoch, (blocks, signals))| {
+                signaled.iter().map(move |bit| {
+                    let signaling = signals.get(bit).copied().unwrap_or_default();
+                    let (low, high) = get_wilson_interval(signaling, *blocks, z_score);
+                    let rate = signaling as f64 / *blocks as f64;
+
+                    VersionBitsTable {
+                        epoch: *epoch,
+                        heights: format!(
+                            "{}-{}",
+                            epoch * RETARGET_INTERVAL,
+                            (epoch + 1) * RETARGET_INTERVAL - 1
+                        ),
+                        bit: *bit,
+                        blocks: *blocks,
+                        signaling,
+                        rate,
+                        interval: format!("{:.2}% - {:.2}%", low * 100.0, high * 100.0),
+                        threshold_reached: rate >= threshold,
+                    }
+                })
+            })
+            .collect();
+
+        VersionBitsReport {
+            entries,
+            blocks: self.0.len(),
+            versionbits_blocks,
+            threshold,
+            z_score,
+        }
+    }
+
+    fn fetch_difficulty_trend(self, count: usize) -> DifficultyTrendReport {
+        // Time of the first sampled header and highest difficulty keyed by
+        // the epoch
+        let mut epochs: BTreeMap<u64, (u32, f64)> = BTreeMap::new();
+
+        self.0.iter().for_each(|(height, header)| {
+            let epoch = epochs
+                .entry(height.0 / RETARGET_INTERVAL)
+                .or_insert((header.time, 0.0));
+            epoch.1 = epoch.1.max(get_difficulty(header.bits));
+        });
+
+        let mut entries = epochs
+            .iter()
+            .map(|(epoch, (time, difficulty))| DifficultyTrendTable {
+                epoch: *epoch,
+                heights: format!(
+                    "{}-{}",
+                    epoch * RETARGET_INTERVAL,
+                    (epoch + 1) * RETARGET_INTERVAL - 1
+                ),
+                date: get_date(*time),
+                difficulty: get_rounded_by(*difficulty, 2),
+                change: epoch
+                    .checked_sub(1)
+                    .and_then(|previous| epochs.get(&previous))
+                    .map(|(_, previous)| get_rounded_by(difficulty / previous - 1.0, 6)),
+                extreme: String::new(),
+            })
+            .collect::<Vec<_>>();
+
+        // Mark the largest retargets in either direction, earliest first
+        // among equals
+        let mut ranked = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| Some((i, row.change?)))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        ranked
+            .iter()
+            .rev()
+            .take(count)
+            .filter(|(_, change)| *change > 0.0)
+            .for_each(|(i, _)| entries[*i].extreme = String::from("Largest Upward"));
+        ranked
+            .iter()
+            .take(count)
+            .filter(|(_, change)| *change < 0.0)
+            .for_each(|(i, _)| entries[*i].extreme = String::from("Largest Downward"));
+
+        let (xs, ys): (Vec<_>, Vec<_>) = epochs
+            .iter()
+            .filter(|(_, (_, difficulty))| *difficulty > 0.0)
+            .map(|(epoch, (_, difficulty))| (*epoch as f64, difficulty.ln()))
+            .unzip();
+        let growth = (xs.len() > 1).then(|| {
+            let (slope, ..) = get_linear_regression(&xs, &ys);
+            slope.exp() - 1.0
+        });
+
+        DifficultyTrendReport {
+            retargets: ranked.len(),
+            entries,
+            blocks: self.0.len(),
+            growth,
+        }
+    }
+
+    fn fetch_difficulty_epochs(self, window: u64) -> DifficultyEpochsReport {
+        let window = window as usize;
+        // Intervals in seconds and highest difficulty keyed by the epoch
+        let mut epochs: BTreeMap<u64, (Vec<f64>, f64)> = BTreeMap::new();
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            blocks.iter().for_each(|(height, header)| {
+                let epoch = epochs.entry(height.0 / RETARGET_INTERVAL).or_default();
+                epoch.1 = epoch.1.max(get_difficulty(header.bits));
+            });
+
+            // The mining time of a block belongs to the epoch of the child
+            blocks.windows(2).for_each(|pair| {
+                let ((_, parent), (height, child)) = (&pair[0], &pair[1]);
+                let seconds = (child.time as i64) - (parent.time as i64);
+                let epoch = epochs.entry(height.0 / RETARGET_INTERVAL).or_default();
+                epoch.0.push(seconds as f64);
+            });
+        });
+
+        let expected_duration = RETARGET_INTERVAL as i64 * TARGET_SPACING;
+        let entries = epochs
+            .iter()
+            .filter(|(_, (intervals, _))| !intervals.is_empty())
+            .map(|(epoch, (intervals, difficulty))| {
+                let mean = get_mean(intervals);
+                let estimated_duration = (mean * RETARGET_INTERVAL as f64).round() as i64;
+                let factor = expected_duration as f64 / estimated_duration.max(1) as f64;
+
+                DifficultyEpochTable {
+                    epoch: *epoch,
+                    heights: format!(
+                        "{}-{}",
+                        epoch * RETARGET_INTERVAL,
+                        (epoch + 1) * RETARGET_INTERVAL - 1
+                    ),
+                    intervals: intervals.len(),
+                    mean_interval: get_rounded_by(mean / 60.0, 2),
+                    expected_duration,
+                    estimated_duration,
+                    difficulty: get_rounded_by(*difficulty, 2),
+                    implied_change: factor.clamp(1.0 / MAX_RETARGET_FACTOR, MAX_RETARGET_FACTOR)
+                        - 1.0,
+                    observed_change: epochs
+                        .get(&(epoch + 1))
+                        .map(|(_, next)| next / difficulty - 1.0),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let errors = entries
+            .iter()
+            .filter_map(|row| Some((row.observed_change? - row.implied_change).abs()))
+            .collect::<Vec<_>>();
+
+        DifficultyEpochsReport {
+            intervals: entries.iter().map(|row| row.intervals).sum(),
+            entries,
+            tracking_error: (!errors.is_empty()).then(|| get_mean(&errors)),
+        }
+    }
+
+    fn fetch_epoch_durations(self, count: usize) -> EpochDurationsReport {
+        let headers = self
+            .0
+            .into_iter()
+            .map(|(height, header)| (height.0, header))
+            .collect::<HashMap<_, _>>();
+        let expected_duration = RETARGET_INTERVAL as i64 * TARGET_SPACING;
+        let mut epochs = headers
+            .keys()
+            .filter(|height| *height % RETARGET_INTERVAL == 0)
+            .map(|height| height / RETARGET_INTERVAL)
+            .collect::<Vec<_>>();
+        epochs.sort_unstable();
+
+        let mut entries = epochs
+            .into_iter()
+            .filter_map(|epoch| {
+                let start = epoch * RETARGET_INTERVAL;
+                let first = headers.get(&start)?;
+                let last = headers.get(&(start + RETARGET_INTERVAL - 1))?;
+                let duration = (last.time as i64) - (first.time as i64);
+                let factor = expected_duration as f64 / duration.max(1) as f64;
+                let difficulty = get_difficulty(first.bits);
+
+                Some(EpochDurationTable {
+                    epoch,
+                    heights: format!("{}-{}", start, start + RETARGET_INTERVAL - 1),
+                    start_date: get_date(first.time),
+                    duration,
+                    deviation: duration - expected_duration,
+                    adjustment: get_rounded_by(
+                        factor.clamp(1.0 / MAX_RETARGET_FACTOR, MAX_RETARGET_FACTOR) - 1.0,
+                        6,
+                    ),
+                    observed_adjustment: headers.get(&(start + RETARGET_INTERVAL)).map(|next| {
+                        get_rounded_by(get_difficulty(next.bits) / difficulty - 1.0, 6)
+                    }),
+                    extreme: String::new(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Mark the fastest and slowest epochs, earliest first among equals
+        let mut ranked = (0..entries.len()).collect::<Vec<_>>();
+        ranked.sort_by_key(|i| entries[*i].duration);
+        ranked.iter().rev().take(count).for_each(|i| {
+            entries[*i].extreme = String::from("Slowest");
+        });
+        ranked.iter().take(count).for_each(|i| {
+            entries[*i].extreme = String::from("Fastest");
+        });
+
+        let days = entries
+            .iter()
+            .map(|row| row.duration as f64 / 86400.0)
+            .collect::<Vec<_>>();
+        let faster = entries
+            .iter()
+            .filter(|row| row.duration <= expected_duration)
+            .count();
+
+        EpochDurationsReport {
+            mean_duration: get_rounded_by(get_mean(&days), 2),
+            median_duration: get_rounded_by(get_median(&days), 2),
+            std_deviation: get_standard_deviation(&days, true),
+            faster: faster as f64 / entries.len().max(1) as f64,
+            entries,
+        }
+    }
+
+    fn fetch_time_warp(self, max_gap: i64) -> TimeWarpReport {
+        let headers = self
+            .0
+            .into_iter()
+            .map(|(height, header)| (height.0, header))
+            .collect::<HashMap<_, _>>();
+        let expected_duration = RETARGET_INTERVAL as i64 * TARGET_SPACING;
+        let factor = |duration: i64| {
+            (expected_duration as f64 / duration.max(1) as f64)
+                .clamp(1.0 / MAX_RETARGET_FACTOR, MAX_RETARGET_FACTOR)
+        };
+        let mut epochs = headers
+            .keys()
+            .filter(|height| *height % RETARGET_INTERVAL == 0)
+            .map(|height| height / RETARGET_INTERVAL)
+            .collect::<Vec<_>>();
+        epochs.sort_unstable();
+
+        let entries = epochs
+            .into_iter()
+            .filter_map(|epoch| {
+                let start = epoch * RETARGET_INTERVAL;
+                let first = headers.get(&start)?.time as i64;
+                let last = headers.get(&(start + RETARGET_INTERVAL - 1))?.time as i64;
+                let previous = start
+                    .checked_sub(1)
+                    .and_then(|height| headers.get(&height))
+                    .map(|header| header.time as i64);
+                let past = start.checked_sub(MTP_BLOCKS).and_then(|low| {
+                    (low..start)
+                        .map(|height| headers.get(&height).map(|header| header.time))
+                        .collect::<Option<Vec<_>>>()
+                });
+                let margin = past.map(|mut past| {
+                    past.sort_unstable();
+                    first - past[past.len() / 2] as i64
+                });
+
+                let gap = previous.map(|previous| first - previous);
+                let backdated = gap.is_some_and(|gap| gap < -max_gap);
+                let honest = match (backdated, previous) {
+                    (true, Some(previous)) => previous - max_gap,
+                    _ => first,
+                };
+                let flag = [(backdated, "Backdated"), (margin == Some(1), "At MTP")]
+                    .iter()
+                    .filter_map(|(flagged, flag)| flagged.then_some(*flag))
+                    .collect::<Vec<_>>();
+
+                Some(TimeWarpTable {
+                    epoch,
+                    heights: format!("{}-{}", start, start + RETARGET_INTERVAL - 1),
+                    duration: last - first,
+                    gap,
+                    margin,
+                    influence: get_rounded_by(
+                        factor(last - first) / factor(last - honest) - 1.0,
+                        6,
+                    ),
+                    flag: flag.join(", "),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        TimeWarpReport {
+            max_gap,
+            flagged: entries.iter().filter(|row| !row.flag.is_empty()).count(),
+            influence: entries
+                .iter()
+                .fold(1.0, |influence, row| influence * (1.0 + row.influence))
+                - 1.0,
+            entries,
+        }
+    }
+
+    fn fetch_cumulative_drift(self, network: Network) -> CumulativeDriftReport {
+        let genesis = genesis_block(network).header.time as i64;
+        let mut blocks = self.0;
+        blocks.sort_by_key(|(height, _)| *height);
+
+        let mut previous = (BlockHeight(0), genesis);
+        let entries = blocks
+            .iter()
+            .map(|(height, header)| {
+                let scheduled = genesis + height.0 as i64 * TARGET_SPACING;
+                let ahead = scheduled - header.time as i64;
+                let (parent_height, parent_time) = previous;
+                previous = (*height, header.time as i64);
+
+                CumulativeDriftTable {
+                    height: *height,
+                    date: get_date(header.time),
+                    scheduled: get_date(scheduled.max(0) as u32),
+                    ahead: get_rounded_by(ahead as f64 / 86400.0, 2),
+                    blocks_ahead: ahead / TARGET_SPACING,
+                    mean_interval: match *height - parent_height {
+                        0 => 0.0,
+                        blocks => get_rounded_by(
+                            (header.time as i64 - parent_time) as f64 / blocks as f64 / 60.0,
+                            2,
+                        ),
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let (mean_interval, drift_rate) = match (blocks.first(), blocks.last()) {
+            (Some((first_height, first)), Some((last_height, last)))
+                if last_height > first_height =>
+            {
+                let blocks = (*last_height - *first_height) as f64;
+                let seconds = (last.time as i64 - first.time as i64) as f64;
+                let gained = blocks * TARGET_SPACING as f64 - seconds;
+                (
+                    seconds / blocks / 60.0,
+                    gained / 86400.0 / (seconds.max(1.0) / (365.25 * 86400.0)),
+                )
+            }
+            _ => (0.0, 0.0),
+        };
+
+        CumulativeDriftReport {
+            ahead: entries.last().map(|row| row.ahead).unwrap_or_default(),
+            blocks_ahead: entries
+                .last()
+                .map(|row| row.blocks_ahead)
+                .unwrap_or_default(),
+            mean_interval: get_rounded_by(mean_interval, 2),
+            drift_rate: get_rounded_by(drift_rate, 2),
+            entries,
+        }
+    }
+
+    fn fetch_halving_era_speed(self, z_score: f64) -> HalvingEraSpeedReport {
+        let headers = self.0.into_iter().collect::<BTreeMap<_, _>>();
+        let blocks = (HALVING_INTERVAL - 1) as f64;
+        let mut previous: Option<(f64, f64)> = None;
+        let mut intervals = 0;
+
+        let entries = headers
+            .keys()
+            .filter(|height| height.0 % HALVING_INTERVAL == 0)
+            .filter_map(|start| {
+                let end = *start + HALVING_INTERVAL - 1;
+                let first = headers.get(start)?;
+                let last = headers.get(&end)?;
+                let duration = (last.time as i64 - first.time as i64) as f64;
+                let mean = duration / blocks / 60.0;
+
+                // Sampled mining times of the blocks after the first of the era
+                let sampled = headers
+                    .range(*start..end)
+                    .filter_map(|(height, parent)| {
+                        let child = headers.get(&(*height + 1))?;
+                        Some((child.time as i64 - parent.time as i64) as f64 / 60.0)
+                    })
+                    .collect::<Vec<_>>();
+                let std_deviation = match sampled.len() {
+                    0 | 1 => None,
+                    _ => Some(get_standard_deviation(&sampled, true)),
+                };
+                intervals += sampled.len();
+
+                // Standard error of the mean over all the intervals of the era
+                let error = std_deviation.map(|std_deviation| std_deviation.powi(2) / blocks);
+                let test = previous.zip(error).map(|((prior, prior_error), error)| {
+                    let z = (mean - prior) / (prior_error + error).sqrt().max(f64::EPSILON);
+                    (mean / prior - 1.0, 2.0 * (1.0 - get_normal_cdf(z.abs())), z)
+                });
+                previous = error.map(|error| (mean, error));
+
+                Some(HalvingEraTable {
+                    era: start.0 / HALVING_INTERVAL,
+                    heights: format!("{}-{}", start, end),
+                    start_date: get_date(first.time),
+                    end_date: get_date(last.time),
+                    duration: get_rounded_by(duration / 86400.0, 2),
+                    mean_interval: get_rounded_by(mean, 4),
+                    blocks_per_day: get_rounded_by(1440.0 / mean.max(f64::EPSILON), 2),
+                    intervals: sampled.len(),
+                    std_deviation: get_rounded_by(std_deviation.unwrap_or_default(), 2),
+                    change: test.map(|(change, _, _)| get_rounded_by(change, 6)),
+                    p_value: test.map(|(_, p_value, _)| get_rounded_by(p_value, 4)),
+                    significant: test.is_some_and(|(_, _, z)| z.abs() >= z_score),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mean_interval = match entries.len() {
+            0 => 0.0,
+            eras => entries.iter().map(|row| row.mean_interval).sum::<f64>() / eras as f64,
+        };
+
+        HalvingEraSpeedReport {
+            intervals,
+            mean_interval: get_rounded_by(mean_interval, 4),
+            significant: entries.iter().filter(|row| row.significant).count(),
+            entries,
+        }
+    }
+
+    fn fetch_rolling_intervals(self, blocks: usize, window: u64) -> RollingReport {
+        let window = window as usize;
+        let mut entries = vec![];
+
+        self.0.windows(window).step_by(window).for_each(|headers| {
+            // Mining times keyed by the height and time of the child
+            let intervals = headers
+                .windows(2)
+                .map(|pair| {
+                    let ((_, parent), (height, child)) = (&pair[0], &pair[1]);
+                    let seconds = (child.time as i64) - (parent.time as i64);
+                    (*height, child.time, seconds as f64 / 60.0)
+                })
+                .collect::<Vec<_>>();
+
+            entries.extend(get_rolling_tables(&intervals, blocks));
+        });
+
+        RollingReport {
+            entries,
+            metric: String::from("Mining Time (m)"),
+            blocks,
+        }
+    }
+
+    fn fetch_hashrate(self, blocks: usize, window: u64) -> HashrateReport {
+        let window = window as usize;
+        // Mining time in seconds and difficulty of every block in the sample
+        let mut intervals = vec![];
+        let mut entries = vec![];
+
+        self.0.windows(window).step_by(window).for_each(|headers| {
+            let pairs = headers
+                .windows(2)
+                .map(|pair| {
+                    let ((_, parent), (height, child)) = (&pair[0], &pair[1]);
+                    let seconds = (child.time as i64) - (parent.time as i64);
+                    (
+                        *height,
+                        child.time,
+                        seconds as f64,
+                        get_difficulty(child.bits),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            entries.extend(pairs.windows(blocks.max(1)).map(|run| {
+                let (height, time, ..) = run[run.len() - 1];
+                let seconds = run.iter().map(|(_, _, seconds, _)| seconds).sum::<f64>();
+                let difficulty = run.iter().map(|(.., difficulty)| difficulty).sum::<f64>();
+                let mean_interval = seconds / run.len() as f64;
+
+                HashrateTable {
+                    height,
+                    date: get_date(time),
+                    mean_interval: get_rounded_by(mean_interval / 60.0, 2),
+                    difficulty: get_rounded_by(difficulty / run.len() as f64, 2),
+                    hashrate: get_hashrate(difficulty / run.len() as f64, mean_interval),
+                }
+            }));
+            intervals.extend(pairs);
+        });
+
+        let mins = intervals
+            .iter()
+            .map(|(_, _, seconds, _)| seconds / 60.0)
+            .collect::<Vec<_>>();
+        let difficulties = intervals
+            .iter()
+            .map(|(.., difficulty)| *difficulty)
+            .collect::<Vec<_>>();
+
+        HashrateReport {
+            entries,
+            blocks,
+            mean: get_mean(&mins),
+            std_deviation: get_standard_deviation(&mins, true),
+            hashrate: get_hashrate(get_mean(&difficulties), get_mean(&mins) * 60.0),
+        }
+    }
+
+    fn fetch_autocorrelation(
+        self,
+        lags: usize,
+        window: u64,
+        z_score: f64,
+    ) -> AutocorrelationReport {
+        let window = window as usize;
+        // Mining times in minutes of the contiguous blocks of every window
+        let runs = self
+            .0
+            .windows(window)
+            .step_by(window)
+            .map(|headers| {
+                headers
+                    .windows(2)
+                    .map(|pair| ((pair[1].1.time as i64) - (pair[0].1.time as i64)) as f64 / 60.0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let autocorrelations = get_autocorrelations(&runs, lags)
+            .into_iter()
+            .zip(1..)
+            .filter(|((pairs, _), _)| *pairs > 0)
+            .collect::<Vec<_>>();
+
+        // Autocorrelations of independent mining times are about normal with
+        // a variance of one over their pairs
+        let statistic = autocorrelations
+            .iter()
+            .map(|((pairs, autocorrelation), _)| *pairs as f64 * autocorrelation.powi(2))
+            .sum::<f64>();
+        let degrees = autocorrelations.len();
+        let entries = autocorrelations
+            .into_iter()
+            .map(|((pairs, autocorrelation), lag)| {
+                let bound = z_score / (pairs as f64).sqrt();
+
+                AutocorrelationTable {
+                    lag,
+                    pairs,
+                    autocorrelation: get_rounded_by(autocorrelation, 4),
+                    bound: get_rounded_by(bound, 4),
+                    significant: autocorrelation.abs() > bound,
+                }
+            })
+            .collect();
+        let intervals = runs.concat();
+
+        AutocorrelationReport {
+            entries,
+            intervals: intervals.len(),
+            mean: get_mean(&intervals),
+            box_pierce: ChiSquareTest {
+                statistic,
+                degrees,
+                p_value: get_chi_square_p_value(statistic, degrees),
+            },
+        }
+    }
+
+    fn fetch_reorg_risk(self, stale_rate: f64, confirmations: u64, window: u64) -> ReorgRiskReport {
+        let window = window as usize;
+        let stale_rate = stale_rate.clamp(0.0, 1.0 - f64::EPSILON);
+        let mut intervals = vec![];
+
+        self.0.windows(window).step_by(window).for_each(|blocks| {
+            blocks.windows(2).for_each(|pair| {
+                let ((_, parent), (_, child)) = (&pair[0], &pair[1]);
+                intervals.push(((child.time as i64) - (parent.time as i64)) as f64);
+            })
+        });
+
+        // Exponential mining times at the target spacing without a variance
+        let (mean, dispersion) = match intervals.len() {
+            0 | 1 => (TARGET_SPACING as f64, 1.0),
+            _ => {
+                let mean = get_mean(&intervals).max(1.0);
+                (mean, get_variance(&intervals, true) / mean.powf(2.0))
+            }
+        };
+        // Delay within which the next block forks the chain with a
+        // probability of the stale rate for exponential mining times
+        let delay = -mean * (1.0 - stale_rate).ln();
+        let race_probability = match dispersion > 0.0 {
+            true => get_gamma_cdf(delay, 1.0 / dispersion, mean * dispersion),
+            false => (delay >= mean) as u8 as f64,
+        };
+
+        let entries = (1..=confirmations)
+            .map(|confirmations| {
+                let probability = stale_rate * race_probability.powi(confirmations as i32 - 1);
+
+                ReorgRiskTable {
+                    confirmations,
+                    probability,
+                    poisson_probability: stale_rate.powi(confirmations as i32),
+                    blocks: (probability > 0.0).then(|| (1.0 / probability).round()),
+                }
+            })
+            .collect();
+
+        ReorgRiskReport {
+            entries,
+            intervals: intervals.len(),
+            mean_interval: get_rounded_by(mean, 2),
+            dispersion: get_rounded_by(dispersion, 4),
+            stale_rate,
+            delay: get_rounded_by(delay, 2),
+            race_probability: get_rounded_by(race_probability, 6),
+        }
+    }
+}
+
+/// Get the warning of an estimate from the occurrences out of n when its
+/// relative standard error exceeds MAX_RELATIVE_ERROR, rather than
+/// presenting a noisy estimate silently.
+fn get_estimate_warning(occurrences: usize, n: usize, what: &str) -> Option<String> {
+    let error = get_relative_standard_error(occurrences, n);
+
+    (error > MAX_RELATIVE_ERROR).then(|| {
+        format!(
+            "Only {} {} out of {}; estimate unstable (relative standard error {})",
+            occurrences,
+            what,
+            n,
+            display_relative_error(error)
+        )
+    })
+}
+
+/// Check whether the child is a testnet minimum difficulty block, mined at
+/// the proof of work limit after 20 minutes without a block.
+pub fn is_min_difficulty_block(parent: &BlockHeader, child: &BlockHeader) -> bool {
+    child.bits == POW_LIMIT_BITS
+        && (child.time as i64) - (parent.time as i64) > TESTNET_MIN_DIFFICULTY_SPACING
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_min_difficulty_block() {
+        let parent = BlockHeader {
+            version: 1,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time: 1_600_000_000,
+            bits: 0x1a0fffff,
+            nonce: 0,
+        };
+        let mut child = BlockHeader {
+            time: parent.time + 1201,
+            bits: POW_LIMIT_BITS,
+            ..parent
+        };
+        assert!(is_min_difficulty_block(&parent, &child));
+
+        child.time = parent.time + 600;
+        assert!(!is_min_difficulty_block(&parent, &child));
+
+        child.time = parent.time + 1201;
+        child.bits = parent.bits;
+        assert!(!is_min_difficulty_block(&parent, &child));
+    }
+
+    #[test]
+    fn test_fetch_block_time_drift() {
+        let header = |time| BlockHeader {
+            version: 1,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time,
+            bits: 0x1d00ffff,
+            nonce: 0,
+        };
+        let data = BlockSampleData::new(vec![
+            (BlockHeight(10), header(0)),
+            (BlockHeight(11), header(600)),
+            (BlockHeight(12), header(3_000)),
+        ]);
+        let report = data.fetch_block_time_drift(
+            1200,
+            3,
+            false,
+            Network::Bitcoin,
+            MinDifficultyBlocks::Tag,
+            1.96,
+        );
+
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].drift, 40);
+        assert_eq!(report.mean, 25.0);
+        // The standard error of the mean is 15 minutes
+        assert_eq!(get_rounded_by(report.mean_low, 2), -4.4);
+        assert_eq!(get_rounded_by(report.mean_high, 2), 54.4);
+        assert_eq!(report.std_deviation, 15.0);
+        assert_eq!(report.min_difficulty_blocks, None);
+        assert_eq!(report.within_target[0].intervals, 1);
+        assert_eq!(report.within_target[2].share, 1.0);
+        assert_eq!(report.windows.len(), 1);
+        assert_eq!(report.windows[0].span, 3_000);
+        assert_eq!(report.windows[0].variance, 225.0);
+        assert_eq!(report.windows[0].max_gap, 40);
+    }
+}
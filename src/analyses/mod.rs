@@ -0,0 +1,186 @@
+//! Statistical analyses of the sampled data along with their reports and
+//! the tables of the reports. Analyses are grouped by the data they run on:
+//! block headers, full blocks and block statistics.
+
+use bitcoincore_rpc::json::{GetChainTipsResultStatus, GetChainTipsResultTip};
+use std::cmp::Reverse;
+
+use crate::utils::*;
+use crate::BlockHeight;
+
+mod blocks;
+mod headers;
+mod reports;
+mod stats;
+mod tables;
+
+pub use blocks::*;
+pub use headers::*;
+pub use reports::*;
+pub use stats::*;
+pub use tables::*;
+
+pub(crate) use tables::display_hashrate;
+
+/// Get the number of stale blocks known to the node from its chain tips
+/// along with their share of the blocks found, the stale rate. Stale blocks
+/// are those of the valid branches off the active chain.
+///
+/// Note: A node only learns of the stale blocks relayed to it while online,
+/// so the stale rate of a recently synced node is underestimated.
+pub fn get_stale_rate(tips: &[GetChainTipsResultTip]) -> (usize, f64) {
+    let mut height = 0;
+    let mut stale = 0;
+
+    tips.iter().for_each(|tip| match tip.status {
+        GetChainTipsResultStatus::Active => height = tip.height,
+        GetChainTipsResultStatus::Invalid => {}
+        _ => stale += tip.branch_length,
+    });
+
+    (stale, stale as f64 / (height as f64 + 1.0 + stale as f64))
+}
+
+/// Get the report of the chain tips known to the node off the active chain,
+/// with the stale rate over the whole chain and over the recent blocks of
+/// the active chain, see get_stale_rate. The stale blocks of a branch are
+/// recent when they are above the recent blocks of the active chain.
+pub fn get_chain_tips_report(tips: &[GetChainTipsResultTip], recent: u64) -> ChainTipsReport {
+    let (stale, stale_rate) = get_stale_rate(tips);
+    let height = tips
+        .iter()
+        .find(|tip| tip.status == GetChainTipsResultStatus::Active)
+        .map_or(0, |tip| tip.height);
+    let start = (height + 1).saturating_sub(recent);
+
+    let mut entries = tips
+        .iter()
+        .filter(|tip| tip.status != GetChainTipsResultStatus::Active)
+        .map(|tip| ChainTipTable {
+            height: tip.height,
+            hash: tip.hash.to_string(),
+            fork_height: tip.height.saturating_sub(tip.branch_length as u64),
+            branch_length: tip.branch_length,
+            status: String::from(match tip.status {
+                GetChainTipsResultStatus::Invalid => "invalid",
+                GetChainTipsResultStatus::HeadersOnly => "headers-only",
+                GetChainTipsResultStatus::ValidHeaders => "valid-headers",
+                GetChainTipsResultStatus::ValidFork => "valid-fork",
+                GetChainTipsResultStatus::Active => "active",
+            }),
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|tip| Reverse(tip.height));
+
+    let recent_stale = tips
+        .iter()
+        .filter(|tip| {
+            !matches!(
+                tip.status,
+                GetChainTipsResultStatus::Active | GetChainTipsResultStatus::Invalid
+            )
+        })
+        .map(|tip| {
+            let fork_height = tip.height.saturating_sub(tip.branch_length as u64);
+            tip.height
+                .saturating_sub(fork_height.max(start.saturating_sub(1))) as usize
+        })
+        .sum::<usize>();
+    let recent = height + 1 - start;
+
+    ChainTipsReport {
+        entries,
+        height,
+        stale,
+        stale_rate,
+        recent,
+        recent_stale,
+        recent_stale_rate: recent_stale as f64 / (recent as f64 + recent_stale as f64).max(1.0),
+    }
+}
+
+/// Get the rolling statistics of the values keyed by the height and time of
+/// their block over every run of contiguous heights.
+fn get_rolling_tables(values: &[(BlockHeight, u32, f64)], blocks: usize) -> Vec<RollingTable> {
+    let mut tables = vec![];
+    let mut start = 0;
+
+    for end in 1..=values.len() {
+        if end < values.len() && values[end].0 == values[end - 1].0 + 1 {
+            continue;
+        }
+
+        let run = &values[start..end];
+        let nums = run.iter().map(|(.., value)| *value).collect::<Vec<_>>();
+        let rolling = get_rolling(&nums, blocks);
+        let ends = run.iter().skip(blocks.max(1) - 1);
+
+        tables.extend(
+            ends.zip(rolling)
+                .map(|((height, time, _), (mean, median))| RollingTable {
+                    height: *height,
+                    date: get_date(*time),
+                    mean: get_rounded_by(mean, 2),
+                    median: get_rounded_by(median, 2),
+                }),
+        );
+        start = end;
+    }
+
+    tables
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_stale_rate() {
+        let tip = |height, branch_length, status| GetChainTipsResultTip {
+            height,
+            hash: Default::default(),
+            branch_length,
+            status,
+        };
+        let tips = [
+            tip(799, 0, GetChainTipsResultStatus::Active),
+            tip(650, 1, GetChainTipsResultStatus::ValidFork),
+            tip(700, 2, GetChainTipsResultStatus::ValidHeaders),
+            tip(750, 5, GetChainTipsResultStatus::Invalid),
+        ];
+
+        assert_eq!(get_stale_rate(&tips), (3, 3.0 / 803.0));
+        assert_eq!(get_stale_rate(&tips[..1]), (0, 0.0));
+    }
+
+    #[test]
+    fn test_get_chain_tips_report() {
+        let tip = |height, branch_length, status| GetChainTipsResultTip {
+            height,
+            hash: Default::default(),
+            branch_length,
+            status,
+        };
+        let tips = [
+            tip(799, 0, GetChainTipsResultStatus::Active),
+            tip(650, 1, GetChainTipsResultStatus::ValidFork),
+            tip(701, 3, GetChainTipsResultStatus::ValidHeaders),
+            tip(750, 5, GetChainTipsResultStatus::Invalid),
+        ];
+        let report = get_chain_tips_report(&tips, 100);
+
+        assert_eq!(report.height, 799);
+        assert_eq!(report.stale, 4);
+        let heights = report.entries.iter().map(|tip| tip.height);
+        assert_eq!(heights.collect::<Vec<_>>(), [750, 701, 650]);
+        assert_eq!(report.entries[1].fork_height, 698);
+        // Only the blocks of the branch above height 699 are recent
+        assert_eq!(report.recent, 100);
+        assert_eq!(report.recent_stale, 2);
+        assert_eq!(report.recent_stale_rate, 2.0 / 102.0);
+
+        let report = get_chain_tips_report(&tips, 1000);
+        assert_eq!(report.recent, 800);
+        assert_eq!(report.recent_stale, 4);
+    }
+}
@@ -0,0 +1,671 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::exclusions::HeightRange;
+use crate::utils::*;
+
+use super::{
+    AutocorrelationTable, BackwardJumpTable, BlockSizeTable, BlockSizeTrendTable,
+    BlockTimeDriftTable, ChainTipTable, CoinbaseRevenueTable, ConfirmationLatencyTable,
+    ConfirmationTimeTable, ConflictTable, ConsolidationTable, CorrelationTable,
+    CumulativeDriftTable, CustomTable, DifficultyEpochTable, DifficultyTrendTable,
+    EmptyBlocksTable, EntityClusterTable, EpochDurationTable, ExampleTable, FeeElasticityTable,
+    FeeStatsTable, FutureTimestampTable, HalvingEraTable, HashrateTable, IndexRepairTable,
+    IntervalPercentileTable, MinDifficultyBlocks, NegativeDriftTable, OutOfOrderTable,
+    PoolLuckTable, PoolShareTable, RecordTable, ReorgRiskTable, RevenueTable, RollingTable,
+    RunTable, SignetProducerTable, StratumTable, StreakTable, TaprootAdoptionTable,
+    TargetShareTable, TimeWarpTable, TransactionArrivalTable, TransactionPercentileTable,
+    VersionBitsTable, WindowTable, WitnessVersionFlowTable,
+};
+
+/// Buckets of a histogram of mining times, the last collecting longer times.
+const HISTOGRAM_BUCKETS: i64 = 24;
+
+/// Result of the block time drift analysis. The mean and standard deviation
+/// are of the mining times in minutes.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct BlockTimeDriftReport {
+    /// Mining times reaching the drift time, longest first
+    pub entries: Vec<BlockTimeDriftTable>,
+    pub mean: f64,
+    /// Confidence interval of the mean at the z-score of the sample
+    pub mean_low: f64,
+    pub mean_high: f64,
+    pub std_deviation: f64,
+    /// Expected hours between mining times reaching the drift time
+    pub poisson_probability: f64,
+    /// Share of the mining times within 10, 20 and 60 minutes
+    pub within_target: Vec<TargetShareTable>,
+    /// Mining times in minutes at the 50th, 90th, 95th and 99th percentile
+    pub percentiles: Vec<IntervalPercentileTable>,
+    /// Mining times within every window, only for windows beyond 2 blocks
+    pub windows: Vec<WindowTable>,
+    /// Handling of the minimum difficulty blocks
+    pub min_difficulty: MinDifficultyBlocks,
+    /// Number of minimum difficulty blocks, only detected on testnet
+    pub min_difficulty_blocks: Option<usize>,
+    /// Distribution of the mining times, only when requested
+    pub histogram: Vec<HistogramBucket>,
+    /// Chi-square test of the mining times being exponential at the mean
+    /// mining time, none with fewer than 15 mining times
+    pub exponential_fit: Option<ChiSquareTest>,
+    /// Kolmogorov-Smirnov test of the mining times being exponential at the
+    /// mean mining time, none without mining times
+    pub exponential_ks: Option<KolmogorovSmirnovTest>,
+    /// Mining times of every stratum, only for stratified samples
+    pub strata: Vec<StratumTable>,
+    /// Standard error of the mean mining time of a stratified sample, from
+    /// the variance within every stratum
+    pub stratified_error: Option<f64>,
+    /// Mining times in minutes the histogram is drawn from
+    #[serde(skip)]
+    pub(crate) deltas: Vec<f64>,
+    /// Estimates of the report too unstable to rely on
+    pub warnings: Vec<String>,
+}
+
+/// Mining times of a bucket of the histogram of mining times in minutes.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct HistogramBucket {
+    pub start: f64,
+    /// Open ended for the last bucket collecting longer mining times
+    pub end: Option<f64>,
+    pub count: usize,
+    /// Count expected of exponential mining times at the mean mining time
+    pub expected: f64,
+}
+
+/// Chi-square goodness of fit test of observations to a distribution.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ChiSquareTest {
+    pub statistic: f64,
+    pub degrees: usize,
+    /// Probability of a fit at least as poor by chance, a low p-value
+    /// rejecting the distribution
+    pub p_value: f64,
+}
+
+/// Kolmogorov-Smirnov test of observations to a distribution.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct KolmogorovSmirnovTest {
+    /// Largest distance between the empirical distribution of the
+    /// observations and the tested one
+    pub statistic: f64,
+    /// Probability of a distance at least as large by chance, a low p-value
+    /// rejecting the distribution
+    pub p_value: f64,
+}
+
+/// Result of the records analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct RecordsReport {
+    /// Longest gaps of the record book followed by the shortest
+    pub entries: Vec<RecordTable>,
+    /// Gaps between contiguous blocks scanned in the sample
+    pub gaps: usize,
+}
+
+/// Result of the negative drifts analysis. The magnitudes are in seconds.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct NegativeDriftsReport {
+    /// Children timestamped before their parent, largest drift first
+    pub entries: Vec<NegativeDriftTable>,
+    /// Pairs of contiguous blocks scanned in the sample
+    pub pairs: usize,
+    /// Share of the pairs with a negative drift
+    pub frequency: f64,
+    /// Wilson score interval of the frequency at the z-score
+    pub frequency_interval: (f64, f64),
+    pub mean_magnitude: f64,
+    pub median_magnitude: f64,
+    pub max_magnitude: i64,
+    /// Estimates of the report too unstable to rely on
+    pub warnings: Vec<String>,
+}
+
+/// Result of the out of order timestamps analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct OutOfOrderReport {
+    /// Years of the sample, earliest first
+    pub entries: Vec<OutOfOrderTable>,
+    /// Pairs of contiguous blocks scanned in the sample
+    pub pairs: usize,
+    pub out_of_order: usize,
+    /// Share of the pairs out of order
+    pub frequency: f64,
+    /// Wilson score interval of the frequency at the z-score
+    pub frequency_interval: (f64, f64),
+    /// Estimates of the report too unstable to rely on
+    pub warnings: Vec<String>,
+}
+
+/// Result of the future timestamps analysis. The margins are in seconds.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct FutureTimestampsReport {
+    /// Blocks closest to either limit, closest to the median time past first
+    pub entries: Vec<FutureTimestampTable>,
+    /// Blocks with all 11 blocks before them in the sample
+    pub blocks: usize,
+    pub mean_margin: f64,
+    pub median_margin: f64,
+    /// Seconds from either limit a block is near it
+    pub near: i64,
+    /// Blocks within the seconds of the median time past
+    pub near_past: usize,
+    /// Blocks estimated within the seconds of the 2 hour future limit
+    pub near_future: usize,
+}
+
+/// Result of the reorg risk analysis. The interval and delay are in seconds.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ReorgRiskReport {
+    /// Reversal probabilities by number of confirmations
+    pub entries: Vec<ReorgRiskTable>,
+    /// Mining times of contiguous blocks in the sample
+    pub intervals: usize,
+    pub mean_interval: f64,
+    /// Variance of the mining times over their squared mean, 1 for a
+    /// Poisson process
+    pub dispersion: f64,
+    /// Share of the blocks found that became stale
+    pub stale_rate: f64,
+    /// Propagation delay implied by the stale rate
+    pub delay: f64,
+    /// Probability of the next block being found within the delay, which
+    /// extends a fork race by another block
+    pub race_probability: f64,
+}
+
+/// Result of the chain tips analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ChainTipsReport {
+    /// Tips off the active chain, highest first
+    pub entries: Vec<ChainTipTable>,
+    /// Height of the active tip
+    pub height: u64,
+    /// Blocks of the valid branches off the active chain
+    pub stale: usize,
+    /// Share of the blocks found that became stale
+    pub stale_rate: f64,
+    /// Latest blocks of the active chain the recent stale rate is over
+    pub recent: u64,
+    pub recent_stale: usize,
+    pub recent_stale_rate: f64,
+}
+
+/// Previous runs recorded in the run history.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct HistoryReport {
+    /// Latest runs, most recent first
+    pub entries: Vec<RunTable>,
+}
+
+/// Result of verifying the header cache, see repair_header_cache.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct IndexVerifyReport {
+    /// Damaged and missing ranges, lowest first
+    pub entries: Vec<IndexRepairTable>,
+    /// Headers cached once repaired
+    pub headers: u64,
+    /// Headers fetched again from the node
+    pub fetched: usize,
+    /// Damaged headers left after the repair
+    pub remaining: usize,
+}
+
+/// Gallery of example studies.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ExamplesReport {
+    /// Examples with their default arguments
+    pub entries: Vec<ExampleTable>,
+}
+
+/// Result of the monotonicity streaks analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct MonotonicityStreaksReport {
+    /// Backward jumps of every period
+    pub entries: Vec<BackwardJumpTable>,
+    /// Longest streaks of strictly increasing timestamps
+    pub streaks: Vec<StreakTable>,
+    pub pairs: usize,
+    pub backward_jumps: usize,
+    /// Pairs of blocks with the same timestamp
+    pub equal_timestamps: usize,
+    /// Mean streak length in blocks
+    pub mean_streak: f64,
+}
+
+/// Result of the difficulty trend analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct DifficultyTrendReport {
+    /// Sampled epochs, earliest first
+    pub entries: Vec<DifficultyTrendTable>,
+    pub blocks: usize,
+    /// Retargets observed between sampled consecutive epochs
+    pub retargets: usize,
+    /// Compound growth of the difficulty per epoch fitted over the sampled
+    /// epochs, none with less than two epochs
+    pub growth: Option<f64>,
+}
+
+/// Result of the version bits analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct VersionBitsReport {
+    /// Signaling of every bit by epoch, earliest first
+    pub entries: Vec<VersionBitsTable>,
+    pub blocks: usize,
+    /// Blocks with a version signaling version bits
+    pub versionbits_blocks: usize,
+    pub threshold: f64,
+    pub z_score: f64,
+}
+
+/// Result of the difficulty epochs analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct DifficultyEpochsReport {
+    /// Sampled epochs, earliest first
+    pub entries: Vec<DifficultyEpochTable>,
+    pub intervals: usize,
+    /// Mean absolute difference between the implied and observed difficulty
+    /// changes, none without an epoch followed by a sampled epoch
+    pub tracking_error: Option<f64>,
+}
+
+/// Result of the epoch durations analysis. The durations are in days.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct EpochDurationsReport {
+    /// Completed epochs, earliest first
+    pub entries: Vec<EpochDurationTable>,
+    pub mean_duration: f64,
+    pub median_duration: f64,
+    pub std_deviation: f64,
+    /// Share of the epochs completed within the 14 days targeted
+    pub faster: f64,
+}
+
+/// Result of the time warp analysis. The gaps are in seconds.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TimeWarpReport {
+    /// Completed epochs, earliest first
+    pub entries: Vec<TimeWarpTable>,
+    /// Gap behind the last block of the previous epoch allowed to the first
+    /// block of an epoch
+    pub max_gap: i64,
+    /// Epochs with a flagged first block
+    pub flagged: usize,
+    /// Compound influence of the flagged timestamps on the difficulty over
+    /// all epochs
+    pub influence: f64,
+}
+
+/// Result of the cumulative drift analysis. The drift is in days, positive
+/// when the chain runs ahead of schedule.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CumulativeDriftReport {
+    /// Checkpoints of the range, lowest height first
+    pub entries: Vec<CumulativeDriftTable>,
+    /// Drift of the latest block
+    pub ahead: f64,
+    pub blocks_ahead: i64,
+    /// Mean minutes between blocks over the range
+    pub mean_interval: f64,
+    /// Days gained on the schedule per year over the range
+    pub drift_rate: f64,
+}
+
+/// Result of the halving era speed analysis. The intervals are in minutes.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct HalvingEraSpeedReport {
+    /// Completed eras, earliest first
+    pub entries: Vec<HalvingEraTable>,
+    /// Sampled intervals over all eras
+    pub intervals: usize,
+    /// Mean minutes between blocks over all eras
+    pub mean_interval: f64,
+    /// Number of eras differing significantly from the era before them
+    pub significant: usize,
+}
+
+/// Rolling statistics of a metric over the sample as a time series.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct RollingReport {
+    /// Rolling statistics ending at every block, in sample order
+    pub entries: Vec<RollingTable>,
+    /// Metric and its unit
+    pub metric: String,
+    /// Number of contiguous blocks rolled over
+    pub blocks: usize,
+}
+
+/// Result of the hashrate analysis. The mean and standard deviation are of
+/// the mining times in minutes, the hashrate in hashes per second.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct HashrateReport {
+    /// Hashrate estimated over every run of blocks, in sample order
+    pub entries: Vec<HashrateTable>,
+    /// Number of contiguous blocks of every estimate
+    pub blocks: usize,
+    pub mean: f64,
+    pub std_deviation: f64,
+    /// Hashrate estimated over the whole sample
+    pub hashrate: f64,
+}
+
+/// Result of the autocorrelation analysis. The mean is of the mining times
+/// in minutes.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct AutocorrelationReport {
+    /// Autocorrelation of the mining times at every lag with pairs
+    pub entries: Vec<AutocorrelationTable>,
+    /// Mining times of contiguous blocks in the sample
+    pub intervals: usize,
+    pub mean: f64,
+    /// Box-Pierce test of the mining times being independent at all lags, a
+    /// low p-value rejecting independent mining times
+    pub box_pierce: ChiSquareTest,
+}
+
+/// Result of the signet producers analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct SignetProducersReport {
+    pub entries: Vec<SignetProducerTable>,
+    pub blocks: usize,
+    /// Number of signers of the challenge
+    pub signers: usize,
+}
+
+/// Result of the size interval correlation analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CorrelationReport {
+    pub entries: Vec<CorrelationTable>,
+    /// Number of blocks with their parent in the same window
+    pub pairs: usize,
+    pub mean_size: f64,
+    pub mean_weight: f64,
+}
+
+/// Result of the pool attribution analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PoolsReport {
+    /// Pools by blocks attributed descending, unattributed blocks as
+    /// "Unknown"
+    pub entries: Vec<PoolShareTable>,
+    pub blocks: usize,
+    /// Blocks attributed to a pool
+    pub attributed: usize,
+    pub z_score: f64,
+}
+
+/// Result of the empty blocks analysis. The mining times are of the blocks
+/// with their parent in the sample.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct EmptyBlocksReport {
+    /// Pools by empty blocks descending, unattributed blocks as "Unknown"
+    pub entries: Vec<EmptyBlocksTable>,
+    pub blocks: usize,
+    /// Blocks with only a coinbase transaction
+    pub empty: usize,
+    pub rate: f64,
+    /// Mean mining time of the empty blocks in minutes
+    pub mean_empty_time: Option<f64>,
+    /// Mean mining time of the other blocks in minutes
+    pub mean_time: Option<f64>,
+    pub z_score: f64,
+}
+
+/// Result of the pool luck analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PoolLuckReport {
+    /// Pools by blocks produced descending
+    pub entries: Vec<PoolLuckTable>,
+    pub blocks: usize,
+    pub z_score: f64,
+    /// Number of pools with a known expected share
+    pub expected_pools: usize,
+}
+
+/// Result of the witness version flow analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct WitnessVersionFlowReport {
+    pub entries: Vec<WitnessVersionFlowTable>,
+    pub blocks: usize,
+    /// Net flow of every script type over all periods
+    pub net_flows: Vec<(String, i64)>,
+}
+
+/// Result of the coinbase revenue analysis. The totals are in BTC.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CoinbaseRevenueReport {
+    pub entries: Vec<CoinbaseRevenueTable>,
+    pub blocks: usize,
+    pub total_subsidy: f64,
+    pub total_fees: f64,
+    pub fee_share: f64,
+    /// Blocks with a coinbase claiming less than the subsidy
+    pub underclaimed: usize,
+}
+
+/// Result of the taproot adoption analysis. The shares are of the
+/// spendable outputs created and of the inputs spent outside of coinbases.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TaprootAdoptionReport {
+    pub entries: Vec<TaprootAdoptionTable>,
+    pub activation_height: u64,
+    pub blocks: usize,
+    /// Sampled blocks before the activation height, not analyzed
+    pub excluded: usize,
+    pub outputs: usize,
+    pub output_share: f64,
+    pub key_path: usize,
+    pub script_path: usize,
+    /// Share of the taproot spends by key path
+    pub key_path_share: f64,
+}
+
+/// Result of the entity clusters analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct EntityClustersReport {
+    pub entries: Vec<EntityClusterTable>,
+    pub scripts: usize,
+    pub clusters: usize,
+    pub largest_cluster: usize,
+    pub transactions: usize,
+    pub coinjoins: usize,
+    pub unattributed: usize,
+    /// The script limit when it was reached
+    pub script_limit: Option<usize>,
+}
+
+/// Result of the custom expression analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CustomReport {
+    pub entries: Vec<CustomTable>,
+    /// Number of blocks or transactions the expression was evaluated on
+    pub evaluated: usize,
+    /// Number of values produced by the expression
+    pub values: usize,
+}
+
+/// Result of the fee elasticity analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct FeeElasticityReport {
+    pub entries: Vec<FeeElasticityTable>,
+    /// Number of blocks with fee paying transactions
+    pub blocks: usize,
+    pub elasticity: f64,
+    pub p_value: f64,
+    pub r_squared: f64,
+}
+
+/// Result of the fee statistics analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct FeeStatsReport {
+    pub entries: Vec<FeeStatsTable>,
+    pub blocks: usize,
+    /// Fees paid by all sampled blocks in BTC
+    pub total_fees: f64,
+    pub z_score: f64,
+}
+
+/// Result of the revenue analysis. The totals are in BTC, the USD totals
+/// only cover the blocks with a known price.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct RevenueReport {
+    pub entries: Vec<RevenueTable>,
+    pub blocks: usize,
+    pub total_subsidy: f64,
+    pub total_fees: f64,
+    pub total_usd: Option<f64>,
+    /// Blocks without a known price
+    pub unpriced: usize,
+}
+
+/// Result of the block size analysis. The fullness is the ratio of the
+/// weight of a block to the maximum block weight.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct BlockSizeReport {
+    /// Mean size, weight and fullness of every period
+    pub entries: Vec<BlockSizeTrendTable>,
+    /// Distribution of the size, weight and fullness of the blocks
+    pub metrics: Vec<BlockSizeTable>,
+    pub blocks: usize,
+    /// Blocks within 1% of the maximum block weight
+    pub full_blocks: usize,
+    /// Least squares change of the fullness over a period, none with less
+    /// than two blocks
+    pub trend: Option<f64>,
+    pub z_score: f64,
+}
+
+/// Result of the transactions per block analysis. The correlation pairs
+/// the transactions of every block with the time taken to mine it.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TransactionsReport {
+    /// Distribution of the transactions per block
+    pub entries: Vec<TransactionPercentileTable>,
+    pub correlation: Vec<CorrelationTable>,
+    pub blocks: usize,
+    /// Number of blocks with their parent in the sample
+    pub pairs: usize,
+    pub mean: f64,
+    pub mean_low: f64,
+    pub mean_high: f64,
+    pub std_deviation: f64,
+    pub z_score: f64,
+}
+
+/// Result of the consolidations analysis.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ConsolidationsReport {
+    /// Detected waves of consolidation
+    pub entries: Vec<ConsolidationTable>,
+    pub periods: usize,
+    pub mean_ratio: f64,
+    pub std_deviation: f64,
+    pub median_fee_rate: f64,
+}
+
+/// Statistics of a transaction watch.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct WatchReport {
+    pub arrivals: TransactionArrivalTable,
+    pub blocks: u64,
+    /// Confirmed transactions never seen in the mempool
+    pub unseen: u64,
+    pub confirmation_times: Vec<ConfirmationTimeTable>,
+    /// Mean time to confirmation in seconds
+    pub mean_confirmation_time: f64,
+    pub conflicts: Vec<ConflictTable>,
+}
+
+/// Wait of the confirmed transactions of a transaction watch.
+#[derive(Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ConfirmationLatencyReport {
+    /// Fee rate bands with confirmed transactions
+    pub entries: Vec<ConfirmationLatencyTable>,
+    pub blocks: u64,
+    pub confirmed: usize,
+    /// Confirmed transactions without a known fee rate
+    pub unknown: usize,
+}
+
+impl BlockTimeDriftReport {
+    /// Add the mining times of every stratum of a stratified sample, see
+    /// BlockSampleData::get_stratum_intervals, along with the standard
+    /// error of the mean over the strata. The strata are sampled in
+    /// proportion to their size, so the mean of the sample is their pooled
+    /// mean.
+    pub fn with_strata(mut self, strata: Vec<(HeightRange, Vec<f64>)>) -> Self {
+        let total = strata
+            .iter()
+            .map(|(_, intervals)| intervals.len())
+            .sum::<usize>() as f64;
+        // Variance of the mean of every stratum weighted by its share
+        let variance = strata
+            .iter()
+            .filter(|(_, intervals)| intervals.len() > 1)
+            .map(|(_, intervals)| {
+                let share = intervals.len() as f64 / total;
+                share.powi(2) * get_variance(intervals, true) / intervals.len() as f64
+            })
+            .sum::<f64>();
+
+        self.strata = strata
+            .into_iter()
+            .map(|(stratum, intervals)| StratumTable {
+                stratum: stratum.to_string(),
+                intervals: intervals.len(),
+                mean: get_rounded_by(get_mean(&intervals), 2),
+                std_deviation: get_standard_deviation(&intervals, intervals.len() > 1),
+            })
+            .collect();
+        self.stratified_error = Some(variance.sqrt());
+        self
+    }
+
+    /// Add the histogram of the mining times with buckets of the width in
+    /// minutes, at most HISTOGRAM_BUCKETS of them from the shortest mining
+    /// time, so the distribution can be compared to exponential mining
+    /// times by eye.
+    pub fn with_histogram(mut self, width: usize) -> Self {
+        let width = width as f64;
+        let buckets = self
+            .deltas
+            .iter()
+            .map(|delta| (delta / width).floor() as i64)
+            .collect::<Vec<_>>();
+        let (first, max) = match (buckets.iter().min(), buckets.iter().max()) {
+            (Some(first), Some(max)) => (*first, *max),
+            _ => return self,
+        };
+        let last = max.min(first + HISTOGRAM_BUCKETS - 1);
+        let mut counts = vec![0; (last - first + 1) as usize];
+
+        buckets
+            .iter()
+            .for_each(|bucket| counts[((*bucket).min(last) - first) as usize] += 1);
+
+        // Share of exponential mining times beyond the minutes
+        let beyond = |minutes: f64| (-minutes.max(0.0) / self.mean).exp();
+        let total = self.deltas.len() as f64;
+
+        self.histogram = counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| {
+                let start = (first + i as i64) as f64 * width;
+                let end = (first + i as i64 != last || last == max).then_some(start + width);
+                let share = beyond(start) - end.map(beyond).unwrap_or_default();
+
+                HistogramBucket {
+                    start,
+                    end,
+                    count,
+                    expected: get_rounded_by(share * total, 2),
+                }
+            })
+            .collect();
+
+        self
+    }
+}
@@ -0,0 +1,466 @@
+use bitcoincore_rpc::json::GetBlockStatsResult;
+use std::collections::BTreeMap;
+
+use crate::prices::PriceFeed;
+use crate::utils::*;
+use crate::BlockStatsData;
+
+use super::{
+    get_rolling_tables, BlockSizeReport, BlockSizeTable, BlockSizeTrendTable, ConsolidationTable,
+    ConsolidationsReport, CorrelationTable, FeeElasticityReport, FeeElasticityTable,
+    FeeStatsReport, FeeStatsTable, RevenueReport, RevenueTable, RollingReport,
+    TransactionPercentileTable, TransactionsReport,
+};
+
+/// Percentiles of the transactions per block reported as the distribution.
+const TRANSACTION_PERCENTILES: [f64; 6] = [10.0, 25.0, 50.0, 75.0, 90.0, 99.0];
+/// Maximum weight of a block in weight units, see BIP141.
+const MAX_BLOCK_WEIGHT: f64 = 4_000_000.0;
+
+/// Possible statistical analysis that can be run on sampled block statistics.
+/// Every analysis returns its report, see the render module for its table.
+pub trait BlockStatsStatistics {
+    /// Run an analysis of how the fee rates respond to the demand for block
+    /// space. A constant elasticity model ln(fee rate) = a + b ln(vbytes) is
+    /// fitted on the median fee rate and confirmed vbytes of every block,
+    /// where b is the elasticity. Blocks without fee paying transactions are
+    /// excluded as their fee rate is undefined.
+    fn fetch_fee_elasticity(self) -> FeeElasticityReport;
+
+    /// Run an analysis of the fees paid by the sampled blocks, reporting the
+    /// distribution of the fees per block, the mean and median fee rates
+    /// and the transactions per block. The confidence interval of every
+    /// mean is the normal interval of the given z-score. Blocks without fee
+    /// paying transactions are excluded from the fee rates as their fee
+    /// rate is undefined.
+    fn fetch_fee_stats(self, z_score: f64) -> FeeStatsReport;
+
+    /// Get the rolling mean and median of the mean fee rate of blocks over
+    /// the number of blocks as a time series. Only contiguous blocks are
+    /// rolled over, so the window needs to reach the number of blocks.
+    fn fetch_rolling_fee_rates(self, blocks: usize) -> RollingReport;
+
+    /// Run an analysis detecting waves of consolidation, periods of the given
+    /// number of blocks where the ratio of inputs to outputs is unusually high
+    /// while fee rates are low. A period is unusual when its ratio is at
+    /// least the threshold in standard deviations above the mean ratio of
+    /// all periods, and its fee rate is below the median of all periods.
+    /// Contiguous detected periods are reported as a single wave.
+    fn fetch_consolidations(self, period: u64, threshold: f64) -> ConsolidationsReport;
+
+    /// Run an analysis of the miner revenue from the subsidy and fees for
+    /// every period of the given number of blocks. With a price feed the
+    /// revenue of every block is valued at the BTC/USD price of its
+    /// timestamp.
+    fn fetch_revenue(self, period: u64, prices: Option<&PriceFeed>) -> RevenueReport;
+
+    /// Run an analysis of the size, weight and fullness of the sampled
+    /// blocks relative to the maximum block weight, reporting their
+    /// distribution and their means for every period of the given number of
+    /// blocks as a trend. The confidence interval of every mean is the
+    /// normal interval of the given z-score.
+    fn fetch_block_size(self, period: u64, z_score: f64) -> BlockSizeReport;
+
+    /// Run an analysis of the transactions per sampled block, reporting
+    /// their mean with the normal confidence interval of the given z-score
+    /// and their distribution. The transactions of every block with its
+    /// parent in the sample are correlated with the time taken to mine the
+    /// block, as a longer wait lets the mempool fill up further.
+    fn fetch_transactions(self, z_score: f64) -> TransactionsReport;
+}
+
+impl BlockStatsStatistics for BlockStatsData {
+    fn fetch_fee_elasticity(self) -> FeeElasticityReport {
+        // Maximum virtual size of a block
+        const MAX_VBYTES: f64 = 1_000_000.0;
+        let mut ln_vbytes = vec![];
+        let mut ln_fee_rates = vec![];
+        // Blocks, vbytes and fee rates by fullness decile
+        let mut bands: BTreeMap<usize, (usize, f64, f64)> = BTreeMap::new();
+
+        self.0.iter().for_each(|(_, stats)| {
+            let vbytes = stats.total_weight as f64 / 4.0;
+            let fee_rate = stats.fee_rate_percentiles.fr_50th.as_sat() as f64;
+
+            if stats.txs <= 1 || vbytes <= 0.0 || fee_rate <= 0.0 {
+                return;
+            }
+
+            ln_vbytes.push(vbytes.ln());
+            ln_fee_rates.push(fee_rate.ln());
+
+            let decile = ((vbytes / MAX_VBYTES * 10.0) as usize).min(9);
+            let band = bands.entry(decile).or_insert((0, 0.0, 0.0));
+            band.0 += 1;
+            band.1 += vbytes;
+            band.2 += fee_rate;
+        });
+
+        let (elasticity, _, r_squared) = get_linear_regression(&ln_vbytes, &ln_fee_rates);
+        let p_value = get_correlation_p_value(r_squared.sqrt(), ln_vbytes.len());
+
+        let entries = bands
+            .into_iter()
+            .map(|(decile, (blocks, vbytes, fee_rate))| FeeElasticityTable {
+                fullness: format!("{}-{}%", decile * 10, (decile + 1) * 10),
+                blocks,
+                mean_vbytes: get_rounded_by(vbytes / blocks as f64, 2),
+                mean_fee_rate: get_rounded_by(fee_rate / blocks as f64, 2),
+            })
+            .collect();
+
+        FeeElasticityReport {
+            entries,
+            blocks: ln_vbytes.len(),
+            elasticity,
+            p_value,
+            r_squared,
+        }
+    }
+
+    fn fetch_fee_stats(self, z_score: f64) -> FeeStatsReport {
+        let fees = self
+            .0
+            .iter()
+            .map(|(_, stats)| stats.total_fee.as_sat() as f64)
+            .collect::<Vec<_>>();
+        let transactions = self
+            .0
+            .iter()
+            .map(|(_, stats)| stats.txs as f64)
+            .collect::<Vec<_>>();
+        let paying = self
+            .0
+            .iter()
+            .filter(|(_, stats)| stats.txs > 1)
+            .map(|(_, stats)| stats)
+            .collect::<Vec<_>>();
+        let mean_fee_rates = paying
+            .iter()
+            .map(|stats| stats.avg_fee_rate.as_sat() as f64)
+            .collect::<Vec<_>>();
+        let median_fee_rates = paying
+            .iter()
+            .map(|stats| stats.fee_rate_percentiles.fr_50th.as_sat() as f64)
+            .collect::<Vec<_>>();
+
+        let row = |metric: &str, values: &[f64]| {
+            let (low, high) = get_mean_interval(values, z_score);
+
+            FeeStatsTable {
+                metric: metric.to_string(),
+                blocks: values.len(),
+                mean: get_rounded_by(get_mean(values), 2),
+                interval: format!("{:.2} - {:.2}", low, high),
+                median: get_rounded_by(get_median(values), 2),
+                p10: get_rounded_by(get_percentile(values, 10.0), 2),
+                p90: get_rounded_by(get_percentile(values, 90.0), 2),
+            }
+        };
+
+        FeeStatsReport {
+            entries: vec![
+                row("Fees per Block (sat)", &fees),
+                row("Mean Fee Rate (sat/vB)", &mean_fee_rates),
+                row("Median Fee Rate (sat/vB)", &median_fee_rates),
+                row("Transactions per Block", &transactions),
+            ],
+            blocks: self.0.len(),
+            total_fees: fees.iter().sum::<f64>() / 100_000_000.0,
+            z_score,
+        }
+    }
+
+    fn fetch_rolling_fee_rates(self, blocks: usize) -> RollingReport {
+        let fee_rates = self
+            .0
+            .iter()
+            .map(|(height, stats)| {
+                let fee_rate = stats.avg_fee_rate.as_sat() as f64;
+                (*height, stats.time as u32, fee_rate)
+            })
+            .collect::<Vec<_>>();
+
+        RollingReport {
+            entries: get_rolling_tables(&fee_rates, blocks),
+            metric: String::from("Mean Fee Rate (sat/vB)"),
+            blocks,
+        }
+    }
+
+    fn fetch_consolidations(self, period: u64, threshold: f64) -> ConsolidationsReport {
+        let period = period.max(1);
+        // Stats of the blocks keyed by the period
+        let mut periods: BTreeMap<u64, Vec<&GetBlockStatsResult>> = BTreeMap::new();
+
+        self.0.iter().for_each(|(height, stats)| {
+            periods.entry(height.0 / period).or_default().push(stats);
+        });
+
+        let ratio = |stats: &[&GetBlockStatsResult]| {
+            let ins = stats.iter().map(|stats| stats.ins).sum::<usize>();
+            let outs = stats.iter().map(|stats| stats.outs).sum::<usize>();
+            ins as f64 / outs.max(1) as f64
+        };
+        let fee_rate = |stats: &[&GetBlockStatsResult]| {
+            let fee_rates = stats
+                .iter()
+                .map(|stats| stats.fee_rate_percentiles.fr_50th.as_sat() as f64)
+                .collect::<Vec<_>>();
+            get_mean(&fee_rates)
+        };
+
+        let ratios = periods
+            .values()
+            .map(|stats| ratio(stats))
+            .collect::<Vec<_>>();
+        let fee_rates = periods
+            .values()
+            .map(|stats| fee_rate(stats))
+            .collect::<Vec<_>>();
+        let mean_ratio = get_mean(&ratios);
+        let std_deviation = get_standard_deviation(&ratios, true);
+        let median_fee_rate = get_median(&fee_rates);
+
+        // Merge contiguous detected periods into waves
+        let mut waves: Vec<(u64, Vec<&GetBlockStatsResult>)> = vec![];
+
+        periods
+            .iter()
+            .zip(ratios.iter().zip(fee_rates.iter()))
+            .filter(|(_, (ratio, fee_rate))| {
+                **ratio >= mean_ratio + threshold * std_deviation && **fee_rate < median_fee_rate
+            })
+            .for_each(|((index, stats), _)| match waves.last_mut() {
+                Some((last, wave)) if *last + 1 == *index => {
+                    *last = *index;
+                    wave.extend(stats);
+                }
+                _ => waves.push((*index, stats.clone())),
+            });
+
+        let entries = waves
+            .into_iter()
+            .map(|(_, stats)| {
+                let first = stats.iter().min_by_key(|stats| stats.height).unwrap();
+                let last = stats.iter().max_by_key(|stats| stats.height).unwrap();
+                let inputs = stats.iter().map(|stats| stats.ins).sum::<usize>();
+                let outputs = stats.iter().map(|stats| stats.outs).sum::<usize>();
+                let volume = stats
+                    .iter()
+                    .map(|stats| stats.total_out.as_btc())
+                    .sum::<f64>();
+
+                ConsolidationTable {
+                    start: get_date(first.time as u32),
+                    end: get_date(last.time as u32),
+                    heights: format!("{}-{}", first.height, last.height),
+                    blocks: stats.len(),
+                    ratio: get_rounded_by(ratio(&stats), 2),
+                    fee_rate: get_rounded_by(fee_rate(&stats), 2),
+                    inputs,
+                    consolidated: inputs as i64 - outputs as i64,
+                    volume: get_rounded_by(volume, 2),
+                }
+            })
+            .collect();
+
+        ConsolidationsReport {
+            entries,
+            periods: ratios.len(),
+            mean_ratio,
+            std_deviation,
+            median_fee_rate,
+        }
+    }
+
+    fn fetch_revenue(self, period: u64, prices: Option<&PriceFeed>) -> RevenueReport {
+        let period = period.max(1);
+        // Subsidy and fees in BTC and price of the blocks keyed by the period
+        let mut periods: BTreeMap<u64, Vec<(f64, f64, Option<f64>)>> = BTreeMap::new();
+        let mut unpriced = 0;
+
+        self.0.iter().for_each(|(height, stats)| {
+            let price = prices.and_then(|prices| prices.get_price(stats.time as u32));
+            unpriced += price.is_none() as usize;
+            periods.entry(height.0 / period).or_default().push((
+                stats.subsidy.as_btc(),
+                stats.total_fee.as_btc(),
+                price,
+            ));
+        });
+
+        let entries = periods
+            .into_iter()
+            .map(|(index, blocks)| {
+                let subsidy = blocks.iter().map(|(subsidy, ..)| subsidy).sum::<f64>();
+                let fees = blocks.iter().map(|(_, fees, _)| fees).sum::<f64>();
+                // Revenue in USD of the blocks with a known price
+                let priced = blocks
+                    .iter()
+                    .filter_map(|(subsidy, fees, price)| {
+                        price.map(|price| (subsidy * price, fees * price, price))
+                    })
+                    .collect::<Vec<_>>();
+                let prices = priced.iter().map(|(.., price)| *price).collect::<Vec<_>>();
+                let total = |revenue: Vec<f64>| {
+                    (!priced.is_empty()).then(|| get_rounded_by(revenue.iter().sum(), 2))
+                };
+
+                RevenueTable {
+                    period: format!("{}-{}", index * period, (index + 1) * period - 1),
+                    blocks: blocks.len(),
+                    subsidy: get_rounded_by(subsidy, 8),
+                    fees: get_rounded_by(fees, 8),
+                    fee_share: fees / (subsidy + fees).max(f64::MIN_POSITIVE),
+                    price: (!prices.is_empty()).then(|| get_rounded_by(get_mean(&prices), 2)),
+                    subsidy_usd: total(priced.iter().map(|(subsidy, ..)| *subsidy).collect()),
+                    fees_usd: total(priced.iter().map(|(_, fees, _)| *fees).collect()),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let usd = entries
+            .iter()
+            .filter_map(|row| Some(row.subsidy_usd? + row.fees_usd?))
+            .collect::<Vec<_>>();
+
+        RevenueReport {
+            blocks: self.0.len(),
+            total_subsidy: get_rounded_by(entries.iter().map(|row| row.subsidy).sum(), 8),
+            total_fees: get_rounded_by(entries.iter().map(|row| row.fees).sum(), 8),
+            total_usd: (!usd.is_empty()).then(|| get_rounded_by(usd.iter().sum(), 2)),
+            unpriced,
+            entries,
+        }
+    }
+
+    fn fetch_block_size(self, period: u64, z_score: f64) -> BlockSizeReport {
+        let period = period.max(1);
+        let sizes = self
+            .0
+            .iter()
+            .map(|(_, stats)| stats.total_size as f64)
+            .collect::<Vec<_>>();
+        let weights = self
+            .0
+            .iter()
+            .map(|(_, stats)| stats.total_weight as f64)
+            .collect::<Vec<_>>();
+        let fullness = weights
+            .iter()
+            .map(|weight| weight / MAX_BLOCK_WEIGHT)
+            .collect::<Vec<_>>();
+
+        let row = |metric: &str, values: &[f64], precision: u8| {
+            let (low, high) = get_mean_interval(values, z_score);
+
+            BlockSizeTable {
+                metric: metric.to_string(),
+                blocks: values.len(),
+                mean: get_rounded_by(get_mean(values), precision),
+                interval: format!(
+                    "{} - {}",
+                    get_rounded_by(low, precision),
+                    get_rounded_by(high, precision)
+                ),
+                median: get_rounded_by(get_median(values), precision),
+                p10: get_rounded_by(get_percentile(values, 10.0), precision),
+                p90: get_rounded_by(get_percentile(values, 90.0), precision),
+            }
+        };
+
+        // Size and weight of the blocks keyed by the period
+        let mut periods: BTreeMap<u64, Vec<(f64, f64)>> = BTreeMap::new();
+        self.0.iter().for_each(|(height, stats)| {
+            periods
+                .entry(height.0 / period)
+                .or_default()
+                .push((stats.total_size as f64, stats.total_weight as f64));
+        });
+
+        let entries = periods
+            .into_iter()
+            .map(|(index, blocks)| {
+                let mean_size = get_mean(&blocks.iter().map(|(size, _)| *size).collect::<Vec<_>>());
+                let mean_weight =
+                    get_mean(&blocks.iter().map(|(_, weight)| *weight).collect::<Vec<_>>());
+
+                BlockSizeTrendTable {
+                    period: format!("{}-{}", index * period, (index + 1) * period - 1),
+                    blocks: blocks.len(),
+                    mean_size: get_rounded_by(mean_size, 2),
+                    mean_weight: get_rounded_by(mean_weight, 2),
+                    fullness: mean_weight / MAX_BLOCK_WEIGHT,
+                }
+            })
+            .collect();
+
+        let heights = self
+            .0
+            .iter()
+            .map(|(height, _)| height.0 as f64)
+            .collect::<Vec<_>>();
+        let trend = (heights.len() > 1).then(|| {
+            let (slope, ..) = get_linear_regression(&heights, &fullness);
+            slope * period as f64
+        });
+
+        BlockSizeReport {
+            entries,
+            metrics: vec![
+                row("Size (bytes)", &sizes, 2),
+                row("Weight (WU)", &weights, 2),
+                row("Fullness", &fullness, 4),
+            ],
+            blocks: self.0.len(),
+            full_blocks: fullness
+                .iter()
+                .filter(|fullness| **fullness >= 0.99)
+                .count(),
+            trend,
+            z_score,
+        }
+    }
+
+    fn fetch_transactions(self, z_score: f64) -> TransactionsReport {
+        let transactions = self
+            .0
+            .iter()
+            .map(|(_, stats)| stats.txs as f64)
+            .collect::<Vec<_>>();
+        let mut intervals = vec![];
+        let mut paired = vec![];
+
+        self.0
+            .windows(2)
+            .filter(|pair| pair[1].0 == pair[0].0 + 1)
+            .for_each(|pair| {
+                let (parent, block) = (&pair[0].1, &pair[1].1);
+                intervals.push((block.time as f64 - parent.time as f64) / 60.0);
+                paired.push(block.txs as f64);
+            });
+
+        let entries = get_percentiles(&transactions, &TRANSACTION_PERCENTILES)
+            .into_iter()
+            .zip(TRANSACTION_PERCENTILES)
+            .map(|(count, percentile)| TransactionPercentileTable {
+                percentile,
+                transactions: get_rounded_by(count, 2),
+            })
+            .collect();
+        let (mean_low, mean_high) = get_mean_interval(&transactions, z_score);
+
+        TransactionsReport {
+            entries,
+            correlation: vec![CorrelationTable::new("Transactions", &intervals, &paired)],
+            blocks: transactions.len(),
+            pairs: intervals.len(),
+            mean: get_rounded_by(get_mean(&transactions), 2),
+            mean_low: get_rounded_by(mean_low, 2),
+            mean_high: get_rounded_by(mean_high, 2),
+            std_deviation: get_rounded_by(get_standard_deviation(&transactions, true), 2),
+            z_score,
+        }
+    }
+}
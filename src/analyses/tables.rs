@@ -0,0 +1,998 @@
+use bitcoincore_rpc::bitcoin::BlockHash;
+use schemars::JsonSchema;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::records::BlockGap;
+use crate::utils::*;
+use crate::BlockHeight;
+
+/// Use a struct to store the drift and blocks for a binary heap.
+/// Doubles as the sample table.
+#[derive(Tabled, Serialize, JsonSchema, Clone, Eq, PartialEq, Debug)]
+pub struct BlockTimeDriftTable {
+    #[tabled(rename = "Mining Time", order = 2, display_with = "display_mins")]
+    pub drift: i64,
+    #[tabled(rename = "Parent Block Hash", order = 0)]
+    #[schemars(with = "String")]
+    pub parent_hash: BlockHash,
+    #[tabled(rename = "Child Block Hash", order = 1)]
+    #[schemars(with = "String")]
+    pub child_hash: BlockHash,
+    #[tabled(rename = "Min Difficulty", order = 3, display_with = "display_flag")]
+    pub min_difficulty: bool,
+}
+
+/// Sample achieved by a collection that was cancelled or ran out of time,
+/// noted along with the reports analyzed from the partial sample.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PartialSampleTable {
+    #[tabled(rename = "Stopped")]
+    pub(crate) reason: String,
+    #[tabled(rename = "Planned")]
+    pub(crate) planned: u64,
+    #[tabled(rename = "Sampled")]
+    pub(crate) sampled: u64,
+    #[tabled(rename = "Population")]
+    pub(crate) population: u64,
+    #[tabled(rename = "Margin of Error", display_with = "display_percent")]
+    pub(crate) margin_error: f64,
+    #[tabled(rename = "Confidence", display_with = "display_percent")]
+    pub(crate) confidence: f64,
+}
+
+/// Mining times within a window of contiguous blocks.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct WindowTable {
+    #[tabled(rename = "Start Height")]
+    pub start_height: BlockHeight,
+    #[tabled(rename = "End Height")]
+    pub end_height: BlockHeight,
+    #[tabled(rename = "Span", display_with = "display_duration")]
+    pub span: i64,
+    #[tabled(rename = "Mean", display_with = "display_mins_f")]
+    pub mean: f64,
+    #[tabled(rename = "Variance")]
+    pub variance: f64,
+    #[tabled(rename = "Max Gap", display_with = "display_mins")]
+    pub max_gap: i64,
+}
+
+/// Share of the intervals within a target mining time.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TargetShareTable {
+    #[tabled(rename = "Target", display_with = "display_within_mins")]
+    pub(crate) target: i64,
+    #[tabled(rename = "Intervals")]
+    pub(crate) intervals: usize,
+    #[tabled(rename = "Share", display_with = "display_percent")]
+    pub(crate) share: f64,
+    #[tabled(rename = "Share Interval")]
+    pub(crate) interval: String,
+}
+
+/// Mining time below which a percentile of the intervals fall.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct IntervalPercentileTable {
+    #[tabled(rename = "Percentile", display_with = "display_percentile")]
+    pub(crate) percentile: f64,
+    #[tabled(rename = "Mining Time", display_with = "display_mins_f")]
+    pub(crate) minutes: f64,
+}
+
+/// Transactions per block below which a percentile of the blocks fall.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TransactionPercentileTable {
+    #[tabled(rename = "Percentile", display_with = "display_percentile")]
+    pub(crate) percentile: f64,
+    #[tabled(rename = "Transactions")]
+    pub(crate) transactions: f64,
+}
+
+/// An inter-block gap within the record book.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct RecordTable {
+    #[tabled(rename = "Record")]
+    pub record: String,
+    #[tabled(rename = "Gap", display_with = "display_duration")]
+    pub seconds: i64,
+    #[tabled(rename = "Parent Height")]
+    pub parent_height: BlockHeight,
+    #[tabled(rename = "Child Height")]
+    pub child_height: BlockHeight,
+    #[tabled(rename = "Date (UTC)")]
+    pub date: String,
+    #[tabled(rename = "Difficulty")]
+    pub difficulty: f64,
+}
+
+/// A child block timestamped before its parent.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct NegativeDriftTable {
+    #[tabled(rename = "Parent Height")]
+    pub(crate) parent_height: BlockHeight,
+    #[tabled(rename = "Child Height")]
+    pub(crate) child_height: BlockHeight,
+    #[tabled(rename = "Parent Date (UTC)")]
+    pub(crate) parent_date: String,
+    #[tabled(rename = "Child Date (UTC)")]
+    pub(crate) child_date: String,
+    #[tabled(rename = "Drift", display_with = "display_duration")]
+    pub(crate) seconds: i64,
+}
+
+/// Pairs of contiguous blocks with the child timestamped before its parent
+/// over a year.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct OutOfOrderTable {
+    #[tabled(rename = "Year")]
+    pub(crate) year: i32,
+    #[tabled(rename = "Pairs")]
+    pub(crate) pairs: usize,
+    #[tabled(rename = "Out of Order")]
+    pub(crate) out_of_order: usize,
+    #[tabled(rename = "Frequency", display_with = "display_percent")]
+    pub(crate) frequency: f64,
+    #[tabled(rename = "Frequency Interval")]
+    pub(crate) interval: String,
+    #[tabled(rename = "Mean Magnitude", display_with = "display_duration")]
+    pub(crate) mean_magnitude: i64,
+    #[tabled(rename = "Max Magnitude", display_with = "display_duration")]
+    pub(crate) max_magnitude: i64,
+}
+
+/// Timestamp of a block against its median time past and the limits of
+/// the protocol.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct FutureTimestampTable {
+    #[tabled(rename = "Extreme")]
+    pub(crate) extreme: String,
+    #[tabled(rename = "Height")]
+    pub(crate) height: BlockHeight,
+    #[tabled(rename = "Date (UTC)")]
+    pub(crate) date: String,
+    #[tabled(rename = "MTP (UTC)")]
+    pub(crate) median_time_past: String,
+    #[tabled(rename = "MTP Margin", display_with = "display_duration")]
+    pub(crate) margin: i64,
+    #[tabled(rename = "Estimated Lead", display_with = "display_duration")]
+    pub(crate) lead: i64,
+}
+
+/// Probability of a transaction with a number of confirmations being
+/// reversed by a natural reorganization.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ReorgRiskTable {
+    #[tabled(rename = "Confirmations")]
+    pub(crate) confirmations: u64,
+    #[tabled(rename = "Probability", display_with = "display_probability")]
+    pub(crate) probability: f64,
+    #[tabled(rename = "Poisson Probability", display_with = "display_probability")]
+    pub(crate) poisson_probability: f64,
+    #[tabled(
+        rename = "Blocks per Reversal",
+        display_with = "display_optional_blocks"
+    )]
+    pub(crate) blocks: Option<f64>,
+}
+
+/// A chain tip known to the node off the active chain.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ChainTipTable {
+    #[tabled(rename = "Height")]
+    pub(crate) height: u64,
+    #[tabled(rename = "Hash")]
+    pub(crate) hash: String,
+    #[tabled(rename = "Fork Height")]
+    pub(crate) fork_height: u64,
+    #[tabled(rename = "Branch Length")]
+    pub(crate) branch_length: usize,
+    #[tabled(rename = "Status")]
+    pub(crate) status: String,
+}
+
+/// A previous run recorded in the run history.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct RunTable {
+    #[tabled(rename = "Run")]
+    pub(crate) id: i64,
+    #[tabled(rename = "Date (UTC)")]
+    pub(crate) date: String,
+    #[tabled(rename = "Analysis")]
+    pub(crate) analysis: String,
+    #[tabled(rename = "Parameters")]
+    pub(crate) parameters: String,
+    #[tabled(rename = "Headline")]
+    pub(crate) headline: String,
+}
+
+/// A damaged or missing range of heights of the header cache along with its
+/// repair.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct IndexRepairTable {
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Damage")]
+    pub(crate) damage: String,
+    #[tabled(rename = "Repair")]
+    pub(crate) repair: String,
+}
+
+/// An example study of the gallery.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ExampleTable {
+    #[tabled(rename = "Example")]
+    pub(crate) name: String,
+    #[tabled(rename = "Description")]
+    pub(crate) description: String,
+    #[tabled(rename = "Parameters")]
+    pub(crate) parameters: String,
+    #[tabled(rename = "Command")]
+    pub(crate) command: String,
+}
+
+/// Backward timestamp jumps between contiguous blocks within a period.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct BackwardJumpTable {
+    #[tabled(rename = "Period")]
+    pub(crate) period: String,
+    #[tabled(rename = "Pairs")]
+    pub(crate) pairs: usize,
+    #[tabled(rename = "Backward Jumps")]
+    pub(crate) jumps: usize,
+    #[tabled(rename = "Frequency", display_with = "display_percent")]
+    pub(crate) frequency: f64,
+    #[tabled(rename = "Mean Magnitude", display_with = "display_duration")]
+    pub(crate) mean_magnitude: i64,
+    #[tabled(rename = "Max Magnitude", display_with = "display_duration")]
+    pub(crate) max_magnitude: i64,
+}
+
+/// A run of contiguous blocks with strictly increasing timestamps.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct StreakTable {
+    #[tabled(rename = "Streak")]
+    pub(crate) streak: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Start Height")]
+    pub(crate) start_height: BlockHeight,
+    #[tabled(rename = "End Height")]
+    pub(crate) end_height: BlockHeight,
+    #[tabled(rename = "Ended By")]
+    pub(crate) ended_by: String,
+}
+
+/// Miner revenue from the subsidy and fees over a period, in USD when the
+/// price is known.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct RevenueTable {
+    #[tabled(rename = "Period")]
+    pub(crate) period: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Subsidy (BTC)")]
+    pub(crate) subsidy: f64,
+    #[tabled(rename = "Fees (BTC)")]
+    pub(crate) fees: f64,
+    #[tabled(rename = "Fee Share", display_with = "display_percent")]
+    pub(crate) fee_share: f64,
+    #[tabled(rename = "Mean Price", display_with = "display_optional_usd")]
+    pub(crate) price: Option<f64>,
+    #[tabled(rename = "Subsidy (USD)", display_with = "display_optional_usd")]
+    pub(crate) subsidy_usd: Option<f64>,
+    #[tabled(rename = "Fees (USD)", display_with = "display_optional_usd")]
+    pub(crate) fees_usd: Option<f64>,
+}
+
+/// Distribution of a block size metric over the sampled blocks.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct BlockSizeTable {
+    #[tabled(rename = "Metric")]
+    pub(crate) metric: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Mean")]
+    pub(crate) mean: f64,
+    #[tabled(rename = "Mean Interval")]
+    pub(crate) interval: String,
+    #[tabled(rename = "Median")]
+    pub(crate) median: f64,
+    #[tabled(rename = "P10")]
+    pub(crate) p10: f64,
+    #[tabled(rename = "P90")]
+    pub(crate) p90: f64,
+}
+
+/// Mean size, weight and fullness of the sampled blocks over a period.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct BlockSizeTrendTable {
+    #[tabled(rename = "Period")]
+    pub(crate) period: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Mean Size (bytes)")]
+    pub(crate) mean_size: f64,
+    #[tabled(rename = "Mean Weight (WU)")]
+    pub(crate) mean_weight: f64,
+    #[tabled(rename = "Fullness", display_with = "display_percent")]
+    pub(crate) fullness: f64,
+}
+
+/// Difficulty of a sampled retarget epoch and its change from the epoch
+/// before it.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct DifficultyTrendTable {
+    #[tabled(rename = "Epoch")]
+    pub epoch: u64,
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Date (UTC)")]
+    pub(crate) date: String,
+    #[tabled(rename = "Difficulty")]
+    pub difficulty: f64,
+    #[tabled(rename = "Retarget", display_with = "display_optional_percent")]
+    pub(crate) change: Option<f64>,
+    #[tabled(rename = "Extreme")]
+    pub(crate) extreme: String,
+}
+
+/// Blocks signaling a version bit over a retarget epoch.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct VersionBitsTable {
+    #[tabled(rename = "Epoch")]
+    pub(crate) epoch: u64,
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Bit")]
+    pub(crate) bit: u8,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Signaling")]
+    pub(crate) signaling: usize,
+    #[tabled(rename = "Rate", display_with = "display_percent")]
+    pub(crate) rate: f64,
+    #[tabled(rename = "Rate Interval")]
+    pub(crate) interval: String,
+    #[tabled(rename = "Threshold Reached", display_with = "display_flag")]
+    pub(crate) threshold_reached: bool,
+}
+
+/// Mining times and difficulty change of a retarget epoch.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct DifficultyEpochTable {
+    #[tabled(rename = "Epoch")]
+    pub(crate) epoch: u64,
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Intervals")]
+    pub(crate) intervals: usize,
+    #[tabled(rename = "Mean Interval", display_with = "display_mins_f")]
+    pub(crate) mean_interval: f64,
+    #[tabled(rename = "Expected Duration", display_with = "display_duration")]
+    pub(crate) expected_duration: i64,
+    #[tabled(rename = "Estimated Duration", display_with = "display_duration")]
+    pub(crate) estimated_duration: i64,
+    #[tabled(rename = "Difficulty")]
+    pub(crate) difficulty: f64,
+    #[tabled(rename = "Implied Change", display_with = "display_percent")]
+    pub(crate) implied_change: f64,
+    #[tabled(rename = "Observed Change", display_with = "display_optional_percent")]
+    pub(crate) observed_change: Option<f64>,
+}
+
+/// Duration of a completed retarget epoch and the adjustment it implies.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct EpochDurationTable {
+    #[tabled(rename = "Epoch")]
+    pub(crate) epoch: u64,
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Start Date (UTC)")]
+    pub(crate) start_date: String,
+    #[tabled(rename = "Duration", display_with = "display_duration")]
+    pub(crate) duration: i64,
+    #[tabled(rename = "Deviation", display_with = "display_duration")]
+    pub(crate) deviation: i64,
+    #[tabled(rename = "Adjustment", display_with = "display_percent")]
+    pub(crate) adjustment: f64,
+    #[tabled(
+        rename = "Observed Adjustment",
+        display_with = "display_optional_percent"
+    )]
+    pub(crate) observed_adjustment: Option<f64>,
+    #[tabled(rename = "Extreme")]
+    pub(crate) extreme: String,
+}
+
+/// Timestamps at the start of a completed retarget epoch and their
+/// influence on the difficulty retargeting set next.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TimeWarpTable {
+    #[tabled(rename = "Epoch")]
+    pub(crate) epoch: u64,
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Duration", display_with = "display_duration")]
+    pub(crate) duration: i64,
+    #[tabled(rename = "Boundary Gap", display_with = "display_optional_duration")]
+    pub(crate) gap: Option<i64>,
+    #[tabled(rename = "MTP Margin", display_with = "display_optional_duration")]
+    pub(crate) margin: Option<i64>,
+    #[tabled(rename = "Influence", display_with = "display_percent")]
+    pub(crate) influence: f64,
+    #[tabled(rename = "Flag")]
+    pub(crate) flag: String,
+}
+
+/// Timestamp of a block against the schedule of a block every 10 minutes
+/// since the genesis block.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CumulativeDriftTable {
+    #[tabled(rename = "Height")]
+    pub(crate) height: BlockHeight,
+    #[tabled(rename = "Date (UTC)")]
+    pub(crate) date: String,
+    #[tabled(rename = "Scheduled (UTC)")]
+    pub(crate) scheduled: String,
+    #[tabled(rename = "Ahead", display_with = "display_days")]
+    pub(crate) ahead: f64,
+    #[tabled(rename = "Blocks Ahead")]
+    pub(crate) blocks_ahead: i64,
+    #[tabled(rename = "Mean Interval", display_with = "display_mins_f")]
+    pub(crate) mean_interval: f64,
+}
+
+/// Block production speed of a completed halving era, compared against the
+/// era before it.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct HalvingEraTable {
+    #[tabled(rename = "Era")]
+    pub(crate) era: u64,
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Start Date (UTC)")]
+    pub(crate) start_date: String,
+    #[tabled(rename = "End Date (UTC)")]
+    pub(crate) end_date: String,
+    #[tabled(rename = "Duration", display_with = "display_days")]
+    pub(crate) duration: f64,
+    #[tabled(rename = "Mean Interval", display_with = "display_mins_f")]
+    pub(crate) mean_interval: f64,
+    #[tabled(rename = "Blocks per Day")]
+    pub(crate) blocks_per_day: f64,
+    #[tabled(rename = "Sampled Intervals")]
+    pub(crate) intervals: usize,
+    #[tabled(rename = "Std Deviation", display_with = "display_mins_f")]
+    pub(crate) std_deviation: f64,
+    #[tabled(rename = "Change", display_with = "display_optional_percent")]
+    pub(crate) change: Option<f64>,
+    #[tabled(rename = "p-value", display_with = "display_optional_p_value")]
+    pub(crate) p_value: Option<f64>,
+    #[tabled(rename = "Significant", display_with = "display_flag")]
+    pub(crate) significant: bool,
+}
+
+/// Rolling mean and median of a metric ending at a block.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct RollingTable {
+    #[tabled(rename = "Height")]
+    pub(crate) height: BlockHeight,
+    #[tabled(rename = "Date (UTC)")]
+    pub(crate) date: String,
+    #[tabled(rename = "Mean")]
+    pub(crate) mean: f64,
+    #[tabled(rename = "Median")]
+    pub(crate) median: f64,
+}
+
+/// Mining times sampled from a stratum of a stratified sample.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct StratumTable {
+    #[tabled(rename = "Stratum")]
+    pub(crate) stratum: String,
+    #[tabled(rename = "Intervals")]
+    pub(crate) intervals: usize,
+    #[tabled(rename = "Mean", display_with = "display_mins_f")]
+    pub(crate) mean: f64,
+    #[tabled(rename = "Standard Deviation")]
+    pub(crate) std_deviation: f64,
+}
+
+/// Autocorrelation of the mining times of contiguous blocks at a lag.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct AutocorrelationTable {
+    #[tabled(rename = "Lag")]
+    pub(crate) lag: usize,
+    #[tabled(rename = "Pairs")]
+    pub(crate) pairs: usize,
+    #[tabled(rename = "Autocorrelation")]
+    pub(crate) autocorrelation: f64,
+    /// Largest autocorrelation expected of independent mining times either
+    /// way at the z-score
+    #[tabled(rename = "Bound")]
+    pub(crate) bound: f64,
+    #[tabled(rename = "Significant", display_with = "display_flag")]
+    pub(crate) significant: bool,
+}
+
+/// Network hashrate estimated over the blocks ending at a block.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct HashrateTable {
+    #[tabled(rename = "Height")]
+    pub(crate) height: BlockHeight,
+    #[tabled(rename = "Date (UTC)")]
+    pub(crate) date: String,
+    #[tabled(rename = "Mean Interval", display_with = "display_mins_f")]
+    pub(crate) mean_interval: f64,
+    #[tabled(rename = "Difficulty")]
+    pub(crate) difficulty: f64,
+    #[tabled(rename = "Hashrate", display_with = "display_hashrate")]
+    pub(crate) hashrate: f64,
+}
+
+/// Block production of a single signet signer.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct SignetProducerTable {
+    #[tabled(rename = "Signer")]
+    pub(crate) signer: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Share", display_with = "display_percent")]
+    pub(crate) share: f64,
+    #[tabled(rename = "Mean Mining Time", display_with = "display_mins_f")]
+    pub(crate) mean_time: f64,
+}
+
+/// Correlation of a block metric with the time taken to mine the block.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CorrelationTable {
+    #[tabled(rename = "Metric")]
+    pub(crate) metric: String,
+    #[tabled(rename = "Pearson")]
+    pub(crate) pearson: f64,
+    #[tabled(rename = "Pearson p-value")]
+    pub(crate) pearson_p_value: f64,
+    #[tabled(rename = "Spearman")]
+    pub(crate) spearman: f64,
+    #[tabled(rename = "Spearman p-value")]
+    pub(crate) spearman_p_value: f64,
+}
+
+/// Observed and expected block production of a single mining pool.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PoolLuckTable {
+    #[tabled(rename = "Pool")]
+    pub(crate) pool: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Share", display_with = "display_percent")]
+    pub(crate) share: f64,
+    #[tabled(rename = "Share Interval")]
+    pub(crate) interval: String,
+    #[tabled(rename = "Expected Share", display_with = "display_optional_percent")]
+    pub(crate) expected_share: Option<f64>,
+    #[tabled(rename = "Luck", display_with = "display_optional_percent")]
+    pub(crate) luck: Option<f64>,
+    #[tabled(rename = "Mean Mining Time", display_with = "display_mins_f")]
+    pub(crate) mean_time: f64,
+}
+
+/// Blocks attributed to a mining pool and its estimated share.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PoolShareTable {
+    #[tabled(rename = "Pool")]
+    pub(crate) pool: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Share", display_with = "display_percent")]
+    pub(crate) share: f64,
+    #[tabled(rename = "Share Interval")]
+    pub(crate) interval: String,
+    #[tabled(rename = "By Tag")]
+    pub(crate) by_tag: usize,
+    #[tabled(rename = "By Address")]
+    pub(crate) by_address: usize,
+}
+
+/// Empty blocks mined by a mining pool.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct EmptyBlocksTable {
+    #[tabled(rename = "Pool")]
+    pub(crate) pool: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Empty")]
+    pub(crate) empty: usize,
+    #[tabled(rename = "Empty Rate", display_with = "display_percent")]
+    pub(crate) rate: f64,
+    #[tabled(rename = "Rate Interval")]
+    pub(crate) interval: String,
+}
+
+/// Fee rates of the blocks within a band of block fullness.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct FeeElasticityTable {
+    #[tabled(rename = "Fullness")]
+    pub(crate) fullness: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Mean vBytes")]
+    pub(crate) mean_vbytes: f64,
+    #[tabled(rename = "Mean Median Fee Rate", display_with = "display_fee_rate")]
+    pub(crate) mean_fee_rate: f64,
+}
+
+/// Distribution of a fee metric over the sampled blocks.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct FeeStatsTable {
+    #[tabled(rename = "Metric")]
+    pub(crate) metric: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Mean")]
+    pub(crate) mean: f64,
+    #[tabled(rename = "Mean Interval")]
+    pub(crate) interval: String,
+    #[tabled(rename = "Median")]
+    pub(crate) median: f64,
+    #[tabled(rename = "P10")]
+    pub(crate) p10: f64,
+    #[tabled(rename = "P90")]
+    pub(crate) p90: f64,
+}
+
+/// Aggregate of a custom expression over the sample.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CustomTable {
+    #[tabled(rename = "Aggregate")]
+    pub(crate) aggregate: String,
+    #[tabled(rename = "Value")]
+    pub(crate) value: f64,
+}
+
+/// Live transaction arrivals observed while watching the mempool.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TransactionArrivalTable {
+    #[tabled(rename = "Elapsed", display_with = "display_duration")]
+    pub(crate) elapsed: i64,
+    #[tabled(rename = "Arrivals")]
+    pub(crate) arrivals: u64,
+    #[tabled(rename = "Arrival Rate")]
+    pub(crate) rate: String,
+    #[tabled(rename = "Confirmed")]
+    pub(crate) confirmed: u64,
+    #[tabled(rename = "Pending")]
+    pub(crate) pending: u64,
+    #[tabled(rename = "Replaced")]
+    pub(crate) replaced: u64,
+    #[tabled(rename = "Conflicted")]
+    pub(crate) conflicted: u64,
+    #[tabled(rename = "Expired")]
+    pub(crate) expired: u64,
+    #[tabled(rename = "Drop Rate", display_with = "display_percent")]
+    pub(crate) drop_rate: f64,
+}
+
+/// Wait of the confirmed transactions within a fee rate band.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ConfirmationLatencyTable {
+    #[tabled(rename = "Fee Rate")]
+    pub(crate) band: String,
+    #[tabled(rename = "Transactions")]
+    pub(crate) transactions: u64,
+    #[tabled(rename = "Median Blocks")]
+    pub(crate) median_blocks: f64,
+    #[tabled(rename = "P90 Blocks")]
+    pub(crate) p90_blocks: f64,
+    #[tabled(rename = "Next Block", display_with = "display_percent")]
+    pub(crate) next_block: f64,
+    #[tabled(rename = "Median Wait", display_with = "display_duration")]
+    pub(crate) median: i64,
+    #[tabled(rename = "P90 Wait", display_with = "display_duration")]
+    pub(crate) p90: i64,
+}
+
+/// Resolution of the conflicting spends observed in the mempool by the
+/// replaceability signaled by the original transaction.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ConflictTable {
+    #[tabled(rename = "Signaling")]
+    pub(crate) signaling: String,
+    #[tabled(rename = "Conflicts")]
+    pub(crate) conflicts: u64,
+    #[tabled(rename = "Replacements")]
+    pub(crate) replacements: u64,
+    #[tabled(rename = "Pending")]
+    pub(crate) pending: u64,
+    #[tabled(rename = "Replacement Confirmed")]
+    pub(crate) replacement: u64,
+    #[tabled(rename = "Original Confirmed")]
+    pub(crate) original: u64,
+    #[tabled(rename = "Unseen Confirmed")]
+    pub(crate) unseen: u64,
+    #[tabled(rename = "Replacement Share", display_with = "display_percent")]
+    pub(crate) replacement_share: f64,
+}
+
+/// Time from first seen in the mempool to confirmation at a percentile.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ConfirmationTimeTable {
+    #[tabled(rename = "Percentile")]
+    pub(crate) percentile: String,
+    #[tabled(rename = "Time to Confirmation", display_with = "display_duration")]
+    pub(crate) time: i64,
+}
+
+/// Clusters of entities within a range of sizes.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct EntityClusterTable {
+    #[tabled(rename = "Cluster Size")]
+    pub(crate) size: String,
+    #[tabled(rename = "Clusters")]
+    pub(crate) clusters: usize,
+    #[tabled(rename = "Scripts")]
+    pub(crate) scripts: usize,
+    #[tabled(rename = "Script Share", display_with = "display_percent")]
+    pub(crate) share: f64,
+}
+
+/// A wave of consolidation over contiguous periods.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct ConsolidationTable {
+    #[tabled(rename = "Start (UTC)")]
+    pub(crate) start: String,
+    #[tabled(rename = "End (UTC)")]
+    pub(crate) end: String,
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Input/Output Ratio")]
+    pub(crate) ratio: f64,
+    #[tabled(rename = "Mean Median Fee Rate", display_with = "display_fee_rate")]
+    pub(crate) fee_rate: f64,
+    #[tabled(rename = "Inputs")]
+    pub(crate) inputs: usize,
+    #[tabled(rename = "Outputs Removed")]
+    pub(crate) consolidated: i64,
+    #[tabled(rename = "Volume (BTC)")]
+    pub(crate) volume: f64,
+}
+
+/// Script type mix of the inputs spent and outputs created over a period.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct WitnessVersionFlowTable {
+    #[tabled(rename = "Period")]
+    pub(crate) period: String,
+    #[tabled(rename = "Script Type")]
+    pub(crate) script_type: String,
+    #[tabled(rename = "Inputs")]
+    pub(crate) inputs: usize,
+    #[tabled(rename = "Input Share", display_with = "display_percent")]
+    pub(crate) input_share: f64,
+    #[tabled(rename = "Outputs")]
+    pub(crate) outputs: usize,
+    #[tabled(rename = "Output Share", display_with = "display_percent")]
+    pub(crate) output_share: f64,
+    #[tabled(rename = "Net Flow")]
+    pub(crate) net: i64,
+}
+
+/// Subsidy and fees claimed by the coinbases of a halving era.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CoinbaseRevenueTable {
+    #[tabled(rename = "Era")]
+    pub(crate) era: u64,
+    #[tabled(rename = "Heights")]
+    pub(crate) heights: String,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "Subsidy (BTC)")]
+    pub(crate) subsidy: f64,
+    #[tabled(rename = "Fees (BTC)")]
+    pub(crate) fees: f64,
+    #[tabled(rename = "Mean Fees (BTC)")]
+    pub(crate) mean_fees: f64,
+    #[tabled(rename = "Fee Share", display_with = "display_percent")]
+    pub(crate) fee_share: f64,
+}
+
+/// Taproot outputs created and spent by key path or script path over a
+/// retarget epoch.
+#[derive(Tabled, Serialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct TaprootAdoptionTable {
+    #[tabled(rename = "Epoch")]
+    pub(crate) epoch: u64,
+    #[tabled(rename = "Blocks")]
+    pub(crate) blocks: usize,
+    #[tabled(rename = "P2TR Outputs")]
+    pub(crate) outputs: usize,
+    #[tabled(rename = "Output Share", display_with = "display_percent")]
+    pub(crate) output_share: f64,
+    #[tabled(rename = "Key Path Spends")]
+    pub(crate) key_path: usize,
+    #[tabled(rename = "Script Path Spends")]
+    pub(crate) script_path: usize,
+    #[tabled(rename = "Input Share", display_with = "display_percent")]
+    pub(crate) input_share: f64,
+}
+
+impl PartialOrd for BlockTimeDriftTable {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BlockTimeDriftTable {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Break ties on the hashes so equal drifts are reported in a
+        // deterministic order
+        self.drift
+            .cmp(&other.drift)
+            .then_with(|| other.parent_hash.cmp(&self.parent_hash))
+            .then_with(|| other.child_hash.cmp(&self.child_hash))
+            .then_with(|| self.min_difficulty.cmp(&other.min_difficulty))
+    }
+}
+
+impl BlockTimeDriftTable {
+    /// Create a new instance of a BlockSample which will specify the
+    /// parameters for fetching the sample data from bitcoind.
+    pub fn new(
+        drift: i64,
+        parent_hash: BlockHash,
+        child_hash: BlockHash,
+        min_difficulty: bool,
+    ) -> Self {
+        BlockTimeDriftTable {
+            drift,
+            parent_hash,
+            child_hash,
+            min_difficulty,
+        }
+    }
+}
+
+impl RecordTable {
+    /// Create a new record row for the gap.
+    pub fn new(record: String, gap: &BlockGap) -> Self {
+        RecordTable {
+            record,
+            seconds: gap.seconds,
+            parent_height: gap.parent_height,
+            child_height: gap.child_height,
+            date: get_date(gap.time),
+            difficulty: get_rounded_by(gap.difficulty, 2),
+        }
+    }
+}
+
+impl CorrelationTable {
+    /// Create a new correlation row of the metric against the intervals.
+    pub fn new(metric: &str, intervals: &[f64], values: &[f64]) -> Self {
+        let pearson = get_pearson_correlation(intervals, values);
+        let spearman = get_spearman_correlation(intervals, values);
+
+        CorrelationTable {
+            metric: metric.to_string(),
+            pearson: get_rounded_by(pearson, 4),
+            pearson_p_value: get_rounded_by(get_correlation_p_value(pearson, values.len()), 4),
+            spearman: get_rounded_by(spearman, 4),
+            spearman_p_value: get_rounded_by(get_correlation_p_value(spearman, values.len()), 4),
+        }
+    }
+}
+
+/// Display table column in minutes
+fn display_mins(mins: &i64) -> String {
+    format!("{} m", mins)
+}
+
+/// Display table column as a percentile, e.g. p95
+fn display_percentile(percentile: &f64) -> String {
+    format!("p{}", percentile)
+}
+
+/// Display table column as an upper bound in minutes
+fn display_within_mins(mins: &i64) -> String {
+    format!("<= {} m", mins)
+}
+
+/// Display boolean table column
+fn display_flag(flag: &bool) -> String {
+    String::from(if *flag { "Yes" } else { "No" })
+}
+
+/// Display table column in hours, minutes and seconds
+fn display_duration(seconds: &i64) -> String {
+    let sign = if *seconds < 0 { "-" } else { "" };
+    let seconds = seconds.abs();
+
+    format!(
+        "{}{}h {}m {}s",
+        sign,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Display optional table column in hours, minutes and seconds, never when
+/// unknown
+fn display_optional_duration(seconds: &Option<i64>) -> String {
+    seconds.as_ref().map(display_duration).unwrap_or_default()
+}
+
+/// Display fractional table column in minutes
+fn display_mins_f(mins: &f64) -> String {
+    format!("{} m", mins)
+}
+
+/// Display table column in days
+fn display_days(days: &f64) -> String {
+    format!("{} days", days)
+}
+
+/// Display table column in satoshis per virtual byte
+fn display_fee_rate(fee_rate: &f64) -> String {
+    format!("{} sat/vB", fee_rate)
+}
+
+/// Display optional table column ratio as a percentage
+fn display_optional_percent(ratio: &Option<f64>) -> String {
+    ratio.as_ref().map(display_percent).unwrap_or_default()
+}
+
+/// Display a relative standard error as a percentage, unbounded without
+/// any occurrence
+pub(super) fn display_relative_error(error: f64) -> String {
+    match error.is_finite() {
+        true => format!("{:.0}%", error * 100.0),
+        false => String::from("unbounded"),
+    }
+}
+
+/// Display optional table column p-value
+fn display_optional_p_value(p_value: &Option<f64>) -> String {
+    p_value
+        .map(|p_value| p_value.to_string())
+        .unwrap_or_default()
+}
+
+/// Display optional table column in US dollars
+fn display_optional_usd(usd: &Option<f64>) -> String {
+    usd.map(|usd| format!("${:.2}", usd)).unwrap_or_default()
+}
+
+/// Display table column in hashes per second with an SI prefix
+pub(crate) fn display_hashrate(hashrate: &f64) -> String {
+    let prefixes = ["", "k", "M", "G", "T", "P", "E", "Z"];
+    let mut hashrate = *hashrate;
+    let mut prefix = 0;
+
+    while hashrate >= 1000.0 && prefix < prefixes.len() - 1 {
+        hashrate /= 1000.0;
+        prefix += 1;
+    }
+
+    format!("{:.2} {}H/s", hashrate, prefixes[prefix])
+}
+
+/// Display table column ratio as a percentage
+fn display_percent(ratio: &f64) -> String {
+    format!("{:.2}%", ratio * 100.0)
+}
+
+/// Display table column probability in scientific notation
+fn display_probability(probability: &f64) -> String {
+    format!("{:.3e}", probability)
+}
+
+/// Display table column number of blocks, never when unknown
+fn display_optional_blocks(blocks: &Option<f64>) -> String {
+    match blocks {
+        Some(blocks) => format!("{:.0}", blocks),
+        None => String::from("Never"),
+    }
+}
@@ -0,0 +1,419 @@
+//! Backend reading blocks straight from the `blocks/blk*.dat` files of a
+//! local node instead of its RPC, for scans of the full population.
+//!
+//! The files hold every block received by the node in the order received,
+//! each record being the network magic, the size of the block and the
+//! block. The headers of all the records are indexed once by reading their
+//! first 80 bytes, and the chain is linked from the genesis block up to the
+//! highest block. The node may keep appending to the files while indexed,
+//! an incomplete record ends its file. Files obfuscated with the key of
+//! `xor.dat` are read as well.
+//!
+//! Like the Electrum backend, the backend answers the calls of bitcoind
+//! made by the RPC client, so every header and full block analysis runs
+//! unchanged. Block statistics are not served.
+
+use bitcoincore_rpc::bitcoin::{
+    consensus::encode::{deserialize, serialize_hex},
+    hashes::hex::ToHex,
+    BlockHash, BlockHeader,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::transport::{
+    get_rpc_error, Answer, Answered, RPC_INVALID_ADDRESS_OR_KEY, RPC_INVALID_PARAMETER,
+    RPC_METHOD_NOT_FOUND,
+};
+use crate::utils::{get_difficulty, get_median};
+
+/// Bytes of the magic and size preceding every block of a file.
+const RECORD_PREFIX: u64 = 8;
+/// Bytes of a serialized block header.
+const HEADER_SIZE: u64 = 80;
+/// Number of blocks of the median time past of a block.
+const MEDIAN_TIME_SPAN: usize = 11;
+
+/// Location of a block within the block files.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct BlockLocation {
+    file: usize,
+    offset: u64,
+    size: u32,
+    header: BlockHeader,
+}
+
+/// Index of the blocks of the block files of a node along with the chain
+/// linked from the genesis block, shared by the transports of a pool.
+#[derive(Debug)]
+pub struct BlockFileIndex {
+    files: Vec<PathBuf>,
+    key: Vec<u8>,
+    blocks: HashMap<BlockHash, BlockLocation>,
+    chain: Vec<BlockHash>,
+}
+
+/// Backend answering the RPC calls of bitcoind from the block files.
+pub struct BlockFileBackend {
+    index: Arc<BlockFileIndex>,
+}
+
+impl BlockFileIndex {
+    /// Index the block files of the blocks directory of a node, e.g.
+    /// `~/.bitcoin/blocks`.
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        let mut files = fs::read_dir(dir)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                let name = path.file_name().and_then(|name| name.to_str());
+                name.is_some_and(|name| name.starts_with("blk") && name.ends_with(".dat"))
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+
+        if files.is_empty() {
+            let e = format!("No blk*.dat files found in {}", dir.display());
+            return Err(io::Error::new(io::ErrorKind::NotFound, e));
+        }
+
+        // Files are not obfuscated without a key
+        let key = match fs::read(dir.join("xor.dat")) {
+            Ok(key) if key.iter().any(|byte| *byte != 0) => key,
+            Ok(_) => vec![],
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e),
+        };
+
+        let mut index = BlockFileIndex {
+            files,
+            key,
+            blocks: HashMap::new(),
+            chain: vec![],
+        };
+
+        for file in 0..index.files.len() {
+            index.index_file(file)?;
+        }
+
+        index.link_chain();
+
+        match index.is_empty() {
+            true => {
+                let e = "No genesis block found, pruned block files are not supported";
+                Err(io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            false => Ok(index),
+        }
+    }
+
+    /// Number of blocks of the chain.
+    pub fn len(&self) -> usize {
+        self.chain.len()
+    }
+
+    /// Check whether the chain holds no block.
+    pub fn is_empty(&self) -> bool {
+        self.chain.is_empty()
+    }
+
+    /// Index the header of every complete record of the file.
+    fn index_file(&mut self, file: usize) -> io::Result<()> {
+        let mut reader = fs::File::open(&self.files[file])?;
+        let length = reader.metadata()?.len();
+        let mut offset = 0;
+
+        while offset + RECORD_PREFIX + HEADER_SIZE <= length {
+            let prefix = self.read_at(&mut reader, offset, RECORD_PREFIX as usize)?;
+            let size = u32::from_le_bytes([prefix[4], prefix[5], prefix[6], prefix[7]]);
+
+            // Preallocated space is zeroed
+            if prefix[..4] == [0; 4] || offset + RECORD_PREFIX + size as u64 > length {
+                break;
+            }
+
+            let bytes = self.read_at(&mut reader, offset + RECORD_PREFIX, HEADER_SIZE as usize)?;
+            let header = deserialize::<BlockHeader>(&bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            self.blocks.insert(
+                header.block_hash(),
+                BlockLocation {
+                    file,
+                    offset: offset + RECORD_PREFIX,
+                    size,
+                    header,
+                },
+            );
+            offset += RECORD_PREFIX + size as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Link the chain of the highest block back to the genesis block, the
+    /// block without a parent. Stale branches are left out, as are blocks
+    /// not linked to the genesis block, e.g. the blocks of pruned files.
+    fn link_chain(&mut self) {
+        // Height of every block, none when not linked to the genesis block
+        let mut heights: HashMap<BlockHash, Option<usize>> = HashMap::new();
+
+        for hash in self.blocks.keys() {
+            // Walk back to the first block of known height
+            let mut path = vec![];
+            let mut current = *hash;
+
+            let base = loop {
+                if let Some(height) = heights.get(&current) {
+                    break height.map(|height| height + 1);
+                }
+
+                match self.blocks.get(&current) {
+                    Some(block) => {
+                        path.push(current);
+                        current = block.header.prev_blockhash;
+                    }
+                    None if current == BlockHash::default() => break Some(0),
+                    None => break None,
+                }
+            };
+
+            for (i, hash) in path.into_iter().rev().enumerate() {
+                heights.insert(hash, base.map(|height| height + i));
+            }
+        }
+
+        let heights = heights
+            .into_iter()
+            .filter_map(|(hash, height)| Some((hash, height?)))
+            .collect::<HashMap<_, _>>();
+
+        // Earliest file first among blocks of the same height
+        let tip = heights.iter().max_by(|(a, a_height), (b, b_height)| {
+            let position = |hash: &BlockHash| {
+                let block = &self.blocks[hash];
+                (block.file, block.offset)
+            };
+            a_height
+                .cmp(b_height)
+                .then_with(|| position(b).cmp(&position(a)))
+        });
+
+        let mut chain = vec![];
+        let mut current = tip.map(|(hash, _)| *hash);
+
+        while let Some(hash) = current {
+            chain.push(hash);
+            current = self
+                .blocks
+                .get(&self.blocks[&hash].header.prev_blockhash)
+                .map(|parent| parent.header.block_hash());
+        }
+
+        chain.reverse();
+        self.chain = chain;
+    }
+
+    /// Read the bytes of the file at the offset, removing the obfuscation.
+    fn read_at(&self, reader: &mut fs::File, offset: u64, size: usize) -> io::Result<Vec<u8>> {
+        let mut bytes = vec![0; size];
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut bytes)?;
+
+        if !self.key.is_empty() {
+            bytes.iter_mut().enumerate().for_each(|(i, byte)| {
+                *byte ^= self.key[(offset as usize + i) % self.key.len()];
+            });
+        }
+
+        Ok(bytes)
+    }
+
+    /// Read the serialized block of the hash.
+    fn read_block(&self, location: &BlockLocation) -> io::Result<Vec<u8>> {
+        let mut reader = fs::File::open(&self.files[location.file])?;
+        self.read_at(&mut reader, location.offset, location.size as usize)
+    }
+
+    /// Get the block of the hash in the format of a verbose getblockheader.
+    fn get_header_info(&self, hash: &BlockHash, location: &BlockLocation) -> Value {
+        let height = self.chain.iter().position(|block| block == hash);
+        let header = location.header;
+        let times = match height {
+            Some(height) => self.chain[(height + 1).saturating_sub(MEDIAN_TIME_SPAN)..=height]
+                .iter()
+                .map(|hash| self.blocks[hash].header.time as f64)
+                .collect(),
+            None => vec![header.time as f64],
+        };
+
+        json!({
+            "hash": hash,
+            // Stale blocks are not part of the chain
+            "confirmations": height.map_or(-1, |height| (self.chain.len() - height) as i64),
+            "height": height.unwrap_or_default(),
+            "version": header.version,
+            "merkleroot": header.merkle_root,
+            "time": header.time,
+            "mediantime": get_median(&times) as u64,
+            "nonce": header.nonce,
+            "bits": format!("{:08x}", header.bits),
+            "difficulty": get_difficulty(header.bits),
+            // Neither is indexed from the headers
+            "chainwork": "00",
+            "nTx": 0,
+            "previousblockhash": header.prev_blockhash,
+        })
+    }
+
+    /// Answer the call of bitcoind from the index. Failures reading the
+    /// files are errors of the transport.
+    fn answer(&self, method: &str, params: &[Value]) -> io::Result<Answered> {
+        let block = || {
+            let location = params
+                .first()
+                .and_then(|hash| serde_json::from_value::<BlockHash>(hash.clone()).ok())
+                .and_then(|hash| Some((hash, self.blocks.get(&hash)?)));
+
+            location.ok_or_else(|| get_rpc_error(RPC_INVALID_ADDRESS_OR_KEY, "Block not found"))
+        };
+
+        Ok(match method {
+            "getblockcount" => Ok(json!(self.chain.len().saturating_sub(1))),
+            "getblockhash" => params
+                .first()
+                .and_then(Value::as_u64)
+                .and_then(|height| self.chain.get(height as usize))
+                .map(|hash| json!(hash))
+                .ok_or_else(|| get_rpc_error(RPC_INVALID_PARAMETER, "Block height out of range")),
+            "getblockheader" => match params.get(1).and_then(Value::as_bool).unwrap_or(true) {
+                true => block().map(|(hash, location)| self.get_header_info(&hash, location)),
+                false => block().map(|(_, location)| json!(serialize_hex(&location.header))),
+            },
+            // Only raw blocks are served
+            "getblock" if params.get(1).and_then(Value::as_u64) == Some(0) => match block() {
+                Ok((_, location)) => Ok(json!(self.read_block(location)?.to_hex())),
+                Err(error) => Err(error),
+            },
+            method => Err(get_rpc_error(
+                RPC_METHOD_NOT_FOUND,
+                format!("{} is not served by the block files", method),
+            )),
+        })
+    }
+}
+
+impl BlockFileBackend {
+    /// Create a new backend reading from the indexed block files.
+    pub fn new(index: Arc<BlockFileIndex>) -> Self {
+        BlockFileBackend { index }
+    }
+}
+
+impl Answer for BlockFileBackend {
+    fn answer(&self, method: &str, params: &[Value]) -> io::Result<Answered> {
+        self.index.answer(method, params)
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} block files", self.index.files.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::{
+        bitcoin::{blockdata::constants::genesis_block, consensus::serialize, Block, Network},
+        jsonrpc, Client, RpcApi,
+    };
+
+    use crate::transport::AnswerTransport;
+
+    /// Serialize the blocks as records of a block file.
+    fn records(blocks: &[&Block]) -> Vec<u8> {
+        blocks
+            .iter()
+            .flat_map(|block| {
+                let block = serialize(*block);
+                [0xf9, 0xbe, 0xb4, 0xd9]
+                    .into_iter()
+                    .chain((block.len() as u32).to_le_bytes())
+                    .chain(block)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_block_files() {
+        let genesis = genesis_block(Network::Bitcoin);
+        let child = |parent: &Block, nonce| Block {
+            header: BlockHeader {
+                prev_blockhash: parent.block_hash(),
+                time: parent.header.time + 600,
+                nonce,
+                ..parent.header
+            },
+            txdata: genesis.txdata.clone(),
+        };
+        let first = child(&genesis, 1);
+        let stale = child(&genesis, 2);
+        let second = child(&first, 3);
+
+        let dir = std::env::temp_dir().join(format!("blocks-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // Obfuscated, received out of order and ending with a partial record
+        let key = [7, 0, 1, 2, 3, 4, 5, 6];
+        let mut second_file = records(&[&second]);
+        second_file.extend([0xf9, 0xbe, 0xb4, 0xd9, 0xff, 0, 0, 0, 1]);
+        for (name, mut bytes) in [
+            ("blk00000.dat", records(&[&genesis, &stale, &first])),
+            ("blk00001.dat", second_file),
+        ] {
+            bytes
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, byte)| *byte ^= key[i % key.len()]);
+            fs::write(dir.join(name), bytes).unwrap();
+        }
+        fs::write(dir.join("xor.dat"), key).unwrap();
+
+        let index = Arc::new(BlockFileIndex::load(&dir).unwrap());
+        let transport = AnswerTransport(BlockFileBackend::new(index));
+        let client = Client::from_jsonrpc(jsonrpc::Client::with_transport(transport));
+
+        assert_eq!(client.get_block_count().unwrap(), 2);
+        let hashes = (0..3)
+            .map(|height| client.get_block_hash(height).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hashes,
+            vec![
+                genesis.block_hash(),
+                first.block_hash(),
+                second.block_hash()
+            ]
+        );
+        assert!(client.get_block_hash(3).is_err());
+        assert_eq!(client.get_block_header(&hashes[2]).unwrap(), second.header);
+        assert_eq!(client.get_block(&hashes[1]).unwrap(), first);
+        assert!(client.get_block_stats(0).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let info = client.get_block_header_info(&hashes[1]).unwrap();
+        assert_eq!(info.height, 1);
+        assert_eq!(info.confirmations, 2);
+        assert_eq!(
+            client
+                .get_block_header_info(&stale.block_hash())
+                .unwrap()
+                .confirmations,
+            -1
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use bitcoincore_rpc::bitcoin::{
+    consensus::{deserialize, serialize},
+    BlockHash, BlockHeader,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::Path,
+    time::Duration,
+};
+
+use crate::{BlockHeight, Result, StatisticsError};
+
+/// File name of the cache database within the cache directory.
+const CACHE_FILE: &str = "headers.sqlite";
+/// Depth below the tip after which headers are cached, shallower headers
+/// may still be reorganized. Matches the coinbase maturity.
+pub const CACHE_DEPTH: u64 = 100;
+/// Time to wait for another run writing to the cache.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Damage of the cache found by verifying it, see HeaderCache::verify.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct CacheDamage {
+    /// Heights of the headers not decoding or not hashing to their hash
+    pub corrupt: Vec<BlockHeight>,
+    /// Heights of the headers not linking to the header cached before them
+    pub broken_links: Vec<BlockHeight>,
+    /// First and last heights of every range missing between the lowest and
+    /// highest cached header
+    pub gaps: Vec<(BlockHeight, BlockHeight)>,
+}
+
+impl CacheDamage {
+    /// Get the heights of the damaged headers, lowest first. A broken link
+    /// damages the headers on both of its sides, as either may be wrong.
+    pub fn get_damaged_heights(&self) -> Vec<BlockHeight> {
+        let links = self
+            .broken_links
+            .iter()
+            .flat_map(|height| [*height - 1, *height]);
+        let heights = self.corrupt.iter().copied().chain(links);
+
+        heights.collect::<BTreeSet<_>>().into_iter().collect()
+    }
+}
+
+/// Local cache of block headers keyed by block height, backed by SQLite, so
+/// repeated analyses only fetch the missing headers from bitcoind. The cache
+/// records the genesis block of its chain, as heights of different networks
+/// never share headers.
+pub struct HeaderCache {
+    connection: Connection,
+}
+
+impl HeaderCache {
+    /// Open the cache of the chain of the genesis block within the
+    /// directory, creating both when missing. The database is written ahead
+    /// to a log, so an interrupted run never leaves a half-written cache
+    /// behind and other runs can keep reading. A cache of another chain is
+    /// refused.
+    pub fn open(dir: &Path, genesis: &BlockHash) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let connection = Connection::open(dir.join(CACHE_FILE))?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.pragma_update(None, "synchronous", "NORMAL")?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        HeaderCache::new(connection, genesis)
+    }
+
+    /// Open a cache of the chain of the genesis block held in memory,
+    /// discarded once dropped.
+    pub fn open_in_memory(genesis: &BlockHash) -> Result<Self> {
+        HeaderCache::new(Connection::open_in_memory()?, genesis)
+    }
+
+    fn new(connection: Connection, genesis: &BlockHash) -> Result<Self> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS headers (
+                height INTEGER PRIMARY KEY,
+                hash TEXT NOT NULL UNIQUE,
+                header BLOB NOT NULL
+            )",
+            [],
+        )?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        let cached = connection
+            .query_row("SELECT value FROM meta WHERE key = 'genesis'", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()?;
+
+        match cached {
+            Some(cached) if cached != genesis.to_string() => {
+                return Err(StatisticsError::CacheChainError {
+                    cached,
+                    genesis: genesis.to_string(),
+                });
+            }
+            Some(_) => {}
+            None => {
+                // The chain of headers cached before the genesis block was
+                // recorded is unknown, so they are dropped
+                connection.execute("DELETE FROM headers", [])?;
+                connection.execute(
+                    "INSERT INTO meta (key, value) VALUES ('genesis', ?1)",
+                    [genesis.to_string()],
+                )?;
+            }
+        }
+
+        Ok(HeaderCache { connection })
+    }
+
+    /// Get the cached header at the height.
+    pub fn get(&self, height: BlockHeight) -> Result<Option<BlockHeader>> {
+        let header = self
+            .connection
+            .query_row(
+                "SELECT header FROM headers WHERE height = ?1",
+                [height.0],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()?;
+
+        Ok(header.map(|header| deserialize(&header)).transpose()?)
+    }
+
+    /// Get the cached headers of the heights keyed by height. Heights not
+    /// in the cache are missing from the result.
+    pub fn get_many(&self, heights: &[BlockHeight]) -> Result<HashMap<BlockHeight, BlockHeader>> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT header FROM headers WHERE height = ?1")?;
+        let mut headers = HashMap::new();
+
+        for height in heights {
+            let header = statement
+                .query_row([height.0], |row| row.get::<_, Vec<u8>>(0))
+                .optional()?;
+
+            if let Some(header) = header {
+                headers.insert(*height, deserialize(&header)?);
+            }
+        }
+
+        Ok(headers)
+    }
+
+    /// Insert the headers keyed by height, replacing any cached header at
+    /// the same height. The headers are inserted in a single transaction so
+    /// either all or none of them are cached, and inserting them again is
+    /// harmless.
+    pub fn insert(&mut self, headers: &[(BlockHeight, BlockHeader)]) -> Result<()> {
+        let transaction = self.connection.transaction()?;
+
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO headers (height, hash, header) VALUES (?1, ?2, ?3)
+                ON CONFLICT (height) DO UPDATE SET hash = excluded.hash, header = excluded.header",
+            )?;
+
+            for (height, header) in headers {
+                statement.execute(params![
+                    height.0,
+                    header.block_hash().to_string(),
+                    serialize(header)
+                ])?;
+            }
+        }
+
+        Ok(transaction.commit()?)
+    }
+
+    /// Verify the cached headers in order of height. Every header needs to
+    /// decode and hash to its recorded hash, and to link to the hash of the
+    /// header cached at the height before it. Heights missing between the
+    /// cached headers are reported as gaps, expected of cached samples.
+    pub fn verify(&self) -> Result<CacheDamage> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT height, hash, header FROM headers ORDER BY height")?;
+        let mut rows = statement.query([])?;
+        let mut damage = CacheDamage::default();
+        // Height and hash of the previous header, without a hash when corrupt
+        let mut previous: Option<(u64, Option<BlockHash>)> = None;
+
+        while let Some(row) = rows.next()? {
+            let height: u64 = row.get(0)?;
+            let hash: String = row.get(1)?;
+            let header = deserialize::<BlockHeader>(&row.get::<_, Vec<u8>>(2)?)
+                .ok()
+                .filter(|header| header.block_hash().to_string() == hash);
+
+            if header.is_none() {
+                damage.corrupt.push(BlockHeight(height));
+            }
+
+            match previous {
+                Some((parent, _)) if parent + 1 < height => {
+                    damage
+                        .gaps
+                        .push((BlockHeight(parent + 1), BlockHeight(height - 1)));
+                }
+                Some((_, Some(parent_hash)))
+                    if header.is_some_and(|header| header.prev_blockhash != parent_hash) =>
+                {
+                    damage.broken_links.push(BlockHeight(height));
+                }
+                _ => {}
+            }
+
+            previous = Some((height, header.map(|header| header.block_hash())));
+        }
+
+        Ok(damage)
+    }
+
+    /// Number of cached headers.
+    pub fn len(&self) -> Result<u64> {
+        let count = self
+            .connection
+            .query_row("SELECT COUNT(*) FROM headers", [], |row| row.get(0))?;
+
+        Ok(count)
+    }
+
+    /// Check whether no headers are cached.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::{blockdata::constants::genesis_block, Network};
+
+    fn genesis(network: Network) -> BlockHash {
+        genesis_block(network).block_hash()
+    }
+
+    fn header(time: u32) -> BlockHeader {
+        BlockHeader {
+            version: 1,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time,
+            bits: 0x1d00ffff,
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn test_header_cache() {
+        let mut cache = HeaderCache::open_in_memory(&genesis(Network::Bitcoin)).unwrap();
+        assert!(cache.is_empty().unwrap());
+
+        let headers = [(BlockHeight(1), header(100)), (BlockHeight(2), header(200))];
+        cache.insert(&headers).unwrap();
+        assert_eq!(cache.len().unwrap(), 2);
+        assert_eq!(cache.get(BlockHeight(1)).unwrap(), Some(header(100)));
+        assert_eq!(cache.get(BlockHeight(3)).unwrap(), None);
+
+        let headers = cache.get_many(&[BlockHeight(2), BlockHeight(3)]).unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[&BlockHeight(2)], header(200));
+
+        cache.insert(&[(BlockHeight(2), header(300))]).unwrap();
+        assert_eq!(cache.len().unwrap(), 2);
+        assert_eq!(cache.get(BlockHeight(2)).unwrap(), Some(header(300)));
+    }
+
+    #[test]
+    fn test_verify() {
+        // Headers linking to the header before them at heights 0 to 5
+        let mut chain: Vec<(BlockHeight, BlockHeader)> = vec![];
+        for height in 0..6 {
+            let prev_blockhash = chain.last().map(|(_, parent)| parent.block_hash());
+            let header = BlockHeader {
+                prev_blockhash: prev_blockhash.unwrap_or_default(),
+                ..header(height as u32)
+            };
+            chain.push((BlockHeight(height), header));
+        }
+        let mut cache = HeaderCache::open_in_memory(&chain[0].1.block_hash()).unwrap();
+        cache.insert(&chain).unwrap();
+        assert_eq!(cache.verify().unwrap(), CacheDamage::default());
+
+        cache.insert(&[(BlockHeight(1), header(100))]).unwrap();
+        cache
+            .connection
+            .execute("DELETE FROM headers WHERE height = 3", [])
+            .unwrap();
+        cache
+            .connection
+            .execute("UPDATE headers SET header = x'00' WHERE height = 5", [])
+            .unwrap();
+        let damage = cache.verify().unwrap();
+        assert_eq!(
+            damage,
+            CacheDamage {
+                corrupt: vec![BlockHeight(5)],
+                broken_links: vec![BlockHeight(1), BlockHeight(2)],
+                gaps: vec![(BlockHeight(3), BlockHeight(3))],
+            }
+        );
+        assert_eq!(
+            damage.get_damaged_heights(),
+            vec![
+                BlockHeight(0),
+                BlockHeight(1),
+                BlockHeight(2),
+                BlockHeight(5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_header_cache_file() {
+        let dir = std::env::temp_dir().join("bitcoin-statistics-test-cache");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mainnet = genesis(Network::Bitcoin);
+        let mut cache = HeaderCache::open(&dir, &mainnet).unwrap();
+        let mode: String = cache
+            .connection
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+
+        cache.insert(&[(BlockHeight(1), header(100))]).unwrap();
+        cache.insert(&[(BlockHeight(1), header(100))]).unwrap();
+        drop(cache);
+        assert_eq!(HeaderCache::open(&dir, &mainnet).unwrap().len().unwrap(), 1);
+
+        // The heights of another network never share the cached headers
+        let testnet = genesis(Network::Testnet);
+        assert!(matches!(
+            HeaderCache::open(&dir, &testnet),
+            Err(StatisticsError::CacheChainError { .. })
+        ));
+        assert_eq!(HeaderCache::open(&dir, &mainnet).unwrap().len().unwrap(), 1);
+
+        // A cache without its genesis block recorded is of an unknown chain
+        let connection = Connection::open(dir.join(CACHE_FILE)).unwrap();
+        connection.execute("DELETE FROM meta", []).unwrap();
+        drop(connection);
+        let cache = HeaderCache::open(&dir, &testnet).unwrap();
+        assert!(cache.is_empty().unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
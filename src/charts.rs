@@ -0,0 +1,348 @@
+use std::{io::Write, path::Path};
+
+use plotters::{coord::Shift, prelude::*};
+
+use crate::{
+    BlockTimeDriftReport, DifficultyTrendReport, Result, StatisticsError, RETARGET_INTERVAL,
+};
+
+/// Size of a chart in pixels.
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 480;
+/// Font of the title and the labels of a chart.
+const FONT: &str = "sans-serif";
+/// Buckets of the histogram of a chart.
+const CHART_BUCKETS: usize = 60;
+/// Colors of the bars, the fit and the outliers of a chart.
+const BAR: RGBColor = RGBColor(0x4c, 0x78, 0xa8);
+const FIT: RGBColor = RGBColor(0xf5, 0x85, 0x18);
+const OUTLIER: RGBColor = RGBColor(0xe4, 0x57, 0x56);
+
+/// Chart of a report, drawn to any backend of plotters.
+#[derive(Clone, Copy, Debug)]
+enum Chart<'a> {
+    Drift(&'a BlockTimeDriftReport),
+    Difficulty(&'a DifficultyTrendReport),
+}
+
+/// Render the chart of the block time drift report to the file, an SVG
+/// or PNG file by its extension, see write_drift_chart.
+pub fn save_drift_chart(report: &BlockTimeDriftReport, path: &Path) -> Result<()> {
+    Chart::Drift(report).save(path)
+}
+
+/// Render the chart of the difficulty trend report to the file, an SVG or
+/// PNG file by its extension, see write_difficulty_chart.
+pub fn save_difficulty_chart(report: &DifficultyTrendReport, path: &Path) -> Result<()> {
+    Chart::Difficulty(report).save(path)
+}
+
+/// Write an SVG chart of the distribution of the mining times of the report
+/// as a histogram, along with the exponential fit at the mean mining time
+/// and the drifts reaching the drift time marked as outliers.
+pub fn write_drift_chart<W: Write>(report: &BlockTimeDriftReport, writer: W) -> Result<()> {
+    Chart::Drift(report).write(writer)
+}
+
+/// Write an SVG chart of the difficulty of the sampled epochs of the report
+/// against the height they start at on a log scale, with the largest
+/// retargets marked.
+pub fn write_difficulty_chart<W: Write>(report: &DifficultyTrendReport, writer: W) -> Result<()> {
+    Chart::Difficulty(report).write(writer)
+}
+
+impl Chart<'_> {
+    /// Render the chart to the SVG or PNG file, other files are not
+    /// supported.
+    fn save(&self, path: &Path) -> Result<()> {
+        let size = (WIDTH, HEIGHT);
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("svg") => self.draw(SVGBackend::new(path, size).into_drawing_area()),
+            Some("png") => self.draw(BitMapBackend::new(path, size).into_drawing_area()),
+            _ => Err(StatisticsError::ChartError(format!(
+                "{} is not an SVG or PNG file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Write the chart as an SVG document.
+    fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        let mut svg = String::new();
+        self.draw(SVGBackend::with_string(&mut svg, (WIDTH, HEIGHT)).into_drawing_area())?;
+        writer.write_all(svg.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Draw the chart to the area and present it.
+    fn draw<DB: DrawingBackend>(&self, area: DrawingArea<DB, Shift>) -> Result<()> {
+        let drawn = match self {
+            Chart::Drift(report) => draw_drift_chart(report, &area),
+            Chart::Difficulty(report) => draw_difficulty_chart(report, &area),
+        };
+
+        drawn
+            .and_then(|_| area.present())
+            .map_err(|e| StatisticsError::ChartError(e.to_string()))
+    }
+}
+
+/// Draw the histogram of the mining times of the report, see
+/// write_drift_chart.
+fn draw_drift_chart<DB: DrawingBackend>(
+    report: &BlockTimeDriftReport,
+    area: &DrawingArea<DB, Shift>,
+) -> DrawResult<(), DB> {
+    let deltas = &report.deltas;
+    let low = deltas.iter().fold(0.0_f64, |low, delta| low.min(*delta));
+    let high = deltas
+        .iter()
+        .fold(low + 1.0, |high, delta| high.max(*delta));
+    let width = (high - low) / CHART_BUCKETS as f64;
+
+    let mut counts = [0_usize; CHART_BUCKETS];
+    deltas.iter().for_each(|delta| {
+        counts[(((delta - low) / width) as usize).min(CHART_BUCKETS - 1)] += 1;
+    });
+
+    // Count expected of exponential mining times within the minutes
+    let beyond = |minutes: f64| (-minutes.max(0.0) / report.mean).exp();
+    let expected = (0..CHART_BUCKETS)
+        .map(|i| {
+            let start = low + i as f64 * width;
+            (beyond(start) - beyond(start + width)) * deltas.len() as f64
+        })
+        .collect::<Vec<_>>();
+    let max = expected
+        .iter()
+        .fold(*counts.iter().max().unwrap_or(&0) as f64, |max, count| {
+            max.max(*count)
+        })
+        .max(1.0);
+
+    area.fill(&WHITE)?;
+    let caption = format!(
+        "Mining Times ({} intervals, mean {:.2} minutes)",
+        deltas.len(),
+        report.mean
+    );
+    let mut chart = ChartBuilder::on(area)
+        .caption(caption, (FONT, 16))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(low..high, 0.0..max)?;
+
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("Minutes")
+        .y_desc("Intervals")
+        .x_label_formatter(&|minutes| format!("{:.0}", minutes))
+        .y_label_formatter(&|count| format!("{:.0}", count))
+        .label_style((FONT, 12))
+        .draw()?;
+
+    chart.draw_series(counts.iter().enumerate().map(|(i, count)| {
+        let start = low + i as f64 * width;
+        Rectangle::new([(start, 0.0), (start + width, *count as f64)], BAR.filled())
+    }))?;
+
+    chart.draw_series(LineSeries::new(
+        expected
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (low + (i as f64 + 0.5) * width, *count)),
+        FIT.stroke_width(2),
+    ))?;
+
+    chart.draw_series(
+        report
+            .entries
+            .iter()
+            .map(|entry| Circle::new((entry.drift as f64, 0.0), 4, OUTLIER.filled())),
+    )?;
+
+    Ok(())
+}
+
+/// Draw the difficulty of the sampled epochs of the report, see
+/// write_difficulty_chart.
+fn draw_difficulty_chart<DB: DrawingBackend>(
+    report: &DifficultyTrendReport,
+    area: &DrawingArea<DB, Shift>,
+) -> DrawResult<(), DB> {
+    let points = report
+        .entries
+        .iter()
+        .filter(|entry| entry.difficulty > 0.0)
+        .map(|entry| {
+            (
+                (entry.epoch * RETARGET_INTERVAL) as f64,
+                entry.difficulty,
+                entry,
+            )
+        })
+        .collect::<Vec<_>>();
+    let bounds = |values: &mut dyn Iterator<Item = f64>| {
+        values.fold((f64::MAX, f64::MIN), |(low, high), value| {
+            (low.min(value), high.max(value))
+        })
+    };
+    let (left, right) = match bounds(&mut points.iter().map(|(height, ..)| *height)) {
+        (left, right) if left < right => (left, right),
+        (left, _) => (left.min(0.0), left.max(0.0) + 1.0),
+    };
+    let (low, high) = match bounds(&mut points.iter().map(|(_, difficulty, _)| *difficulty)) {
+        (low, high) if low < high => (low, high),
+        (low, _) if low < f64::MAX => (low / 2.0, low * 2.0),
+        _ => (1.0, 10.0),
+    };
+
+    area.fill(&WHITE)?;
+    let caption = format!("Difficulty ({} epochs, log scale)", points.len());
+    let mut chart = ChartBuilder::on(area)
+        .caption(caption, (FONT, 16))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(70)
+        .build_cartesian_2d(left..right, (low..high).log_scale())?;
+
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("Height")
+        .x_label_formatter(&|height| format!("{:.0}", height))
+        .y_label_formatter(&|difficulty| format!("{:.2e}", difficulty))
+        .label_style((FONT, 12))
+        .draw()?;
+
+    chart.draw_series(LineSeries::new(
+        points
+            .iter()
+            .map(|(height, difficulty, _)| (*height, *difficulty)),
+        BAR.stroke_width(2),
+    ))?;
+
+    chart.draw_series(
+        points
+            .iter()
+            .filter(|(.., entry)| !entry.extreme.is_empty())
+            .map(|(height, difficulty, _)| {
+                Circle::new((*height, *difficulty), 4, OUTLIER.filled())
+            }),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BlockHeight, BlockSampleData, BlockStatistics, MinDifficultyBlocks};
+    use bitcoincore_rpc::bitcoin::{BlockHeader, Network};
+
+    /// Draw the chart to the pixels of an image, checking that it also
+    /// renders to a PNG file.
+    fn get_pixels(chart: Chart, name: &str) -> Vec<u8> {
+        let mut pixels = vec![0; (WIDTH * HEIGHT * 3) as usize];
+        let area = BitMapBackend::with_buffer(&mut pixels, (WIDTH, HEIGHT)).into_drawing_area();
+        chart.draw(area).unwrap();
+
+        let path = std::env::temp_dir().join(format!("{}-{}.png", name, std::process::id()));
+        chart.save(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap()[..8], *b"\x89PNG\r\n\x1a\n");
+        std::fs::remove_file(path).unwrap();
+
+        pixels
+    }
+
+    /// Check whether the pixels contain the color.
+    fn has_color(pixels: &[u8], color: RGBColor) -> bool {
+        pixels
+            .chunks(3)
+            .any(|pixel| pixel == [color.0, color.1, color.2])
+    }
+
+    #[test]
+    fn test_write_drift_chart() {
+        let headers = [0, 600, 1500, 1560, 3960]
+            .iter()
+            .enumerate()
+            .map(|(i, time)| {
+                let header = BlockHeader {
+                    version: 1,
+                    prev_blockhash: Default::default(),
+                    merkle_root: Default::default(),
+                    time: 1_600_000_000 + time,
+                    bits: 0x1d00ffff,
+                    nonce: i as u32,
+                };
+                (BlockHeight(i as u64), header)
+            })
+            .collect();
+        let report = BlockSampleData::new(headers).fetch_block_time_drift(
+            1200,
+            5,
+            false,
+            Network::Bitcoin,
+            MinDifficultyBlocks::Tag,
+            1.96,
+        );
+
+        let mut svg = vec![];
+        write_drift_chart(&report, &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("Mining Times (4 intervals"));
+        // The background and the bars of the histogram
+        assert_eq!(svg.matches("<rect").count(), CHART_BUCKETS + 1);
+        assert_eq!(svg.matches("<circle").count(), 1);
+        assert!(save_drift_chart(&report, Path::new("chart.gif")).is_err());
+
+        let pixels = get_pixels(Chart::Drift(&report), "drift");
+        assert!([BAR, FIT, OUTLIER, BLACK]
+            .iter()
+            .all(|color| has_color(&pixels, *color)));
+    }
+
+    #[test]
+    fn test_write_difficulty_chart() {
+        let headers = [(2015, 0x1d00ffff), (4031, 0x1c7fff80), (6047, 0x1d00ffff)]
+            .iter()
+            .map(|(height, bits)| {
+                let header = BlockHeader {
+                    version: 1,
+                    prev_blockhash: Default::default(),
+                    merkle_root: Default::default(),
+                    time: 1_600_000_000 + *height as u32 * 600,
+                    bits: *bits,
+                    nonce: 0,
+                };
+                (BlockHeight(*height), header)
+            })
+            .collect();
+        let report = BlockSampleData::new(headers).fetch_difficulty_trend(1);
+
+        let mut svg = vec![];
+        write_difficulty_chart(&report, &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("Difficulty (3 epochs, log scale)"));
+        // The largest upward and downward retargets
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert!(save_difficulty_chart(&report, Path::new("chart.gif")).is_err());
+
+        let pixels = get_pixels(Chart::Difficulty(&report), "difficulty");
+        assert!([BAR, OUTLIER, BLACK]
+            .iter()
+            .all(|color| has_color(&pixels, *color)));
+        assert!(!has_color(&pixels, FIT));
+    }
+}
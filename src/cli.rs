@@ -1,4 +1,20 @@
+use bitcoin_statistics::{
+    custom::{Aggregate, CustomScope},
+    exclusions::HeightRange,
+    l10n::Language,
+    planner::DataNeed,
+    render::OutputFormat,
+    templates::ExportTemplate,
+    transport::{FailurePolicy, DEFAULT_BATCH_SIZE, DEFAULT_CONCURRENCY},
+    utils, Backend, BlockHeight, MinDifficultyBlocks, SamplingStrategy, VERSIONBITS_NUM_BITS,
+};
+use bitcoincore_rpc::bitcoin::Network;
 use clap::{Parser, Subcommand};
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 #[derive(Parser, Debug)]
 #[clap(name = "Bitcoin Statistics")]
@@ -10,13 +26,22 @@ pub struct Args {
     #[clap(short, long, default_value_t = String::from("src/config"))]
     pub config: String,
 
+    /// Path of the cookie file of bitcoind to authenticate with instead of
+    /// the configured username and password (e.g. ~/.bitcoin/.cookie)
+    #[clap(long)]
+    pub cookie: Option<String>,
+
     /// Analysis to run
     #[clap(subcommand)]
     pub command: Analysis,
 
-    /// Z-Score for sampling
-    #[clap(short, long, default_value_t = 1.96)]
-    pub z_score: f64,
+    /// Confidence level for sampling, converted to its z-score
+    #[clap(long, default_value_t = 0.95, parse(try_from_str = parse_confidence))]
+    pub confidence: f64,
+
+    /// Z-Score for sampling, overrides the z-score of the confidence level
+    #[clap(short, long)]
+    pub z_score: Option<f64>,
 
     /// Margin of error for sampling
     #[clap(short, long, default_value_t = 0.05)]
@@ -30,6 +55,148 @@ pub struct Args {
     /// Small hack as clap does not handle bools properly
     #[clap(short, long, parse(try_from_str), default_value = "false")]
     pub full_population: bool,
+
+    /// Network of the connected node (bitcoin, testnet, signet, regtest)
+    #[clap(short, long, default_value_t = Network::Bitcoin)]
+    pub network: Network,
+
+    /// Memory budget for the collected blocks, a larger sample is thinned to fit (e.g. 512MB, 2GB)
+    #[clap(long, parse(try_from_str = parse_bytes))]
+    pub max_memory: Option<u64>,
+
+    /// Download budget for full block analyses (e.g. 512MB, 2GB)
+    #[clap(long, parse(try_from_str = parse_bytes))]
+    pub max_bytes: Option<u64>,
+
+    /// Time budget of the run (e.g. 90s, 30m, 2h), the partial sample
+    /// collected so far is analyzed once it runs out
+    #[clap(long, parse(try_from_str = parse_duration))]
+    pub timeout: Option<Duration>,
+
+    /// Path of a file of block heights or ranges to exclude from sampling, one per line
+    #[clap(long)]
+    pub exclude_heights: Option<PathBuf>,
+
+    /// Ranges of block heights to exclude from sampling (e.g. 0..210000,481824)
+    #[clap(long, value_delimiter = ',')]
+    pub exclude_ranges: Vec<HeightRange>,
+
+    /// Lowest block height of the population to sample from
+    #[clap(long)]
+    pub start_height: Option<BlockHeight>,
+
+    /// Highest block height of the population to sample from, defaults to the tip
+    #[clap(long)]
+    pub end_height: Option<BlockHeight>,
+
+    /// Sample the blocks from the UTC day or unix time (e.g. 2023-01-01)
+    #[clap(long, parse(try_from_str = parse_date))]
+    pub since: Option<u32>,
+
+    /// Sample the blocks before the UTC day or unix time (e.g. 2024-01-01)
+    #[clap(long, parse(try_from_str = parse_date))]
+    pub until: Option<u32>,
+
+    /// Strategy of drawing the sampled windows (uniform, stratified by
+    /// difficulty epoch, systematic every k-th window)
+    #[clap(long, default_value = "uniform")]
+    pub strategy: SamplingStrategy,
+
+    /// Ranges of block heights sampled as strata in proportion to their
+    /// size, implies the stratified strategy (e.g. 0..210000,210000..420000)
+    #[clap(long, value_delimiter = ',')]
+    pub strata: Vec<HeightRange>,
+
+    /// Seed of the random sampling to reproduce the sample of a previous
+    /// run, printed with every sampled run
+    #[clap(long)]
+    pub seed: Option<u64>,
+
+    /// Directory of the local cache of block headers, repeated header
+    /// analyses only fetch the headers missing from the cache
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Number of RPC clients fetching concurrently, ideally matching the
+    /// rpcthreads of bitcoind
+    #[clap(long, default_value_t = 4)]
+    pub rpc_clients: usize,
+
+    /// Number of headers fetched by every batched RPC request
+    #[clap(long, default_value_t = DEFAULT_BATCH_SIZE, parse(try_from_str = parse_count))]
+    pub batch_size: usize,
+
+    /// Number of times a request failing transiently is retried before the
+    /// block is left out of the sample
+    #[clap(long, default_value_t = 3)]
+    pub retries: u32,
+
+    /// Backoff before the first retry, doubled after every retry (e.g. 500ms, 2s)
+    #[clap(long, default_value = "500ms", parse(try_from_str = parse_duration))]
+    pub retry_backoff: Duration,
+
+    /// Fraction by which every backoff is randomized in either direction
+    #[clap(long, default_value_t = 0.5)]
+    pub retry_jitter: f64,
+
+    /// Handling of a block still failing once out of retries (skip, abort)
+    #[clap(long, default_value = "skip")]
+    pub on_failure: FailurePolicy,
+
+    /// Number of RPC requests in flight at once while fetching blocks
+    #[clap(long, default_value_t = DEFAULT_CONCURRENCY, parse(try_from_str = parse_count))]
+    pub concurrency: usize,
+
+    /// Format of the reports (table, json, csv, markdown)
+    #[clap(long, default_value = "table")]
+    pub format: OutputFormat,
+
+    /// Language of the titles, column headers and summaries of the reports
+    /// (en, es)
+    #[clap(long, default_value = "en")]
+    pub lang: Language,
+
+    /// Path of a file to write the results to instead of stdout (reports,
+    /// schemas, the run history and RPC results), progress stays on stderr
+    #[clap(long)]
+    pub output: Option<PathBuf>,
+
+    /// Path of a self-contained HTML page of the run with its parameters,
+    /// reports and charts, to share the results without the CLI
+    #[clap(long)]
+    pub report: Option<PathBuf>,
+
+    /// Path of the SQLite run history recording every run and its report
+    #[clap(long)]
+    pub history: Option<PathBuf>,
+
+    /// Path of a JSON file to record the RPC responses of the run to as
+    /// fixtures
+    #[clap(long, conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Path of a JSON file of recorded RPC responses to replay instead of
+    /// connecting to bitcoind
+    #[clap(long)]
+    pub replay: Option<PathBuf>,
+
+    /// Verify the proof of work of the collected headers before the
+    /// analysis, failing on any header not meeting its target. Only
+    /// analyses of headers or blocks collect headers to verify
+    #[clap(long)]
+    pub verify_pow: bool,
+
+    /// Backend of the configured source to fetch from instead of the
+    /// cheapest source serving the analysis (bitcoind, electrum, esplora,
+    /// blockfiles, rest)
+    #[clap(long)]
+    pub source: Option<Backend>,
+
+    /// OTLP/HTTP endpoint of an OpenTelemetry collector to export the
+    /// traces and metrics of the run to (e.g. http://localhost:4318), when
+    /// built with the otel feature
+    #[clap(long)]
+    pub otel_endpoint: Option<String>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -42,5 +209,777 @@ pub enum Analysis {
         /// Number of contiguous blocks within the sample
         #[clap(short, long, default_value_t = 2)]
         window: u64,
+        /// Handling of testnet minimum difficulty blocks (keep, tag, filter)
+        #[clap(long, default_value = "tag")]
+        min_difficulty: MinDifficultyBlocks,
+        /// Path of a CSV file to export the raw intervals of the sample to
+        #[clap(long)]
+        export_intervals: Option<PathBuf>,
+        /// Path of a file to export a row of every block of the sample to
+        #[clap(long)]
+        export_blocks: Option<PathBuf>,
+        /// Columns of the exported blocks (mempool-space, blockchair or the
+        /// path of a template file)
+        #[clap(long, default_value = "mempool-space", parse(try_from_str = parse_template))]
+        template: ExportTemplate,
+        /// Report preliminary statistics on a growing sample until the
+        /// margin of error is met
+        #[clap(long)]
+        progressive: bool,
+        /// Number of blocks of the rolling mean and median mining times
+        /// reported as a time series instead
+        #[clap(long)]
+        rolling: Option<usize>,
+        /// Width in minutes of the buckets of a histogram of the mining
+        /// times drawn along with the report
+        #[clap(long, parse(try_from_str = parse_count))]
+        histogram: Option<usize>,
+        /// Path of an SVG or PNG chart of the distribution of the mining
+        /// times along with the exponential fit and the drifts, rendered
+        /// when built with the charts feature
+        #[clap(long)]
+        chart: Option<PathBuf>,
+        /// Measure the mining times between the median time past of the
+        /// blocks (BIP113) instead of their timestamps
+        #[clap(long)]
+        median_time_past: bool,
+    },
+    /// Run the analysis of the autocorrelation of mining times of contiguous blocks by lag
+    Autocorrelation {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+        /// Highest lag in blocks, below the window
+        #[clap(long, default_value_t = 10, parse(try_from_str = parse_count))]
+        lags: usize,
+    },
+    /// Run the comparison of the block production speed of every completed halving era
+    HalvingEraSpeed,
+    /// Run the estimation of the network hashrate from the difficulty and mining times
+    Hashrate {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 288)]
+        window: u64,
+        /// Number of blocks of every rolling estimate
+        #[clap(long, default_value_t = 144)]
+        rolling: usize,
+    },
+    /// Run an experimental analysis compiled to WASM
+    #[cfg(feature = "plugins")]
+    Plugin {
+        /// Path of the WASM module of the analysis
+        path: PathBuf,
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+    },
+    /// Run the analysis of streaks of increasing timestamps and backward jumps
+    MonotonicityStreaks {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+        /// Number of blocks of every period of backward jumps
+        #[clap(short, long, default_value_t = 2016)]
+        period: u64,
+        /// Number of longest streaks reported
+        #[clap(long, default_value_t = 5)]
+        count: usize,
+    },
+    /// Run the analysis of blocks timestamped before their parent
+    NegativeDrifts {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+    },
+    /// Run the analysis of how close timestamps come to the median time past and the 2 hour limit
+    FutureTimestamps {
+        /// Number of contiguous blocks within the sample, each fetched with
+        /// the 11 blocks before it
+        #[clap(short, long, default_value_t = 1)]
+        window: u64,
+        /// Number of blocks closest to either limit listed
+        #[clap(long, default_value_t = 5)]
+        count: usize,
+        /// Seconds from a limit a block is counted as near it
+        #[clap(long, default_value_t = 600)]
+        near: i64,
+    },
+    /// Run the analysis of the frequency of children timestamped before their parent by year
+    OutOfOrder {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+    },
+    /// Run the estimation of the probability of confirmations being reversed by a reorg
+    ReorgRisk {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+        /// Highest number of confirmations reported
+        #[clap(long, default_value_t = 6)]
+        confirmations: u64,
+        /// Share of the blocks found that became stale, instead of the stale
+        /// blocks known to the node
+        #[clap(long)]
+        stale_rate: Option<f64>,
+    },
+    /// Run the record book analysis of the longest and shortest block times
+    Records {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+        /// Number of records kept for the longest and shortest block times
+        #[clap(long, default_value_t = 5)]
+        count: usize,
+        /// Path of a JSON record book to update across runs
+        #[clap(short, long)]
+        book: Option<PathBuf>,
+        /// Path of a CSV file to export the raw intervals of the sample to
+        #[clap(long)]
+        export_intervals: Option<PathBuf>,
+        /// Path of a file to export a row of every block of the sample to
+        #[clap(long)]
+        export_blocks: Option<PathBuf>,
+        /// Columns of the exported blocks (mempool-space, blockchair or the
+        /// path of a template file)
+        #[clap(long, default_value = "mempool-space", parse(try_from_str = parse_template))]
+        template: ExportTemplate,
+    },
+    /// Call an RPC method of bitcoind with the configured connection and print its result
+    Rpc {
+        /// Name of the RPC method (e.g. getblockchaininfo)
+        method: String,
+        /// Parameters of the method, parsed as JSON or passed as strings otherwise
+        #[clap(parse(from_str = parse_param))]
+        params: Vec<Value>,
+    },
+    /// Print the JSON schema of the output of every analysis
+    Schema,
+    /// List the previous runs recorded in the run history
+    History {
+        /// Number of latest runs listed
+        #[clap(short, long, default_value_t = 20)]
+        count: usize,
+    },
+    /// Print the report of a previous run without fetching any blocks again
+    Show {
+        /// Id of the run as listed by the history
+        id: i64,
+    },
+    /// Maintain the local header cache of --cache-dir
+    Index {
+        #[clap(subcommand)]
+        command: IndexCommand,
+    },
+    /// Run the miner revenue analysis of the subsidy and fees, in USD with a price feed
+    Revenue {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+        /// Number of blocks within a period (defaults to a difficulty epoch)
+        #[clap(short, long, default_value_t = 2016)]
+        period: u64,
+        /// Path of a CSV file of daily BTC/USD prices as DATE,PRICE, or its
+        /// URL when built with the price-feed feature
+        #[clap(long)]
+        price_feed: Option<String>,
+    },
+    /// Run the block size analysis of size, weight and fullness against the maximum weight
+    BlockSize {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 1)]
+        window: u64,
+        /// Number of blocks within a period of the trend (defaults to a difficulty epoch)
+        #[clap(short, long, default_value_t = 2016)]
+        period: u64,
+    },
+    /// Run the analysis of transactions per block and their correlation with mining time
+    Transactions {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+    },
+    /// Run the signet block producer analysis using the signet challenge
+    SignetProducers {
+        /// Hex encoded block challenge of the signet (defaults to the public signet)
+        #[clap(short, long)]
+        challenge: Option<String>,
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+    },
+    /// Watch the ZMQ notifications of bitcoind reporting the wait of transactions by fee rate
+    ConfirmationLatency {
+        /// ZMQ endpoint publishing rawtx and rawblock notifications
+        #[clap(long, default_value = "tcp://127.0.0.1:28332")]
+        zmq: Vec<String>,
+        /// Seconds between each report
+        #[clap(short, long, default_value_t = 600)]
+        interval: u64,
+        /// Hours after which a pending transaction is considered expired
+        #[clap(long, default_value_t = 336)]
+        expiry: u64,
+    },
+    /// Run the consolidation wave analysis of input to output ratios and fee rates
+    Consolidations {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+        /// Number of blocks within a period
+        #[clap(short, long, default_value_t = 144)]
+        period: u64,
+        /// Standard deviations above the mean ratio for a period to be detected
+        #[clap(short, long, default_value_t = 1.0)]
+        threshold: f64,
+    },
+    /// Run the analysis of the cumulative time of the chain against the schedule since genesis
+    CumulativeDrift {
+        /// Number of blocks between each checkpoint
+        #[clap(long, default_value_t = 2016)]
+        step: u64,
+    },
+    /// Run a custom rhai expression over the sample and aggregate its values
+    Custom {
+        /// Expression evaluated per block or transaction (e.g. "weight / 4")
+        expression: String,
+        /// Evaluate the expression per block or per transaction (block, tx)
+        #[clap(long, default_value = "block")]
+        per: CustomScope,
+        /// Aggregates of the values (count, sum, mean, min, max or pN for a percentile)
+        #[clap(short, long, default_values = &["count", "sum", "mean", "p50"])]
+        aggregate: Vec<Aggregate>,
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+    },
+    /// Run the heuristic clustering of spent scripts into entities
+    EntityClusters {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+        /// Maximum number of scripts tracked by the clustering
+        #[clap(long, default_value_t = 1_000_000)]
+        max_scripts: usize,
+    },
+    /// Run the analysis of mining times and difficulty changes by retarget epoch
+    DifficultyEpochs {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+    },
+    /// Run the analysis of the duration of every completed retarget epoch
+    EpochDurations {
+        /// Number of fastest and slowest epochs marked
+        #[clap(long, default_value_t = 3)]
+        count: usize,
     },
+    /// List the curated example studies, or run one against the configured source
+    Examples {
+        /// Name of the example to run, every example is listed without one
+        name: Option<String>,
+        /// Parameter of the example as NAME=VALUE overriding its default
+        /// (e.g. since=2022-01-01)
+        #[clap(long = "set", parse(try_from_str = parse_example_param))]
+        params: Vec<(String, String)>,
+    },
+    /// Run the fee elasticity analysis of fee rates against block space demand
+    FeeElasticity {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+    },
+    /// Run the fee analysis of total fees, fees per block and fee rates
+    FeeStats {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 1)]
+        window: u64,
+        /// Number of blocks of the rolling mean and median fee rates
+        /// reported as a time series instead
+        #[clap(long)]
+        rolling: Option<usize>,
+    },
+    /// Run the luck analysis of mining pools against their expected share
+    PoolLuck {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+        /// Expected share of a pool as POOL=SHARE (e.g. "Foundry USA=0.3")
+        #[clap(short, long, parse(try_from_str = parse_share))]
+        expected: Vec<(String, f64)>,
+    },
+    /// Run the analysis of empty blocks by mining pool and their mining times
+    EmptyBlocks {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+        /// Path of a file of further pool signatures, see the pools analysis
+        #[clap(long)]
+        signatures: Option<PathBuf>,
+    },
+    /// Run the attribution of blocks to mining pools by coinbase tags and payout addresses
+    Pools {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 1)]
+        window: u64,
+        /// Path of a file of further pool signatures as NAME,tag:TEXT or
+        /// NAME,address:ADDRESS per line, matched before the bundled ones
+        #[clap(long)]
+        signatures: Option<PathBuf>,
+    },
+    /// Run the correlation analysis of block size and weight against mining time
+    SizeIntervalCorrelation {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+        /// Path of a CSV file to export the raw intervals of the sample to
+        #[clap(long)]
+        export_intervals: Option<PathBuf>,
+        /// Path of a file to export a row of every block of the sample to
+        #[clap(long)]
+        export_blocks: Option<PathBuf>,
+        /// Columns of the exported blocks (mempool-space, blockchair or the
+        /// path of a template file)
+        #[clap(long, default_value = "mempool-space", parse(try_from_str = parse_template))]
+        template: ExportTemplate,
+        /// Number of blocks of the rolling mean and median block sizes
+        /// reported as a time series instead
+        #[clap(long)]
+        rolling: Option<usize>,
+    },
+    /// Watch the ZMQ notifications of bitcoind reporting live transaction statistics
+    Watch {
+        /// ZMQ endpoint publishing rawtx and rawblock notifications
+        #[clap(long, default_value = "tcp://127.0.0.1:28332")]
+        zmq: Vec<String>,
+        /// Seconds between each report
+        #[clap(short, long, default_value_t = 60)]
+        interval: u64,
+        /// Hours after which a pending transaction is considered expired
+        #[clap(long, default_value_t = 336)]
+        expiry: u64,
+        /// Log conflicting spends and their resolution as they are observed
+        #[clap(long)]
+        log_conflicts: bool,
+    },
+    /// Watch the ZMQ block notifications of bitcoind reporting live drift statistics
+    WatchBlocks {
+        /// ZMQ endpoint publishing hashblock notifications
+        #[clap(long, default_value = "tcp://127.0.0.1:28332")]
+        zmq: Vec<String>,
+        /// Number of latest intervals within the rolling window
+        #[clap(short, long, default_value_t = 144)]
+        window: usize,
+        /// Time between two contiguous blocks
+        #[clap(short, long, default_value_t = 7200)]
+        drift_time: i64,
+        /// Handling of testnet minimum difficulty blocks (keep, tag, filter)
+        #[clap(long, default_value = "tag")]
+        min_difficulty: MinDifficultyBlocks,
+    },
+    /// Run the analysis of the script types of inputs spent against outputs created
+    WitnessVersionFlow {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+        /// Number of blocks within a period (defaults to a difficulty epoch)
+        #[clap(short, long, default_value_t = 2016)]
+        period: u64,
+    },
+    /// Run the analysis of the subsidy and fees claimed by coinbases by halving era
+    CoinbaseRevenue {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 1)]
+        window: u64,
+    },
+    /// Run the analysis of soft fork signaling by the version bits of headers by epoch
+    VersionBits {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 1)]
+        window: u64,
+        /// Version bit reported, repeated for several bits (defaults to the
+        /// bits 0 to 12 not rolled by ASICBoost)
+        #[clap(short, long, parse(try_from_str = parse_bit))]
+        bit: Vec<u8>,
+        /// Signaling rate reaching the lock in threshold of a deployment
+        #[clap(short, long, default_value_t = 0.95)]
+        threshold: f64,
+    },
+    /// List the stale and fork tips known to the node with the recent stale rate
+    ChainTips {
+        /// Number of latest blocks of the active chain of the recent stale
+        /// rate
+        #[clap(short, long, default_value_t = 2016)]
+        recent: u64,
+    },
+    /// Run the analysis of the difficulty of the sampled headers by epoch with the largest retargets
+    DifficultyTrend {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 1)]
+        window: u64,
+        /// Number of largest upward and downward retargets marked
+        #[clap(long, default_value_t = 3)]
+        count: usize,
+        /// Path of an SVG or PNG chart of the difficulty by height on a log
+        /// scale, rendered when built with the charts feature
+        #[clap(long)]
+        chart: Option<PathBuf>,
+    },
+    /// Run the analysis of backdated timestamps at retarget boundaries for the time warp attack
+    TimeWarp {
+        /// Seconds the first block of an epoch may be timestamped behind
+        /// the last block of the epoch before (BIP54)
+        #[clap(long, default_value_t = 7200)]
+        max_gap: i64,
+    },
+    /// Run the analysis of P2TR outputs created and key path and script path spends by epoch
+    TaprootAdoption {
+        /// Number of contiguous blocks within the sample
+        #[clap(short, long, default_value_t = 2)]
+        window: u64,
+        /// Height taproot activated at, the sample starts no earlier (defaults to mainnet)
+        #[clap(short, long, default_value_t = 709_632)]
+        activation_height: u64,
+    },
+    /// Run several analyses with their default parameters over a single collection of the sample
+    RunAll {
+        /// Number of contiguous blocks within the sample, shared by the analyses
+        #[clap(short, long, default_value_t = 144)]
+        window: u64,
+        /// Analyses to run separated by commas, either all of headers or all
+        /// of block statistics (e.g. block-time-drift,negative-drifts)
+        #[clap(
+            long,
+            required = true,
+            value_delimiter = ',',
+            parse(try_from_str = parse_analysis)
+        )]
+        analyses: Vec<Analysis>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum IndexCommand {
+    /// Verify the cached headers and their links, fetching damaged headers again
+    Verify {
+        /// Fetch the headers missing between the cached headers as well
+        #[clap(long)]
+        fill_gaps: bool,
+    },
+}
+
+impl Analysis {
+    /// Get the data fetched by the analysis, none when it does not connect.
+    pub fn get_data_need(&self) -> Option<DataNeed> {
+        match self {
+            Analysis::Autocorrelation { .. }
+            | Analysis::BlockTimeDrift { .. }
+            | Analysis::CumulativeDrift { .. }
+            | Analysis::DifficultyEpochs { .. }
+            | Analysis::DifficultyTrend { .. }
+            | Analysis::EpochDurations { .. }
+            | Analysis::FutureTimestamps { .. }
+            | Analysis::TimeWarp { .. }
+            | Analysis::HalvingEraSpeed
+            | Analysis::Hashrate { .. }
+            | Analysis::MonotonicityStreaks { .. }
+            | Analysis::NegativeDrifts { .. }
+            | Analysis::OutOfOrder { .. }
+            | Analysis::Records { .. }
+            | Analysis::VersionBits { .. }
+            | Analysis::ReorgRisk {
+                stale_rate: Some(_),
+                ..
+            } => Some(DataNeed::Headers),
+            #[cfg(feature = "plugins")]
+            Analysis::Plugin { .. } => Some(DataNeed::Blocks),
+            Analysis::CoinbaseRevenue { .. }
+            | Analysis::Custom { .. }
+            | Analysis::EmptyBlocks { .. }
+            | Analysis::EntityClusters { .. }
+            | Analysis::PoolLuck { .. }
+            | Analysis::Pools { .. }
+            | Analysis::SignetProducers { .. }
+            | Analysis::SizeIntervalCorrelation { .. }
+            | Analysis::TaprootAdoption { .. }
+            | Analysis::WitnessVersionFlow { .. } => Some(DataNeed::Blocks),
+            Analysis::BlockSize { .. }
+            | Analysis::Consolidations { .. }
+            | Analysis::FeeElasticity { .. }
+            | Analysis::FeeStats { .. }
+            | Analysis::Revenue { .. }
+            | Analysis::Transactions { .. } => Some(DataNeed::BlockStats),
+            // The stale rate is estimated from the chain tips of the node
+            Analysis::ReorgRisk { .. }
+            | Analysis::ChainTips { .. }
+            | Analysis::ConfirmationLatency { .. }
+            | Analysis::Index { .. }
+            | Analysis::Rpc { .. }
+            | Analysis::Watch { .. }
+            | Analysis::WatchBlocks { .. } => Some(DataNeed::Node),
+            Analysis::Schema
+            | Analysis::History { .. }
+            | Analysis::Show { .. }
+            | Analysis::Examples { .. } => None,
+            Analysis::RunAll { analyses, .. } => analyses.first()?.get_data_need(),
+        }
+    }
+
+    /// Check whether the analysis can share the collection of run-all with
+    /// other analyses, running over its data alone with the shared window.
+    pub fn is_shareable(&self) -> bool {
+        matches!(
+            self,
+            Analysis::Autocorrelation { .. }
+                | Analysis::BlockTimeDrift { .. }
+                | Analysis::DifficultyEpochs { .. }
+                | Analysis::DifficultyTrend { .. }
+                | Analysis::Hashrate { .. }
+                | Analysis::MonotonicityStreaks { .. }
+                | Analysis::NegativeDrifts { .. }
+                | Analysis::OutOfOrder { .. }
+                | Analysis::Records { .. }
+                | Analysis::VersionBits { .. }
+                | Analysis::BlockSize { .. }
+                | Analysis::Consolidations { .. }
+                | Analysis::FeeElasticity { .. }
+                | Analysis::FeeStats { .. }
+                | Analysis::Transactions { .. }
+        )
+    }
+}
+
+/// Parse an analysis of run-all by its name with its default parameters.
+fn parse_analysis(name: &str) -> Result<Analysis, String> {
+    let analysis = Args::try_parse_from(["bitcoin-statistics", name.trim()])
+        .map_err(|_| format!("Unknown analysis: {}", name))?
+        .command;
+
+    match analysis.is_shareable() {
+        true => Ok(analysis),
+        false => Err(format!("{} cannot share a collection", name)),
+    }
+}
+
+/// Parse the expected share of a pool given as POOL=SHARE.
+fn parse_share(share: &str) -> Result<(String, f64), String> {
+    let (pool, share) = share
+        .rsplit_once('=')
+        .ok_or_else(|| format!("Expected POOL=SHARE but got: {}", share))?;
+    let share = share.trim().parse::<f64>().map_err(|e| e.to_string())?;
+
+    Ok((pool.trim().to_string(), share))
+}
+
+/// Parse a parameter of an example given as NAME=VALUE.
+fn parse_example_param(param: &str) -> Result<(String, String), String> {
+    param
+        .split_once('=')
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .ok_or_else(|| format!("Expected NAME=VALUE but got: {}", param))
+}
+
+/// Parse a version bit available to deployments.
+fn parse_bit(bit: &str) -> Result<u8, String> {
+    match bit.parse::<u8>() {
+        Ok(bit) if bit < VERSIONBITS_NUM_BITS => Ok(bit),
+        _ => Err(format!(
+            "Expected a version bit below {} but got: {}",
+            VERSIONBITS_NUM_BITS, bit
+        )),
+    }
+}
+
+/// Parse a confidence level between 0 and 1 exclusive.
+fn parse_confidence(confidence: &str) -> Result<f64, String> {
+    match confidence.parse::<f64>() {
+        Ok(confidence) if confidence > 0.0 && confidence < 1.0 => Ok(confidence),
+        _ => Err(format!(
+            "Expected a confidence between 0 and 1 but got: {}",
+            confidence
+        )),
+    }
+}
+
+/// Parse a duration in seconds with an optional ms, s, m or h suffix.
+fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let duration = duration.trim().to_lowercase();
+    let (number, unit) = match duration.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => duration.split_at(index),
+        None => (duration.as_str(), ""),
+    };
+    let multiplier = match unit.trim() {
+        "ms" => 1,
+        "" | "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        unit => return Err(format!("Unknown duration unit: {}", unit)),
+    };
+
+    let number = number.parse::<u64>().map_err(|e| e.to_string())?;
+
+    number
+        .checked_mul(multiplier)
+        .map(Duration::from_millis)
+        .ok_or_else(|| format!("Duration too long: {}", duration))
+}
+
+/// Parse a count of at least one.
+fn parse_count(count: &str) -> Result<usize, String> {
+    match count.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("Expected a count of at least 1 but got: {}", count)),
+    }
+}
+
+/// Parse the name of a built in export template or the path of a template
+/// file.
+fn parse_template(template: &str) -> Result<ExportTemplate, String> {
+    template
+        .parse()
+        .or_else(|_| ExportTemplate::load(Path::new(template)).map_err(|e| e.to_string()))
+}
+
+/// Parse a UTC day or a unix time into a unix time.
+fn parse_date(date: &str) -> Result<u32, String> {
+    utils::parse_date(date).ok_or_else(|| format!("Expected YYYY-MM-DD but got: {}", date))
+}
+
+/// Parse an RPC parameter as JSON, or as a string otherwise like bitcoin-cli.
+fn parse_param(param: &str) -> Value {
+    serde_json::from_str(param).unwrap_or_else(|_| Value::String(param.to_string()))
+}
+
+/// Parse a byte size with an optional KB, MB or GB suffix.
+fn parse_bytes(size: &str) -> Result<u64, String> {
+    let size = size.trim().to_uppercase();
+    let (number, unit) = match size.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => size.split_at(index),
+        None => (size.as_str(), ""),
+    };
+    let multiplier = match unit.trim() {
+        "" | "B" => 1,
+        "KB" | "K" => 1 << 10,
+        "MB" | "M" => 1 << 20,
+        "GB" | "G" => 1 << 30,
+        unit => return Err(format!("Unknown size unit: {}", unit)),
+    };
+
+    let number = number.parse::<u64>().map_err(|e| e.to_string())?;
+
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Size too large: {}", size))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoin_statistics::examples::EXAMPLES;
+
+    #[test]
+    fn test_parse_share() {
+        assert_eq!(
+            parse_share("Foundry USA=0.3"),
+            Ok((String::from("Foundry USA"), 0.3))
+        );
+        assert!(parse_share("AntPool").is_err());
+        assert!(parse_share("AntPool=a").is_err());
+    }
+
+    #[test]
+    fn test_parse_bit() {
+        assert_eq!(parse_bit("1"), Ok(1));
+        assert_eq!(parse_bit("28"), Ok(28));
+        assert!(parse_bit("29").is_err());
+        assert!(parse_bit("a").is_err());
+    }
+
+    #[test]
+    fn test_parse_analysis() {
+        assert!(matches!(
+            parse_analysis("negative-drifts"),
+            Ok(Analysis::NegativeDrifts { window: 2 })
+        ));
+        assert!(parse_analysis(" fee-stats").is_ok());
+        assert!(parse_analysis("pools").is_err());
+        assert!(parse_analysis("run-all").is_err());
+        assert!(parse_analysis("unknown").is_err());
+    }
+
+    #[test]
+    fn test_parse_example_param() {
+        assert_eq!(
+            parse_example_param("since=2022-01-01"),
+            Ok((String::from("since"), String::from("2022-01-01")))
+        );
+        assert!(parse_example_param("2022").is_err());
+    }
+
+    #[test]
+    fn test_examples() {
+        // Every example runs an analysis of the CLI
+        for example in EXAMPLES {
+            let args = example.get_args(&[]).unwrap();
+            let cli = Args::try_parse_from(
+                std::iter::once("bitcoin-statistics").chain(args.iter().map(String::as_str)),
+            )
+            .unwrap_or_else(|e| panic!("{}: {}", example.name, e));
+            assert!(cli.command.get_data_need().is_some(), "{}", example.name);
+        }
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(parse_bytes("1024"), Ok(1024));
+        assert_eq!(parse_bytes("2kb"), Ok(2048));
+        assert_eq!(parse_bytes("512MB"), Ok(512 << 20));
+        assert_eq!(parse_bytes("1 G"), Ok(1 << 30));
+        assert!(parse_bytes("1TB").is_err());
+        assert!(parse_bytes("MB").is_err());
+        assert!(parse_bytes("99999999999999G").is_err());
+    }
+
+    #[test]
+    fn test_parse_confidence() {
+        assert_eq!(parse_confidence("0.99"), Ok(0.99));
+        assert!(parse_confidence("95").is_err());
+        assert!(parse_confidence("1").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("2H"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("99999999999999999h").is_err());
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("16"), Ok(16));
+        assert!(parse_count("0").is_err());
+        assert!(parse_count("-1").is_err());
+    }
+
+    #[test]
+    fn test_parse_param() {
+        assert_eq!(parse_param("100"), Value::from(100));
+        assert_eq!(parse_param("true"), Value::Bool(true));
+        assert_eq!(parse_param(r#"["a"]"#), serde_json::json!(["a"]));
+        assert_eq!(
+            parse_param("00000000000000000002"),
+            Value::String(String::from("00000000000000000002"))
+        );
+        assert_eq!(parse_param("abc"), Value::String(String::from("abc")));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2023-01-01"), Ok(1672531200));
+        assert!(parse_date("01/01/2023").is_err());
+    }
 }
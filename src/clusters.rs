@@ -0,0 +1,126 @@
+use bitcoincore_rpc::bitcoin::Script;
+use std::collections::HashMap;
+
+/// Disjoint sets of scripts assumed to be controlled by the same entity.
+/// The number of scripts tracked is capped to bound the memory used, scripts
+/// beyond the capacity are not tracked.
+#[derive(Clone, Debug)]
+pub struct ScriptClusters {
+    index: HashMap<Script, usize>,
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    capacity: usize,
+}
+
+impl ScriptClusters {
+    /// Create empty clusters tracking up to the capacity of scripts.
+    pub fn new(capacity: usize) -> Self {
+        ScriptClusters {
+            index: HashMap::new(),
+            parents: vec![],
+            sizes: vec![],
+            capacity,
+        }
+    }
+
+    /// Number of scripts tracked.
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Check whether no scripts are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Check whether the capacity of scripts was reached.
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.capacity
+    }
+
+    /// Get the index of the script tracking it when new. None is returned
+    /// when the script is new and the capacity was reached.
+    pub fn insert(&mut self, script: &Script) -> Option<usize> {
+        if let Some(index) = self.index.get(script) {
+            return Some(*index);
+        } else if self.is_full() {
+            return None;
+        }
+
+        let index = self.parents.len();
+        self.index.insert(script.clone(), index);
+        self.parents.push(index);
+        self.sizes.push(1);
+        Some(index)
+    }
+
+    /// Merge the clusters of the two scripts.
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+
+        if a == b {
+            return;
+        }
+
+        // Union by size keeps the trees shallow
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+    }
+
+    /// Get the root of the cluster of the script.
+    pub fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // Path compression
+        let mut index = index;
+        while self.parents[index] != root {
+            let parent = self.parents[index];
+            self.parents[index] = root;
+            index = parent;
+        }
+
+        root
+    }
+
+    /// Get the size of every cluster.
+    pub fn cluster_sizes(&self) -> Vec<usize> {
+        (0..self.parents.len())
+            .filter(|index| self.parents[*index] == *index)
+            .map(|root| self.sizes[root])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_script_clusters() {
+        let scripts = (0..4u8).map(|i| Script::from(vec![i])).collect::<Vec<_>>();
+        let mut clusters = ScriptClusters::new(3);
+        let a = clusters.insert(&scripts[0]).unwrap();
+        let b = clusters.insert(&scripts[1]).unwrap();
+        let c = clusters.insert(&scripts[2]).unwrap();
+
+        assert_eq!(clusters.insert(&scripts[0]), Some(a));
+        assert_eq!(clusters.insert(&scripts[3]), None);
+        assert!(clusters.is_full());
+
+        clusters.union(a, b);
+        assert_eq!(clusters.find(a), clusters.find(b));
+        assert_ne!(clusters.find(a), clusters.find(c));
+
+        let mut sizes = clusters.cluster_sizes();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 2]);
+    }
+}
@@ -0,0 +1,244 @@
+use bitcoincore_rpc::bitcoin::{Block, Transaction};
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::{fmt, str::FromStr};
+
+use crate::utils::{get_difficulty, get_mean, get_percentile};
+use crate::Result;
+
+/// Maximum number of operations an expression may run per evaluation.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What a custom expression is evaluated over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CustomScope {
+    /// Evaluate the expression once per block
+    Block,
+    /// Evaluate the expression once per transaction
+    Transaction,
+}
+
+/// Aggregation of the values of a custom expression.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+    /// Percentile between 0 and 100
+    Percentile(f64),
+}
+
+/// A user provided rhai expression evaluated over blocks or transactions.
+///
+/// Block expressions can use the variables height, time, interval (seconds
+/// since the parent block when within the sample), size, weight, txs,
+/// version, bits, nonce and difficulty.
+///
+/// Transaction expressions can use the variables height, time, coinbase,
+/// inputs, outputs, size, weight, vsize and value (satoshis created).
+///
+/// The expression evaluates to a number, a boolean (counted as 1 or 0) or
+/// unit `()` to skip the block or transaction.
+pub struct CustomExpression {
+    engine: Engine,
+    ast: AST,
+}
+
+impl FromStr for CustomScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(CustomScope::Block),
+            "tx" => Ok(CustomScope::Transaction),
+            _ => Err(format!("Unknown expression scope: {}", s)),
+        }
+    }
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "count" => Ok(Aggregate::Count),
+            "sum" => Ok(Aggregate::Sum),
+            "mean" => Ok(Aggregate::Mean),
+            "min" => Ok(Aggregate::Min),
+            "max" => Ok(Aggregate::Max),
+            _ => match s.strip_prefix('p').map(str::parse::<f64>) {
+                Some(Ok(percentile)) if (0.0..=100.0).contains(&percentile) => {
+                    Ok(Aggregate::Percentile(percentile))
+                }
+                _ => Err(format!("Unknown aggregate: {}", s)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Aggregate::Count => f.write_str("Count"),
+            Aggregate::Sum => f.write_str("Sum"),
+            Aggregate::Mean => f.write_str("Mean"),
+            Aggregate::Min => f.write_str("Min"),
+            Aggregate::Max => f.write_str("Max"),
+            Aggregate::Percentile(percentile) => write!(f, "P{}", percentile),
+        }
+    }
+}
+
+impl Aggregate {
+    /// Aggregate the values.
+    pub fn apply(&self, values: &[f64]) -> f64 {
+        match self {
+            Aggregate::Count => values.len() as f64,
+            Aggregate::Sum => values.iter().sum(),
+            Aggregate::Mean => get_mean(values),
+            Aggregate::Min => values.iter().copied().fold(f64::NAN, f64::min),
+            Aggregate::Max => values.iter().copied().fold(f64::NAN, f64::max),
+            Aggregate::Percentile(percentile) => get_percentile(values, *percentile),
+        }
+    }
+}
+
+impl CustomExpression {
+    /// Compile the expression.
+    pub fn compile(expression: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile_expression(expression)
+            .map_err(Box::<EvalAltResult>::from)?;
+
+        Ok(CustomExpression { engine, ast })
+    }
+
+    /// Evaluate the expression for the block, the parent is the block
+    /// preceding it when within the sample.
+    pub fn eval_block(
+        &self,
+        height: u64,
+        block: &Block,
+        parent: Option<&Block>,
+    ) -> Result<Option<f64>> {
+        let mut scope = Scope::new();
+        scope.push("height", height as i64);
+        scope.push("time", block.header.time as i64);
+        scope.push_dynamic(
+            "interval",
+            parent
+                .map(|parent| Dynamic::from(block.header.time as i64 - parent.header.time as i64))
+                .unwrap_or(Dynamic::UNIT),
+        );
+        scope.push("size", block.size() as i64);
+        scope.push("weight", block.weight() as i64);
+        scope.push("txs", block.txdata.len() as i64);
+        scope.push("version", block.header.version as i64);
+        scope.push("bits", block.header.bits as i64);
+        scope.push("nonce", block.header.nonce as i64);
+        scope.push("difficulty", get_difficulty(block.header.bits));
+
+        self.eval(&mut scope)
+    }
+
+    /// Evaluate the expression for the transaction of the block.
+    pub fn eval_transaction(
+        &self,
+        height: u64,
+        block: &Block,
+        tx: &Transaction,
+    ) -> Result<Option<f64>> {
+        let mut scope = Scope::new();
+        scope.push("height", height as i64);
+        scope.push("time", block.header.time as i64);
+        scope.push("coinbase", tx.is_coin_base());
+        scope.push("inputs", tx.input.len() as i64);
+        scope.push("outputs", tx.output.len() as i64);
+        scope.push("size", tx.size() as i64);
+        scope.push("weight", tx.weight() as i64);
+        scope.push("vsize", tx.vsize() as i64);
+        scope.push(
+            "value",
+            tx.output.iter().map(|output| output.value).sum::<u64>() as i64,
+        );
+
+        self.eval(&mut scope)
+    }
+
+    /// Evaluate the expression as a number within the scope.
+    fn eval(&self, scope: &mut Scope) -> Result<Option<f64>> {
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(scope, &self.ast)?;
+
+        if result.is_unit() {
+            Ok(None)
+        } else if let Ok(int) = result.as_int() {
+            Ok(Some(int as f64))
+        } else if let Ok(float) = result.as_float() {
+            Ok(Some(float))
+        } else if let Ok(flag) = result.as_bool() {
+            Ok(Some(if flag { 1.0 } else { 0.0 }))
+        } else {
+            Err(Box::<EvalAltResult>::from(format!(
+                "Expression must evaluate to a number, boolean or () but got {}",
+                result.type_name()
+            ))
+            .into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::{blockdata::constants::genesis_block, Network};
+
+    #[test]
+    fn test_aggregate_from_str() {
+        assert_eq!("sum".parse(), Ok(Aggregate::Sum));
+        assert_eq!("p90".parse(), Ok(Aggregate::Percentile(90.0)));
+        assert!("p101".parse::<Aggregate>().is_err());
+        assert!("median".parse::<Aggregate>().is_err());
+    }
+
+    #[test]
+    fn test_aggregate_apply() {
+        let values = [4.0, 1.0, 3.0, 2.0];
+        assert_eq!(Aggregate::Count.apply(&values), 4.0);
+        assert_eq!(Aggregate::Sum.apply(&values), 10.0);
+        assert_eq!(Aggregate::Min.apply(&values), 1.0);
+        assert_eq!(Aggregate::Max.apply(&values), 4.0);
+        assert_eq!(Aggregate::Percentile(50.0).apply(&values), 2.5);
+    }
+
+    #[test]
+    fn test_custom_expression() {
+        let block = genesis_block(Network::Bitcoin);
+        let expression = CustomExpression::compile("size * 2 + height").unwrap();
+        assert_eq!(expression.eval_block(1, &block, None).unwrap(), Some(571.0));
+
+        let expression =
+            CustomExpression::compile("if interval == () { () } else { interval }").unwrap();
+        assert_eq!(expression.eval_block(1, &block, None).unwrap(), None);
+        assert_eq!(
+            expression.eval_block(1, &block, Some(&block)).unwrap(),
+            Some(0.0)
+        );
+
+        let expression = CustomExpression::compile("coinbase").unwrap();
+        assert_eq!(
+            expression
+                .eval_transaction(0, &block, &block.txdata[0])
+                .unwrap(),
+            Some(1.0)
+        );
+
+        let expression = CustomExpression::compile("\"text\"").unwrap();
+        assert!(expression.eval_block(0, &block, None).is_err());
+        assert!(CustomExpression::compile("let x = 1;").is_err());
+    }
+}
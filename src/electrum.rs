@@ -0,0 +1,369 @@
+//! Backend fetching block headers from an Electrum server instead of the
+//! RPC of bitcoind.
+//!
+//! The backend answers the calls of bitcoind made by the RPC client used
+//! for sampling headers with the Electrum protocol, so every header analysis
+//! runs unchanged. Electrum servers look blocks up by height only, so a
+//! block hash is known once returned by `getblockhash`. Full blocks and
+//! block statistics are not served.
+//!
+//! See https://electrumx.readthedocs.io/en/latest/protocol-methods.html
+
+use bitcoincore_rpc::bitcoin::{
+    consensus::encode::{deserialize, serialize_hex},
+    hashes::hex::FromHex,
+    BlockHash, BlockHeader,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::transport::{
+    get_rpc_error, Answer, Answered, DEFAULT_TIMEOUT, RPC_INVALID_ADDRESS_OR_KEY,
+    RPC_METHOD_NOT_FOUND,
+};
+use crate::utils::{get_difficulty, get_median};
+
+/// Port of the Electrum server when none is given.
+const DEFAULT_PORT: u16 = 50001;
+/// Number of blocks of the median time past of a block.
+const MEDIAN_TIME_SPAN: u64 = 11;
+
+/// Headers returned by the server keyed by their hash, shared by the
+/// transports of a client pool so a hash can be looked up on any of them.
+pub type HeaderIndex = Arc<Mutex<HashMap<BlockHash, (u64, BlockHeader)>>>;
+
+/// Backend answering the RPC calls of bitcoind with the headers of an
+/// Electrum server over TCP, e.g. `electrum.example.org:50001`.
+pub struct ElectrumBackend {
+    host: String,
+    port: u16,
+    timeout: Duration,
+    index: HeaderIndex,
+}
+
+impl ElectrumBackend {
+    /// Create a new backend of the server at `host` or `host:port`, an
+    /// optional `tcp://` scheme is ignored.
+    pub fn new(server: &str, index: HeaderIndex) -> Self {
+        let server = server.trim_start_matches("tcp://").trim_end_matches('/');
+        let (host, port) = match server.rsplit_once(':') {
+            Some((host, port)) if !host.ends_with(':') => match port.parse() {
+                Ok(port) => (host, port),
+                Err(_) => (server, DEFAULT_PORT),
+            },
+            _ => (server, DEFAULT_PORT),
+        };
+
+        ElectrumBackend {
+            host: host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_string(),
+            port,
+            timeout: DEFAULT_TIMEOUT,
+            index,
+        }
+    }
+
+    /// Get the Electrum calls answering the call of bitcoind.
+    fn get_calls(&self, method: &str, params: &[Value]) -> Answered<Vec<(&'static str, Value)>> {
+        match method {
+            "getblockcount" => Ok(vec![("blockchain.headers.subscribe", json!([]))]),
+            "getblockhash" => Ok(vec![("blockchain.block.header", json!(params))]),
+            "getblockheader" => {
+                let (height, _) = self.get_header(params)?;
+
+                match params.get(1).and_then(Value::as_bool).unwrap_or(true) {
+                    true => {
+                        let start = height.saturating_sub(MEDIAN_TIME_SPAN - 1);
+                        Ok(vec![
+                            (
+                                "blockchain.block.headers",
+                                json!([start, height - start + 1]),
+                            ),
+                            ("blockchain.headers.subscribe", json!([])),
+                        ])
+                    }
+                    false => Ok(vec![]),
+                }
+            }
+            method => Err(get_rpc_error(
+                RPC_METHOD_NOT_FOUND,
+                format!("{} is not served by the Electrum backend", method),
+            )),
+        }
+    }
+
+    /// Answer the call of bitcoind with the results of its Electrum calls.
+    fn get_answer(&self, method: &str, params: &[Value], results: Vec<Value>) -> Answered {
+        match method {
+            "getblockcount" => Ok(results[0]["height"].clone()),
+            "getblockhash" => {
+                let header = parse_headers(&results[0])?[0];
+                let hash = header.block_hash();
+                let height = params.first().and_then(Value::as_u64).unwrap_or_default();
+                let mut index = self.index.lock().expect("Header index is poisoned");
+                index.insert(hash, (height, header));
+                Ok(json!(hash))
+            }
+            "getblockheader" => {
+                let (height, header) = self.get_header(params)?;
+
+                if results.is_empty() {
+                    return Ok(json!(serialize_hex(&header)));
+                }
+
+                let times = parse_headers(&results[0]["hex"])?
+                    .iter()
+                    .map(|header| header.time as f64)
+                    .collect::<Vec<_>>();
+                let tip = results[1]["height"].as_u64().unwrap_or(height);
+
+                Ok(json!({
+                    "hash": header.block_hash(),
+                    "confirmations": tip.saturating_sub(height) + 1,
+                    "height": height,
+                    "version": header.version,
+                    "merkleroot": header.merkle_root,
+                    "time": header.time,
+                    "mediantime": get_median(&times) as u64,
+                    "nonce": header.nonce,
+                    "bits": format!("{:08x}", header.bits),
+                    "difficulty": get_difficulty(header.bits),
+                    // Neither is known to an Electrum server
+                    "chainwork": "00",
+                    "nTx": 0,
+                    "previousblockhash": header.prev_blockhash,
+                }))
+            }
+            _ => unreachable!("Only calls with Electrum calls are answered"),
+        }
+    }
+
+    /// Get the height and header of the block hash of the parameters.
+    fn get_header(&self, params: &[Value]) -> Answered<(u64, BlockHeader)> {
+        let index = self.index.lock().expect("Header index is poisoned");
+        let hash = params
+            .first()
+            .and_then(Value::as_str)
+            .and_then(|hash| hash.parse::<BlockHash>().ok());
+
+        hash.and_then(|hash| index.get(&hash).copied())
+            .ok_or_else(|| {
+                get_rpc_error(
+                    RPC_INVALID_ADDRESS_OR_KEY,
+                    "Block not found, look up its height first",
+                )
+            })
+    }
+
+    /// Send the calls to the server in a single write and read their
+    /// results, notifications of subscriptions are skipped.
+    fn call(&self, calls: &[(&str, Value)]) -> io::Result<Vec<Answered>> {
+        if calls.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let stream = self.connect()?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let mut lines = vec![];
+        for (id, (method, params)) in calls.iter().enumerate() {
+            let call = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+            lines.extend(serde_json::to_vec(&call)?);
+            lines.push(b'\n');
+        }
+        (&stream).write_all(&lines)?;
+
+        let mut results = vec![None; calls.len()];
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+
+        while results.iter().any(Option::is_none) {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "The Electrum server closed the connection",
+                ));
+            }
+
+            let response = serde_json::from_str::<Value>(&line)?;
+            let id = response["id"].as_u64().map(|id| id as usize);
+
+            if let Some(result) = id.and_then(|id| results.get_mut(id)) {
+                *result = Some(match &response["error"] {
+                    Value::Null => Ok(response["result"].clone()),
+                    error => Err(get_rpc_error(
+                        error["code"].as_i64().unwrap_or_default() as i32,
+                        error["message"]
+                            .as_str()
+                            .map(String::from)
+                            .unwrap_or_else(|| error.to_string()),
+                    )),
+                });
+            }
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    /// Connect to the first reachable address of the server.
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut error = io::Error::new(io::ErrorKind::NotFound, "No address for the host");
+
+        for address in (self.host.as_str(), self.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error)
+    }
+}
+
+impl Answer for ElectrumBackend {
+    fn answer(&self, method: &str, params: &[Value]) -> io::Result<Answered> {
+        Ok(self.answer_batch(&[(method, params.to_vec())])?.remove(0))
+    }
+
+    /// Answer the calls, sending the Electrum calls of every call to the
+    /// server at once over a single connection.
+    fn answer_batch(&self, calls: &[(&str, Vec<Value>)]) -> io::Result<Vec<Answered>> {
+        let plans = calls
+            .iter()
+            .map(|(method, params)| self.get_calls(method, params))
+            .collect::<Vec<_>>();
+        let electrum = plans
+            .iter()
+            .flat_map(|plan| plan.as_ref().map(Vec::as_slice).unwrap_or_default())
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut results = self.call(&electrum)?.into_iter();
+
+        Ok(calls
+            .iter()
+            .zip(plans)
+            .map(|((method, params), plan)| {
+                plan.and_then(|electrum| {
+                    let results = results.by_ref().take(electrum.len()).collect::<Vec<_>>();
+                    let results = results.into_iter().collect::<Answered<Vec<_>>>()?;
+                    self.get_answer(method, params, results)
+                })
+            })
+            .collect())
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "electrum://{}:{}", self.host, self.port)
+    }
+}
+
+/// Parse the concatenated hex of block headers returned by the server.
+fn parse_headers(hex: &Value) -> Answered<Vec<BlockHeader>> {
+    let invalid = |message: &str| {
+        get_rpc_error(
+            RPC_INVALID_ADDRESS_OR_KEY,
+            format!("Invalid headers from the Electrum server: {}", message),
+        )
+    };
+    let bytes = hex
+        .as_str()
+        .ok_or_else(|| invalid("not hex"))
+        .and_then(|hex| Vec::<u8>::from_hex(hex).map_err(|e| invalid(&e.to_string())))?;
+
+    bytes
+        .chunks(80)
+        .map(|header| deserialize(header).map_err(|e| invalid(&e.to_string())))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|headers| match headers.is_empty() {
+            true => Err(invalid("no headers")),
+            false => Ok(headers),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::{
+        bitcoin::{blockdata::constants::genesis_block, Network},
+        jsonrpc, Client, RpcApi,
+    };
+    use std::{net::TcpListener, thread};
+
+    use crate::transport::AnswerTransport;
+
+    /// Serve the genesis header at every height for every request.
+    fn serve(listener: TcpListener) {
+        let header = serialize_hex(&genesis_block(Network::Bitcoin).header);
+
+        for stream in listener.incoming().take(3) {
+            let stream = stream.unwrap();
+            let mut writer = stream.try_clone().unwrap();
+
+            for line in BufReader::new(stream).lines() {
+                let call = serde_json::from_str::<Value>(&line.unwrap()).unwrap();
+                let result = match call["method"].as_str().unwrap() {
+                    "blockchain.headers.subscribe" => json!({"height": 800_000, "hex": header}),
+                    "blockchain.block.header" => json!(header),
+                    _ => json!({"count": 3, "hex": header.repeat(3), "max": 2016}),
+                };
+                // Notifications are interleaved with the responses
+                writeln!(
+                    writer,
+                    "{}",
+                    json!({"method": "blockchain.headers.subscribe"})
+                )
+                .unwrap();
+                writeln!(writer, "{}", json!({"id": call["id"], "result": result})).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_electrum_transport() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener));
+
+        let backend = ElectrumBackend::new(&format!("tcp://{}", address), HeaderIndex::default());
+        let transport = AnswerTransport(backend);
+        let client = Client::from_jsonrpc(jsonrpc::Client::with_transport(transport));
+        let genesis = genesis_block(Network::Bitcoin).header;
+
+        assert_eq!(client.get_block_count().unwrap(), 800_000);
+        assert!(client.get_block_header(&genesis.block_hash()).is_err());
+
+        let hash = client.get_block_hash(0).unwrap();
+        assert_eq!(hash, genesis.block_hash());
+        assert_eq!(client.get_block_header(&hash).unwrap(), genesis);
+
+        let info = client.get_block_header_info(&hash).unwrap();
+        assert_eq!(info.height, 0);
+        assert_eq!(info.confirmations, 800_001);
+        assert_eq!(info.median_time, Some(genesis.time as usize));
+        assert!(client.get_block(&hash).is_err());
+    }
+
+    #[test]
+    fn test_electrum_server() {
+        let index = HeaderIndex::default();
+        let backend = ElectrumBackend::new("electrum.example.org", index.clone());
+        assert_eq!(
+            (backend.host.as_str(), backend.port),
+            ("electrum.example.org", 50001)
+        );
+
+        let backend = ElectrumBackend::new("[::1]:50002", index);
+        assert_eq!((backend.host.as_str(), backend.port), ("::1", 50002));
+    }
+}
@@ -0,0 +1,259 @@
+//! Backend fetching blocks from the REST API of an Esplora instance, e.g.
+//! `https://blockstream.info/api`, instead of the RPC of bitcoind.
+//!
+//! Like the Electrum backend, the backend answers the calls of bitcoind
+//! made by the RPC client with requests of the API, so sampled analyses of
+//! headers and full blocks run without any node. Block statistics are not
+//! served. Public instances limit the requests of a client, so requests are
+//! spaced out by a rate limit shared by the pool.
+//!
+//! See https://github.com/Blockstream/esplora/blob/master/API.md
+
+use bitcoincore_rpc::bitcoin::hashes::hex::ToHex;
+use serde_json::{json, Value};
+use std::{
+    fmt,
+    io::{self, Read},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::transport::{
+    get_rpc_error, Answer, Answered, DEFAULT_TIMEOUT, RPC_INVALID_ADDRESS_OR_KEY,
+    RPC_METHOD_NOT_FOUND,
+};
+
+/// Requests per second sent to an instance by default, within the limits
+/// of the public instances.
+pub const DEFAULT_RATE_LIMIT: f64 = 5.0;
+
+/// Limit of the requests sent per second, shared by the transports of a
+/// client pool so the limit holds across all of them.
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    interval: Duration,
+    next: Arc<Mutex<Instant>>,
+}
+
+/// Backend answering the RPC calls of bitcoind with the REST API of an
+/// Esplora instance.
+pub struct EsploraBackend {
+    url: String,
+    agent: ureq::Agent,
+    limit: RateLimit,
+}
+
+impl RateLimit {
+    /// Create a new limit of the requests per second.
+    pub fn new(per_second: f64) -> Self {
+        RateLimit {
+            interval: Duration::from_secs_f64(1.0 / per_second.max(f64::EPSILON)),
+            next: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Wait for the slot of the next request.
+    fn wait(&self) {
+        let now = Instant::now();
+        let slot = {
+            let mut next = self.next.lock().expect("Rate limit is poisoned");
+            let slot = (*next).max(now);
+            *next = slot + self.interval;
+            slot
+        };
+
+        thread::sleep(slot - now);
+    }
+}
+
+impl EsploraBackend {
+    /// Create a new backend of the API at the URL, limited to the rate.
+    pub fn new(url: &str, limit: RateLimit) -> Self {
+        EsploraBackend {
+            url: url.trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new().timeout(DEFAULT_TIMEOUT).build(),
+            limit,
+        }
+    }
+
+    /// Get the block of the hash in the format of a verbose getblockheader.
+    fn get_header_info(&self, hash: &str) -> io::Result<Answered> {
+        let block = match self.get(&format!("/block/{}", hash))? {
+            Ok(block) => serde_json::from_str::<Value>(&block)?,
+            Err(error) => return Ok(Err(error)),
+        };
+        let tip = match self.get("/blocks/tip/height")? {
+            Ok(tip) => tip.parse::<u64>().unwrap_or_default(),
+            Err(error) => return Ok(Err(error)),
+        };
+        let height = block["height"].as_u64().unwrap_or_default();
+
+        Ok(Ok(json!({
+            "hash": block["id"],
+            "confirmations": tip.saturating_sub(height) + 1,
+            "height": height,
+            "version": block["version"],
+            "merkleroot": block["merkle_root"],
+            "time": block["timestamp"],
+            "mediantime": block["mediantime"],
+            "nonce": block["nonce"],
+            "bits": format!("{:08x}", block["bits"].as_u64().unwrap_or_default()),
+            "difficulty": block["difficulty"],
+            // Not known to an Esplora instance
+            "chainwork": "00",
+            "nTx": block["tx_count"],
+            "previousblockhash": block["previousblockhash"],
+        })))
+    }
+
+    /// Get the text at the path of the API.
+    fn get(&self, path: &str) -> io::Result<Answered<String>> {
+        Ok(self
+            .get_bytes(path)?
+            .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string()))
+    }
+
+    /// Get the bytes at the path of the API, an unknown block is an error of
+    /// the RPC while other failures are errors of the transport.
+    fn get_bytes(&self, path: &str) -> io::Result<Answered<Vec<u8>>> {
+        self.limit.wait();
+
+        match self.agent.get(&format!("{}{}", self.url, path)).call() {
+            Ok(response) => {
+                let mut bytes = vec![];
+                response.into_reader().read_to_end(&mut bytes)?;
+                Ok(Ok(bytes))
+            }
+            Err(ureq::Error::Status(400 | 404, response)) => Ok(Err(get_rpc_error(
+                RPC_INVALID_ADDRESS_OR_KEY,
+                response.into_string().unwrap_or_default(),
+            ))),
+            Err(e) => Err(io::Error::other(format!("{}{}: {}", self.url, path, e))),
+        }
+    }
+}
+
+impl Answer for EsploraBackend {
+    /// Answer the call of bitcoind with the requests of the API. Failures
+    /// of the instance are errors of the transport so they are retried.
+    fn answer(&self, method: &str, params: &[Value]) -> io::Result<Answered> {
+        let param = |i: usize| match params.get(i) {
+            Some(Value::String(param)) => param.clone(),
+            Some(param) => param.to_string(),
+            None => String::new(),
+        };
+
+        match method {
+            "getblockcount" => self.get("/blocks/tip/height").map(|height| {
+                height.map(|height| json!(height.parse::<u64>().unwrap_or_default()))
+            }),
+            "getblockhash" => self
+                .get(&format!("/block-height/{}", param(0)))
+                .map(|hash| hash.map(Value::String)),
+            "getblockheader" => match params.get(1).and_then(Value::as_bool).unwrap_or(true) {
+                true => self.get_header_info(&param(0)),
+                false => self
+                    .get(&format!("/block/{}/header", param(0)))
+                    .map(|header| header.map(Value::String)),
+            },
+            // Only raw blocks are served
+            "getblock" if params.get(1).and_then(Value::as_u64) == Some(0) => self
+                .get_bytes(&format!("/block/{}/raw", param(0)))
+                .map(|block| block.map(|block| json!(block.to_hex()))),
+            method => Ok(Err(get_rpc_error(
+                RPC_METHOD_NOT_FOUND,
+                format!("{} is not served by the Esplora backend", method),
+            ))),
+        }
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::{
+        bitcoin::{blockdata::constants::genesis_block, consensus::serialize, Network},
+        jsonrpc, Client, RpcApi,
+    };
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    use crate::transport::AnswerTransport;
+
+    /// Serve the genesis block at every height.
+    fn serve(listener: TcpListener) {
+        let block = genesis_block(Network::Bitcoin);
+
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+
+            let path = line.split(' ').nth(1).unwrap_or_default();
+            let (status, body) = match path {
+                "/api/blocks/tip/height" => (200, b"800000".to_vec()),
+                "/api/block-height/0" => (200, block.block_hash().to_string().into_bytes()),
+                "/api/block-height/1" => (404, b"Block not found".to_vec()),
+                path if path.ends_with("/header") => {
+                    (200, serialize(&block.header).to_hex().into_bytes())
+                }
+                path if path.ends_with("/raw") => (200, serialize(&block)),
+                _ => (
+                    200,
+                    json!({"id": block.block_hash(), "height": 0, "bits": block.header.bits})
+                        .to_string()
+                        .into_bytes(),
+                ),
+            };
+
+            write!(
+                stream,
+                "HTTP/1.1 {} OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_esplora_transport() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/", listener.local_addr().unwrap());
+        thread::spawn(move || serve(listener));
+
+        let transport = AnswerTransport(EsploraBackend::new(&url, RateLimit::new(1000.0)));
+        let client = Client::from_jsonrpc(jsonrpc::Client::with_transport(transport));
+        let genesis = genesis_block(Network::Bitcoin);
+
+        assert_eq!(client.get_block_count().unwrap(), 800_000);
+        let hash = client.get_block_hash(0).unwrap();
+        assert_eq!(hash, genesis.block_hash());
+        assert!(client.get_block_hash(1).is_err());
+        assert_eq!(client.get_block_header(&hash).unwrap(), genesis.header);
+        assert_eq!(client.get_block(&hash).unwrap(), genesis);
+        assert!(client.get_block_stats(0).is_err());
+
+        let info = client
+            .call::<Value>("getblockheader", &[json!(hash)])
+            .unwrap();
+        assert_eq!(info["confirmations"], 800_001);
+        assert_eq!(info["bits"], "1d00ffff");
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let limit = RateLimit::new(100.0);
+        let start = Instant::now();
+        (0..5).for_each(|_| limit.wait());
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,153 @@
+//! Gallery of curated example studies, each the arguments of an analysis
+//! with parameters of its own, e.g. the year of a study. Running an example
+//! documents a study by executing it against any configured backend, while
+//! the tests of the CLI parse every example as a smoke test of its analyses.
+
+use crate::{ExampleTable, ExamplesReport, Result, StatisticsError};
+
+/// An example study.
+pub struct Example {
+    /// Name the example is run by
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Arguments of the study separated by whitespace, with the parameters
+    /// as `{name}`
+    pub args: &'static str,
+    /// Parameters of the study and their defaults
+    pub params: &'static [(&'static str, &'static str)],
+}
+
+/// Curated example studies.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "two-hour-gaps",
+        description: "Frequency of 2-hour gaps between blocks in 2023",
+        args: "--since {since} --until {until} block-time-drift --drift-time {drift}",
+        params: &[
+            ("since", "2023-01-01"),
+            ("until", "2024-01-01"),
+            ("drift", "7200"),
+        ],
+    },
+    Example {
+        name: "taproot-adoption",
+        description: "Script types spent and created since the activation of taproot",
+        args: "--start-height {height} witness-version-flow --period {period}",
+        params: &[("height", "709632"), ("period", "2016")],
+    },
+    Example {
+        name: "halving-eras",
+        description: "Block production speed of every completed halving era",
+        args: "halving-era-speed",
+        params: &[],
+    },
+    Example {
+        name: "epoch-extremes",
+        description: "Fastest and slowest retarget epochs since genesis",
+        args: "epoch-durations --count {count}",
+        params: &[("count", "5")],
+    },
+    Example {
+        name: "fee-rate-trend",
+        description: "Rolling fee rates within windows of a day since the start of 2024",
+        args: "--since {since} fee-stats --window 144 --rolling {rolling}",
+        params: &[("since", "2024-01-01"), ("rolling", "12")],
+    },
+    Example {
+        name: "timestamp-streaks",
+        description: "Streaks of increasing timestamps and backward jumps in 2022",
+        args: "--since {since} --until {until} monotonicity-streaks",
+        params: &[("since", "2022-01-01"), ("until", "2023-01-01")],
+    },
+];
+
+impl Example {
+    /// Get the arguments of the study with the parameters given as
+    /// `(name, value)` overriding their defaults.
+    pub fn get_args(&self, params: &[(String, String)]) -> Result<Vec<String>> {
+        if let Some((name, _)) = params
+            .iter()
+            .find(|(name, _)| !self.params.iter().any(|(param, _)| param == name))
+        {
+            return Err(StatisticsError::ExampleError(format!(
+                "The example {} has no parameter {}",
+                self.name, name
+            )));
+        }
+
+        let args = self
+            .args
+            .split_whitespace()
+            .map(|arg| {
+                self.params
+                    .iter()
+                    .fold(arg.to_string(), |arg, (param, default)| {
+                        let value = params
+                            .iter()
+                            .rev()
+                            .find(|(name, _)| name == param)
+                            .map_or(*default, |(_, value)| value.as_str());
+                        arg.replace(&format!("{{{}}}", param), value)
+                    })
+            })
+            .collect();
+
+        Ok(args)
+    }
+}
+
+/// Get the example of the name.
+pub fn get_example(name: &str) -> Result<&'static Example> {
+    EXAMPLES
+        .iter()
+        .find(|example| example.name == name)
+        .ok_or_else(|| StatisticsError::ExampleError(format!("Unknown example {}", name)))
+}
+
+/// Get the gallery of examples with their default arguments.
+pub fn get_examples_report() -> ExamplesReport {
+    let entries = EXAMPLES
+        .iter()
+        .map(|example| ExampleTable {
+            name: example.name.to_string(),
+            description: example.description.to_string(),
+            parameters: example
+                .params
+                .iter()
+                .map(|(name, default)| format!("{}={}", name, default))
+                .collect::<Vec<_>>()
+                .join(", "),
+            command: example.get_args(&[]).unwrap_or_default().join(" "),
+        })
+        .collect();
+
+    ExamplesReport { entries }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_args() {
+        let example = get_example("two-hour-gaps").unwrap();
+        let params = [(String::from("until"), String::from("2023-07-01"))];
+        assert_eq!(
+            example.get_args(&params).unwrap().join(" "),
+            "--since 2023-01-01 --until 2023-07-01 block-time-drift --drift-time 7200"
+        );
+
+        let params = [(String::from("year"), String::from("2022"))];
+        assert!(example.get_args(&params).is_err());
+        assert!(get_example("unknown").is_err());
+
+        // Every parameter of an example is used by its arguments
+        for example in EXAMPLES {
+            let args = example.get_args(&[]).unwrap().join(" ");
+            assert!(!args.contains('{'), "{}", example.name);
+            for (param, _) in example.params {
+                assert!(example.args.contains(&format!("{{{}}}", param)));
+            }
+        }
+    }
+}
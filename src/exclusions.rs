@@ -0,0 +1,187 @@
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use crate::{Result, StatisticsError};
+
+/// A half open range of block heights.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct HeightRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Block heights excluded from sampling, e.g. the bootstrapping era or
+/// blocks with known broken timestamps. The ranges are kept sorted and
+/// merged.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Debug)]
+pub struct Exclusions {
+    ranges: Vec<HeightRange>,
+}
+
+impl FromStr for HeightRange {
+    type Err = String;
+
+    /// Parse a single height (`1000`), a half open range (`0..210000`) or
+    /// an inclusive range (`0..=209999`).
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let parse = |height: &str| {
+            height
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid height range: {}", s))
+        };
+
+        let (start, end) = if let Some((start, end)) = s.split_once("..=") {
+            (parse(start)?, parse(end)?.saturating_add(1))
+        } else if let Some((start, end)) = s.split_once("..") {
+            (parse(start)?, parse(end)?)
+        } else {
+            let height = parse(s)?;
+            (height, height.saturating_add(1))
+        };
+
+        match start < end {
+            true => Ok(HeightRange { start, end }),
+            false => Err(format!("Empty height range: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for HeightRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.end - self.start {
+            1 => write!(f, "{}", self.start),
+            _ => write!(f, "{}..{}", self.start, self.end),
+        }
+    }
+}
+
+impl Exclusions {
+    /// Create the exclusions from the ranges.
+    pub fn new(mut ranges: Vec<HeightRange>) -> Self {
+        ranges.sort();
+        let mut merged: Vec<HeightRange> = Vec::with_capacity(ranges.len());
+
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        Exclusions { ranges: merged }
+    }
+
+    /// Load the exclusions from a file of heights or ranges, one per line.
+    /// Empty lines and comments starting with `#` are ignored.
+    pub fn load(path: &Path) -> Result<Self> {
+        let ranges = fs::read_to_string(path)?
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| line.parse().map_err(StatisticsError::HeightRangeError))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Exclusions::new(ranges))
+    }
+
+    /// Merge the exclusions with another set of exclusions.
+    pub fn merge(self, other: Exclusions) -> Self {
+        Exclusions::new([self.ranges, other.ranges].concat())
+    }
+
+    /// The merged ranges of excluded heights.
+    pub fn ranges(&self) -> &[HeightRange] {
+        &self.ranges
+    }
+
+    /// Check whether no heights are excluded.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Check whether the height is excluded.
+    pub fn contains(&self, height: u64) -> bool {
+        let index = self.ranges.partition_point(|range| range.end <= height);
+        matches!(self.ranges.get(index), Some(range) if range.start <= height)
+    }
+
+    /// Number of excluded heights below the block height.
+    pub fn count_below(&self, block_max: u64) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.end.min(block_max).saturating_sub(range.start))
+            .sum()
+    }
+
+    /// Get the ranges of heights below the block height where a window of
+    /// contiguous blocks can start without including an excluded height.
+    pub fn get_window_starts(&self, block_max: u64, window: u64) -> Vec<HeightRange> {
+        let mut starts = vec![];
+        let mut start = 0;
+        let gaps = self.ranges.iter().map(|range| (range.start, range.end));
+
+        for (end, next) in gaps.chain([(block_max, block_max)]) {
+            let end = end.min(block_max);
+
+            if end >= start + window {
+                starts.push(HeightRange {
+                    start,
+                    end: end - window + 1,
+                });
+            }
+
+            start = start.max(next);
+        }
+
+        starts
+    }
+}
+
+impl fmt::Display for Exclusions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranges = self.ranges.iter().map(HeightRange::to_string);
+        f.write_str(&ranges.collect::<Vec<_>>().join(", "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn range(start: u64, end: u64) -> HeightRange {
+        HeightRange { start, end }
+    }
+
+    #[test]
+    fn test_height_range_from_str() {
+        assert_eq!("0..210000".parse(), Ok(range(0, 210_000)));
+        assert_eq!("5..=9".parse(), Ok(range(5, 10)));
+        assert_eq!("1000".parse(), Ok(range(1_000, 1_001)));
+        assert!("10..10".parse::<HeightRange>().is_err());
+        assert!("a..b".parse::<HeightRange>().is_err());
+    }
+
+    #[test]
+    fn test_exclusions() {
+        let exclusions = Exclusions::new(vec![range(20, 30), range(0, 5), range(4, 10)]);
+        assert_eq!(exclusions.ranges(), &[range(0, 10), range(20, 30)]);
+        assert_eq!(exclusions.to_string(), "0..10, 20..30");
+        assert!(exclusions.contains(0) && exclusions.contains(29));
+        assert!(!exclusions.contains(10) && !exclusions.contains(30));
+        assert_eq!(exclusions.count_below(25), 15);
+    }
+
+    #[test]
+    fn test_get_window_starts() {
+        let exclusions = Exclusions::new(vec![range(0, 10), range(20, 30), range(33, 34)]);
+        assert_eq!(
+            exclusions.get_window_starts(40, 2),
+            vec![range(10, 19), range(30, 32), range(34, 39)]
+        );
+        assert_eq!(
+            Exclusions::default().get_window_starts(40, 2),
+            vec![range(0, 39)]
+        );
+        assert!(exclusions.get_window_starts(40, 11).is_empty());
+    }
+}
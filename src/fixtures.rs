@@ -0,0 +1,223 @@
+use bitcoincore_rpc::{
+    jsonrpc::{self, client::Transport, error::RpcError, Request, Response},
+    Client,
+};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::transport::ClientPool;
+use crate::Result;
+
+/// RPC error code of a request without a recorded response. Not transient,
+/// so a replay fails at once instead of retrying.
+const RPC_NO_FIXTURE: i32 = -32601;
+
+/// Responses of bitcoind keyed by the method and parameters of the request,
+/// shared by the transports of a client pool. Recorded fixtures can be
+/// replayed without a node, e.g. to reproduce a bug report in a test.
+#[derive(Clone, Default, Debug)]
+pub struct Fixtures {
+    responses: Arc<Mutex<BTreeMap<String, Response>>>,
+}
+
+/// Transport recording the responses of the inner transport as fixtures.
+pub struct RecordingTransport<T> {
+    inner: T,
+    fixtures: Fixtures,
+}
+
+/// Transport answering requests with the recorded fixtures.
+pub struct ReplayTransport {
+    fixtures: Fixtures,
+}
+
+impl Fixtures {
+    /// Load the fixtures recorded to a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let responses = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        Ok(Fixtures {
+            responses: Arc::new(Mutex::new(responses)),
+        })
+    }
+
+    /// Save the fixtures to a JSON file, replacing it.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let responses = self.responses.lock().expect("Fixtures are poisoned");
+        fs::write(path, serde_json::to_string_pretty(&*responses)?)?;
+        Ok(())
+    }
+
+    /// Number of recorded responses.
+    pub fn len(&self) -> usize {
+        self.responses.lock().expect("Fixtures are poisoned").len()
+    }
+
+    /// Check whether no response was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Record the response to the request.
+    fn insert(&self, request: &Request, response: &Response) {
+        let response = Response {
+            id: serde_json::Value::Null,
+            ..response.clone()
+        };
+        let mut responses = self.responses.lock().expect("Fixtures are poisoned");
+        responses.insert(get_key(request), response);
+    }
+
+    /// Get the recorded response to the request, answering its id.
+    fn get(&self, request: &Request) -> Response {
+        let responses = self.responses.lock().expect("Fixtures are poisoned");
+        let key = get_key(request);
+
+        match responses.get(&key) {
+            Some(response) => Response {
+                id: request.id.clone(),
+                ..response.clone()
+            },
+            None => Response {
+                result: None,
+                error: Some(RpcError {
+                    code: RPC_NO_FIXTURE,
+                    message: format!("No fixture recorded for {}", key),
+                    data: None,
+                }),
+                id: request.id.clone(),
+                jsonrpc: request.jsonrpc.map(String::from),
+            },
+        }
+    }
+
+    /// Create a pool of clients replaying the fixtures.
+    pub fn get_replay_client(&self, clients: usize) -> ClientPool {
+        let clients = (0..clients.max(1))
+            .map(|_| {
+                let transport = ReplayTransport {
+                    fixtures: self.clone(),
+                };
+                Client::from_jsonrpc(jsonrpc::Client::with_transport(transport))
+            })
+            .collect();
+
+        ClientPool::new(clients)
+    }
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    /// Record the responses of the transport to the fixtures.
+    pub fn new(inner: T, fixtures: Fixtures) -> Self {
+        RecordingTransport { inner, fixtures }
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn send_request(&self, request: Request) -> std::result::Result<Response, jsonrpc::Error> {
+        let response = self.inner.send_request(request.clone())?;
+        self.fixtures.insert(&request, &response);
+        Ok(response)
+    }
+
+    fn send_batch(
+        &self,
+        requests: &[Request],
+    ) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+        let responses = self.inner.send_batch(requests)?;
+
+        // Responses of a batch may arrive in any order
+        for response in &responses {
+            if let Some(request) = requests.iter().find(|request| request.id == response.id) {
+                self.fixtures.insert(request, response);
+            }
+        }
+
+        Ok(responses)
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt_target(f)
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send_request(&self, request: Request) -> std::result::Result<Response, jsonrpc::Error> {
+        Ok(self.fixtures.get(&request))
+    }
+
+    fn send_batch(
+        &self,
+        requests: &[Request],
+    ) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+        Ok(requests
+            .iter()
+            .map(|request| self.fixtures.get(request))
+            .collect())
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "replay")
+    }
+}
+
+/// Get the key of the fixture of a request, its method and parameters.
+fn get_key(request: &Request) -> String {
+    let params = request
+        .params
+        .iter()
+        .map(|param| param.get())
+        .collect::<Vec<_>>();
+
+    format!("{} [{}]", request.method, params.join(","))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::RpcApi;
+
+    /// Transport answering every request with the block count.
+    struct CountTransport;
+
+    impl Transport for CountTransport {
+        fn send_request(&self, request: Request) -> std::result::Result<Response, jsonrpc::Error> {
+            Ok(Response {
+                result: Some(jsonrpc::arg(800_000)),
+                error: None,
+                id: request.id,
+                jsonrpc: Some(String::from("2.0")),
+            })
+        }
+
+        fn send_batch(&self, _: &[Request]) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+            Ok(vec![])
+        }
+
+        fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "count")
+        }
+    }
+
+    #[test]
+    fn test_record_replay() {
+        let fixtures = Fixtures::default();
+        let transport = RecordingTransport::new(CountTransport, fixtures.clone());
+        let client = Client::from_jsonrpc(jsonrpc::Client::with_transport(transport));
+
+        assert_eq!(client.get_block_count().unwrap(), 800_000);
+        assert_eq!(fixtures.len(), 1);
+
+        let path = std::env::temp_dir().join(format!("fixtures-{}.json", std::process::id()));
+        fixtures.save(&path).unwrap();
+        let replay = Fixtures::load(&path).unwrap().get_replay_client(1);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(replay.get().get_block_count().unwrap(), 800_000);
+        assert!(replay.get().get_block_hash(1).is_err());
+    }
+}
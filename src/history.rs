@@ -0,0 +1,186 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{fs, path::Path, time::Duration};
+
+use crate::render::{get_markdown, render, OutputFormat, Report};
+use crate::utils::get_date;
+use crate::{Result, RunTable, StatisticsError};
+
+/// Time to wait for another run writing to the history.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Number of scalar fields of a report listed as its headline metrics.
+const HEADLINE_FIELDS: usize = 4;
+
+/// Registry of previous runs and their reports, backed by SQLite, so past
+/// reports can be listed and rendered again without fetching any blocks.
+pub struct RunHistory {
+    connection: Connection,
+}
+
+impl RunHistory {
+    /// Open the history at the path, creating it when missing. The database
+    /// is written ahead to a log like the header cache.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        let connection = Connection::open(path)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        RunHistory::new(connection)
+    }
+
+    /// Open a history held in memory, discarded once dropped.
+    pub fn open_in_memory() -> Result<Self> {
+        RunHistory::new(Connection::open_in_memory()?)
+    }
+
+    fn new(connection: Connection) -> Result<Self> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                time INTEGER NOT NULL,
+                analysis TEXT NOT NULL,
+                parameters TEXT NOT NULL,
+                headline TEXT NOT NULL DEFAULT '',
+                report_table TEXT,
+                report_json TEXT,
+                report_csv TEXT
+            )",
+            [],
+        )?;
+
+        Ok(RunHistory { connection })
+    }
+
+    /// Start a run of the analysis with the parameters at the unix time,
+    /// returning the id of the run.
+    pub fn start(&self, analysis: &str, parameters: &str, time: u64) -> Result<i64> {
+        self.connection.execute(
+            "INSERT INTO runs (time, analysis, parameters) VALUES (?1, ?2, ?3)",
+            params![time, analysis, parameters],
+        )?;
+
+        Ok(self.connection.last_insert_rowid())
+    }
+
+    /// Record the report of the run in every output format, replacing the
+    /// previous report of the run so the latest report is kept.
+    pub fn record<R: Report>(&self, id: i64, report: &R) -> Result<()> {
+        let json = render(report, OutputFormat::Json)?;
+
+        self.connection.execute(
+            "UPDATE runs SET headline = ?2, report_table = ?3, report_json = ?4, report_csv = ?5
+            WHERE id = ?1",
+            params![
+                id,
+                get_headline(&json),
+                render(report, OutputFormat::Table)?,
+                json,
+                render(report, OutputFormat::Csv)?
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the latest runs, most recent first.
+    pub fn list(&self, count: usize) -> Result<Vec<RunTable>> {
+        let mut statement = self.connection.prepare(
+            "SELECT id, time, analysis, parameters, headline FROM runs ORDER BY id DESC LIMIT ?1",
+        )?;
+        let runs = statement.query_map([count as i64], |row| {
+            Ok(RunTable {
+                id: row.get(0)?,
+                date: get_date(row.get(1)?),
+                analysis: row.get(2)?,
+                parameters: row.get(3)?,
+                headline: row.get(4)?,
+            })
+        })?;
+
+        Ok(runs.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Get the report of the run rendered in the format.
+    pub fn get_report(&self, id: i64, format: OutputFormat) -> Result<String> {
+        let column = match format {
+            OutputFormat::Table | OutputFormat::Markdown => "report_table",
+            OutputFormat::Json => "report_json",
+            OutputFormat::Csv => "report_csv",
+        };
+        let report = self
+            .connection
+            .query_row(
+                &format!("SELECT {} FROM runs WHERE id = ?1", column),
+                [id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()?;
+
+        let report = report
+            .flatten()
+            .ok_or(StatisticsError::RunNotFoundError(id))?;
+
+        match format {
+            OutputFormat::Markdown => Ok(get_markdown(&report)),
+            _ => Ok(report),
+        }
+    }
+}
+
+/// Get the headline metrics of a report rendered as JSON, a few of its numeric
+/// fields. The fields of a partial report are those of the nested report.
+fn get_headline(json: &str) -> String {
+    let value = serde_json::from_str::<serde_json::Value>(json).unwrap_or_default();
+    let report = value.get("report").unwrap_or(&value);
+
+    report
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .filter(|(_, value)| value.is_number())
+                .take(HEADLINE_FIELDS)
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RecordsReport;
+
+    #[test]
+    fn test_run_history() {
+        let history = RunHistory::open_in_memory().unwrap();
+        let report = RecordsReport {
+            entries: vec![],
+            gaps: 12,
+        };
+
+        let first = history
+            .start("records", "records --count 3", 1_600_000_000)
+            .unwrap();
+        history.record(first, &report).unwrap();
+        let second = history.start("watch", "watch", 1_600_000_600).unwrap();
+
+        let runs = history.list(10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].id, second);
+        assert_eq!(runs[1].analysis, "records");
+        assert_eq!(runs[1].date, "2020-09-13 12:26");
+        assert_eq!(runs[1].headline, "gaps: 12");
+
+        let json = history.get_report(first, OutputFormat::Json).unwrap();
+        assert_eq!(json, r#"{"entries":[],"gaps":12}"#);
+        assert!(matches!(
+            history.get_report(second, OutputFormat::Table),
+            Err(StatisticsError::RunNotFoundError(_))
+        ));
+        assert!(history.get_report(3, OutputFormat::Table).is_err());
+    }
+}
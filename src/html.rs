@@ -0,0 +1,196 @@
+use std::fmt::{self, Display};
+use std::{fs, path::Path};
+
+use crate::l10n::localize;
+use crate::render::{get_blocks, Block, RenderedTable};
+use crate::Result;
+
+/// Style of the page, kept inline so the file is self-contained.
+const STYLE: &str = "body{font-family:sans-serif;margin:2em auto;max-width:72em;color:#222}
+table{border-collapse:collapse;margin:1em 0}
+caption{font-weight:bold;text-align:left;padding:.5em 0}
+th,td{border:1px solid #ccc;padding:.3em .6em;text-align:right}
+th{background:#f3f3f3}
+pre{background:#f6f6f6;padding:1em;overflow-x:auto}
+figure{margin:1em 0}";
+
+/// Self-contained HTML page of a run, the parameters of the run followed by
+/// its reports as tables and the charts of the analysis inlined as SVG, so
+/// a run can be shared with readers without the CLI.
+#[derive(Clone, PartialEq, Debug)]
+pub struct HtmlReport {
+    analysis: String,
+    parameters: Vec<(String, String)>,
+    /// Rendered HTML of the reports and charts
+    sections: Vec<String>,
+    /// Whether reports are added after the previous reports, otherwise a
+    /// report replaces them along with their charts
+    append: bool,
+}
+
+impl HtmlReport {
+    pub fn new(analysis: &str) -> Self {
+        HtmlReport {
+            analysis: analysis.to_string(),
+            parameters: vec![],
+            sections: vec![],
+            append: false,
+        }
+    }
+
+    /// Add a parameter of the run to the summary, the label is localized.
+    pub fn with_parameter<T: Display>(mut self, label: &str, value: T) -> Self {
+        self.parameters.push((localize(label), value.to_string()));
+        self
+    }
+
+    /// Keep every report of the run, e.g. the analyses of run-all.
+    pub fn with_append(mut self) -> Self {
+        self.append = true;
+        self
+    }
+
+    /// Add a report rendered as tables, see get_blocks.
+    pub fn add_report(&mut self, rendered: &str) {
+        if !self.append {
+            self.sections.clear();
+        }
+
+        for block in get_blocks(rendered) {
+            self.sections.push(match block {
+                Block::Text(text) if text.len() == 1 => format!("<p>{}</p>", escape(&text[0])),
+                Block::Text(text) => format!("<pre>{}</pre>", escape(&text.join("\n"))),
+                Block::Table(table) => get_html_table(&table),
+            });
+        }
+    }
+
+    /// Add an SVG chart after the report it belongs to.
+    pub fn add_chart(&mut self, svg: &str) {
+        // An XML declaration is not valid within an HTML page
+        let svg = svg.find("<svg").map_or(svg, |start| &svg[start..]);
+        self.sections
+            .push(format!("<figure>{}</figure>", svg.trim()));
+    }
+
+    /// Write the page to the file, replacing it.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, self.to_string())?)
+    }
+}
+
+impl Display for HtmlReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = format!("bitcoin-statistics {}", self.analysis);
+
+        writeln!(f, "<!DOCTYPE html>")?;
+        writeln!(f, "<html>")?;
+        writeln!(f, "<head>")?;
+        writeln!(f, "<meta charset=\"utf-8\">")?;
+        writeln!(f, "<title>{}</title>", escape(&title))?;
+        writeln!(f, "<style>\n{}\n</style>", STYLE)?;
+        writeln!(f, "</head>")?;
+        writeln!(f, "<body>")?;
+        writeln!(f, "<h1>{}</h1>", escape(&title))?;
+        writeln!(f, "<table>")?;
+        writeln!(f, "<caption>{}</caption>", escape(&localize("Parameters")))?;
+
+        for (label, value) in &self.parameters {
+            writeln!(
+                f,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape(label),
+                escape(value)
+            )?;
+        }
+
+        writeln!(f, "</table>")?;
+
+        for section in &self.sections {
+            writeln!(f, "{}", section)?;
+        }
+
+        writeln!(f, "</body>")?;
+        writeln!(f, "</html>")
+    }
+}
+
+/// Convert a parsed table to HTML, the title as its caption and the pairs
+/// of its footer as a list below it.
+fn get_html_table(table: &RenderedTable) -> String {
+    let cells = |row: &[String], tag: &str| {
+        let cells = row
+            .iter()
+            .map(|cell| format!("<{0}>{1}</{0}>", tag, escape(cell).replace('\n', "<br>")));
+        format!("<tr>{}</tr>", cells.collect::<String>())
+    };
+    let mut html = vec!["<table>".to_string()];
+
+    if let Some(title) = &table.title {
+        html.push(format!("<caption>{}</caption>", escape(title)));
+    }
+
+    if let Some((header, body)) = table.rows.split_first() {
+        html.push(format!("<thead>{}</thead>", cells(header, "th")));
+        html.push("<tbody>".to_string());
+        html.extend(body.iter().map(|row| cells(row, "td")));
+        html.push("</tbody>".to_string());
+    }
+
+    html.push("</table>".to_string());
+
+    if let Some(footer) = &table.footer {
+        let pairs = footer.split(", ").map(|pair| match pair.split_once(": ") {
+            Some((label, value)) => {
+                format!(
+                    "<li><strong>{}:</strong> {}</li>",
+                    escape(label),
+                    escape(value)
+                )
+            }
+            None => format!("<li>{}</li>", escape(pair)),
+        });
+        html.push(format!("<ul>{}</ul>", pairs.collect::<String>()));
+    }
+
+    html.join("\n")
+}
+
+/// Escape the text for the content or attributes of HTML elements.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_html_report() {
+        let rendered = "+---+-----+\n| Title   |\n+---+-----+\n| A | B   |\n+---+-----+\n| 1 | <y> |\n|   | z   |\n+---+-----+\n| Mean: 1 s |\n+---+-----+\nWarning: unstable";
+        let mut html = HtmlReport::new("records").with_parameter("Confidence", 0.95);
+
+        html.add_report("stale");
+        html.add_report(rendered);
+        html.add_chart("<?xml version=\"1.0\"?>\n<svg></svg>\n");
+        let page = html.to_string();
+
+        assert!(page.contains("<title>bitcoin-statistics records</title>"));
+        assert!(page.contains("<tr><th>Confidence</th><td>0.95</td></tr>"));
+        assert!(page.contains("<caption>Title</caption>"));
+        assert!(page.contains("<thead><tr><th>A</th><th>B</th></tr></thead>"));
+        assert!(page.contains("<tr><td>1</td><td>&lt;y&gt;<br>z</td></tr>"));
+        assert!(page.contains("<ul><li><strong>Mean:</strong> 1 s</li></ul>"));
+        assert!(page.contains("<p>Warning: unstable</p>"));
+        assert!(page.contains("<figure><svg></svg></figure>"));
+        assert!(!page.contains("stale"));
+
+        let mut html = html.with_append();
+        html.add_report("stale");
+        assert!(html.to_string().contains("<p>stale</p>"));
+        assert!(html.to_string().contains("<figure>"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Localization of the rendered reports with Fluent, see
+//! https://projectfluent.org. The messages of every language are bundled
+//! from src/locales and keyed by the slug of their English text, e.g.
+//! `block-times` for "Block Times", so the titles, column headers and
+//! summaries of the tables are looked up by their English text. Text
+//! without a message in the language is rendered in English.
+
+use fluent_bundle::{concurrent::FluentBundle, FluentResource};
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+/// Localizer of the rendered reports, English until the language is set.
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+/// Language of the rendered reports.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+/// Messages of a language.
+pub struct Localizer {
+    bundle: FluentBundle<FluentResource>,
+}
+
+impl Language {
+    /// Get the Fluent messages of the language.
+    fn get_messages(&self) -> &'static str {
+        match self {
+            Language::English => include_str!("locales/en.ftl"),
+            Language::Spanish => include_str!("locales/es.ftl"),
+        }
+    }
+}
+
+impl Localizer {
+    /// Create a new localizer of the bundled messages of the language.
+    pub fn new(language: Language) -> Self {
+        let resource = FluentResource::try_new(language.get_messages().to_string())
+            .expect("Bundled messages are valid Fluent");
+        let id = language.to_string().parse().expect("Valid language id");
+        let mut bundle = FluentBundle::new_concurrent(vec![id]);
+        // Isolation marks would misalign the columns of the tables
+        bundle.set_use_isolating(false);
+        bundle
+            .add_resource(resource)
+            .expect("Bundled messages are unique");
+
+        Localizer { bundle }
+    }
+
+    /// Get the message of the English text, none without a message.
+    pub fn get(&self, text: &str) -> Option<String> {
+        let message = self.bundle.get_message(&get_message_id(text))?;
+        let mut errors = vec![];
+        let value = self
+            .bundle
+            .format_pattern(message.value()?, None, &mut errors);
+
+        Some(value.into_owned())
+    }
+}
+
+/// Render the reports in the language from now on. The language is only
+/// set once, later calls are ignored.
+pub fn set_language(language: Language) {
+    if LOCALIZER.get().is_none() {
+        let _ = LOCALIZER.set(Localizer::new(language));
+    }
+}
+
+/// Localize the English text in the language of the reports.
+pub fn localize(text: &str) -> String {
+    match LOCALIZER.get() {
+        Some(localizer) => localize_with(localizer, text),
+        None => text.to_string(),
+    }
+}
+
+/// Localize a summary of comma separated pairs of a label and a value, e.g.
+/// `Mean: 9.82 minutes`, see localize_summary_with.
+pub fn localize_summary(summary: &str) -> String {
+    match LOCALIZER.get() {
+        Some(localizer) => localize_summary_with(localizer, summary),
+        None => summary.to_string(),
+    }
+}
+
+/// Localize the English text with the localizer, the text as is without
+/// a message.
+fn localize_with(localizer: &Localizer, text: &str) -> String {
+    localizer.get(text).unwrap_or_else(|| text.to_string())
+}
+
+/// Localize the labels of the summary along with the words of their values,
+/// e.g. the units, keeping the numbers as they are. Lowercase words stay
+/// lowercase, e.g. `blocks` of the message of "Blocks".
+fn localize_summary_with(localizer: &Localizer, summary: &str) -> String {
+    summary
+        .split(", ")
+        .map(|pair| match pair.split_once(": ") {
+            Some((label, value)) => {
+                let words = value
+                    .split(' ')
+                    .map(|word| {
+                        let inner = word.trim_matches(|c| c == '(' || c == ')');
+
+                        if inner.is_empty() || !inner.chars().all(|c| c.is_ascii_alphabetic()) {
+                            return word.to_string();
+                        }
+
+                        let localized = match inner.starts_with(|c: char| c.is_lowercase()) {
+                            true => localize_with(localizer, inner).to_lowercase(),
+                            false => localize_with(localizer, inner),
+                        };
+                        word.replace(inner, &localized)
+                    })
+                    .collect::<Vec<_>>();
+
+                format!("{}: {}", localize_with(localizer, label), words.join(" "))
+            }
+            None => localize_with(localizer, pair),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Get the id of the message of the English text, its lowercase words
+/// joined by dashes, e.g. `date-utc` for "Date (UTC)".
+fn get_message_id(text: &str) -> String {
+    text.to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Language::English => write!(f, "en"),
+            Language::Spanish => write!(f, "es"),
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Language::English),
+            "es" => Ok(Language::Spanish),
+            _ => Err(format!("Unsupported language: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Get the ids of the messages of the language.
+    fn get_ids(language: Language) -> Vec<&'static str> {
+        let mut ids = language
+            .get_messages()
+            .lines()
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn test_localize() {
+        let spanish = Localizer::new(Language::Spanish);
+        assert_eq!(get_message_id("Date (UTC)"), "date-utc");
+        assert_eq!(localize_with(&spanish, "Block Times"), "Tiempos de Bloque");
+        assert_eq!(localize_with(&spanish, "Unknown Text"), "Unknown Text");
+        assert_eq!(
+            localize_summary_with(&spanish, "Occurrences: 3, Mean: 9.82 minutes (5 blocks)"),
+            "Ocurrencias: 3, Media: 9.82 minutos (5 bloques)"
+        );
+        assert!("fr".parse::<Language>().is_err());
+
+        // Every language translates every message
+        assert_eq!(get_ids(Language::English), get_ids(Language::Spanish));
+    }
+}
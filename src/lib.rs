@@ -1,20 +1,101 @@
-use bitcoincore_rpc::{bitcoin::BlockHeader, Auth, Client, RpcApi};
+use bitcoincore_rpc::{
+    bitcoin::{hashes::hex, Block, BlockHeader, Network},
+    json::GetBlockStatsResult,
+    jsonrpc, Auth, Client, RpcApi,
+};
 use config::Config;
-use indicatif::ProgressBar;
-use rand::{distributions::Uniform, Rng};
-use serde::Deserialize;
-use std::{collections::BinaryHeap, path::PathBuf, sync::Arc};
-use tabled::{Footer, Header, Table, Tabled};
+use rand::{distributions::Uniform, rngs::StdRng, Rng, SeedableRng};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    io::{BufWriter, Write},
+    mem::size_of,
+    ops::{Add, Range, Sub},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 use thiserror::Error;
 use tokio::task::JoinError;
-use zeroize::ZeroizeOnDrop;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
+pub use tokio_util::sync::CancellationToken;
+
+pub use crate::analyses::*;
+
+use crate::blockfiles::{BlockFileBackend, BlockFileIndex};
+use crate::cache::{HeaderCache, CACHE_DEPTH};
+use crate::electrum::{ElectrumBackend, HeaderIndex};
+#[cfg(feature = "esplora")]
+use crate::esplora::{EsploraBackend, RateLimit, DEFAULT_RATE_LIMIT};
+use crate::exclusions::{Exclusions, HeightRange};
+use crate::fixtures::{Fixtures, RecordingTransport};
+use crate::planner::SourceConfig;
+use crate::pow::verify_pow;
+use crate::progress::{FetchProgress, ProgressSink};
+use crate::rest::RestBackend;
+use crate::templates::ExportTemplate;
+use crate::transport::{
+    get_block_headers, AnswerTransport, ClientPool, Endpoint, FailurePolicy, HttpTransport,
+    RpcTransport, DEFAULT_BATCH_SIZE,
+};
 use crate::utils::*;
 
+pub mod analyses;
+pub mod blockfiles;
+pub mod cache;
+#[cfg(feature = "charts")]
+pub mod charts;
+pub mod clusters;
+pub mod custom;
+pub mod electrum;
+#[cfg(feature = "esplora")]
+pub mod esplora;
+pub mod examples;
+pub mod exclusions;
+pub mod fixtures;
+pub mod history;
+pub mod html;
+pub mod l10n;
+pub mod planner;
+#[cfg(feature = "plugins")]
+pub mod plugins;
+pub mod pools;
+pub mod pow;
+pub mod prices;
+pub mod progress;
+pub mod records;
+pub mod render;
+pub mod rest;
+pub mod schema;
+pub mod scripts;
+pub mod signet;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+pub mod templates;
+pub mod transport;
 pub mod utils;
+pub mod watch;
 
 pub type Result<T> = std::result::Result<T, StatisticsError>;
-pub type BlockHeap = BinaryHeap<BlockTimeDriftTable>;
+
+/// Compact difficulty target of the proof of work limit.
+const POW_LIMIT_BITS: u32 = 0x1d00ffff;
+/// Rounds of a progressive collection, each halving the margin of error.
+const PROGRESSIVE_ROUNDS: u32 = 4;
+/// Share of the population from which a sample is replaced by the whole
+/// population, as random windows would mostly draw the same blocks.
+const EXHAUSTIVE_SHARE: f64 = 0.5;
+/// Number of past blocks of the median time past, see BIP113.
+pub const MTP_BLOCKS: u64 = 11;
+/// Number of blocks between difficulty retargets.
+const RETARGET_INTERVAL: u64 = 2016;
+/// Target seconds between blocks.
+const TARGET_SPACING: i64 = 600;
+/// Number of blocks between subsidy halvings.
+const HALVING_INTERVAL: u64 = 210_000;
 
 /// Application errors.
 #[derive(Error, Debug)]
@@ -25,6 +106,65 @@ pub enum StatisticsError {
     ClientError(#[from] bitcoincore_rpc::Error),
     #[error("An error occurred fetching block data")]
     RPCError(#[from] JoinError),
+    #[error("Unable to fetch block {height}")]
+    BlockFetchError {
+        height: BlockHeight,
+        #[source]
+        source: Box<StatisticsError>,
+    },
+    #[error("Invalid signet challenge")]
+    ChallengeError(#[from] hex::Error),
+    #[error(
+        "The sample requires about {required} bytes exceeding the memory budget of {budget} bytes"
+    )]
+    MemoryBudgetError { required: u64, budget: u64 },
+    #[error("Invalid bitcoind endpoint {endpoint}: {reason}")]
+    EndpointError { endpoint: String, reason: String },
+    #[error("ZMQ subscription error: {0}")]
+    ZmqError(#[from] zmq::Error),
+    #[error("Unable to decode notification: {0}")]
+    DecodeError(#[from] bitcoincore_rpc::bitcoin::consensus::encode::Error),
+    #[error("{0}")]
+    HeightRangeError(String),
+    #[error("Unable to access file")]
+    IOError(#[from] std::io::Error),
+    #[error("Unable to serialize data")]
+    SerializationError(#[from] serde_json::Error),
+    #[error("Unable to write CSV: {0}")]
+    CsvError(#[from] csv::Error),
+    #[error("Header cache error: {0}")]
+    CacheError(#[from] rusqlite::Error),
+    #[error("The header cache belongs to the chain of genesis block {cached}, not {genesis}")]
+    CacheChainError { cached: String, genesis: String },
+    #[error("No report recorded for run {0}")]
+    RunNotFoundError(i64),
+    #[error("Price feed error: {0}")]
+    PriceFeedError(String),
+    #[error("Unable to render chart: {0}")]
+    ChartError(String),
+    #[error("Unable to export telemetry: {0}")]
+    TelemetryError(String),
+    #[error("Invalid export template: {0}")]
+    TemplateError(String),
+    #[error("Unsupported backend: {0}")]
+    BackendError(String),
+    #[error("{0}")]
+    ExampleError(String),
+    #[error("Invalid pool signature: {0}")]
+    PoolSignatureError(String),
+    #[error("{count} headers fail their proof of work, the first at height {height}")]
+    ProofOfWorkError { count: usize, height: BlockHeight },
+    #[error("Invalid custom expression: {0}")]
+    ExpressionError(#[from] Box<rhai::EvalAltResult>),
+    #[cfg(feature = "plugins")]
+    #[error("WASM plugin error: {0}")]
+    PluginError(#[from] wasmi::Error),
+    #[cfg(feature = "plugins")]
+    #[error("Unsupported plugin ABI version {version}, expected {expected}")]
+    PluginAbiError { version: i32, expected: i32 },
+    #[cfg(feature = "plugins")]
+    #[error("The plugin failed with exit code {0}")]
+    PluginExitError(i32),
 }
 
 /// Configurations required for connecting to bitcoind via RPC.
@@ -32,10 +172,65 @@ pub enum StatisticsError {
 #[derive(Deserialize, ZeroizeOnDrop)]
 pub struct ClientConfig {
     host: String,
+    #[serde(default)]
     username: String,
+    #[serde(default)]
     password: String,
+    /// Path of the cookie file of bitcoind, used instead of the username
+    /// and password when given
+    #[serde(default)]
+    cookie_file: Option<String>,
+    /// Server the blocks are fetched from
+    #[serde(default)]
+    #[zeroize(skip)]
+    backend: Backend,
+    /// Requests per second sent to an Esplora instance
+    #[serde(default)]
+    #[zeroize(skip)]
+    rate_limit: Option<f64>,
+    /// Further sources picked from by the data needs of an analysis
+    #[serde(default)]
+    #[zeroize(skip)]
+    sources: Vec<SourceConfig>,
+}
+
+/// Server the blocks are fetched from.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// The RPC of bitcoind
+    #[default]
+    Bitcoind,
+    /// An Electrum server serving block headers only, see electrum
+    Electrum,
+    /// The REST API of an Esplora instance, see esplora
+    Esplora,
+    /// The blk*.dat files of the blocks directory of a local node, see
+    /// blockfiles
+    BlockFiles,
+    /// The REST interface of bitcoind, see rest
+    Rest,
 }
 
+/// Height of a block within the chain, kept apart from counts of blocks and
+/// indices into a sample. Serialized as the bare number.
+#[derive(
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+)]
+#[serde(transparent)]
+pub struct BlockHeight(pub u64);
+
 /// Configuration for sampling data from the network.
 #[derive(Clone, PartialEq, PartialOrd, Debug)]
 pub struct BlockSample {
@@ -43,62 +238,424 @@ pub struct BlockSample {
     margin_error: f64,
     std_deviation: f64,
     full_population: bool,
+    max_memory: Option<u64>,
+    max_bytes: Option<u64>,
+    exclusions: Exclusions,
+    start_height: u64,
+    end_height: Option<u64>,
+    since: Option<u32>,
+    until: Option<u32>,
+    cache_dir: Option<PathBuf>,
+    seed: u64,
+    batch_size: usize,
+    context: u64,
+    strategy: SamplingStrategy,
+}
+
+/// Strategy of drawing the windows of a sample from the population.
+#[derive(Clone, Default, PartialEq, PartialOrd, Debug)]
+pub enum SamplingStrategy {
+    /// Draw the windows uniformly from the whole population
+    #[default]
+    Uniform,
+    /// Draw the windows from every stratum in proportion to its share of
+    /// the population, so every hashrate regime is represented
+    Stratified(Strata),
+    /// Draw every k-th window from a random offset, spreading the windows
+    /// evenly over the population
+    Systematic,
+}
+
+/// Strata of the population of a stratified sample.
+#[derive(Clone, Default, PartialEq, PartialOrd, Debug)]
+pub enum Strata {
+    /// Difficulty retarget epochs of 2016 blocks
+    #[default]
+    Epochs,
+    /// Ranges of heights, the heights outside every range are not sampled
+    Ranges(Vec<HeightRange>),
+}
+
+/// Collected sample data ready for analysis, keyed by block height.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlockSampleData(Vec<(BlockHeight, BlockHeader)>);
+
+/// Collected sample of block statistics (getblockstats) ready for analysis,
+/// keyed by block height.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlockStatsData(Vec<(BlockHeight, GetBlockStatsResult)>);
+
+/// Outcome of a cancellable run. A cancelled run still returns the data
+/// gathered up to the point of cancellation.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Outcome<T> {
+    /// The run finished
+    Completed(T),
+    /// The run was cancelled and only holds partial data
+    Cancelled { partial: T },
 }
 
-/// Collected sample data ready for analysis.
+/// Collected sample of full blocks ready for analysis, keyed by block height.
 #[derive(Clone, PartialEq, Debug)]
-pub struct BlockSampleData(Vec<BlockHeader>);
-
-/// Use a struct to store the drift and blocks for a binary heap.
-/// Doubles as the sample table.
-#[derive(Tabled, Clone, Eq, PartialEq, Debug)]
-pub struct BlockTimeDriftTable {
-    #[tabled(rename = "Mining Time", order = 2, display_with = "display_mins")]
-    drift: i64,
-    #[tabled(rename = "Parent Block Hash", order = 0)]
-    parent_hash: String,
-    #[tabled(rename = "Child Block Hash", order = 1)]
-    child_hash: String,
-}
-
-/// Possible statistical analysis that can be run on sampled data.
-pub trait BlockStatistics {
-    /// Run a statistical analysis of two contiguous blocks having a specified
-    /// drift time between mining. The window specifies the number of
-    /// contiguous blocks occur in the sample data.
+pub struct BlockSampleFullData(Vec<(BlockHeight, Block)>);
+
+impl fmt::Display for BlockHeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Backend::Bitcoind => write!(f, "bitcoind"),
+            Backend::Electrum => write!(f, "electrum"),
+            Backend::Esplora => write!(f, "esplora"),
+            Backend::BlockFiles => write!(f, "blockfiles"),
+            Backend::Rest => write!(f, "rest"),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "bitcoind" => Ok(Backend::Bitcoind),
+            "electrum" => Ok(Backend::Electrum),
+            "esplora" => Ok(Backend::Esplora),
+            "blockfiles" => Ok(Backend::BlockFiles),
+            "rest" => Ok(Backend::Rest),
+            _ => Err(format!("Unknown backend: {}", s)),
+        }
+    }
+}
+
+impl From<u64> for BlockHeight {
+    fn from(height: u64) -> Self {
+        BlockHeight(height)
+    }
+}
+
+impl From<BlockHeight> for u64 {
+    fn from(height: BlockHeight) -> Self {
+        height.0
+    }
+}
+
+impl Add<u64> for BlockHeight {
+    type Output = BlockHeight;
+
+    /// The height a number of blocks later.
+    fn add(self, blocks: u64) -> BlockHeight {
+        BlockHeight(self.0 + blocks)
+    }
+}
+
+impl Sub<u64> for BlockHeight {
+    type Output = BlockHeight;
+
+    /// The height a number of blocks earlier.
+    fn sub(self, blocks: u64) -> BlockHeight {
+        BlockHeight(self.0 - blocks)
+    }
+}
+
+impl Sub for BlockHeight {
+    type Output = u64;
+
+    /// The number of blocks between the heights.
+    fn sub(self, other: BlockHeight) -> u64 {
+        self.0 - other.0
+    }
+}
+
+impl FromStr for BlockHeight {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse()
+            .map(BlockHeight)
+            .map_err(|_| format!("Invalid block height: {}", s))
+    }
+}
+
+impl BlockSampleData {
+    /// Create the sample data from block headers keyed by height, allowing
+    /// analyses over headers from any source.
+    pub fn new(blocks: Vec<(BlockHeight, BlockHeader)>) -> Self {
+        BlockSampleData(blocks)
+    }
+
+    /// Number of blocks in the sample.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check whether the sample has no blocks.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Export the raw intervals of the sample as CSV, see write_intervals.
+    pub fn export_intervals<W: Write>(&self, writer: W, window: u64) -> Result<()> {
+        let headers = self.0.iter().map(|(height, header)| (*height, header));
+        write_intervals(writer, &headers.collect::<Vec<_>>(), window)
+    }
+
+    /// Export a row of every block of the sample formatted by the template.
+    pub fn export_blocks<W: Write>(&self, writer: W, template: &ExportTemplate) -> Result<()> {
+        let headers = self.0.iter().map(|(height, header)| (*height, header));
+        template.write(writer, &headers.collect::<Vec<_>>())
+    }
+
+    /// Verify the proof of work of every header of the sample, see
+    /// pow::verify_pow.
+    pub fn verify_pow(&self, network: Network) -> Result<()> {
+        verify_pow(&self.0, network)
+    }
+
+    /// Get the mining times in minutes of the contiguous blocks of every
+    /// window grouped by the stratum of the block mined, ordered by stratum.
+    /// Blocks outside every stratum are left out.
+    pub fn get_stratum_intervals(
+        &self,
+        strata: &Strata,
+        window: u64,
+    ) -> Vec<(HeightRange, Vec<f64>)> {
+        let window = window as usize;
+        let mut intervals: BTreeMap<HeightRange, Vec<f64>> = BTreeMap::new();
+
+        self.0.windows(window).step_by(window).for_each(|headers| {
+            headers.windows(2).for_each(|pair| {
+                let ((_, parent), (height, child)) = (&pair[0], &pair[1]);
+
+                if let Some(stratum) = strata.get_stratum(height.0) {
+                    let seconds = (child.time as i64) - (parent.time as i64);
+                    intervals
+                        .entry(stratum)
+                        .or_default()
+                        .push(seconds as f64 / 60.0);
+                }
+            });
+        });
+
+        intervals.into_iter().collect()
+    }
+
+    /// Replace the timestamp of every block with its median time past, the
+    /// median timestamp of the 11 blocks before it, smoothing the noise of
+    /// miner timestamps. Blocks without all 11 of them in the sample are
+    /// dropped, so every window needs to be collected with MTP_BLOCKS more
+    /// blocks before it as context.
     ///
-    /// Note: This will not be totally accurate as blocks depends on miner
-    /// provided timestamps which must be within a 2-hour window of network
-    /// adjusted time and be greater than the median of the past 11 blocks.
     /// See https://github.com/bitcoin/bips/blob/master/bip-0113.mediawiki
-    /// See https://arxiv.org//pdf/1803.09028.pdf
-    fn fetch_block_time_drift(self, drift_time: i64, window: u64, sample: bool);
+    pub fn with_median_time_past(self) -> Self {
+        let times = self
+            .0
+            .iter()
+            .map(|(height, header)| (*height, header.time))
+            .collect::<HashMap<_, _>>();
+        let blocks = self
+            .0
+            .into_iter()
+            .filter_map(|(height, header)| {
+                let mut past = (height.0.checked_sub(MTP_BLOCKS)?..height.0)
+                    .map(|height| times.get(&BlockHeight(height)).copied())
+                    .collect::<Option<Vec<_>>>()?;
+                past.sort_unstable();
+
+                let time = past[past.len() / 2];
+                Some((height, BlockHeader { time, ..header }))
+            })
+            .collect();
+
+        BlockSampleData(blocks)
+    }
 }
 
-impl PartialOrd for BlockTimeDriftTable {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.drift.partial_cmp(&other.drift)
+impl BlockSampleFullData {
+    /// Create the sample data from full blocks keyed by height, allowing
+    /// analyses over blocks from any source.
+    pub fn new(blocks: Vec<(BlockHeight, Block)>) -> Self {
+        BlockSampleFullData(blocks)
+    }
+
+    /// Export the raw intervals of the sample as CSV, see write_intervals.
+    pub fn export_intervals<W: Write>(&self, writer: W, window: u64) -> Result<()> {
+        let headers = self
+            .0
+            .iter()
+            .map(|(height, block)| (*height, &block.header));
+        write_intervals(writer, &headers.collect::<Vec<_>>(), window)
+    }
+
+    /// Export a row of every block of the sample formatted by the template.
+    pub fn export_blocks<W: Write>(&self, writer: W, template: &ExportTemplate) -> Result<()> {
+        let headers = self
+            .0
+            .iter()
+            .map(|(height, block)| (*height, &block.header));
+        template.write(writer, &headers.collect::<Vec<_>>())
+    }
+
+    /// Verify the proof of work of the header of every block of the
+    /// sample, see pow::verify_pow.
+    pub fn verify_pow(&self, network: Network) -> Result<()> {
+        let headers = self.0.iter().map(|(height, block)| (*height, block.header));
+        verify_pow(&headers.collect::<Vec<_>>(), network)
+    }
+
+    /// Number of blocks in the sample.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check whether the sample has no blocks.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
 }
 
-impl Ord for BlockTimeDriftTable {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.drift.cmp(&other.drift)
+impl BlockStatsData {
+    /// Create the sample data from block statistics keyed by height,
+    /// allowing analyses over statistics from any source.
+    pub fn new(stats: Vec<(BlockHeight, GetBlockStatsResult)>) -> Self {
+        BlockStatsData(stats)
+    }
+
+    /// Number of blocks in the sample.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check whether the sample has no blocks.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
 }
 
-impl BlockTimeDriftTable {
-    /// Create a new instance of a BlockSample which will specify the
-    /// parameters for fetching the sample data from bitcoind.
-    pub fn new(drift: i64, parent_hash: String, child_hash: String) -> Self {
-        BlockTimeDriftTable {
-            drift,
-            parent_hash,
-            child_hash,
+impl<T> Outcome<T> {
+    /// Check whether the run was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Outcome::Cancelled { .. })
+    }
+
+    /// Get a reference to the (possibly partial) data of the run.
+    pub fn inner(&self) -> &T {
+        match self {
+            Outcome::Completed(data) => data,
+            Outcome::Cancelled { partial } => partial,
+        }
+    }
+
+    /// Get the (possibly partial) data of the run.
+    pub fn into_inner(self) -> T {
+        match self {
+            Outcome::Completed(data) => data,
+            Outcome::Cancelled { partial } => partial,
+        }
+    }
+
+    /// Transform the data of the run keeping the outcome.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Outcome<U> {
+        match self {
+            Outcome::Completed(data) => Outcome::Completed(f(data)),
+            Outcome::Cancelled { partial } => Outcome::Cancelled {
+                partial: f(partial),
+            },
+        }
+    }
+}
+
+impl FromStr for SamplingStrategy {
+    type Err = String;
+
+    /// Parse a strategy, stratified by difficulty epoch.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(SamplingStrategy::Uniform),
+            "stratified" => Ok(SamplingStrategy::Stratified(Strata::Epochs)),
+            "systematic" => Ok(SamplingStrategy::Systematic),
+            _ => Err(format!("Unknown sampling strategy: {}", s)),
+        }
+    }
+}
+
+impl Strata {
+    /// Get the strata within the heights from the start height below the
+    /// block height, clipped to the heights.
+    pub fn get_ranges(&self, start: u64, block_max: u64) -> Vec<HeightRange> {
+        let mut ranges = match self {
+            Strata::Epochs => (start / RETARGET_INTERVAL * RETARGET_INTERVAL..block_max)
+                .step_by(RETARGET_INTERVAL as usize)
+                .map(|epoch| HeightRange {
+                    start: epoch,
+                    end: epoch + RETARGET_INTERVAL,
+                })
+                .collect(),
+            Strata::Ranges(ranges) => ranges.clone(),
+        };
+        ranges.sort();
+
+        ranges
+            .into_iter()
+            .map(|range| HeightRange {
+                start: range.start.max(start),
+                end: range.end.min(block_max),
+            })
+            .filter(|range| range.start < range.end)
+            .collect()
+    }
+
+    /// Get the stratum of the height, none outside every stratum.
+    pub fn get_stratum(&self, height: u64) -> Option<HeightRange> {
+        match self {
+            Strata::Epochs => {
+                let start = height / RETARGET_INTERVAL * RETARGET_INTERVAL;
+                Some(HeightRange {
+                    start,
+                    end: start + RETARGET_INTERVAL,
+                })
+            }
+            Strata::Ranges(ranges) => ranges
+                .iter()
+                .find(|range| range.start <= height && height < range.end)
+                .copied(),
         }
     }
 }
 
+impl ClientConfig {
+    /// Authenticate with the cookie file of bitcoind, e.g.
+    /// `~/.bitcoin/.cookie`, instead of the username and password.
+    pub fn with_cookie_file(mut self, path: String) -> Self {
+        self.cookie_file = Some(path);
+        self
+    }
+
+    /// Connect to the source instead of the main source, keeping the
+    /// credentials of bitcoind.
+    pub fn with_source(mut self, source: SourceConfig) -> Self {
+        self.backend = source.backend;
+        self.host = source.host;
+        self.rate_limit = source.rate_limit;
+        self
+    }
+
+    /// Get the configured sources, the main source first.
+    pub fn get_sources(&self) -> Vec<SourceConfig> {
+        let main = SourceConfig {
+            backend: self.backend,
+            host: self.host.clone(),
+            rate_limit: self.rate_limit,
+        };
+
+        [main].into_iter().chain(self.sources.clone()).collect()
+    }
+}
+
 impl BlockSample {
     /// Create a new instance of a BlockSample which will specify the
     /// parameters for fetching the sample data from bitcoind.
@@ -108,225 +665,2019 @@ impl BlockSample {
             std_deviation,
             margin_error,
             full_population,
+            max_memory: None,
+            max_bytes: None,
+            exclusions: Exclusions::default(),
+            start_height: 0,
+            end_height: None,
+            since: None,
+            until: None,
+            cache_dir: None,
+            seed: rand::random(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            context: 0,
+            strategy: SamplingStrategy::Uniform,
         }
     }
 
+    /// Draw the windows of the sample by the strategy, uniformly from the
+    /// whole population by default.
+    pub fn with_strategy(mut self, strategy: SamplingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Cache the fetched block headers in the directory, see collect.
+    pub fn with_cache_dir(mut self, dir: PathBuf) -> Self {
+        self.cache_dir = Some(dir);
+        self
+    }
+
+    /// Exclude heights from the population, e.g. known anomalous regions.
+    /// Sampled windows never include an excluded height.
+    pub fn with_exclusions(mut self, exclusions: Exclusions) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+
+    /// Restrict the population to the heights from the start height, e.g.
+    /// only blocks after the activation of Taproot.
+    pub fn with_start_height(mut self, height: BlockHeight) -> Self {
+        self.start_height = height.0;
+        self
+    }
+
+    /// Restrict the population to the heights up to the end height
+    /// inclusive instead of the tip.
+    pub fn with_end_height(mut self, height: BlockHeight) -> Self {
+        self.end_height = Some(height.0);
+        self
+    }
+
+    /// Seed the random sampling to reproduce the sample of a previous run,
+    /// a random seed is used otherwise.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Fetch the headers in batched requests of the number of blocks, a
+    /// batch size of 1 requests every block on its own.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Fetch the number of contiguous blocks before every window as its
+    /// context, e.g. MTP_BLOCKS for the median time past of every block.
+    /// The context is not counted towards the sample size.
+    pub fn with_context(mut self, blocks: u64) -> Self {
+        self.context = blocks;
+        self
+    }
+
+    /// Restrict the population to the blocks from the unix time, resolved
+    /// into the start height when collecting, see find_height.
+    pub fn with_since(mut self, timestamp: u32) -> Self {
+        self.since = Some(timestamp);
+        self
+    }
+
+    /// Restrict the population to the blocks before the unix time, resolved
+    /// into the end height when collecting, see find_height.
+    pub fn with_until(mut self, timestamp: u32) -> Self {
+        self.until = Some(timestamp);
+        self
+    }
+
+    /// Limit the memory used for retaining the collected blocks. A sample
+    /// that would exceed the budget is reduced to as many of its windows as
+    /// fit, spread evenly over the sample, see fit_memory. Only the full
+    /// population or a budget too small for a single window fails, before
+    /// fetching any blocks.
+    pub fn with_max_memory(mut self, bytes: u64) -> Self {
+        self.max_memory = Some(bytes);
+        self
+    }
+
+    /// Limit the bytes of full blocks downloaded. Fetching stops once the
+    /// budget is reached and the analysis uses the blocks fetched so far,
+    /// see collect_full.
+    pub fn with_max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
     /// Collect the sample data from the blockchain. You can provide an
     /// optional window if you want to handle n contiguous blocks. This
     /// will return the random sampling / window. This is important when
     /// comparing contiguous blocks and defaults to 2 (min required).
-    pub async fn collect(&self, client: Client, window: Option<u64>) -> Result<BlockSampleData> {
-        let block_heights;
-        let progress_bar = ProgressBar::new_spinner();
-        progress_bar.println("Fetching current block height...");
-        // Get the current block height
-        let block_max = client.get_block_count()?;
+    /// Progress of the collection is reported to the provided sink and the
+    /// collection stops with the blocks fetched so far once cancelled.
+    ///
+    /// With a cache directory, the headers are looked up in the header
+    /// cache first and only the missing headers are fetched. Fetched
+    /// headers deep enough not to be reorganized are added to the cache.
+    pub async fn collect(
+        &self,
+        clients: ClientPool,
+        window: Option<u64>,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<Outcome<BlockSampleData>> {
+        let block_size = size_of::<BlockHeader>() as u64;
+        let (block_heights, _, range) =
+            self.get_heights(&clients.get(), window, block_size, progress)?;
+
+        let mut cache = match &self.cache_dir {
+            Some(dir) => HeaderCache::open(dir, &clients.get().get_block_hash(0)?)?,
+            None => {
+                // Get the block data from the sample indexes
+                let blocks =
+                    get_headers(block_heights, &clients, self.batch_size, progress, cancel);
+                return Ok(blocks.await?.map(BlockSampleData));
+            }
+        };
+
+        let mut cached = cache.get_many(&block_heights)?;
+        let missing = block_heights
+            .iter()
+            .filter(|height| !cached.contains_key(height))
+            .copied()
+            .collect::<Vec<_>>();
 
-        progress_bar.finish_with_message(format!("Success! Block height: {}", block_max));
+        eprintln!(
+            "Found {} of {} headers in the cache",
+            cached.len(),
+            block_heights.len()
+        );
 
+        let blocks = get_headers(missing, &clients, self.batch_size, progress, cancel);
+        let outcome = blocks.await?;
+        let final_headers = outcome
+            .inner()
+            .iter()
+            .filter(|(height, _)| height.0 + CACHE_DEPTH <= range.end)
+            .cloned()
+            .collect::<Vec<_>>();
+        cache.insert(&final_headers)?;
+
+        Ok(outcome.map(|fetched| {
+            cached.extend(fetched);
+            // Keep the order of the sample so windows stay contiguous, the
+            // windows may overlap so a height can be sampled more than once
+            let headers = block_heights
+                .iter()
+                .filter_map(|height| cached.get(height).copied().map(|header| (*height, header)));
+            BlockSampleData(headers.collect())
+        }))
+    }
+
+    /// Collect the sample data from the blockchain progressively, starting
+    /// with a small sample at a wide margin of error and enlarging it every
+    /// round until the margin of error of the sample is met. After every
+    /// round but the last, the callback receives the sample so far along
+    /// with its margin of error for a preliminary analysis. See collect for
+    /// the window handling and cancellation, a cancelled round ends the
+    /// collection with the blocks fetched so far.
+    pub async fn collect_progressive<F>(
+        &self,
+        clients: ClientPool,
+        window: Option<u64>,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+        mut preliminary: F,
+    ) -> Result<Outcome<BlockSampleData>>
+    where
+        F: FnMut(&BlockSampleData, f64),
+    {
         if self.full_population {
-            // Get all the blocks for full population analysis
-            block_heights = Vec::from_iter(0..block_max);
+            return self.collect(clients, window, progress, cancel).await;
+        }
 
-            println!("Using total population of {}", block_max);
-        } else {
-            // Get a sample of randomized block heights
-            block_heights = self.get_random_heights(block_max, window.unwrap_or(2));
+        let window = window.unwrap_or(2);
+        let (sample, tip) = self.get_resolved(&clients.get(), progress)?;
+        let block_max = sample.get_block_max(tip);
+        let population = block_max - sample.get_exclusions().count_below(block_max);
+        let sample_size = self.get_sample_size(population);
 
-            println!("Utilizing a z-score of {}", self.z_score);
-            println!("With a standard deviation of {}", self.std_deviation);
-            println!("Within a {:.2}% error margin", (self.margin_error * 100.0));
-            println!(
-                "Sampling {} blocks from a population of {}",
-                block_heights.len(),
-                block_max
+        if self.is_exhaustive(population) {
+            // A progressive sample of a small population gains nothing
+            return sample
+                .collect(clients, Some(window), progress, cancel)
+                .await;
+        }
+
+        let max_windows =
+            self.get_memory_windows(window + self.context, size_of::<BlockHeader>() as u64)?;
+
+        if sample_size / window > max_windows {
+            eprintln!(
+                "Memory budget of {} bytes caps the sample at {} of {} windows",
+                self.max_memory.unwrap_or_default(),
+                max_windows,
+                sample_size / window
             );
         }
 
-        // Get the block data from the sample indexes
-        let blocks = get_blocks(block_heights, Arc::new(client));
-        Ok(BlockSampleData(blocks.await?))
-    }
+        eprintln!(
+            "Sampling progressively up to {} blocks from a population of {} within a {:.2}% error margin",
+            sample_size,
+            population,
+            self.margin_error * 100.0
+        );
+        eprintln!("Seeded with {}", self.seed);
 
-    /// Calculate the sample size based on the known highest block height.
-    /// We'll use the Cochran Formula for this as there are a lot of blocks
-    /// (large population) at this point on the Bitcoin network.
-    fn get_sample_size(&self, n: u64) -> u64 {
-        let zpq = self.z_score.powf(2.0) * (self.std_deviation * (1.0 - self.std_deviation));
-        let n0 = (zpq / self.margin_error.powf(2.0)).ceil();
-        let sample = n0 / (1.0 + ((n0 - 1.0) / n as f64));
+        let mut data = BlockSampleData(vec![]);
+        let mut rng = sample.get_rng();
 
-        sample.ceil() as u64
-    }
+        for round in (0..PROGRESSIVE_ROUNDS).rev() {
+            // Every round halves the margin of error of the previous round
+            let round_sample = BlockSample {
+                margin_error: self.margin_error * 2.0_f64.powi(round as i32),
+                ..self.clone()
+            };
+            let windows = (round_sample.get_sample_size(population) / window).min(max_windows);
+            let drawn = data.0.len() as u64 / (window + self.context);
+            let count = windows.saturating_sub(drawn);
+            let heights = sample.get_windows(&mut rng, block_max, window, count);
+            let heights = heights.into_iter().map(BlockHeight).collect();
 
-    /// Get the randomized sample of block heights.
-    fn get_random_heights(&self, block_max: u64, window: u64) -> Vec<u64> {
-        let range = Uniform::new(0, block_max);
-        let mut rng = rand::thread_rng();
-        let mut result: Vec<u64> = Vec::new();
+            let blocks = get_headers(heights, &clients, self.batch_size, progress, cancel);
+            let outcome = blocks.await?;
+            let cancelled = outcome.is_cancelled();
+            data.0.extend(outcome.into_inner());
 
-        // O(n) time windowing for allowing contiguous sample blocks
-        for _ in 0..(self.get_sample_size(block_max) / window) {
-            let mut sample = rng.sample(&range);
-            result.push(sample);
+            if cancelled {
+                return Ok(Outcome::Cancelled { partial: data });
+            }
 
-            for _ in 0..window - 1 {
-                sample += 1;
-                result.push(sample);
+            if round > 0 {
+                let margin_error = self.get_margin_error(data.0.len() as u64, population);
+                preliminary(&data, margin_error);
             }
         }
 
-        result
+        Ok(Outcome::Completed(data))
     }
-}
 
-impl BlockStatistics for BlockSampleData {
-    fn fetch_block_time_drift(self, drift_time: i64, window: u64, sample: bool) {
-        let window = window as usize;
-        // Store the data in a binary heap to bubble up the longest drifts
-        let mut heap: BlockHeap = BinaryHeap::new();
-        // Result formatting for sample data
-        let mut sample_table = vec![];
-        // Result formatting for probability data
-        let mut block_deltas = vec![];
-
-        // Add the blocks to the heap by their timestamp difference
-        self.0.windows(window).step_by(window).for_each(|blocks| {
-            let mut prev = &blocks[0];
-
-            blocks.iter().skip(1).for_each(|block| {
-                let drift = (block.time as i64).checked_sub(prev.time as i64);
-                if let Some(time) = drift {
-                    block_deltas.push(time as f64 / 60.0);
-                    // Pushing to the heap after iterating blocks gives us
-                    // O(n log n) time
-                    heap.push(BlockTimeDriftTable::new(
-                        time as i64 / 60,
-                        prev.block_hash().to_string(),
-                        block.block_hash().to_string(),
-                    ));
-                    prev = block;
-                }
-            })
-        });
+    /// Collect the headers of the first and last block of every completed
+    /// retarget epoch within the range of heights, along with the first
+    /// block of the epoch after and the context blocks before the first
+    /// block of every epoch, see with_context. The boundaries are always
+    /// collected in full as there are only a few headers per epoch,
+    /// regardless of the sample size and exclusions.
+    pub async fn collect_epoch_boundaries(
+        &self,
+        clients: ClientPool,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<Outcome<BlockSampleData>> {
+        let (sample, tip) = self.get_resolved(&clients.get(), progress)?;
+        let heights =
+            get_epoch_boundaries(sample.start_height, sample.get_block_max(tip), self.context);
+        eprintln!("Collecting {} epoch boundaries", heights.len());
+        let heights = heights.into_iter().map(BlockHeight).collect();
+
+        let blocks = get_headers(heights, &clients, self.batch_size, progress, cancel);
+        Ok(blocks.await?.map(BlockSampleData))
+    }
+
+    /// Collect the headers of every step of blocks within the range of
+    /// heights from the start height, along with the last block of the
+    /// range. The checkpoints are always collected in full regardless of
+    /// the sample size and exclusions.
+    pub async fn collect_checkpoints(
+        &self,
+        clients: ClientPool,
+        step: u64,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<Outcome<BlockSampleData>> {
+        let (sample, tip) = self.get_resolved(&clients.get(), progress)?;
+        let heights = get_checkpoints(sample.start_height, sample.get_block_max(tip), step);
+        eprintln!("Collecting {} checkpoints", heights.len());
+        let heights = heights.into_iter().map(BlockHeight).collect();
+
+        let blocks = get_headers(heights, &clients, self.batch_size, progress, cancel);
+        Ok(blocks.await?.map(BlockSampleData))
+    }
+
+    /// Collect the sampled pairs of blocks along with the headers of the
+    /// first and last block of every completed halving era within the range
+    /// of heights. The boundaries are always collected in full regardless of
+    /// the sample size and exclusions.
+    pub async fn collect_halving_eras(
+        &self,
+        clients: ClientPool,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<Outcome<BlockSampleData>> {
+        let block_size = size_of::<BlockHeader>() as u64;
+        let (block_heights, _, range) =
+            self.get_heights(&clients.get(), Some(2), block_size, progress)?;
+        let boundaries = get_halving_boundaries(range.start, range.end);
+        eprintln!("Collecting {} era boundaries", boundaries.len());
+
+        let heights = block_heights
+            .into_iter()
+            .chain(boundaries.into_iter().map(BlockHeight))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let blocks = get_headers(heights, &clients, self.batch_size, progress, cancel);
+        Ok(blocks.await?.map(BlockSampleData))
+    }
+
+    /// Collect the sample data from the blockchain including the
+    /// transactions of every block. See collect for the window handling
+    /// and cancellation.
+    ///
+    /// With a download budget, whole windows of the sample are fetched in
+    /// rounds sized by the estimated block size until the budget is
+    /// reached. The sample is then smaller than planned and the effective
+    /// sample along with its margin of error is reported.
+    pub async fn collect_full(
+        &self,
+        clients: ClientPool,
+        window: Option<u64>,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<Outcome<BlockSampleFullData>> {
+        let mut block_size = 0;
+
+        if self.max_memory.is_some() || self.max_bytes.is_some() {
+            // Estimate the size of a block using the average on disk
+            let info = clients.get().get_blockchain_info()?;
+            block_size = info.size_on_disk / info.blocks.max(1);
+        }
+
+        let (block_heights, population, _) =
+            self.get_heights(&clients.get(), window, block_size, progress)?;
+
+        let budget = match self.max_bytes {
+            Some(budget) => budget,
+            None => {
+                // Get the full blocks from the sample indexes
+                let blocks = get_blocks(block_heights, &clients, get_block, progress, cancel);
+                return Ok(blocks.await?.map(BlockSampleFullData));
+            }
+        };
 
-        // Popping from the heap will give us the highest drifts descending
-        while let Some(leaf) = heap.pop() {
-            if leaf.drift >= drift_time as i64 / 60 {
-                sample_table.push(leaf);
-            } else {
+        let sampled = block_heights.len();
+        let window = window.unwrap_or(2).max(1) as usize;
+        let mut windows = block_heights.chunks(window);
+        let mut blocks = vec![];
+        let mut fetched_bytes = 0;
+
+        while fetched_bytes < budget {
+            // Size the round by the remaining budget, at least one window
+            let count = (budget - fetched_bytes) / (block_size * window as u64).max(1);
+            let heights = windows
+                .by_ref()
+                .take(count.max(1) as usize)
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>();
+
+            if heights.is_empty() {
                 break;
             }
+
+            let outcome = get_blocks(heights, &clients, get_block, progress, cancel);
+            let outcome = outcome.await?;
+            let cancelled = outcome.is_cancelled();
+            let fetched = outcome.into_inner();
+            fetched_bytes += fetched
+                .iter()
+                .map(|(_, block)| block.size() as u64)
+                .sum::<u64>();
+            blocks.extend(fetched);
+
+            if cancelled {
+                return Ok(Outcome::Cancelled {
+                    partial: BlockSampleFullData(blocks),
+                });
+            }
         }
 
-        let occurences = sample_table.len();
-        let hours = -(drift_time as f64 / 60.0_f64.powf(2.0));
-        // Get the mean block minting time
-        let mean_time = get_mean(&block_deltas);
-        // Get the standard deviation
-        let std_deviation = get_standard_deviation(&block_deltas, sample);
-        // Get the poisson probability using the sample data
-        let poisson_prob = get_poisson_probability(60.0 / mean_time, hours);
+        if blocks.len() < sampled {
+            eprintln!(
+                "Download budget of {} bytes reached after {} of {} blocks ({} bytes)",
+                budget,
+                blocks.len(),
+                sampled,
+                fetched_bytes
+            );
 
-        let table = Table::new(sample_table)
-            .with(Header("Block Times"))
-            .with(Footer(format!(
-                "Occurrences: {}, Mean: {} minutes, Standard Deviation: {}, Poisson Probability: 1 / {} hours",
-                occurences,
-                get_rounded_by(mean_time, 2),
-                std_deviation,
-                get_rounded_by(poisson_prob, 2)
-            )));
+            if !self.full_population {
+                eprintln!(
+                    "Effective sample of {} blocks within a {:.2}% error margin",
+                    blocks.len(),
+                    self.get_margin_error(blocks.len() as u64, population) * 100.0
+                );
+            }
+        }
 
-        // Output the table
-        println!("{}", table);
+        Ok(Outcome::Completed(BlockSampleFullData(blocks)))
     }
-}
-
-/// Fetch settings for connecting to bitcoind.
-pub fn fetch_settings(config_path: PathBuf) -> Result<ClientConfig> {
-    let path = config_path.to_str().expect("Cannot parse path");
-    let settings = Config::builder()
-        .add_source(config::File::with_name(path))
-        .add_source(config::Environment::with_prefix("APP"))
-        .build()?
-        .try_deserialize::<ClientConfig>()?;
 
-    Ok(settings)
-}
+    /// Collect the sample data from the blockchain using the block
+    /// statistics of every block. See collect for the window handling and
+    /// cancellation.
+    pub async fn collect_stats(
+        &self,
+        clients: ClientPool,
+        window: Option<u64>,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<Outcome<BlockStatsData>> {
+        let block_size = size_of::<GetBlockStatsResult>() as u64;
+        let (block_heights, _, _) =
+            self.get_heights(&clients.get(), window, block_size, progress)?;
+        // Get the block statistics from the sample indexes
+        let stats = get_blocks(block_heights, &clients, get_block_stats, progress, cancel);
+        Ok(stats.await?.map(BlockStatsData))
+    }
 
-/// Return a new bitcoin RPC client using the specified configuration.
-pub fn fetch_client(config: ClientConfig) -> Result<Client> {
-    println!("Connecting to: {}...", config.host);
+    /// Get the block heights to fetch for the sample along with the size of
+    /// the population, the range of heights less the excluded heights, and
+    /// the range of heights with the dates resolved. The heights are fitted within the
+    /// memory budget given the size of a retained block, see fit_memory.
+    fn get_heights(
+        &self,
+        client: &Client,
+        window: Option<u64>,
+        block_size: u64,
+        progress: &dyn ProgressSink,
+    ) -> Result<(Vec<BlockHeight>, u64, Range<u64>)> {
+        let block_heights;
+        let (sample, tip) = self.get_resolved(client, progress)?;
+        let block_max = sample.get_block_max(tip);
+        let exclusions = sample.get_exclusions();
+        let population = block_max - exclusions.count_below(block_max);
 
-    let client = Client::new(
-        &config.host,
-        // TODO: Bitcoin RPC needs to zeroize but does not currently
-        Auth::UserPass(config.username.to_owned(), config.password.to_owned()),
-    )?;
+        if sample.start_height > 0 || block_max < tip {
+            eprintln!(
+                "Restricting the population to heights {}..{}",
+                sample.start_height, block_max
+            );
+        }
 
-    println!("Connected to: {}!", config.host);
+        if !self.exclusions.is_empty() {
+            eprintln!(
+                "Excluding {} heights from the population: {}",
+                self.exclusions.count_below(block_max),
+                self.exclusions
+            );
+        }
 
-    // Clear sensitive information (ZeroizeOnDrop)
-    drop(config);
-    Ok(client)
-}
+        if self.is_exhaustive(population) {
+            // Get all the blocks for full population analysis
+            let heights = (sample.start_height.saturating_sub(self.context)..block_max)
+                .filter(|height| !exclusions.contains(*height))
+                .map(BlockHeight)
+                .collect::<Vec<_>>();
 
-/// Get the blocks using the list of block heights and the specified
-/// RPC client.
-///
-/// TODO: bitcoincore_rpc does not yet support RPC batch calls which is quite
-/// unfortunate. We will work around this using multiple async calls.
-/// See https://github.com/rust-bitcoin/rust-bitcoincore-rpc/issues/24
-async fn get_blocks(block_heights: Vec<u64>, client: Arc<Client>) -> Result<Vec<BlockHeader>> {
-    let progress_bar = ProgressBar::new(block_heights.len() as u64);
-    let mut result = Vec::new();
-    let mut handles = Vec::new();
+            if !self.full_population {
+                eprintln!(
+                    "Sampling exhaustively as a sample of {} blocks covers over {:.0}% of the population",
+                    self.get_sample_size(population),
+                    EXHAUSTIVE_SHARE * 100.0
+                );
+            }
 
-    for height in block_heights.iter() {
-        handles.push(tokio::spawn(get_block(*height, Arc::clone(&client))));
-    }
+            let total = heights.len();
+            block_heights = self.fit_memory(heights, window, block_size, population)?;
 
-    for handle in handles {
-        if let Ok(block) = handle.await {
-            progress_bar.set_message(format!("Fetched block {}", block.block_hash()));
-            result.push(block);
+            if block_heights.len() == total {
+                eprintln!("Using total population of {}", population);
+            }
         } else {
-            progress_bar.println("Error retrieving block");
+            // Get a sample of randomized block heights
+            let heights = sample.get_random_heights(block_max, window.unwrap_or(2));
+
+            eprintln!("Utilizing a z-score of {}", self.z_score);
+            eprintln!("Seeded with {}", self.seed);
+            eprintln!("With a standard deviation of {}", self.std_deviation);
+            eprintln!("Within a {:.2}% error margin", (self.margin_error * 100.0));
+            eprintln!(
+                "Sampling {} blocks from a population of {}",
+                heights.len(),
+                population
+            );
+
+            let heights = heights.into_iter().map(BlockHeight).collect();
+            block_heights = self.fit_memory(heights, window, block_size, population)?;
         }
 
-        progress_bar.inc(1);
+        Ok((block_heights, population, sample.start_height..block_max))
     }
 
-    println!("Finished fetching {} blocks.", result.len());
-    Ok(result)
-}
+    /// Get the size of the population of the sample, the blocks within the
+    /// range of heights less the excluded heights.
+    pub fn get_population(&self, client: &Client) -> Result<u64> {
+        let tip = client.get_block_count()?;
+        let sample = self.resolve_dates(client, tip)?;
+        let block_max = sample.get_block_max(tip);
 
-/// Get a block by block height.
-async fn get_block(block_height: u64, client: Arc<Client>) -> BlockHeader {
-    let hash = client.get_block_hash(block_height).unwrap();
-    client.get_block_header(&hash).unwrap()
-}
+        Ok(block_max - sample.get_exclusions().count_below(block_max))
+    }
 
-/// Display table column in minutes
-fn display_mins(mins: &i64) -> String {
-    format!("{} m", mins)
-}
+    /// Get the sample achieved by a partial collection of the sampled
+    /// blocks out of the population, stopped for the reason. The margin of
+    /// error is adjusted at the planned confidence and the confidence at
+    /// the planned margin of error.
+    pub fn get_partial_sample(
+        &self,
+        reason: &str,
+        sampled: u64,
+        population: u64,
+    ) -> PartialSampleTable {
+        let planned = match self.is_exhaustive(population) {
+            true => population,
+            false => self.get_sample_size(population),
+        };
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        PartialSampleTable {
+            reason: reason.to_string(),
+            planned,
+            sampled,
+            population,
+            margin_error: self.get_margin_error(sampled, population),
+            confidence: self.get_confidence(sampled, population),
+        }
+    }
 
-    #[test]
+    /// Get the number of windows of blocks that can be retained within the
+    /// memory budget, unlimited without a budget. Fails when not even a
+    /// single window fits.
+    fn get_memory_windows(&self, window: u64, block_size: u64) -> Result<u64> {
+        let required = window * block_size;
+
+        match self.max_memory {
+            Some(budget) if required > budget => {
+                Err(StatisticsError::MemoryBudgetError { required, budget })
+            }
+            Some(budget) => Ok(budget / required.max(1)),
+            None => Ok(u64::MAX),
+        }
+    }
+
+    /// Fit the sampled heights within the memory budget given the size of
+    /// a retained block. Rather than running out of memory, a sample over
+    /// the budget keeps as many of its windows as fit, every k-th window so
+    /// they still span the sample, and the effective sample along with its
+    /// margin of error is reported. The windows are the runs of contiguous
+    /// heights cut to the window and its context, shorter runs are dropped.
+    /// An exhaustive sample becomes a systematic sample while the full
+    /// population fails as it cannot be thinned.
+    fn fit_memory(
+        &self,
+        heights: Vec<BlockHeight>,
+        window: Option<u64>,
+        block_size: u64,
+        population: u64,
+    ) -> Result<Vec<BlockHeight>> {
+        let window = window.unwrap_or(2).max(1) + self.context;
+        let count = self.get_memory_windows(window, block_size)?;
+
+        if heights.len() as u64 <= count.saturating_mul(window) {
+            return Ok(heights);
+        }
+
+        if self.full_population {
+            return Err(StatisticsError::MemoryBudgetError {
+                required: heights.len() as u64 * block_size,
+                budget: self.max_memory.unwrap_or_default(),
+            });
+        }
+
+        let windows = get_contiguous_windows(&heights, window as usize);
+        let count = (count as usize).min(windows.len());
+
+        let fitted = (0..count)
+            .flat_map(|i| windows[i * windows.len() / count])
+            .copied()
+            .collect::<Vec<_>>();
+
+        eprintln!(
+"Memory budget of {} bytes holds {} of {} blocks, keeping a systematic sample of {} windows",
+            self.max_memory.unwrap_or_default(),
+            fitted.len(),
+            heights.len(),
+            count
+        );
+        eprintln!(
+            "Effective sample of {} blocks within a {:.2}% error margin",
+            fitted.len(),
+            self.get_margin_error(fitted.len() as u64, population) * 100.0
+        );
+
+        Ok(fitted)
+    }
+
+    /// Calculate the sample size based on the known highest block height.
+    /// We'll use the Cochran Formula for this as there are a lot of blocks
+    /// (large population) at this point on the Bitcoin network. The finite
+    /// population correction keeps the sample within small populations
+    /// such as regtest, the sample is capped at the population regardless.
+    fn get_sample_size(&self, n: u64) -> u64 {
+        if n == 0 {
+            return 0;
+        }
+
+        let zpq = self.z_score.powf(2.0) * (self.std_deviation * (1.0 - self.std_deviation));
+        let n0 = (zpq / self.margin_error.powf(2.0)).ceil();
+        let sample = n0 / (1.0 + ((n0 - 1.0) / n as f64));
+
+        (sample.ceil() as u64).min(n)
+    }
+
+    /// Check whether the sample covers enough of the population to analyze
+    /// the whole population instead, see EXHAUSTIVE_SHARE.
+    fn is_exhaustive(&self, n: u64) -> bool {
+        self.full_population || self.get_sample_size(n) as f64 >= n as f64 * EXHAUSTIVE_SHARE
+    }
+
+    /// Calculate the margin of error achieved by a sample of the given size
+    /// by inverting the Cochran Formula used for the sample size.
+    fn get_margin_error(&self, sample: u64, n: u64) -> f64 {
+        let zpq = self.z_score.powf(2.0) * (self.std_deviation * (1.0 - self.std_deviation));
+
+        if sample >= n {
+            return 0.0;
+        }
+
+        let n0 = sample as f64 * (n as f64 - 1.0) / (n - sample) as f64;
+        (zpq / n0).sqrt()
+    }
+
+    /// Calculate the confidence achieved by a sample of the given size at
+    /// the margin of error, inverting the Cochran Formula for the z-score.
+    fn get_confidence(&self, sample: u64, n: u64) -> f64 {
+        if sample >= n {
+            return 1.0;
+        }
+
+        let pq = self.std_deviation * (1.0 - self.std_deviation);
+        let n0 = sample as f64 * (n as f64 - 1.0) / (n - sample) as f64;
+        get_confidence(self.margin_error * (n0 / pq).sqrt())
+    }
+
+    /// Get the exclusive upper bound of the heights of the population given
+    /// the height of the tip.
+    fn get_block_max(&self, tip: u64) -> u64 {
+        match self.end_height {
+            Some(end) => tip.min(end.saturating_add(1)),
+            None => tip,
+        }
+    }
+
+    /// Get the sample with its dates resolved along with the current block
+    /// height they were resolved against, see resolve_dates.
+    fn get_resolved(
+        &self,
+        client: &Client,
+        progress: &dyn ProgressSink,
+    ) -> Result<(BlockSample, u64)> {
+        progress.report(FetchProgress::FetchingHeight);
+        // Get the current block height
+        let tip = client.get_block_count()?;
+
+        progress.report(FetchProgress::Height {
+            height: BlockHeight(tip),
+        });
+
+        Ok((self.resolve_dates(client, tip)?, tip))
+    }
+
+    /// Get the sample with the since and until times resolved into the
+    /// start and end heights, keeping the narrower of both ranges. The
+    /// resolved sample holds no times so they are not resolved again.
+    fn resolve_dates(&self, client: &Client, tip: u64) -> Result<BlockSample> {
+        let mut sample = BlockSample {
+            since: None,
+            until: None,
+            ..self.clone()
+        };
+
+        if let Some(since) = self.since {
+            let start = find_height(client, since, tip)?.0;
+            sample.start_height = sample.start_height.max(start);
+        }
+
+        if let Some(until) = self.until {
+            let end = find_height(client, until, tip)?.0.saturating_sub(1);
+            sample.end_height = Some(sample.end_height.map_or(end, |height| height.min(end)));
+        }
+
+        if self.since.is_some() || self.until.is_some() {
+            eprintln!(
+                "Resolved the dates to heights {}..={}",
+                sample.start_height,
+                sample.get_block_max(tip).saturating_sub(1)
+            );
+        }
+
+        Ok(sample)
+    }
+
+    /// Get the excluded heights including those below the start height.
+    fn get_exclusions(&self) -> Exclusions {
+        match self.start_height {
+            0 => self.exclusions.clone(),
+            start => self
+                .exclusions
+                .clone()
+                .merge(Exclusions::new(vec![HeightRange {
+                    start: 0,
+                    end: start,
+                }])),
+        }
+    }
+
+    /// Get the random number generator of the sample from its seed.
+    fn get_rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
+
+    /// Get the randomized sample of block heights, see get_windows.
+    fn get_random_heights(&self, block_max: u64, window: u64) -> Vec<u64> {
+        let population = block_max - self.get_exclusions().count_below(block_max);
+        let count = self.get_sample_size(population) / window;
+        self.get_windows(&mut self.get_rng(), block_max, window, count)
+    }
+
+    /// Get the block heights of a number of windows drawn by the strategy
+    /// of the sample.
+    fn get_windows(&self, rng: &mut StdRng, block_max: u64, window: u64, count: u64) -> Vec<u64> {
+        match &self.strategy {
+            SamplingStrategy::Uniform => self.get_random_windows(rng, block_max, window, count),
+            SamplingStrategy::Stratified(strata) => {
+                self.get_stratified_windows(rng, block_max, window, count, strata)
+            }
+            SamplingStrategy::Systematic => {
+                self.get_systematic_windows(rng, block_max, window, count)
+            }
+        }
+    }
+
+    /// Get the block heights of a number of windows spread evenly over the
+    /// starting heights of get_random_windows, every k-th starting height
+    /// from a random offset below k, where k is the starting heights over
+    /// the number of windows.
+    fn get_systematic_windows(
+        &self,
+        rng: &mut StdRng,
+        block_max: u64,
+        window: u64,
+        count: u64,
+    ) -> Vec<u64> {
+        let window = window + self.context;
+        let starts = self.get_exclusions().get_window_starts(block_max, window);
+        let total: u64 = starts.iter().map(|range| range.end - range.start).sum();
+
+        if total == 0 || count == 0 {
+            return vec![];
+        }
+
+        let step = (total / count).max(1);
+        let offset = rng.gen_range(0..step);
+
+        (0..count.min(total))
+            .map(|i| get_window_start(&starts, offset + i * step))
+            .flat_map(|start| start..start + window)
+            .collect()
+    }
+
+    /// Get the block heights of a number of random windows allocated to the
+    /// strata in proportion to their heights clear of the excluded heights,
+    /// see get_random_windows. Windows never cross the bounds of a stratum,
+    /// so a stratum narrower than a window is not sampled.
+    fn get_stratified_windows(
+        &self,
+        rng: &mut StdRng,
+        block_max: u64,
+        window: u64,
+        count: u64,
+        strata: &Strata,
+    ) -> Vec<u64> {
+        let exclusions = self.get_exclusions();
+        let ranges = strata.get_ranges(self.start_height, block_max);
+        let sizes = ranges
+            .iter()
+            .map(|range| {
+                let excluded =
+                    exclusions.count_below(range.end) - exclusions.count_below(range.start);
+                range.end - range.start - excluded
+            })
+            .collect::<Vec<_>>();
+
+        ranges
+            .iter()
+            .zip(get_proportional_allocation(&sizes, count))
+            .flat_map(|(range, count)| {
+                let stratum = BlockSample {
+                    start_height: range.start,
+                    ..self.clone()
+                };
+                stratum.get_random_windows(rng, range.end, window, count)
+            })
+            .collect()
+    }
+
+    /// Get the block heights of a number of random windows. Windows are
+    /// drawn uniformly from the starting heights where the whole window is
+    /// below the block height and clear of the excluded heights. Every
+    /// window is preceded by the blocks of its context.
+    fn get_random_windows(
+        &self,
+        rng: &mut StdRng,
+        block_max: u64,
+        window: u64,
+        count: u64,
+    ) -> Vec<u64> {
+        let window = window + self.context;
+        let starts = self.get_exclusions().get_window_starts(block_max, window);
+        let total: u64 = starts.iter().map(|range| range.end - range.start).sum();
+        let mut result: Vec<u64> = Vec::new();
+
+        if total == 0 {
+            return result;
+        }
+
+        let range = Uniform::new(0, total);
+
+        // O(n) time windowing for allowing contiguous sample blocks
+        for _ in 0..count {
+            let mut sample = get_window_start(&starts, rng.sample(range));
+            result.push(sample);
+
+            for _ in 0..window - 1 {
+                sample += 1;
+                result.push(sample);
+            }
+        }
+
+        result
+    }
+}
+
+/// Fetch settings for connecting to bitcoind.
+pub fn fetch_settings(config_path: PathBuf) -> Result<ClientConfig> {
+    let path = config_path.to_str().expect("Cannot parse path");
+    let settings = Config::builder()
+        .add_source(config::File::with_name(path))
+        .add_source(config::Environment::with_prefix("APP"))
+        .build()?
+        .try_deserialize::<ClientConfig>()?;
+
+    Ok(settings)
+}
+
+/// Return a pool of new bitcoin RPC clients using the specified
+/// configuration. Fetches are spread over the clients of the pool, at
+/// least one client is created. The responses are recorded to the
+/// fixtures when given.
+pub fn fetch_client(
+    config: ClientConfig,
+    clients: usize,
+    recording: Option<&Fixtures>,
+) -> Result<ClientPool> {
+    eprintln!("Connecting to: {}...", config.host);
+
+    let clients = match config.backend {
+        Backend::Bitcoind => {
+            let endpoint = config.host.parse::<Endpoint>()?;
+            let mut auth = match &config.cookie_file {
+                Some(path) => Auth::CookieFile(PathBuf::from(path)),
+                None => Auth::UserPass(config.username.to_owned(), config.password.to_owned()),
+            };
+            let clients = (0..clients.max(1))
+                .map(|_| {
+                    let transport = RpcTransport::new(endpoint.clone(), auth.clone())?;
+                    Ok(get_client(transport, recording))
+                })
+                .collect::<Result<Vec<_>>>();
+
+            // The transports keep their own copy of the credentials
+            if let Auth::UserPass(user, pass) = &mut auth {
+                user.zeroize();
+                pass.zeroize();
+            }
+
+            clients?
+        }
+        Backend::Electrum => {
+            let index = HeaderIndex::default();
+
+            (0..clients.max(1))
+                .map(|_| {
+                    get_client(
+                        AnswerTransport(ElectrumBackend::new(&config.host, index.clone())),
+                        recording,
+                    )
+                })
+                .collect()
+        }
+        #[cfg(feature = "esplora")]
+        Backend::Esplora => {
+            let limit = RateLimit::new(config.rate_limit.unwrap_or(DEFAULT_RATE_LIMIT));
+
+            (0..clients.max(1))
+                .map(|_| {
+                    get_client(
+                        AnswerTransport(EsploraBackend::new(&config.host, limit.clone())),
+                        recording,
+                    )
+                })
+                .collect()
+        }
+        #[cfg(not(feature = "esplora"))]
+        Backend::Esplora => {
+            return Err(StatisticsError::BackendError(String::from(
+                "Esplora requires the esplora feature",
+            )))
+        }
+        Backend::BlockFiles => {
+            eprintln!("Indexing the block files of {}...", config.host);
+            let index = Arc::new(BlockFileIndex::load(Path::new(&config.host))?);
+            eprintln!("Indexed a chain of {} blocks", index.len());
+
+            (0..clients.max(1))
+                .map(|_| {
+                    let backend = BlockFileBackend::new(Arc::clone(&index));
+                    get_client(AnswerTransport(backend), recording)
+                })
+                .collect()
+        }
+        Backend::Rest => {
+            let endpoint = config.host.parse::<Endpoint>()?;
+            let index = HeaderIndex::default();
+
+            (0..clients.max(1))
+                .map(|_| {
+                    let http = HttpTransport::new(endpoint.clone(), Auth::None)?;
+                    Ok(get_client(
+                        AnswerTransport(RestBackend::new(http, index.clone())),
+                        recording,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+    };
+
+    eprintln!(
+        "Connected to: {} with {} clients!",
+        config.host,
+        clients.len()
+    );
+
+    // Clear sensitive information (ZeroizeOnDrop)
+    drop(config);
+    Ok(ClientPool::new(clients))
+}
+
+/// Create an RPC client over the transport, recording its responses to the
+/// fixtures when given.
+fn get_client<T: jsonrpc::client::Transport>(transport: T, recording: Option<&Fixtures>) -> Client {
+    // The calls are recorded to the telemetry of the run once initialized
+    #[cfg(feature = "otel")]
+    let transport = telemetry::TelemetryTransport::new(transport);
+    let client = match recording {
+        Some(fixtures) => {
+            jsonrpc::Client::with_transport(RecordingTransport::new(transport, fixtures.clone()))
+        }
+        None => jsonrpc::Client::with_transport(transport),
+    };
+
+    Client::from_jsonrpc(client)
+}
+
+/// Get the blocks using the list of block heights and the specified
+/// RPC client pool. The fetch function determines what is retrieved per height
+/// and the result is paired with the height it was retrieved for.
+/// Outstanding fetches are aborted when the token is cancelled. At most as
+/// many fetches as the concurrency of the pool are in flight at once.
+///
+/// The calls of the RPC client block, so every fetch runs on the blocking
+/// threads of tokio rather than its workers, which would otherwise bound
+/// the fetches in flight by the number of workers. Fetches are spread over
+/// the clients of the pool in turn, each keeping its own connection.
+///
+/// Fetches failing transiently are retried with the retry policy of the
+/// pool. A block still failing is left out of the sample, or aborts the
+/// collection with the error naming its height if the policy says so.
+///
+/// TODO: bitcoincore_rpc does not yet support RPC batch calls which is quite
+/// unfortunate. We will work around this using multiple async calls, only
+/// headers are batched through the transport, see get_headers.
+/// See https://github.com/rust-bitcoin/rust-bitcoincore-rpc/issues/24
+async fn get_blocks<T, F>(
+    block_heights: Vec<BlockHeight>,
+    clients: &ClientPool,
+    fetch: F,
+    progress: &dyn ProgressSink,
+    cancel: &CancellationToken,
+) -> Result<Outcome<Vec<(BlockHeight, T)>>>
+where
+    F: Fn(BlockHeight, &Client) -> Result<T> + Clone + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    progress.report(FetchProgress::Started {
+        total: block_heights.len() as u64,
+    });
+    let mut result = Vec::new();
+    let mut handles = Vec::new();
+
+    for height in block_heights.iter() {
+        let (permits, retry) = (clients.permits(), clients.retry());
+        let (height, client, fetch) = (*height, clients.get(), fetch.clone());
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            retry
+                .run(|| {
+                    let (client, fetch) = (Arc::clone(&client), fetch.clone());
+                    async move { tokio::task::spawn_blocking(move || fetch(height, &client)).await? }
+                })
+                .await
+        }));
+    }
+
+    let mut cancelled = false;
+
+    for (height, handle) in block_heights.iter().zip(handles.iter_mut()) {
+        let fetched = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                cancelled = true;
+                break;
+            }
+            fetched = handle => fetched,
+        };
+
+        match get_fetched(fetched, *height) {
+            Ok(block) => {
+                progress.report(FetchProgress::Fetched { height: *height });
+                result.push((*height, block));
+            }
+            Err(e) => {
+                progress.report(FetchProgress::Failed { height: *height });
+
+                if clients.retry().failure() == FailurePolicy::Abort {
+                    handles.iter().for_each(|handle| handle.abort());
+                    progress.report(FetchProgress::Finished {
+                        fetched: result.len() as u64,
+                    });
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    progress.report(FetchProgress::Finished {
+        fetched: result.len() as u64,
+    });
+
+    if cancelled {
+        handles.iter().for_each(|handle| handle.abort());
+        Ok(Outcome::Cancelled { partial: result })
+    } else {
+        Ok(Outcome::Completed(result))
+    }
+}
+
+/// Get the data of a spawned fetch, or the error of the fetch naming the
+/// height of the block.
+fn get_fetched<T>(
+    fetched: std::result::Result<Result<T>, JoinError>,
+    height: BlockHeight,
+) -> Result<T> {
+    let error = match fetched {
+        Ok(Ok(data)) => return Ok(data),
+        Ok(Err(e)) => e,
+        Err(e) => e.into(),
+    };
+
+    Err(StatisticsError::BlockFetchError {
+        height,
+        source: Box::new(error),
+    })
+}
+
+/// Fetch the headers of the block heights in batches, see get_blocks. Every
+/// batch fetches the hashes and then the headers of its heights with one
+/// batched request each instead of two requests per block. A failed batch
+/// reports all its heights as failed, an aborting batch is named by its
+/// first height. Batches run on the blocking threads of tokio like the
+/// fetches of get_blocks.
+async fn get_headers(
+    block_heights: Vec<BlockHeight>,
+    clients: &ClientPool,
+    batch_size: usize,
+    progress: &dyn ProgressSink,
+    cancel: &CancellationToken,
+) -> Result<Outcome<Vec<(BlockHeight, BlockHeader)>>> {
+    progress.report(FetchProgress::Started {
+        total: block_heights.len() as u64,
+    });
+    let batches = block_heights
+        .chunks(batch_size.max(1))
+        .map(<[BlockHeight]>::to_vec)
+        .collect::<Vec<_>>();
+    let mut result = Vec::new();
+    let mut handles = Vec::new();
+
+    for heights in batches.iter() {
+        let (permits, retry) = (clients.permits(), clients.retry());
+        let (client, heights) = (clients.get(), heights.clone());
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            retry
+                .run(|| {
+                    let (client, heights) = (Arc::clone(&client), heights.clone());
+                    async move {
+                        tokio::task::spawn_blocking(move || get_block_headers(&client, &heights))
+                            .await?
+                    }
+                })
+                .await
+        }));
+    }
+
+    let mut cancelled = false;
+
+    for (heights, handle) in batches.iter().zip(handles.iter_mut()) {
+        let fetched = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                cancelled = true;
+                break;
+            }
+            fetched = handle => fetched,
+        };
+
+        match get_fetched(fetched, heights[0]) {
+            Ok(headers) => {
+                for (height, header) in heights.iter().zip(headers) {
+                    progress.report(FetchProgress::Fetched { height: *height });
+                    result.push((*height, header));
+                }
+            }
+            Err(e) => {
+                for height in heights {
+                    progress.report(FetchProgress::Failed { height: *height });
+                }
+
+                if clients.retry().failure() == FailurePolicy::Abort {
+                    handles.iter().for_each(|handle| handle.abort());
+                    progress.report(FetchProgress::Finished {
+                        fetched: result.len() as u64,
+                    });
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    progress.report(FetchProgress::Finished {
+        fetched: result.len() as u64,
+    });
+
+    if cancelled {
+        handles.iter().for_each(|handle| handle.abort());
+        Ok(Outcome::Cancelled { partial: result })
+    } else {
+        Ok(Outcome::Completed(result))
+    }
+}
+
+/// Write the interval between every pair of contiguous blocks within the
+/// windows of the sample as CSV rows of the parent and child heights, their
+/// timestamps and the seconds between them.
+fn write_intervals<W: Write>(
+    writer: W,
+    headers: &[(BlockHeight, &BlockHeader)],
+    window: u64,
+) -> Result<()> {
+    let window = window as usize;
+    let mut writer = BufWriter::new(writer);
+    writeln!(
+        writer,
+        "parent_height,child_height,parent_time,child_time,seconds"
+    )?;
+
+    for blocks in headers.windows(window).step_by(window) {
+        for pair in blocks.windows(2) {
+            let ((parent_height, parent), (child_height, child)) = (&pair[0], &pair[1]);
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                parent_height,
+                child_height,
+                parent.time,
+                child.time,
+                (child.time as i64) - (parent.time as i64)
+            )?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Get the block statistics by block height.
+fn get_block_stats(block_height: BlockHeight, client: &Client) -> Result<GetBlockStatsResult> {
+    Ok(client.get_block_stats(block_height.0)?)
+}
+
+/// Find the first height with a median time past at or after the unix time
+/// by binary search over the headers up to the tip, the height after the tip
+/// when there is none. The median time past of the previous 11 blocks is
+/// monotonic unlike the timestamps provided by miners, see BIP-113.
+pub fn find_height(client: &Client, timestamp: u32, tip: u64) -> Result<BlockHeight> {
+    let (mut low, mut high) = (0, tip + 1);
+
+    while low < high {
+        let height = low + (high - low) / 2;
+        let hash = client.get_block_hash(height)?;
+        let median_time = client.get_block_header_info(&hash)?.median_time;
+
+        match median_time.unwrap_or_default() < timestamp as usize {
+            true => low = height + 1,
+            false => high = height,
+        }
+    }
+
+    Ok(BlockHeight(low))
+}
+
+/// Get the starting height at the offset into the ranges of starting
+/// heights of windows, see Exclusions::get_window_starts.
+fn get_window_start(starts: &[HeightRange], mut offset: u64) -> u64 {
+    starts
+        .iter()
+        .find_map(|range| match range.end - range.start {
+            len if offset < len => Some(range.start + offset),
+            len => {
+                offset -= len;
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Get the heights of every step of blocks from the start height below the
+/// maximum height, along with the last height below it.
+fn get_checkpoints(start_height: u64, block_max: u64, step: u64) -> Vec<u64> {
+    (start_height..block_max)
+        .step_by(step.max(1) as usize)
+        .chain(
+            block_max
+                .checked_sub(1)
+                .filter(|last| *last >= start_height),
+        )
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Split the heights into windows of the given length made of contiguous
+/// heights. A window ends at its length or at a gap, shorter runs such as
+/// the ones cut by an exclusion or at the end of the heights are dropped.
+/// Overlapping windows repeating a height are kept apart.
+fn get_contiguous_windows(heights: &[BlockHeight], window: usize) -> Vec<&[BlockHeight]> {
+    let mut windows = vec![];
+    let mut start = 0;
+
+    for end in 1..=heights.len() {
+        let full = end - start == window;
+        let gap = end == heights.len() || heights[end].0 != heights[end - 1].0 + 1;
+
+        if full {
+            windows.push(&heights[start..end]);
+        }
+
+        if full || gap {
+            start = end;
+        }
+    }
+
+    windows
+}
+
+/// Get the heights of the first and last block of every retarget epoch
+/// completed within the heights from the start height below the maximum
+/// height, along with the first block of the epoch after when below it.
+/// The number of context blocks before the first block of every epoch are
+/// included too.
+fn get_epoch_boundaries(start_height: u64, block_max: u64, context: u64) -> Vec<u64> {
+    let first = start_height.div_ceil(RETARGET_INTERVAL);
+
+    (first..)
+        .map(|epoch| epoch * RETARGET_INTERVAL)
+        .take_while(|start| start + RETARGET_INTERVAL - 1 < block_max)
+        .flat_map(|start| {
+            (start.saturating_sub(context)..=start)
+                .chain([start + RETARGET_INTERVAL - 1, start + RETARGET_INTERVAL])
+        })
+        .filter(|height| *height < block_max)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Get the heights of the first and last block of every halving era
+/// completed within the heights from the start height below the maximum
+/// height.
+fn get_halving_boundaries(start_height: u64, block_max: u64) -> Vec<u64> {
+    (start_height.div_ceil(HALVING_INTERVAL)..)
+        .map(|era| era * HALVING_INTERVAL)
+        .take_while(|start| start + HALVING_INTERVAL <= block_max)
+        .flat_map(|start| [start, start + HALVING_INTERVAL - 1])
+        .collect()
+}
+
+/// Verify the header cache and repair it from the node. The corrupt headers
+/// and the headers on both sides of every broken link are fetched again,
+/// along with the headers missing between the cached headers when filling
+/// the gaps. The cache is verified again after the repair.
+pub fn repair_header_cache(
+    cache: &mut HeaderCache,
+    client: &Client,
+    fill_gaps: bool,
+) -> Result<IndexVerifyReport> {
+    let damage = cache.verify()?;
+    let mut heights = damage.get_damaged_heights();
+    let mut entries = vec![];
+
+    for (first, last) in get_height_ranges(&damage.corrupt) {
+        entries.push((first, last, "Corrupt Header", "Fetched Again"));
+    }
+
+    for height in &damage.broken_links {
+        entries.push((*height - 1, *height, "Broken Link", "Fetched Again"));
+    }
+
+    for (first, last) in &damage.gaps {
+        if fill_gaps {
+            heights.extend((first.0..=last.0).map(BlockHeight));
+        }
+
+        let repair = if fill_gaps { "Filled" } else { "None" };
+        entries.push((*first, *last, "Gap", repair));
+    }
+
+    for chunk in heights.chunks(DEFAULT_BATCH_SIZE) {
+        let headers = get_block_headers(client, chunk)?;
+        cache.insert(&chunk.iter().copied().zip(headers).collect::<Vec<_>>())?;
+    }
+
+    entries.sort_by_key(|(first, last, ..)| (*first, *last));
+    let remaining = cache.verify()?.get_damaged_heights().len();
+
+    Ok(IndexVerifyReport {
+        entries: entries
+            .into_iter()
+            .map(|(first, last, damage, repair)| IndexRepairTable {
+                heights: format!("{}-{}", first, last),
+                damage: damage.to_string(),
+                repair: repair.to_string(),
+            })
+            .collect(),
+        headers: cache.len()?,
+        fetched: heights.len(),
+        remaining,
+    })
+}
+
+/// Get the first and last heights of every run of consecutive heights.
+fn get_height_ranges(heights: &[BlockHeight]) -> Vec<(BlockHeight, BlockHeight)> {
+    let mut ranges: Vec<(BlockHeight, BlockHeight)> = vec![];
+
+    for height in heights {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == *height => *last = *height,
+            _ => ranges.push((*height, *height)),
+        }
+    }
+
+    ranges
+}
+
+/// Get a full block by block height.
+fn get_block(block_height: BlockHeight, client: &Client) -> Result<Block> {
+    let hash = client.get_block_hash(block_height.0)?;
+    Ok(client.get_block(&hash)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::progress::NoProgress;
+    use bitcoincore_rpc::bitcoin::{consensus::serialize, hashes::hex::ToHex};
+    use bitcoincore_rpc::jsonrpc::{self, client::Transport, Request, Response};
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[test]
     fn test_get_sample_size() {
         let sample = BlockSample::new(1.96, 0.5, 0.05, false);
         assert_eq!(sample.get_sample_size(2000), 323);
+        assert_eq!(sample.get_sample_size(1), 1);
+        assert_eq!(sample.get_sample_size(0), 0);
+    }
+
+    #[test]
+    fn test_with_median_time_past() {
+        let header = |time| BlockHeader {
+            version: 1,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time,
+            bits: POW_LIMIT_BITS,
+            nonce: 0,
+        };
+        // A window of 13 blocks with a noisy timestamp at height 105
+        let blocks = (100..113)
+            .map(|height| match height {
+                105 => (BlockHeight(height), header(0)),
+                _ => (BlockHeight(height), header(height as u32 * 600)),
+            })
+            .collect::<Vec<_>>();
+        let data = BlockSampleData::new(blocks).with_median_time_past();
+
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.0[0].0, BlockHeight(111));
+        assert_eq!(data.0[0].1.time, 104 * 600);
+        assert_eq!(data.0[1].1.time, 106 * 600);
+    }
+
+    #[test]
+    fn test_get_checkpoints() {
+        assert_eq!(get_checkpoints(0, 4040, 2016), vec![0, 2016, 4032, 4039]);
+        assert_eq!(get_checkpoints(10, 13, 0), vec![10, 11, 12]);
+        assert_eq!(get_checkpoints(4032, 4033, 2016), vec![4032]);
+        assert!(get_checkpoints(5, 5, 2016).is_empty());
+    }
+
+    #[test]
+    fn test_get_epoch_boundaries() {
+        assert_eq!(
+            get_epoch_boundaries(0, 4040, 0),
+            vec![0, 2015, 2016, 4031, 4032]
+        );
+        assert_eq!(get_epoch_boundaries(1, 4032, 0), vec![2016, 4031]);
+        assert!(get_epoch_boundaries(0, 2015, 0).is_empty());
+        assert_eq!(
+            get_epoch_boundaries(2000, 4040, 2),
+            vec![2014, 2015, 2016, 4031, 4032]
+        );
+    }
+
+    #[test]
+    fn test_get_halving_boundaries() {
+        assert_eq!(
+            get_halving_boundaries(0, 420_000),
+            vec![0, 209_999, 210_000, 419_999]
+        );
+        assert_eq!(get_halving_boundaries(1, 500_000), vec![210_000, 419_999]);
+        assert!(get_halving_boundaries(0, 209_999).is_empty());
+    }
+
+    #[test]
+    fn test_is_exhaustive() {
+        let sample = BlockSample::new(1.96, 0.5, 0.05, false);
+        assert!(!sample.is_exhaustive(2000));
+        assert!(sample.is_exhaustive(300));
+        assert!(sample.is_exhaustive(0));
+
+        let sample = BlockSample::new(1.96, 0.5, 0.05, true);
+        assert!(sample.is_exhaustive(2000));
+    }
+
+    #[test]
+    fn test_export_intervals() {
+        let header = |time| BlockHeader {
+            version: 1,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time,
+            bits: 0x1d00ffff,
+            nonce: 0,
+        };
+        let data = BlockSampleData::new(vec![
+            (BlockHeight(10), header(1_000)),
+            (BlockHeight(11), header(1_600)),
+            (BlockHeight(50), header(9_000)),
+            (BlockHeight(51), header(8_900)),
+        ]);
+        let mut csv = vec![];
+        data.export_intervals(&mut csv, 2).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "parent_height,child_height,parent_time,child_time,seconds\n\
+             10,11,1000,1600,600\n\
+             50,51,9000,8900,-100\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_cancelled() {
+        let client = ClientPool::from(Client::new("127.0.0.1:0", Auth::None).unwrap());
+        let fetch = |height: BlockHeight, _: &Client| Ok(height.0);
+        let heights = vec![BlockHeight(1), BlockHeight(2)];
+        let cancel = CancellationToken::new();
+
+        let result = get_blocks(heights.clone(), &client, fetch, &NoProgress, &cancel).await;
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Completed(vec![(BlockHeight(1), 1), (BlockHeight(2), 2)])
+        );
+
+        cancel.cancel();
+        let result = get_blocks(heights, &client, fetch, &NoProgress, &cancel).await;
+        assert_eq!(result.unwrap(), Outcome::Cancelled { partial: vec![] });
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_failure() {
+        let client = ClientPool::from(Client::new("127.0.0.1:0", Auth::None).unwrap());
+        let fetch = |height: BlockHeight, _: &Client| match height.0 {
+            2 => Err(StatisticsError::HeightRangeError(String::from("failed"))),
+            height => Ok(height),
+        };
+        let heights = (1..4).map(BlockHeight).collect::<Vec<_>>();
+        let cancel = CancellationToken::new();
+
+        let result = get_blocks(heights.clone(), &client, fetch, &NoProgress, &cancel).await;
+        assert_eq!(
+            result.unwrap(),
+            Outcome::Completed(vec![(BlockHeight(1), 1), (BlockHeight(3), 3)])
+        );
+
+        let retry = client.retry().with_failure(FailurePolicy::Abort);
+        let client = client.with_retry(retry);
+        let result = get_blocks(heights, &client, fetch, &NoProgress, &cancel).await;
+        assert!(matches!(
+            result,
+            Err(StatisticsError::BlockFetchError {
+                height: BlockHeight(2),
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_concurrency() {
+        let client = Client::new("127.0.0.1:0", Auth::None).unwrap();
+        let client = ClientPool::from(client).with_concurrency(2);
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let fetch = {
+            let peak = Arc::clone(&peak);
+            move |height, _: &Client| {
+                let current = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(5));
+                active.fetch_sub(1, Ordering::SeqCst);
+                Ok(height)
+            }
+        };
+        let cancel = CancellationToken::new();
+
+        let heights = (0..10).map(BlockHeight).collect();
+        let result = get_blocks(heights, &client, fetch, &NoProgress, &cancel).await;
+        assert_eq!(result.unwrap().into_inner().len(), 10);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_client_pool() {
+        use std::io::{BufRead, BufReader, Read};
+        use std::net::TcpListener;
+
+        // Every request is held until as many as the clients are in flight
+        // at the server, or a while passed
+        const CLIENTS: usize = 4;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let served = Arc::clone(&peak);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (active, peak) = (Arc::clone(&active), Arc::clone(&served));
+                let mut stream = BufReader::new(stream.unwrap());
+
+                std::thread::spawn(move || loop {
+                    let mut head = String::new();
+
+                    while !head.ends_with("\r\n\r\n") {
+                        match stream.read_line(&mut head) {
+                            Ok(0) | Err(_) => return,
+                            Ok(_) => {}
+                        }
+                    }
+
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("Content-Length: "))
+                        .map_or(0, |length| length.parse().unwrap());
+                    let mut body = vec![0; length];
+                    stream.read_exact(&mut body).unwrap();
+                    let request = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+
+                    let current = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(current, Ordering::SeqCst);
+
+                    for _ in 0..200 {
+                        if active.load(Ordering::SeqCst) >= CLIENTS
+                            || peak.load(Ordering::SeqCst) >= CLIENTS
+                        {
+                            break;
+                        }
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    let body = serde_json::json!({"result": 7, "error": null, "id": request["id"]});
+                    let body = body.to_string();
+                    write!(
+                        stream.get_mut(),
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                });
+            }
+        });
+
+        let clients = (0..CLIENTS)
+            .map(|_| {
+                let transport = HttpTransport::new(endpoint.parse().unwrap(), Auth::None);
+                get_client(transport.unwrap(), None)
+            })
+            .collect();
+        let clients = ClientPool::new(clients).with_concurrency(CLIENTS);
+        let fetch = |_, client: &Client| Ok(client.get_block_count()?);
+        let cancel = CancellationToken::new();
+
+        let heights = (0..8).map(BlockHeight).collect();
+        let result = get_blocks(heights, &clients, fetch, &NoProgress, &cancel).await;
+        assert_eq!(
+            result.unwrap().into_inner(),
+            (0..8).map(|h| (BlockHeight(h), 7)).collect::<Vec<_>>()
+        );
+        assert_eq!(peak.load(Ordering::SeqCst), CLIENTS);
+    }
+
+    /// Transport serving the tip, hashes and headers of a chain.
+    struct ChainTransport(Vec<BlockHeader>);
+
+    impl ChainTransport {
+        fn answer(&self, request: &Request) -> Response {
+            let param = request
+                .params
+                .first()
+                .map(|param| param.get().trim_matches('"'));
+            let result = match (request.method, param) {
+                ("getblockcount", _) => jsonrpc::arg(self.0.len() - 1),
+                ("getblockhash", Some(height)) => {
+                    jsonrpc::arg(self.0[height.parse::<usize>().unwrap()].block_hash())
+                }
+                (_, hash) => {
+                    let header = self
+                        .0
+                        .iter()
+                        .find(|header| Some(header.block_hash().to_string().as_str()) == hash);
+                    jsonrpc::arg(serialize(header.unwrap()).to_hex())
+                }
+            };
+
+            Response {
+                result: Some(result),
+                error: None,
+                id: request.id.clone(),
+                jsonrpc: Some(String::from("2.0")),
+            }
+        }
+    }
+
+    impl Transport for ChainTransport {
+        fn send_request(&self, request: Request) -> std::result::Result<Response, jsonrpc::Error> {
+            Ok(self.answer(&request))
+        }
+
+        fn send_batch(
+            &self,
+            requests: &[Request],
+        ) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+            Ok(requests
+                .iter()
+                .map(|request| self.answer(request))
+                .collect())
+        }
+
+        fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "chain")
+        }
+    }
+
+    /// Get a chain of headers ten minutes apart.
+    fn get_chain(blocks: u32) -> Vec<BlockHeader> {
+        let mut chain: Vec<BlockHeader> = vec![];
+        for height in 0..blocks {
+            chain.push(BlockHeader {
+                version: 1,
+                prev_blockhash: chain
+                    .last()
+                    .map(|parent| parent.block_hash())
+                    .unwrap_or_default(),
+                merkle_root: Default::default(),
+                time: height * 600,
+                bits: 0x1d00ffff,
+                nonce: 0,
+            });
+        }
+        chain
+    }
+
+    #[test]
+    fn test_repair_header_cache() {
+        let chain = get_chain(6);
+        let client = Client::from_jsonrpc(jsonrpc::Client::with_transport(ChainTransport(
+            chain.clone(),
+        )));
+        let mut cache = HeaderCache::open_in_memory(&chain[0].block_hash()).unwrap();
+        let headers = (0..6).map(BlockHeight).zip(chain.clone());
+        // A stale header at height 1, missing height 3
+        let mut headers = headers
+            .filter(|(height, _)| height.0 != 3)
+            .collect::<Vec<_>>();
+        headers[1].1.nonce = 7;
+        cache.insert(&headers).unwrap();
+
+        let report = repair_header_cache(&mut cache, &client, false).unwrap();
+        let entries = report
+            .entries
+            .iter()
+            .map(|row| format!("{} {} {}", row.heights, row.damage, row.repair))
+            .collect::<Vec<_>>();
+        assert_eq!(entries, ["1-2 Broken Link Fetched Again", "3-3 Gap None"]);
+        assert_eq!(
+            (report.headers, report.fetched, report.remaining),
+            (5, 2, 0)
+        );
+        assert_eq!(cache.get(BlockHeight(1)).unwrap(), Some(chain[1]));
+
+        let report = repair_header_cache(&mut cache, &client, true).unwrap();
+        assert_eq!(report.entries[0].repair, "Filled");
+        assert_eq!(
+            (report.headers, report.fetched, report.remaining),
+            (6, 1, 0)
+        );
+        assert_eq!(cache.verify().unwrap(), Default::default());
+    }
+
+    #[test]
+    fn test_fit_memory() {
+        let heights = (0..1_000).map(BlockHeight).collect::<Vec<_>>();
+        let sample = BlockSample::new(1.96, 0.5, 0.05, false);
+        let fitted = sample.fit_memory(heights.clone(), Some(2), 80, 1_000);
+        assert_eq!(fitted.unwrap(), heights);
+
+        let sample = sample.with_max_memory(80_000);
+        let fitted = sample.fit_memory(heights.clone(), Some(2), 80, 1_000);
+        assert_eq!(fitted.unwrap(), heights);
+
+        // Every other window of the sample fits in half the memory
+        let sample = sample.with_max_memory(40_000);
+        let fitted = sample.fit_memory(heights, Some(2), 80, 1_000).unwrap();
+        assert_eq!(fitted.len(), 500);
+        assert_eq!(fitted[..4], [0, 1, 4, 5].map(BlockHeight));
+        assert_eq!(fitted[498..], [996, 997].map(BlockHeight));
+
+        // Windows are cut at gaps and the shorter runs are dropped
+        let heights = [0, 1, 2, 4, 5, 6, 7, 8, 10, 10, 11, 12]
+            .map(BlockHeight)
+            .to_vec();
+        let sample = sample.with_max_memory(80 * 2 * 4);
+        let fitted = sample.fit_memory(heights.clone(), Some(2), 80, 1_000);
+        assert_eq!(fitted.unwrap(), [0, 1, 4, 5, 6, 7, 10, 11].map(BlockHeight));
+
+        // The full population is never thinned
+        let full = BlockSample::new(1.96, 0.5, 0.05, true).with_max_memory(80 * 2 * 4);
+        assert!(matches!(
+            full.fit_memory(heights, Some(2), 80, 1_000),
+            Err(StatisticsError::MemoryBudgetError {
+                required: 960,
+                budget: 640
+            })
+        ));
+
+        let sample = sample.with_max_memory(159);
+        assert!(matches!(
+            sample.fit_memory(vec![BlockHeight(0)], Some(2), 80, 1_000),
+            Err(StatisticsError::MemoryBudgetError {
+                required: 160,
+                budget: 159
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_collect_cached_overlapping_windows() {
+        let chain = get_chain(401);
+        let client = || {
+            let transport = ChainTransport(chain.clone());
+            ClientPool::from(Client::from_jsonrpc(jsonrpc::Client::with_transport(
+                transport,
+            )))
+        };
+        let sample = BlockSample::new(1.96, 0.5, 0.1, false);
+        // A seed drawing overlapping windows, repeating heights
+        let sample = (0..)
+            .map(|seed| sample.clone().with_seed(seed))
+            .find(|sample| {
+                let heights = sample.get_random_heights(400, 8);
+                heights.iter().collect::<BTreeSet<_>>().len() < heights.len()
+            })
+            .unwrap();
+        let cancel = CancellationToken::new();
+        let dir = std::env::temp_dir().join(format!("overlap-cache-{}", std::process::id()));
+        let cached = sample.clone().with_cache_dir(dir.clone());
+        let mut collected = vec![];
+
+        // Uncached, filling the cache, then served from the cache
+        for sample in [&sample, &cached, &cached] {
+            let outcome = sample.collect(client(), Some(8), &NoProgress, &cancel);
+            collected.push(outcome.await.unwrap().into_inner().0);
+        }
+
+        assert_eq!(collected[1], collected[0]);
+        assert_eq!(collected[2], collected[0]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_collect_within_memory() {
+        let chain = get_chain(101);
+        let client = Client::from_jsonrpc(jsonrpc::Client::with_transport(ChainTransport(
+            chain.clone(),
+        )));
+        let budget = 10 * size_of::<BlockHeader>() as u64;
+        let sample = BlockSample::new(1.96, 0.5, 0.05, false).with_max_memory(budget);
+        let cancel = CancellationToken::new();
+
+        // An exhaustive sample of 100 blocks over a budget of 10 completes
+        let outcome = sample.collect(ClientPool::from(client), Some(2), &NoProgress, &cancel);
+        let data = match outcome.await.unwrap() {
+            Outcome::Completed(data) => data.0,
+            Outcome::Cancelled { .. } => panic!("The collection was not cancelled"),
+        };
+        let heights = data.iter().map(|(height, _)| height.0).collect::<Vec<_>>();
+        assert_eq!(heights, [0, 1, 20, 21, 40, 41, 60, 61, 80, 81]);
+        assert!(data
+            .iter()
+            .all(|(height, header)| *header == chain[height.0 as usize]));
+    }
+
+    #[test]
+    fn test_get_margin_error() {
+        let sample = BlockSample::new(1.96, 0.5, 0.05, false);
+        let n = sample.get_sample_size(2000);
+        assert!(sample.get_margin_error(n, 2000) <= 0.05);
+        assert_eq!(
+            get_rounded_by(sample.get_margin_error(n / 2, 2000), 4),
+            0.0741
+        );
+        assert_eq!(sample.get_margin_error(2000, 2000), 0.0);
+    }
+
+    #[test]
+    fn test_get_partial_sample() {
+        let sample = BlockSample::new(1.96, 0.5, 0.05, false);
+        let n = sample.get_sample_size(2000);
+        let partial = sample.get_partial_sample("Timed out", n, 2000);
+        assert_eq!(partial.planned, n);
+        assert_eq!(get_rounded_by(partial.confidence, 2), 0.95);
+
+        let partial = sample.get_partial_sample("Timed out", n / 2, 2000);
+        assert_eq!(get_rounded_by(partial.margin_error, 4), 0.0741);
+        assert_eq!(get_rounded_by(partial.confidence, 4), 0.8141);
+        assert_eq!(
+            get_rounded_by(sample.get_partial_sample("", 0, 2000).confidence, 6),
+            0.0
+        );
+        assert_eq!(sample.get_partial_sample("", 2000, 2000).confidence, 1.0);
     }
 
     #[test]
     fn test_get_random_heights() {
         let sample = BlockSample::new(1.96, 0.5, 0.05, false);
-        let result = sample.get_random_heights(10 as u64, 2);
+        let result = sample.get_random_heights(10, 2);
         assert_eq!(result.len(), 10);
+        assert!(result.iter().all(|height| *height < 10));
+        assert_eq!(
+            sample
+                .get_random_windows(&mut sample.get_rng(), 10, 2, 3)
+                .len(),
+            6
+        );
+
+        // Every window is preceded by its context
+        let context = sample.clone().with_context(MTP_BLOCKS);
+        let result = context.get_random_windows(&mut sample.get_rng(), 1000, 2, 3);
+        assert_eq!(result.len(), 39);
+        assert_eq!(result[12], result[0] + 12);
+
+        let seeded = sample.clone().with_seed(42);
+        let result = seeded.get_random_heights(1000, 2);
+        assert_eq!(result, seeded.get_random_heights(1000, 2));
+        assert_eq!(
+            result,
+            sample.clone().with_seed(42).get_random_heights(1000, 2)
+        );
+
+        let exclusions = Exclusions::new(vec!["0..5".parse().unwrap(), "8".parse().unwrap()]);
+        let sample = sample.with_exclusions(exclusions);
+        let result = sample.get_random_heights(10, 2);
+        assert_eq!(result.len(), 4);
+        assert!(result
+            .chunks(2)
+            .all(|window| window == [5, 6] || window == [6, 7]));
+        assert!(sample.get_random_heights(10, 4).is_empty());
+
+        let sample = BlockSample::new(1.96, 0.5, 0.05, false)
+            .with_start_height(BlockHeight(6))
+            .with_end_height(BlockHeight(7));
+        let block_max = sample.get_block_max(10);
+        assert_eq!(block_max, 8);
+        assert_eq!(sample.get_random_heights(block_max, 2), vec![6, 7]);
+    }
+
+    #[test]
+    fn test_get_stratified_windows() {
+        let sample = BlockSample::new(1.96, 0.5, 0.05, false)
+            .with_start_height(BlockHeight(1008))
+            .with_strategy(SamplingStrategy::Stratified(Strata::Epochs));
+        assert_eq!(Strata::Epochs.get_ranges(1008, 5040).len(), 3);
+
+        // The epochs span 1008, 2016 and 1008 heights
+        let heights = sample.get_windows(&mut sample.get_rng(), 5040, 2, 100);
+        let count = |start: u64, end: u64| {
+            heights
+                .iter()
+                .filter(|height| (start..end).contains(*height))
+                .count()
+        };
+        assert_eq!(count(1008, 2016), 50);
+        assert_eq!(count(2016, 4032), 100);
+        assert_eq!(count(4032, 5040), 50);
+        // Windows never cross the bounds of an epoch
+        assert!(heights
+            .chunks(2)
+            .all(|window| window[0] / 2016 == window[1] / 2016));
+
+        let strata = Strata::Ranges(vec!["500..600".parse().unwrap(), "0..100".parse().unwrap()]);
+        let sample = BlockSample::new(1.96, 0.5, 0.05, false)
+            .with_strategy(SamplingStrategy::Stratified(strata.clone()));
+        let heights = sample.get_windows(&mut sample.get_rng(), 550, 2, 10);
+        assert_eq!(heights.len(), 20);
+        assert!(heights
+            .iter()
+            .all(|height| strata.get_stratum(*height).is_some()));
+        // The stratum of heights 500..550 is half the size of the other
+        assert_eq!(heights.iter().filter(|height| **height < 100).count(), 14);
+    }
+
+    #[test]
+    fn test_get_systematic_windows() {
+        let sample =
+            BlockSample::new(1.96, 0.5, 0.05, false).with_strategy(SamplingStrategy::Systematic);
+        // 99 starting heights of windows of 2 blocks below 100
+        let heights = sample.get_windows(&mut sample.get_rng(), 100, 2, 9);
+        assert_eq!(heights.len(), 18);
+        assert!(heights[0] < 11);
+        assert!(heights
+            .chunks(2)
+            .zip(heights.chunks(2).skip(1))
+            .all(|(window, next)| next[0] == window[0] + 11 && window[1] == window[0] + 1));
+
+        // Excluded heights are skipped over
+        let exclusions = Exclusions::new(vec!["10..60".parse().unwrap()]);
+        let sample = sample.with_exclusions(exclusions);
+        let heights = sample.get_windows(&mut sample.get_rng(), 100, 2, 4);
+        assert_eq!(heights.len(), 8);
+        assert!(heights.iter().all(|height| !(10..60).contains(height)));
     }
 }
@@ -0,0 +1,316 @@
+# English messages of the rendered reports keyed by the slug of their text, the
+# reference for the messages of every other language, see src/l10n.rs
+
+adjustment = Adjustment
+aggregate = Aggregate
+ahead = Ahead
+analysis = Analysis
+arguments = Arguments
+arrival-rate = Arrival Rate
+arrivals = Arrivals
+attributed = Attributed
+autocorrelation = Autocorrelation
+autocorrelation-of-mining-times = Autocorrelation of Mining Times
+backward-jumps = Backward Jumps
+backward-timestamp-jumps = Backward Timestamp Jumps
+bit = Bit
+block-size = Block Size
+block-size-by-period = Block Size by Period
+block-size-vs-mining-time = Block Size vs Mining Time
+block-time-records = Block Time Records
+block-times = Block Times
+blocks = Blocks
+blocks-ahead = Blocks Ahead
+blocks-per-day = Blocks per Day
+blocks-per-reversal = Blocks per Reversal
+blocks-without-a-price = Blocks without a Price
+bound = Bound
+boundary-gap = Boundary Gap
+box-pierce-q = Box-Pierce Q
+branch-length = Branch Length
+by-address = By Address
+by-tag = By Tag
+bytes = bytes
+chain-tips = Chain Tips
+challenge-signers = Challenge Signers
+change = Change
+checkpoints = Checkpoints
+chi-square = Chi-Square
+child-block-hash = Child Block Hash
+child-date-utc = Child Date (UTC)
+child-height = Child Height
+cluster-size = Cluster Size
+clusters = Clusters
+coinbase-revenue-by-halving-era = Coinbase Revenue by Halving Era
+coinjoins-skipped = CoinJoins Skipped
+command = Command
+confidence = Confidence
+confirmation-latency-by-fee-rate = Confirmation Latency by Fee Rate
+confirmations = Confirmations
+confirmed = Confirmed
+confirmed-unseen = Confirmed Unseen
+conflicted = Conflicted
+conflicting-spends = Conflicting Spends
+conflicts = Conflicts
+consolidation-waves = Consolidation Waves
+cumulative-drift = Cumulative Drift
+custom-expression = Custom Expression
+damage = Damage
+date-utc = Date (UTC)
+days = days
+delay = Delay
+description = Description
+deviation = Deviation
+difficulty = Difficulty
+difficulty-epochs = Difficulty Epochs
+difficulty-trend = Difficulty Trend
+dispersion = Dispersion
+drift = Drift
+drift-rate = Drift Rate
+drop-rate = Drop Rate
+duration = Duration
+elapsed = Elapsed
+elasticity = Elasticity
+empty = Empty
+empty-blocks = Empty Blocks
+empty-mean = Empty Mean
+empty-rate = Empty Rate
+end-date-utc = End Date (UTC)
+end-height = End Height
+end-utc = End (UTC)
+ended-by = Ended By
+entity-clusters-heuristic = Entity Clusters (Heuristic)
+epoch = Epoch
+epoch-durations = Epoch Durations
+epochs = Epochs
+equal-timestamps = Equal Timestamps
+era = Era
+eras = Eras
+estimated-duration = Estimated Duration
+estimated-lead = Estimated Lead
+estimates = Estimates
+evaluated = Evaluated
+example = Example
+example-studies = Example Studies
+examples = Examples
+excluded-before-activation = Excluded before Activation
+expected = expected
+expected-duration = Expected Duration
+expected-share = Expected Share
+expired = Expired
+extreme = Extreme
+fee-elasticity = Fee Elasticity
+fee-rate = Fee Rate
+fee-share = Fee Share
+fee-statistics = Fee Statistics
+fees = Fees
+fees-btc = Fees (BTC)
+fees-usd = Fees (USD)
+fetched = Fetched
+flag = Flag
+flagged = Flagged
+fork-height = Fork Height
+frequency = Frequency
+frequency-interval = Frequency Interval
+full-blocks = Full Blocks
+fullness = Fullness
+fullness-trend = Fullness Trend
+future-timestamps = Future Timestamps
+gap = Gap
+gaps-scanned = Gaps Scanned
+generated-utc = Generated (UTC)
+halving-era-speed = Halving Era Speed
+hash = Hash
+hashrate = Hashrate
+header-cache = Header Cache
+headers = Headers
+headline = Headline
+height = Height
+heights = Heights
+hours = hours
+implied-change = Implied Change
+influence = Influence
+input-output-ratio = Input/Output Ratio
+input-share = Input Share
+inputs = Inputs
+inputs-unattributed = Inputs Unattributed
+intervals = Intervals
+key-path-spends = Key Path Spends
+kolmogorov-smirnov-d = Kolmogorov-Smirnov D
+lag = Lag
+largest-cluster = Largest Cluster
+luck = Luck
+margin-of-error = Margin of Error
+max = Max
+max-gap = Max Gap
+max-magnitude = Max Magnitude
+maximum-gap = Maximum Gap
+mean = Mean
+mean-confidence-interval = Mean Confidence Interval
+mean-fees-btc = Mean Fees (BTC)
+mean-interval = Mean Interval
+mean-magnitude = Mean Magnitude
+mean-margin = Mean Margin
+mean-max-gap = Mean Max Gap
+mean-median-fee-rate = Mean Median Fee Rate
+mean-mining-time = Mean Mining Time
+mean-price = Mean Price
+mean-ratio = Mean Ratio
+mean-size = Mean Size
+mean-size-bytes = Mean Size (bytes)
+mean-span = Mean Span
+mean-streak = Mean Streak
+mean-tracking-error = Mean Tracking Error
+mean-variance = Mean Variance
+mean-vbytes = Mean vBytes
+mean-weight = Mean Weight
+mean-weight-wu = Mean Weight (WU)
+median = Median
+median-blocks = Median Blocks
+median-fee-rate = Median Fee Rate
+median-margin = Median Margin
+median-wait = Median Wait
+metric = Metric
+min-difficulty = Min Difficulty
+min-difficulty-blocks-filtered = Min Difficulty Blocks Filtered
+min-difficulty-blocks-tagged = Min Difficulty Blocks Tagged
+miner-revenue = Miner Revenue
+mining-pools = Mining Pools
+mining-time = Mining Time
+mining-time-distribution-minutes = Mining Time Distribution (minutes)
+mining-time-percentiles = Mining Time Percentiles
+mining-times-by-stratum = Mining Times by Stratum
+mining-times-within-target = Mining Times Within Target
+minutes = minutes
+monotonicity-streaks = Monotonicity Streaks
+mtp-margin = MTP Margin
+mtp-utc = MTP (UTC)
+near = Near
+near-future-limit = Near Future Limit
+near-mtp = Near MTP
+negative-drifts = Negative Drifts
+net-flow = Net Flow
+network = Network
+next-block = Next Block
+none = None
+observed-adjustment = Observed Adjustment
+observed-change = Observed Change
+occurrences = Occurrences
+original-confirmed = Original Confirmed
+other-mean = Other Mean
+out-of-order = Out of Order
+out-of-order-timestamps = Out of Order Timestamps
+output-share = Output Share
+outputs = Outputs
+outputs-removed = Outputs Removed
+p-value = p-value
+p10 = P10
+p2tr-outputs = P2TR Outputs
+p90 = P90
+p90-blocks = P90 Blocks
+p90-wait = P90 Wait
+pairs = Pairs
+parameters = Parameters
+parent-block-hash = Parent Block Hash
+parent-date-utc = Parent Date (UTC)
+parent-height = Parent Height
+partial-sample = Partial Sample
+pearson = Pearson
+pearson-p-value = Pearson p-value
+pending = Pending
+per = per
+percentile = Percentile
+period = Period
+periods = Periods
+planned = Planned
+points = Points
+poisson-probability = Poisson Probability
+pool = Pool
+pool-luck = Pool Luck
+pooled-mean = Pooled Mean
+pools-with-expected-share = Pools with Expected Share
+population = Population
+probability = Probability
+race = Race
+rate = Rate
+rate-interval = Rate Interval
+recent-blocks = Recent Blocks
+recent-stale = Recent Stale
+record = Record
+remaining-damage = Remaining Damage
+reorg-risk = Reorg Risk
+repair = Repair
+replaced = Replaced
+replacement-confirmed = Replacement Confirmed
+replacement-share = Replacement Share
+replacements = Replacements
+retarget = Retarget
+revenue = Revenue
+run = Run
+run-history = Run History
+runs = Runs
+sample-hashrate = Sample Hashrate
+sampled = Sampled
+sampled-intervals = Sampled Intervals
+scheduled-utc = Scheduled (UTC)
+script-limit-reached = Script Limit Reached
+script-path-spends = Script Path Spends
+script-share = Script Share
+script-type = Script Type
+scripts = Scripts
+share = Share
+share-interval = Share Interval
+signaling = Signaling
+signer = Signer
+signet-producers = Signet Producers
+significant = Significant
+significant-changes = Significant Changes
+size-bytes = Size (bytes)
+skipped = Skipped
+span = Span
+spearman = Spearman
+spearman-p-value = Spearman p-value
+stale = Stale
+stale-rate = Stale Rate
+standard-deviation = Standard Deviation
+standard-error = Standard Error
+start-date-utc = Start Date (UTC)
+start-height = Start Height
+start-utc = Start (UTC)
+status = Status
+std-deviation = Std Deviation
+stopped = Stopped
+strata = Strata
+stratum = Stratum
+streak = Streak
+subsidy = Subsidy
+subsidy-btc = Subsidy (BTC)
+subsidy-usd = Subsidy (USD)
+taproot-adoption = Taproot Adoption
+target = Target
+the-collection-stopped-early-the-report-covers-the-sampled-blocks = The collection stopped early, the report covers the sampled blocks
+threshold = Threshold
+threshold-reached = Threshold Reached
+time-to-confirmation = Time to Confirmation
+time-warp = Time Warp
+total-fees = Total Fees
+transaction-arrivals = Transaction Arrivals
+transactions = Transactions
+transactions-per-block = Transactions per Block
+transactions-vs-mining-time = Transactions vs Mining Time
+underclaimed-blocks = Underclaimed Blocks
+unknown-fee-rate = Unknown Fee Rate
+unseen-confirmed = Unseen Confirmed
+value = Value
+values = Values
+variance = Variance
+version-bits-blocks = Version Bits Blocks
+version-bits-signaling = Version Bits Signaling
+volume-btc = Volume (BTC)
+warning = Warning
+weight-wu = Weight (WU)
+windows = Windows
+within-14-days = Within 14 Days
+witness-version-flow = Witness Version Flow
+year = year
+z-score = Z-Score
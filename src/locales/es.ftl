@@ -0,0 +1,315 @@
+# Spanish messages of the rendered reports, see en.ftl
+
+adjustment = Ajuste
+aggregate = Agregado
+ahead = Adelanto
+analysis = Análisis
+arguments = Argumentos
+arrival-rate = Tasa de Llegada
+arrivals = Llegadas
+attributed = Atribuidos
+autocorrelation = Autocorrelación
+autocorrelation-of-mining-times = Autocorrelación de los Tiempos de Minado
+backward-jumps = Saltos Hacia Atrás
+backward-timestamp-jumps = Saltos de Marca de Tiempo Hacia Atrás
+bit = Bit
+block-size = Tamaño de Bloque
+block-size-by-period = Tamaño de Bloque por Período
+block-size-vs-mining-time = Tamaño de Bloque vs Tiempo de Minado
+block-time-records = Récords de Tiempo de Bloque
+block-times = Tiempos de Bloque
+blocks = Bloques
+blocks-ahead = Bloques de Adelanto
+blocks-per-day = Bloques por Día
+blocks-per-reversal = Bloques por Reversión
+blocks-without-a-price = Bloques sin Precio
+bound = Límite
+boundary-gap = Brecha en el Límite
+box-pierce-q = Q de Box-Pierce
+branch-length = Longitud de la Rama
+by-address = Por Dirección
+by-tag = Por Etiqueta
+bytes = bytes
+chain-tips = Puntas de la Cadena
+challenge-signers = Firmantes del Desafío
+change = Cambio
+checkpoints = Puntos de Control
+chi-square = Chi-Cuadrado
+child-block-hash = Hash del Bloque Hijo
+child-date-utc = Fecha del Hijo (UTC)
+child-height = Altura del Hijo
+cluster-size = Tamaño del Grupo
+clusters = Grupos
+coinbase-revenue-by-halving-era = Ingresos del Coinbase por Era de Halving
+coinjoins-skipped = CoinJoins Omitidos
+command = Comando
+confidence = Confianza
+confirmation-latency-by-fee-rate = Latencia de Confirmación por Tasa de Comisión
+confirmations = Confirmaciones
+confirmed = Confirmadas
+confirmed-unseen = Confirmadas sin Ver
+conflicted = En Conflicto
+conflicting-spends = Gastos en Conflicto
+conflicts = Conflictos
+consolidation-waves = Olas de Consolidación
+cumulative-drift = Deriva Acumulada
+custom-expression = Expresión Personalizada
+damage = Daño
+date-utc = Fecha (UTC)
+days = días
+delay = Retraso
+description = Descripción
+deviation = Desviación
+difficulty = Dificultad
+difficulty-epochs = Épocas de Dificultad
+difficulty-trend = Tendencia de la Dificultad
+dispersion = Dispersión
+drift = Deriva
+drift-rate = Tasa de Deriva
+drop-rate = Tasa de Abandono
+duration = Duración
+elapsed = Transcurrido
+elasticity = Elasticidad
+empty = Vacíos
+empty-blocks = Bloques Vacíos
+empty-mean = Media de Vacíos
+empty-rate = Tasa de Vacíos
+end-date-utc = Fecha Final (UTC)
+end-height = Altura Final
+end-utc = Final (UTC)
+ended-by = Terminada Por
+entity-clusters-heuristic = Grupos de Entidades (Heurístico)
+epoch = Época
+epoch-durations = Duración de las Épocas
+epochs = Épocas
+equal-timestamps = Marcas de Tiempo Iguales
+era = Era
+eras = Eras
+estimated-duration = Duración Estimada
+estimated-lead = Adelanto Estimado
+estimates = Estimaciones
+evaluated = Evaluados
+example = Ejemplo
+example-studies = Estudios de Ejemplo
+examples = Ejemplos
+excluded-before-activation = Excluidos antes de la Activación
+expected = esperados
+expected-duration = Duración Esperada
+expected-share = Participación Esperada
+expired = Expiradas
+extreme = Extremo
+fee-elasticity = Elasticidad de las Comisiones
+fee-rate = Tasa de Comisión
+fee-share = Participación de Comisiones
+fee-statistics = Estadísticas de Comisiones
+fees = Comisiones
+fees-btc = Comisiones (BTC)
+fees-usd = Comisiones (USD)
+fetched = Obtenidas
+flag = Marca
+flagged = Marcadas
+fork-height = Altura de la Bifurcación
+frequency = Frecuencia
+frequency-interval = Intervalo de la Frecuencia
+full-blocks = Bloques Llenos
+fullness = Ocupación
+fullness-trend = Tendencia de Ocupación
+future-timestamps = Marcas de Tiempo Futuras
+gap = Intervalo
+gaps-scanned = Intervalos Examinados
+generated-utc = Generado (UTC)
+halving-era-speed = Velocidad por Era de Halving
+hash = Hash
+hashrate = Tasa de Hash
+header-cache = Caché de Cabeceras
+headers = Cabeceras
+headline = Titular
+height = Altura
+heights = Alturas
+hours = horas
+implied-change = Cambio Implícito
+influence = Influencia
+input-output-ratio = Razón Entradas/Salidas
+input-share = Participación de Entradas
+inputs = Entradas
+inputs-unattributed = Entradas sin Atribuir
+intervals = Intervalos
+key-path-spends = Gastos por Ruta de Clave
+kolmogorov-smirnov-d = D de Kolmogorov-Smirnov
+lag = Retardo
+largest-cluster = Grupo más Grande
+luck = Suerte
+margin-of-error = Margen de Error
+max = Máximo
+max-gap = Intervalo Máximo
+max-magnitude = Magnitud Máxima
+maximum-gap = Brecha Máxima
+mean = Media
+mean-confidence-interval = Intervalo de Confianza de la Media
+mean-fees-btc = Comisiones Medias (BTC)
+mean-interval = Intervalo Medio
+mean-magnitude = Magnitud Media
+mean-margin = Margen Medio
+mean-max-gap = Intervalo Máximo Medio
+mean-median-fee-rate = Tasa de Comisión Mediana Media
+mean-mining-time = Tiempo de Minado Medio
+mean-price = Precio Medio
+mean-ratio = Razón Media
+mean-size = Tamaño Medio
+mean-size-bytes = Tamaño Medio (bytes)
+mean-span = Duración Media
+mean-streak = Racha Media
+mean-tracking-error = Error de Seguimiento Medio
+mean-variance = Varianza Media
+mean-vbytes = vBytes Medios
+mean-weight = Peso Medio
+mean-weight-wu = Peso Medio (WU)
+median = Mediana
+median-blocks = Bloques Medianos
+median-fee-rate = Tasa de Comisión Mediana
+median-margin = Margen Mediano
+median-wait = Espera Mediana
+metric = Métrica
+min-difficulty = Dificultad Mínima
+min-difficulty-blocks-filtered = Bloques de Dificultad Mínima Filtrados
+min-difficulty-blocks-tagged = Bloques de Dificultad Mínima Marcados
+miner-revenue = Ingresos de los Mineros
+mining-pools = Pools de Minería
+mining-time = Tiempo de Minado
+mining-time-distribution-minutes = Distribución del Tiempo de Minado (minutos)
+mining-time-percentiles = Percentiles del Tiempo de Minado
+mining-times-by-stratum = Tiempos de Minado por Estrato
+mining-times-within-target = Tiempos de Minado Dentro del Objetivo
+minutes = minutos
+monotonicity-streaks = Rachas de Monotonía
+mtp-margin = Margen sobre MTP
+mtp-utc = MTP (UTC)
+near = Cerca
+near-future-limit = Cerca del Límite Futuro
+near-mtp = Cerca del MTP
+negative-drifts = Derivas Negativas
+net-flow = Flujo Neto
+network = Red
+next-block = Siguiente Bloque
+none = Ninguno
+observed-adjustment = Ajuste Observado
+observed-change = Cambio Observado
+occurrences = Ocurrencias
+original-confirmed = Original Confirmada
+other-mean = Media de Otros
+out-of-order = Desordenados
+out-of-order-timestamps = Marcas de Tiempo Desordenadas
+output-share = Participación de Salidas
+outputs = Salidas
+outputs-removed = Salidas Eliminadas
+p-value = valor p
+p10 = P10
+p2tr-outputs = Salidas P2TR
+p90 = P90
+p90-blocks = Bloques P90
+p90-wait = Espera P90
+pairs = Pares
+parameters = Parámetros
+parent-block-hash = Hash del Bloque Padre
+parent-date-utc = Fecha del Padre (UTC)
+parent-height = Altura del Padre
+partial-sample = Muestra Parcial
+pearson = Pearson
+pearson-p-value = Valor p de Pearson
+pending = Pendientes
+per = por
+percentile = Percentil
+period = Período
+periods = Períodos
+planned = Planificada
+points = Puntos
+poisson-probability = Probabilidad de Poisson
+pool = Pool
+pool-luck = Suerte de los Pools
+pooled-mean = Media Combinada
+pools-with-expected-share = Pools con Participación Esperada
+population = Población
+probability = Probabilidad
+race = Carrera
+rate = Tasa
+rate-interval = Intervalo de la Tasa
+recent-blocks = Bloques Recientes
+recent-stale = Huérfanos Recientes
+record = Récord
+remaining-damage = Daño Restante
+reorg-risk = Riesgo de Reorganización
+repair = Reparación
+replaced = Reemplazadas
+replacement-confirmed = Reemplazo Confirmado
+replacement-share = Participación de Reemplazos
+replacements = Reemplazos
+retarget = Reajuste
+revenue = Ingresos
+run = Ejecución
+run-history = Historial de Ejecuciones
+runs = Ejecuciones
+sample-hashrate = Tasa de Hash de la Muestra
+sampled = Muestreados
+sampled-intervals = Intervalos Muestreados
+scheduled-utc = Programado (UTC)
+script-limit-reached = Límite de Scripts Alcanzado
+script-path-spends = Gastos por Ruta de Script
+script-share = Participación del Script
+script-type = Tipo de Script
+scripts = Scripts
+share = Participación
+share-interval = Intervalo de la Participación
+signaling = Señalizando
+signer = Firmante
+signet-producers = Productores de Signet
+significant = Significativo
+significant-changes = Cambios Significativos
+size-bytes = Tamaño (bytes)
+skipped = Omitidos
+span = Duración
+spearman = Spearman
+spearman-p-value = Valor p de Spearman
+stale = Huérfanos
+stale-rate = Tasa de Bloques Huérfanos
+standard-deviation = Desviación Estándar
+standard-error = Error Estándar
+start-date-utc = Fecha Inicial (UTC)
+start-height = Altura Inicial
+start-utc = Inicio (UTC)
+status = Estado
+std-deviation = Desviación Estándar
+stopped = Detenida
+strata = Estratos
+stratum = Estrato
+streak = Racha
+subsidy = Subsidio
+subsidy-btc = Subsidio (BTC)
+subsidy-usd = Subsidio (USD)
+taproot-adoption = Adopción de Taproot
+target = Objetivo
+the-collection-stopped-early-the-report-covers-the-sampled-blocks = La recolección se detuvo antes, el informe cubre los bloques muestreados
+threshold = Umbral
+threshold-reached = Umbral Alcanzado
+time-to-confirmation = Tiempo hasta la Confirmación
+time-warp = Distorsión Temporal
+total-fees = Comisiones Totales
+transaction-arrivals = Llegadas de Transacciones
+transactions = Transacciones
+transactions-per-block = Transacciones por Bloque
+transactions-vs-mining-time = Transacciones vs Tiempo de Minado
+underclaimed-blocks = Bloques con Reclamo Incompleto
+unknown-fee-rate = Tasa de Comisión Desconocida
+unseen-confirmed = Confirmadas sin Ver
+value = Valor
+values = Valores
+variance = Varianza
+version-bits-blocks = Bloques con Bits de Versión
+version-bits-signaling = Señalización de Bits de Versión
+volume-btc = Volumen (BTC)
+warning = Advertencia
+weight-wu = Peso (WU)
+windows = Ventanas
+within-14-days = Dentro de 14 Días
+witness-version-flow = Flujo por Versión de Testigo
+year = año
+z-score = Puntuación Z
@@ -1,32 +1,1332 @@
+use std::cell::RefCell;
 use std::error::Error;
-use std::path::PathBuf;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
 
-use bitcoin_statistics::{fetch_client, fetch_settings, BlockSample, BlockStatistics};
-use clap::Parser;
+use bitcoin_statistics::{
+    cache::HeaderCache,
+    custom::CustomExpression,
+    examples::{get_example, get_examples_report},
+    exclusions::Exclusions,
+    fetch_client, fetch_settings,
+    fixtures::Fixtures,
+    get_chain_tips_report, get_stale_rate,
+    history::RunHistory,
+    html::HtmlReport,
+    l10n,
+    planner::{plan_source, DataNeed},
+    pools::{get_default_pools, load_pools, PoolSignature},
+    prices::PriceFeed,
+    progress::ProgressBarSink,
+    records::RecordBook,
+    render::{render, Destination, OutputFormat, PartialReport, Report},
+    repair_header_cache,
+    schema::get_report_schemas,
+    signet::SignetChallenge,
+    transport::get_block_headers,
+    transport::{ClientPool, RetryPolicy},
+    utils::{get_date, get_rounded_by, get_timestamp, get_z_score},
+    watch::{
+        get_fee_rate, subscribe, BlockWatch, Notification, TransactionWatch, BLOCK_TOPICS,
+        TRANSACTION_TOPICS,
+    },
+    BlockHeight, BlockSample, BlockSampleData, BlockSampleFullData, BlockStatistics,
+    BlockStatsData, BlockStatsStatistics, CancellationToken, FullBlockStatistics, HistoryReport,
+    Outcome, PartialSampleTable, SamplingStrategy, Strata, MTP_BLOCKS,
+};
+use bitcoincore_rpc::{bitcoin::Network, Client, RpcApi};
+use clap::{CommandFactory, FromArgMatches};
 
 mod cli;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    // Parses CLI arguments
-    let cli = cli::Args::parse();
-    // Fetch the RPC client
-    let rpc = fetch_client(fetch_settings(PathBuf::from(cli.config))?)?;
+    // Parses CLI arguments, keeping the name of the analysis for the history
+    let mut matches = cli::Args::command().get_matches();
+    let mut cli = cli::Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // An example runs its analysis along with the options given before it
+    if let cli::Analysis::Examples {
+        name: Some(name),
+        params,
+    } = &cli.command
+    {
+        let args = get_example(name)?.get_args(params)?;
+        let argv = std::env::args()
+            .take_while(|arg| arg != "examples")
+            .chain(args);
+        matches = cli::Args::command().get_matches_from(argv);
+        cli = cli::Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    }
+
+    let analysis = matches.subcommand_name().unwrap_or_default();
+    l10n::set_language(cli.lang);
+    // Destination of every result, the reports of run-all are appended
+    let destination = match cli.command {
+        cli::Analysis::RunAll { .. } => Destination::new(cli.output.clone()).with_append()?,
+        _ => Destination::new(cli.output.clone()),
+    };
+
+    // Schemas are known without connecting to bitcoind
+    if let cli::Analysis::Schema = cli.command {
+        destination.write(&serde_json::to_string_pretty(&get_report_schemas())?)?;
+        return Ok(());
+    }
+
+    // As are the examples
+    if let cli::Analysis::Examples { name: None, .. } = cli.command {
+        destination.write(&render(&get_examples_report(), cli.format)?)?;
+        return Ok(());
+    }
+
+    // Only headers and full blocks carry the proof of work to verify
+    if cli.verify_pow
+        && !matches!(
+            cli.command.get_data_need(),
+            Some(DataNeed::Headers | DataNeed::Blocks)
+        )
+    {
+        return Err("--verify-pow requires an analysis of headers or blocks, block statistics and node calls carry no headers to verify".into());
+    }
+
+    // Analyses run together share a single collection of the same data
+    if let cli::Analysis::RunAll { analyses, .. } = &cli.command {
+        if analyses
+            .iter()
+            .any(|analysis| analysis.get_data_need() != cli.command.get_data_need())
+        {
+            return Err("The analyses of run-all need to fetch the same data, either headers or block statistics"
+                    .into());
+        }
+    }
+
+    let history = cli.history.as_deref().map(RunHistory::open).transpose()?;
+
+    // Previous runs are rendered from the history alone
+    match (&cli.command, &history) {
+        (cli::Analysis::History { count }, Some(history)) => {
+            let report = HistoryReport {
+                entries: history.list(*count)?,
+            };
+            destination.write(&render(&report, cli.format)?)?;
+            return Ok(());
+        }
+        (cli::Analysis::Show { id }, Some(history)) => {
+            destination.write(&history.get_report(*id, cli.format)?)?;
+            return Ok(());
+        }
+        (cli::Analysis::History { .. } | cli::Analysis::Show { .. }, None) => {
+            return Err("The run history requires --history".into());
+        }
+        _ => {}
+    }
+
+    // Fail without telemetry support before connecting
+    #[cfg(not(feature = "otel"))]
+    if cli.otel_endpoint.is_some() {
+        return Err("Exporting telemetry requires the otel feature".into());
+    }
+
+    // Telemetry is exported once more when the run ends
+    #[cfg(feature = "otel")]
+    let _telemetry = match &cli.otel_endpoint {
+        Some(endpoint) => Some(bitcoin_statistics::telemetry::init(endpoint)?),
+        None => None,
+    };
+
+    // Responses recorded to the fixtures are saved once the run ends
+    let recording = cli.record.clone().map(|path| Recording {
+        fixtures: Fixtures::default(),
+        path,
+    });
+
+    // Fetch the pool of RPC clients, replaying recorded fixtures without
+    // connecting to bitcoind
+    let rpc = match &cli.replay {
+        Some(path) => {
+            let fixtures = Fixtures::load(path)?;
+            eprintln!(
+                "Replaying {} responses from {}",
+                fixtures.len(),
+                path.display()
+            );
+            fixtures.get_replay_client(cli.rpc_clients)
+        }
+        None => {
+            let mut settings = fetch_settings(PathBuf::from(&cli.config))?;
+
+            if let Some(cookie) = &cli.cookie {
+                settings = settings.with_cookie_file(cookie.clone());
+            }
+
+            // Connect to the cheapest configured source serving the analysis
+            if let Some(need) = cli.command.get_data_need() {
+                let sources = settings.get_sources();
+                let plan = plan_source(need, &sources, cli.source, cli.cache_dir.is_some())?;
+                eprintln!("Plan: {}", plan);
+                settings = settings.with_source(plan.source);
+            }
+
+            let fixtures = recording.as_ref().map(|recording| &recording.fixtures);
+            fetch_client(settings, cli.rpc_clients, fixtures)?
+        }
+    };
+    let retry = RetryPolicy::new(cli.retries, cli.retry_backoff, cli.retry_jitter)
+        .with_failure(cli.on_failure);
+    let rpc = rpc.with_concurrency(cli.concurrency).with_retry(retry);
+    // The z-score of the confidence level unless given, 1.96 for 0.95
+    let z_score = cli
+        .z_score
+        .unwrap_or_else(|| get_rounded_by(get_z_score(cli.confidence), 3));
     // Create a new sample based on inputs
-    let sample = BlockSample::new(
-        cli.z_score,
+    let mut sample = BlockSample::new(
+        z_score,
         cli.std_deviation,
         cli.margin_error,
         cli.full_population,
     );
 
+    if let Some(max_memory) = cli.max_memory {
+        sample = sample.with_max_memory(max_memory);
+    }
+
+    if let Some(max_bytes) = cli.max_bytes {
+        sample = sample.with_max_bytes(max_bytes);
+    }
+
+    let mut exclusions = Exclusions::new(cli.exclude_ranges.clone());
+
+    if let Some(path) = &cli.exclude_heights {
+        exclusions = exclusions.merge(Exclusions::load(path)?);
+    }
+
+    sample = sample.with_exclusions(exclusions);
+
+    if let Some(height) = cli.start_height {
+        sample = sample.with_start_height(height);
+    }
+
+    if let Some(height) = cli.end_height {
+        sample = sample.with_end_height(height);
+    }
+
+    if let Some(timestamp) = cli.since {
+        sample = sample.with_since(timestamp);
+    }
+
+    if let Some(timestamp) = cli.until {
+        sample = sample.with_until(timestamp);
+    }
+
+    sample = sample.with_batch_size(cli.batch_size);
+
+    // Strata given by height imply the stratified strategy
+    let strategy = match cli.strata.is_empty() {
+        true => cli.strategy.clone(),
+        false => SamplingStrategy::Stratified(Strata::Ranges(cli.strata.clone())),
+    };
+    sample = sample.with_strategy(strategy.clone());
+
+    if let Some(seed) = cli.seed {
+        sample = sample.with_seed(seed);
+    }
+
+    if let Some(dir) = &cli.cache_dir {
+        sample = sample.with_cache_dir(dir.clone());
+    }
+
+    // Taproot is only adopted from its activation on
+    if let cli::Analysis::TaprootAdoption {
+        activation_height, ..
+    } = cli.command
+    {
+        let height = cli.start_height.map_or(0, |height| height.0);
+        sample = sample.with_start_height(BlockHeight(height.max(activation_height)));
+    }
+
+    // The median time past of a block needs the blocks before it
+    if let cli::Analysis::BlockTimeDrift {
+        median_time_past: true,
+        ..
+    }
+    | cli::Analysis::TimeWarp { .. }
+    | cli::Analysis::FutureTimestamps { .. } = cli.command
+    {
+        sample = sample.with_context(MTP_BLOCKS);
+    }
+
+    // Report the collection progress on the terminal
+    let progress = ProgressBarSink::new();
+    // Along with a span of every batch of fetches, children of the span of
+    // the analysis ending with the run
+    #[cfg(feature = "otel")]
+    let progress = bitcoin_statistics::telemetry::TelemetrySink::new(
+        progress,
+        bitcoin_statistics::telemetry::start_analysis(analysis),
+    );
+    // Page of the run to share, keeping every report of run-all
+    let page = cli.report.clone().map(|path| {
+        let arguments = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+        let page = HtmlReport::new(analysis)
+            .with_parameter("Arguments", arguments)
+            .with_parameter("Network", cli.network)
+            .with_parameter("Confidence", cli.confidence)
+            .with_parameter("Z-Score", z_score)
+            .with_parameter("Standard Deviation", cli.std_deviation)
+            .with_parameter("Margin of Error", cli.margin_error)
+            .with_parameter("Generated (UTC)", get_date(get_timestamp() as u32));
+
+        match cli.command {
+            cli::Analysis::RunAll { .. } => (RefCell::new(page.with_append()), path),
+            _ => (RefCell::new(page), path),
+        }
+    });
+    // Destination of the reports
+    let output = Output {
+        format: cli.format,
+        destination,
+        partial: RefCell::new(None),
+        run: start_run(history, analysis, &cli.command)?,
+        page,
+    };
+    // Stop collecting and analyze the partial sample on Ctrl-C
+    let cancel = CancellationToken::new();
+    let interrupt = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            interrupt.cancel();
+        }
+    });
+    // Likewise once the time budget runs out
+    let timed_out = Arc::new(AtomicBool::new(false));
+
+    if let Some(timeout) = cli.timeout {
+        let interrupt = cancel.clone();
+        let timed_out = Arc::clone(&timed_out);
+        tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            timed_out.store(true, Ordering::SeqCst);
+            interrupt.cancel();
+        });
+    }
+
+    let collection = Collection {
+        sample: &sample,
+        clients: rpc.clone(),
+        timed_out,
+        output: &output,
+        verify_pow: cli.verify_pow.then_some(cli.network),
+    };
+
     // Run the selected analysis on the data
     match &cli.command {
-        cli::Analysis::BlockTimeDrift { drift_time, window } => {
-            let data = sample.collect(rpc, Some(*window)).await?;
-            data.fetch_block_time_drift(*drift_time, *window, cli.full_population);
+        cli::Analysis::BlockTimeDrift {
+            drift_time,
+            window,
+            min_difficulty,
+            export_intervals,
+            export_blocks,
+            template,
+            progressive,
+            rolling,
+            histogram,
+            chart,
+            median_time_past,
+        } => {
+            // Fail without chart support before fetching any blocks
+            #[cfg(not(feature = "charts"))]
+            if let Some(path) = chart {
+                let e = format!("Rendering {} requires the charts feature", path.display());
+                return Err(e.into());
+            }
+
+            let smooth = |data: BlockSampleData| match median_time_past {
+                true => data.with_median_time_past(),
+                false => data,
+            };
+            let analyze = |data: BlockSampleData| {
+                let report = data.fetch_block_time_drift(
+                    *drift_time,
+                    *window,
+                    cli.full_population,
+                    cli.network,
+                    *min_difficulty,
+                    z_score,
+                );
+
+                match histogram {
+                    Some(width) => report.with_histogram(*width),
+                    None => report,
+                }
+            };
+            let outcome = match progressive {
+                true => {
+                    let preliminary = |data: &BlockSampleData, margin_error: f64| {
+                        eprintln!(
+                            "Preliminary sample of {} blocks within a {:.2}% error margin",
+                            data.len(),
+                            margin_error * 100.0
+                        );
+                        if let Err(e) = output.emit(&analyze(smooth(data.clone()))) {
+                            eprintln!("Cannot output the preliminary report: {}", e);
+                        }
+                    };
+                    sample
+                        .collect_progressive(rpc, Some(*window), &progress, &cancel, preliminary)
+                        .await?
+                }
+                false => {
+                    sample
+                        .collect(rpc, Some(*window), &progress, &cancel)
+                        .await?
+                }
+            };
+            let data = smooth(collection.completed(outcome)?);
+
+            if let Some(path) = export_intervals {
+                data.export_intervals(File::create(path)?, *window)?;
+            }
+
+            if let Some(path) = export_blocks {
+                data.export_blocks(File::create(path)?, template)?;
+            }
+
+            match rolling {
+                Some(blocks) => output.emit(&data.fetch_rolling_intervals(*blocks, *window))?,
+                None => {
+                    let report = match &strategy {
+                        SamplingStrategy::Stratified(strata) => {
+                            let intervals = data.get_stratum_intervals(strata, *window);
+                            analyze(data).with_strata(intervals)
+                        }
+                        SamplingStrategy::Uniform | SamplingStrategy::Systematic => analyze(data),
+                    };
+
+                    #[cfg(feature = "charts")]
+                    if let Some(path) = chart {
+                        bitcoin_statistics::charts::save_drift_chart(&report, path)?;
+                    }
+
+                    output.emit(&report)?;
+                    #[cfg(feature = "charts")]
+                    output.chart(|svg| {
+                        Ok(bitcoin_statistics::charts::write_drift_chart(&report, svg)?)
+                    })?;
+                }
+            }
+        }
+        cli::Analysis::CumulativeDrift { step } => {
+            let data = collection.completed(
+                sample
+                    .collect_checkpoints(rpc, *step, &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_cumulative_drift(cli.network);
+            output.emit(&report)?;
+        }
+        cli::Analysis::EpochDurations { count } => {
+            let data = collection.completed(
+                sample
+                    .collect_epoch_boundaries(rpc, &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_epoch_durations(*count);
+            output.emit(&report)?;
+        }
+        cli::Analysis::TimeWarp { max_gap } => {
+            let data = collection.completed(
+                sample
+                    .collect_epoch_boundaries(rpc, &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_time_warp(*max_gap);
+            output.emit(&report)?;
+        }
+        cli::Analysis::HalvingEraSpeed => {
+            let data = collection
+                .completed(sample.collect_halving_eras(rpc, &progress, &cancel).await?)?;
+            let report = data.fetch_halving_era_speed(z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::VersionBits {
+            window,
+            bit,
+            threshold,
+        } => {
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_version_bits(bit, *threshold, z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::DifficultyTrend {
+            window,
+            count,
+            chart,
+        } => {
+            #[cfg(not(feature = "charts"))]
+            if let Some(path) = chart {
+                let e = format!("Rendering {} requires the charts feature", path.display());
+                return Err(e.into());
+            }
+
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_difficulty_trend(*count);
+
+            #[cfg(feature = "charts")]
+            if let Some(path) = chart {
+                bitcoin_statistics::charts::save_difficulty_chart(&report, path)?;
+            }
+
+            output.emit(&report)?;
+            #[cfg(feature = "charts")]
+            output.chart(|svg| {
+                Ok(bitcoin_statistics::charts::write_difficulty_chart(
+                    &report, svg,
+                )?)
+            })?;
+        }
+        cli::Analysis::Autocorrelation { window, lags } => {
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_autocorrelation(*lags, *window, z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::Hashrate { window, rolling } => {
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_hashrate(*rolling, *window);
+            output.emit(&report)?;
+        }
+        #[cfg(feature = "plugins")]
+        cli::Analysis::Plugin { path, window } => {
+            // Fail on an invalid plugin before fetching any blocks
+            let plugin = bitcoin_statistics::plugins::WasmPlugin::load(path)?;
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_plugin(&plugin)?;
+            output.emit(&report)?;
+        }
+        cli::Analysis::MonotonicityStreaks {
+            window,
+            period,
+            count,
+        } => {
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_monotonicity_streaks(*period, *count, *window);
+            output.emit(&report)?;
+        }
+        cli::Analysis::NegativeDrifts { window } => {
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_negative_drifts(*window, z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::FutureTimestamps {
+            window,
+            count,
+            near,
+        } => {
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_future_timestamps(*count, *near);
+            output.emit(&report)?;
+        }
+        cli::Analysis::OutOfOrder { window } => {
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_out_of_order(*window, z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::ReorgRisk {
+            window,
+            confirmations,
+            stale_rate,
+        } => {
+            let stale_rate = match stale_rate {
+                Some(stale_rate) => *stale_rate,
+                None => {
+                    let (stale, stale_rate) = get_stale_rate(&rpc.get().get_chain_tips()?);
+                    eprintln!("The node knows of {} stale blocks", stale);
+                    stale_rate
+                }
+            };
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_reorg_risk(stale_rate, *confirmations, *window);
+            output.emit(&report)?;
+        }
+        cli::Analysis::Records {
+            window,
+            count,
+            book,
+            export_intervals,
+            export_blocks,
+            template,
+        } => {
+            let mut records = match book {
+                Some(path) => RecordBook::load(path)?,
+                None => RecordBook::default(),
+            };
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+
+            if let Some(path) = export_intervals {
+                data.export_intervals(File::create(path)?, *window)?;
+            }
+
+            if let Some(path) = export_blocks {
+                data.export_blocks(File::create(path)?, template)?;
+            }
+
+            let report = data.fetch_records(&mut records, *count, *window);
+            output.emit(&report)?;
+
+            if let Some(path) = book {
+                records.save(path)?;
+            }
+        }
+        cli::Analysis::Revenue {
+            window,
+            period,
+            price_feed,
+        } => {
+            // Fail on an invalid price feed before fetching any blocks
+            let prices = price_feed.as_deref().map(load_price_feed).transpose()?;
+            let data = collection.completed(
+                sample
+                    .collect_stats(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_revenue(*period, prices.as_ref());
+            output.emit(&report)?;
+        }
+        cli::Analysis::BlockSize { window, period } => {
+            let data = collection.completed(
+                sample
+                    .collect_stats(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_block_size(*period, z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::Transactions { window } => {
+            let data = collection.completed(
+                sample
+                    .collect_stats(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_transactions(z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::Rpc { method, params } => {
+            let result: serde_json::Value = rpc.get().call(method, params)?;
+            output
+                .destination
+                .write(&serde_json::to_string_pretty(&result)?)?;
+        }
+        cli::Analysis::Index {
+            command: cli::IndexCommand::Verify { fill_gaps },
+        } => {
+            let dir = cli
+                .cache_dir
+                .as_ref()
+                .ok_or("The header cache requires --cache-dir")?;
+            let client = rpc.get();
+            let mut cache = HeaderCache::open(dir, &client.get_block_hash(0)?)?;
+            let report = repair_header_cache(&mut cache, &client, *fill_gaps)?;
+            output.emit(&report)?;
+        }
+        cli::Analysis::Schema => unreachable!("Schemas are printed before connecting"),
+        cli::Analysis::Examples { .. } => {
+            unreachable!("Examples are listed or expanded before connecting")
+        }
+        cli::Analysis::History { .. } | cli::Analysis::Show { .. } => {
+            unreachable!("Previous runs are printed before connecting")
+        }
+        cli::Analysis::SignetProducers { challenge, window } => {
+            let challenge = match challenge {
+                Some(challenge) => SignetChallenge::from_hex(challenge)?,
+                None => SignetChallenge::default(),
+            };
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_signet_producers(&challenge, *window);
+            output.emit(&report)?;
+        }
+        cli::Analysis::ChainTips { recent } => {
+            let report = get_chain_tips_report(&rpc.get().get_chain_tips()?, *recent);
+            output.emit(&report)?;
+        }
+        cli::Analysis::ConfirmationLatency {
+            zmq,
+            interval,
+            expiry,
+        } => {
+            let watching = TransactionWatch::new(get_timestamp(), expiry * 3600);
+            let client = rpc.get();
+            let report = TransactionWatch::report_latency;
+            let client = Some(client.as_ref());
+            watch(watching, zmq, *interval, client, &cancel, &output, report).await?;
+        }
+        cli::Analysis::Consolidations {
+            window,
+            period,
+            threshold,
+        } => {
+            let data = collection.completed(
+                sample
+                    .collect_stats(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_consolidations(*period, *threshold);
+            output.emit(&report)?;
+        }
+        cli::Analysis::Custom {
+            expression,
+            per,
+            aggregate,
+            window,
+        } => {
+            // Fail on an invalid expression before fetching any blocks
+            let expression = CustomExpression::compile(expression)?;
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_custom(&expression, *per, aggregate, *window)?;
+            output.emit(&report)?;
+        }
+        cli::Analysis::DifficultyEpochs { window } => {
+            let data = collection.completed(
+                sample
+                    .collect(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_difficulty_epochs(*window);
+            output.emit(&report)?;
+        }
+        cli::Analysis::EntityClusters {
+            window,
+            max_scripts,
+        } => {
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_entity_clusters(*max_scripts);
+            output.emit(&report)?;
+        }
+        cli::Analysis::FeeElasticity { window } => {
+            let data = collection.completed(
+                sample
+                    .collect_stats(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_fee_elasticity();
+            output.emit(&report)?;
+        }
+        cli::Analysis::FeeStats { window, rolling } => {
+            let data = collection.completed(
+                sample
+                    .collect_stats(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+
+            match rolling {
+                Some(blocks) => output.emit(&data.fetch_rolling_fee_rates(*blocks))?,
+                None => output.emit(&data.fetch_fee_stats(z_score))?,
+            }
+        }
+        cli::Analysis::PoolLuck { window, expected } => {
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_pool_luck(
+                &get_default_pools(),
+                &expected.iter().cloned().collect(),
+                z_score,
+                *window,
+            );
+            output.emit(&report)?;
+        }
+        cli::Analysis::EmptyBlocks { window, signatures } => {
+            // Fail on invalid signatures before fetching any blocks
+            let pools = load_signatures(signatures.as_deref())?;
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_empty_blocks(&pools, z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::Pools { window, signatures } => {
+            // Fail on invalid signatures before fetching any blocks
+            let pools = load_signatures(signatures.as_deref())?;
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_pools(&pools, z_score);
+            output.emit(&report)?;
+        }
+        cli::Analysis::SizeIntervalCorrelation {
+            window,
+            export_intervals,
+            export_blocks,
+            template,
+            rolling,
+        } => {
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+
+            if let Some(path) = export_intervals {
+                data.export_intervals(File::create(path)?, *window)?;
+            }
+
+            if let Some(path) = export_blocks {
+                data.export_blocks(File::create(path)?, template)?;
+            }
+
+            match rolling {
+                Some(blocks) => output.emit(&data.fetch_rolling_sizes(*blocks))?,
+                None => output.emit(&data.fetch_size_interval_correlation(*window))?,
+            }
+        }
+        cli::Analysis::Watch {
+            zmq,
+            interval,
+            expiry,
+            log_conflicts,
+        } => {
+            let mut watching = TransactionWatch::new(get_timestamp(), expiry * 3600);
+
+            if *log_conflicts {
+                watching = watching.with_conflict_log();
+            }
+
+            let report = |watch: &TransactionWatch| watch.report(get_timestamp());
+            watch(watching, zmq, *interval, None, &cancel, &output, report).await?;
+        }
+        cli::Analysis::WatchBlocks {
+            zmq,
+            window,
+            drift_time,
+            min_difficulty,
+        } => {
+            let report = |data: BlockSampleData| {
+                let blocks = data.len() as u64;
+                data.fetch_block_time_drift(
+                    *drift_time,
+                    blocks,
+                    true,
+                    cli.network,
+                    *min_difficulty,
+                    z_score,
+                )
+            };
+            let client = rpc.get();
+            watch_blocks(*window, zmq, &client, &cancel, &output, report).await?;
+        }
+        cli::Analysis::WitnessVersionFlow { window, period } => {
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_witness_version_flow(*period);
+            output.emit(&report)?;
+        }
+        cli::Analysis::CoinbaseRevenue { window } => {
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_coinbase_revenue();
+            output.emit(&report)?;
+        }
+        cli::Analysis::RunAll { window, analyses } => match cli.command.get_data_need() {
+            Some(DataNeed::BlockStats) => {
+                let data = collection.completed(
+                    sample
+                        .collect_stats(rpc, Some(*window), &progress, &cancel)
+                        .await?,
+                )?;
+
+                for analysis in analyses {
+                    analyze_stats(analysis, data.clone(), z_score, &output)?;
+                }
+            }
+            _ => {
+                let data = collection.completed(
+                    sample
+                        .collect(rpc, Some(*window), &progress, &cancel)
+                        .await?,
+                )?;
+
+                for analysis in analyses {
+                    analyze_headers(analysis, data.clone(), *window, &cli, z_score, &output)?;
+                }
+            }
+        },
+        cli::Analysis::TaprootAdoption {
+            window,
+            activation_height,
+        } => {
+            let data = collection.completed(
+                sample
+                    .collect_full(rpc, Some(*window), &progress, &cancel)
+                    .await?,
+            )?;
+            let report = data.fetch_taproot_adoption(*activation_height);
+            output.emit(&report)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Watch the ZMQ notifications until cancelled, printing the report of the
+/// watch every interval along with its conflict log. The fee rates of new
+/// transactions are looked up when a client is provided. The conflict log is
+/// written to stderr unless the reports are printed as tables.
+async fn watch<F, R>(
+    mut watch: TransactionWatch,
+    zmq: &[String],
+    interval: u64,
+    client: Option<&Client>,
+    cancel: &CancellationToken,
+    output: &Output,
+    report: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&TransactionWatch) -> R,
+    R: Report,
+{
+    let mut notifications = subscribe(zmq, &TRANSACTION_TOPICS, cancel.clone())?;
+    let mut reports = tokio::time::interval(Duration::from_secs(interval));
+    // The first tick completes immediately
+    reports.tick().await;
+
+    eprintln!("Watching: {}... (Ctrl-C to stop)", zmq.join(", "));
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = reports.tick() => output.emit(&report(&watch))?,
+            notification = notifications.recv() => match notification {
+                Some(notification) => {
+                    let arrival = watch.observe(&notification?, get_timestamp());
+
+                    if let (Some(txid), Some(client)) = (arrival, client) {
+                        if let Some(fee_rate) = get_fee_rate(client, &txid) {
+                            watch.set_fee_rate(&txid, fee_rate);
+                        }
+                    }
+
+                    for event in watch.take_conflict_log() {
+                        match (output.format, output.destination.get_path()) {
+                            (OutputFormat::Table, None) => println!("{}", event),
+                            _ => eprintln!("{}", event),
+                        }
+                    }
+                }
+                None => break,
+            },
+        }
+    }
+
+    output.emit(&report(&watch))?;
+    Ok(())
+}
+
+/// Watch the block notifications until cancelled, printing the report of
+/// the rolling window of intervals every time a block is connected. The
+/// window starts with the latest blocks of the node and blocks missed in
+/// between notifications are fetched to keep it contiguous.
+async fn watch_blocks<F, R>(
+    window: usize,
+    zmq: &[String],
+    client: &Client,
+    cancel: &CancellationToken,
+    output: &Output,
+    report: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(BlockSampleData) -> R,
+    R: Report,
+{
+    let mut watch = BlockWatch::new(window);
+    let mut notifications = subscribe(zmq, &BLOCK_TOPICS, cancel.clone())?;
+    let connect = |watch: &mut BlockWatch, heights: Vec<u64>| -> Result<(), Box<dyn Error>> {
+        let headers = heights.iter().copied().map(BlockHeight).collect::<Vec<_>>();
+        let headers = get_block_headers(client, &headers)?;
+        heights
+            .into_iter()
+            .zip(headers)
+            .for_each(|(height, header)| watch.connect(height, header));
+        Ok(())
+    };
+
+    let tip = client.get_block_count()?;
+    connect(
+        &mut watch,
+        (tip.saturating_sub(window as u64)..=tip).collect(),
+    )?;
+    output.emit(&report(watch.get_data()))?;
+
+    eprintln!("Watching: {}... (Ctrl-C to stop)", zmq.join(", "));
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            notification = notifications.recv() => match notification {
+                Some(notification) => {
+                    let hash = match notification? {
+                        Notification::BlockHash(hash) => hash,
+                        _ => continue,
+                    };
+                    let height = client.get_block_header_info(&hash)?.height as u64;
+
+                    if let Some(next) = watch.next_height().filter(|next| *next < height) {
+                        let start = next.max(height.saturating_sub(window as u64));
+                        connect(&mut watch, (start..height).collect())?;
+                    }
+
+                    watch.connect(height, client.get_block_header(&hash)?);
+                    output.emit(&report(watch.get_data()))?;
+                }
+                None => break,
+            },
         }
     }
 
     Ok(())
 }
+
+/// Destination of the rendered reports.
+struct Output {
+    format: OutputFormat,
+    /// Stdout or the file replaced with every report
+    destination: Destination,
+    /// Sample achieved once the collection stopped early
+    partial: RefCell<Option<PartialSampleTable>>,
+    /// History recording the reports of the run with the id of the run
+    run: Option<(RunHistory, i64)>,
+    /// HTML page of the run with the file it is saved to on every report
+    page: Option<(RefCell<HtmlReport>, PathBuf)>,
+}
+
+/// Fixtures recorded during the run, saved to the file when dropped so the
+/// responses leading up to a failure are kept as well.
+struct Recording {
+    fixtures: Fixtures,
+    path: PathBuf,
+}
+
+/// Context of a collection for noting a partial sample on the reports.
+struct Collection<'a> {
+    sample: &'a BlockSample,
+    clients: ClientPool,
+    timed_out: Arc<AtomicBool>,
+    output: &'a Output,
+    /// Network to verify the proof of work of the collected headers for
+    verify_pow: Option<Network>,
+}
+
+/// Collected data with the number of sampled blocks.
+trait Sampled {
+    fn sampled(&self) -> usize;
+    fn verify_pow(&self, network: Network) -> Result<(), Box<dyn Error>>;
+}
+
+impl Output {
+    /// Render the report and print it or write it to the file. Reports of
+    /// a partial sample are marked with the achieved sample.
+    fn emit<R: Report>(&self, report: &R) -> Result<(), Box<dyn Error>> {
+        match &*self.partial.borrow() {
+            Some(partial) => self.write(&PartialReport { partial, report }),
+            None => self.write(report),
+        }
+    }
+
+    /// Write the rendered report, recording it as the report of the run.
+    fn write<R: Report>(&self, report: &R) -> Result<(), Box<dyn Error>> {
+        let rendered = render(report, self.format)?;
+
+        if let Some((history, id)) = &self.run {
+            history.record(*id, report)?;
+        }
+
+        if let Some((page, path)) = &self.page {
+            page.borrow_mut()
+                .add_report(&render(report, OutputFormat::Table)?);
+            page.borrow().save(path)?;
+        }
+
+        Ok(self.destination.write(&rendered)?)
+    }
+
+    /// Add the chart written by the function to the page of the run after
+    /// the report it belongs to.
+    #[cfg(feature = "charts")]
+    fn chart<F>(&self, write: F) -> Result<(), Box<dyn Error>>
+    where
+        F: FnOnce(&mut Vec<u8>) -> Result<(), Box<dyn Error>>,
+    {
+        if let Some((page, path)) = &self.page {
+            let mut svg = vec![];
+            write(&mut svg)?;
+            page.borrow_mut().add_chart(&String::from_utf8_lossy(&svg));
+            page.borrow().save(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        match self.fixtures.save(&self.path) {
+            Ok(()) => eprintln!(
+                "Recorded {} responses to {}",
+                self.fixtures.len(),
+                self.path.display()
+            ),
+            Err(e) => eprintln!("Cannot record the fixtures: {}", e),
+        }
+    }
+}
+
+/// Start a run of the analysis in the history with the arguments it was
+/// invoked with. Calls of RPC methods are not analyses and never recorded.
+fn start_run(
+    history: Option<RunHistory>,
+    analysis: &str,
+    command: &cli::Analysis,
+) -> Result<Option<(RunHistory, i64)>, Box<dyn Error>> {
+    match (history, command) {
+        (Some(_), cli::Analysis::Rpc { .. }) | (None, _) => Ok(None),
+        (Some(history), _) => {
+            let parameters = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+            let id = history.start(analysis, &parameters, get_timestamp())?;
+            eprintln!("Recording run {} in the history", id);
+            Ok(Some((history, id)))
+        }
+    }
+}
+
+/// Run an analysis of headers of run-all on the shared data over its
+/// window, see Analysis::is_shareable.
+fn analyze_headers(
+    analysis: &cli::Analysis,
+    data: BlockSampleData,
+    window: u64,
+    cli: &cli::Args,
+    z_score: f64,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    match analysis {
+        cli::Analysis::Autocorrelation { lags, .. } => {
+            output.emit(&data.fetch_autocorrelation(*lags, window, z_score))
+        }
+        cli::Analysis::BlockTimeDrift {
+            drift_time,
+            min_difficulty,
+            ..
+        } => output.emit(&data.fetch_block_time_drift(
+            *drift_time,
+            window,
+            cli.full_population,
+            cli.network,
+            *min_difficulty,
+            z_score,
+        )),
+        cli::Analysis::DifficultyEpochs { .. } => {
+            output.emit(&data.fetch_difficulty_epochs(window))
+        }
+        cli::Analysis::DifficultyTrend { count, .. } => {
+            output.emit(&data.fetch_difficulty_trend(*count))
+        }
+        cli::Analysis::Hashrate { rolling, .. } => {
+            output.emit(&data.fetch_hashrate(*rolling, window))
+        }
+        cli::Analysis::MonotonicityStreaks { period, count, .. } => {
+            output.emit(&data.fetch_monotonicity_streaks(*period, *count, window))
+        }
+        cli::Analysis::NegativeDrifts { .. } => {
+            output.emit(&data.fetch_negative_drifts(window, z_score))
+        }
+        cli::Analysis::OutOfOrder { .. } => output.emit(&data.fetch_out_of_order(window, z_score)),
+        cli::Analysis::Records { count, .. } => {
+            output.emit(&data.fetch_records(&mut RecordBook::default(), *count, window))
+        }
+        cli::Analysis::VersionBits { bit, threshold, .. } => {
+            output.emit(&data.fetch_version_bits(bit, *threshold, z_score))
+        }
+        _ => Err(format!("{:?} does not run on headers alone", analysis).into()),
+    }
+}
+
+/// Run an analysis of block statistics of run-all on the shared data, see
+/// Analysis::is_shareable.
+fn analyze_stats(
+    analysis: &cli::Analysis,
+    data: BlockStatsData,
+    z_score: f64,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    match analysis {
+        cli::Analysis::BlockSize { period, .. } => {
+            output.emit(&data.fetch_block_size(*period, z_score))
+        }
+        cli::Analysis::Consolidations {
+            period, threshold, ..
+        } => output.emit(&data.fetch_consolidations(*period, *threshold)),
+        cli::Analysis::FeeElasticity { .. } => output.emit(&data.fetch_fee_elasticity()),
+        cli::Analysis::FeeStats { .. } => output.emit(&data.fetch_fee_stats(z_score)),
+        cli::Analysis::Transactions { .. } => output.emit(&data.fetch_transactions(z_score)),
+        _ => Err(format!("{:?} does not run on block statistics", analysis).into()),
+    }
+}
+
+/// Load the pool signatures of the file matched before the bundled ones.
+fn load_signatures(path: Option<&Path>) -> Result<Vec<PoolSignature>, Box<dyn Error>> {
+    let mut pools = match path {
+        Some(path) => load_pools(path)?,
+        None => vec![],
+    };
+    pools.extend(get_default_pools());
+
+    Ok(pools)
+}
+
+/// Load the price feed from a file, or fetch it when given a URL.
+fn load_price_feed(source: &str) -> Result<PriceFeed, Box<dyn Error>> {
+    match source.starts_with("http://") || source.starts_with("https://") {
+        false => Ok(PriceFeed::load(Path::new(source))?),
+        #[cfg(feature = "price-feed")]
+        true => Ok(PriceFeed::fetch(source)?),
+        #[cfg(not(feature = "price-feed"))]
+        true => Err(format!("Fetching {} requires the price-feed feature", source).into()),
+    }
+}
+
+impl Collection<'_> {
+    /// Get the collected data, noting when the analysis runs on a partial
+    /// sample so the reports are marked with the achieved sample.
+    fn completed<T: Sampled>(&self, outcome: Outcome<T>) -> Result<T, Box<dyn Error>> {
+        if outcome.is_cancelled() {
+            let reason = match self.timed_out.load(Ordering::SeqCst) {
+                true => "Timed out",
+                false => "Cancelled",
+            };
+            eprintln!(
+                "Collection {}, analyzing the partial sample.",
+                reason.to_lowercase()
+            );
+
+            match self.sample.get_population(&self.clients.get()) {
+                Ok(population) => {
+                    let sampled = outcome.inner().sampled() as u64;
+                    let partial = self.sample.get_partial_sample(reason, sampled, population);
+                    *self.output.partial.borrow_mut() = Some(partial);
+                }
+                Err(e) => eprintln!("Cannot determine the population of the sample: {}", e),
+            }
+        }
+
+        let data = outcome.into_inner();
+
+        if let Some(network) = self.verify_pow {
+            data.verify_pow(network)?;
+            eprintln!("Verified the proof of work of {} blocks", data.sampled());
+        }
+
+        Ok(data)
+    }
+}
+
+impl Sampled for BlockSampleData {
+    fn sampled(&self) -> usize {
+        self.len()
+    }
+
+    fn verify_pow(&self, network: Network) -> Result<(), Box<dyn Error>> {
+        Ok(self.verify_pow(network)?)
+    }
+}
+
+impl Sampled for BlockSampleFullData {
+    fn sampled(&self) -> usize {
+        self.len()
+    }
+
+    fn verify_pow(&self, network: Network) -> Result<(), Box<dyn Error>> {
+        Ok(self.verify_pow(network)?)
+    }
+}
+
+impl Sampled for BlockStatsData {
+    fn sampled(&self) -> usize {
+        self.len()
+    }
+
+    /// Block statistics carry no headers to verify, see the check of
+    /// --verify-pow before connecting.
+    fn verify_pow(&self, _: Network) -> Result<(), Box<dyn Error>> {
+        Err("Block statistics carry no headers to verify the proof of work of".into())
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Deserialize;
+use std::fmt;
+
+use crate::{Backend, Result, StatisticsError};
+
+/// Data fetched by an analysis, picking the sources able to serve it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DataNeed {
+    /// Block headers, served from the local header cache when enabled
+    Headers,
+    /// Full blocks with their transactions
+    Blocks,
+    /// Statistics of blocks (getblockstats)
+    BlockStats,
+    /// Calls only a node answers, e.g. its chain tips or notifications
+    Node,
+}
+
+/// Further source of blocks configured along with the main source, see
+/// ClientConfig. A bitcoind source authenticates with the credentials of
+/// the main source.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct SourceConfig {
+    pub backend: Backend,
+    pub host: String,
+    /// Requests per second sent to an Esplora instance
+    #[serde(default)]
+    pub rate_limit: Option<f64>,
+}
+
+/// Source picked for the data of an analysis.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SourcePlan {
+    pub need: DataNeed,
+    pub source: SourceConfig,
+    /// Headers are served from the local header cache first
+    pub cached: bool,
+    /// The source was picked by the override instead of its cost
+    pub overridden: bool,
+}
+
+/// Pick the cheapest source serving the data among the configured sources,
+/// the first source of the backend when overridden. Headers are served from
+/// the local header cache first when cached, fetching only the headers
+/// missing from it from the source.
+pub fn plan_source(
+    need: DataNeed,
+    sources: &[SourceConfig],
+    preferred: Option<Backend>,
+    cached: bool,
+) -> Result<SourcePlan> {
+    let source = match preferred {
+        Some(backend) => {
+            let source = sources
+                .iter()
+                .find(|source| source.backend == backend)
+                .ok_or_else(|| {
+                    StatisticsError::BackendError(format!("No {} source is configured", backend))
+                })?;
+
+            match get_cost(backend, need) {
+                Some(_) => source,
+                None => {
+                    return Err(StatisticsError::BackendError(format!(
+                        "{} does not serve {}",
+                        backend, need
+                    )))
+                }
+            }
+        }
+        // The earliest configured source among equally cheap sources
+        None => sources
+            .iter()
+            .filter_map(|source| Some((get_cost(source.backend, need)?, source)))
+            .min_by_key(|(cost, _)| *cost)
+            .map(|(_, source)| source)
+            .ok_or_else(|| {
+                StatisticsError::BackendError(format!("No configured source serves {}", need))
+            })?,
+    };
+
+    Ok(SourcePlan {
+        need,
+        source: source.clone(),
+        cached: cached && need == DataNeed::Headers,
+        overridden: preferred.is_some(),
+    })
+}
+
+/// Get the relative cost of fetching the data from the backend, none when
+/// the backend does not serve it. Block files are read from the local disk
+/// without any request, the REST interface of bitcoind fetches up to 2000
+/// headers per request, bitcoind batches the requests of headers and serves
+/// everything, Electrum needs a request per header and Esplora two requests
+/// per block limited to the rate of its instance.
+fn get_cost(backend: Backend, need: DataNeed) -> Option<u32> {
+    match (backend, need) {
+        (Backend::BlockFiles, DataNeed::Headers | DataNeed::Blocks) => Some(0),
+        (Backend::Rest, DataNeed::Headers) => Some(1),
+        (Backend::Bitcoind, _) | (Backend::Rest, DataNeed::Blocks) => Some(2),
+        (Backend::Electrum, DataNeed::Headers) => Some(3),
+        (Backend::Esplora, DataNeed::Headers | DataNeed::Blocks) => Some(4),
+        _ => None,
+    }
+}
+
+impl fmt::Display for DataNeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataNeed::Headers => write!(f, "block headers"),
+            DataNeed::Blocks => write!(f, "full blocks"),
+            DataNeed::BlockStats => write!(f, "block statistics"),
+            DataNeed::Node => write!(f, "node calls"),
+        }
+    }
+}
+
+impl fmt::Display for SourcePlan {
+    /// Print the plan, e.g. `block headers from electrum at host:50001`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} from {} at {}",
+            self.need, self.source.backend, self.source.host
+        )?;
+
+        if self.cached {
+            write!(f, " missing from the header cache")?;
+        }
+
+        match self.overridden {
+            true => write!(f, " (overridden)"),
+            false => write!(f, " (cheapest capable source)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plan_source() {
+        let source = |backend, host: &str| SourceConfig {
+            backend,
+            host: host.to_string(),
+            rate_limit: None,
+        };
+        let sources = [
+            source(Backend::Esplora, "https://blockstream.info/api"),
+            source(Backend::Electrum, "electrum.example.org:50001"),
+        ];
+
+        let plan = plan_source(DataNeed::Headers, &sources, None, true).unwrap();
+        assert_eq!(plan.source.backend, Backend::Electrum);
+        assert_eq!(
+            plan.to_string(),
+            "block headers from electrum at electrum.example.org:50001 missing from the \
+             header cache (cheapest capable source)"
+        );
+
+        let plan = plan_source(DataNeed::Blocks, &sources, None, true).unwrap();
+        assert_eq!(plan.source.backend, Backend::Esplora);
+        assert!(!plan.cached);
+
+        let plan = plan_source(DataNeed::Headers, &sources, Some(Backend::Esplora), false);
+        assert!(plan.unwrap().overridden);
+
+        assert!(plan_source(DataNeed::BlockStats, &sources, None, false).is_err());
+        assert!(plan_source(DataNeed::Blocks, &sources, Some(Backend::Electrum), false).is_err());
+        assert!(plan_source(DataNeed::Headers, &sources, Some(Backend::Bitcoind), false).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Experimental loader of analyses compiled to WASM.
+//!
+//! A plugin is a WASM module exporting its `memory`, an `abi_version`
+//! function returning the version of the host ABI it was built against and a
+//! `run` function returning 0 on success. While running, the plugin can call
+//! the following functions imported from the `bitcoin_statistics` module:
+//!
+//! - `block_count() -> i32` number of blocks in the sample
+//! - `block_height(index: i32) -> i64` height of a block, -1 when out of range
+//! - `block_len(index: i32) -> i32` size of a consensus encoded block, -1 when
+//!   out of range
+//! - `read_block(index: i32, ptr: i32) -> i32` copy a consensus encoded block
+//!   to the memory of the plugin returning its size, -1 when out of range
+//! - `output(ptr: i32, len: i32)` append UTF-8 text to the report
+
+use bitcoincore_rpc::bitcoin::{consensus::encode::serialize, Block};
+use std::{fs, path::Path};
+use wasmi::{core::Trap, Caller, Config, Engine, Extern, Linker, Memory, Module, Store};
+
+use crate::{BlockHeight, Result, StatisticsError};
+
+/// Version of the host ABI exposed to plugins.
+pub const ABI_VERSION: i32 = 1;
+/// Module name of the functions imported by plugins.
+const HOST_MODULE: &str = "bitcoin_statistics";
+/// Fuel available to a plugin run, bounding the instructions it executes.
+const MAX_FUEL: u64 = 1 << 40;
+
+/// An analysis compiled to WASM.
+pub struct WasmPlugin {
+    engine: Engine,
+    module: Module,
+}
+
+/// Data of the sample exposed to a running plugin.
+struct HostState {
+    blocks: Vec<(u64, Vec<u8>)>,
+    output: String,
+}
+
+impl WasmPlugin {
+    /// Load the plugin from a WASM file.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Load the plugin from WASM bytes.
+    pub fn from_bytes(wasm: &[u8]) -> Result<Self> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm)?;
+
+        Ok(WasmPlugin { engine, module })
+    }
+
+    /// Run the plugin over the blocks returning its report.
+    pub fn run(&self, blocks: &[(BlockHeight, Block)]) -> Result<String> {
+        let state = HostState {
+            blocks: blocks
+                .iter()
+                .map(|(height, block)| (height.0, serialize(block)))
+                .collect(),
+            output: String::new(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.add_fuel(MAX_FUEL).map_err(wasmi::Error::from)?;
+
+        let linker = get_linker(&self.engine)?;
+        let instance = linker
+            .instantiate(&mut store, &self.module)?
+            .start(&mut store)?;
+
+        let version = instance
+            .get_typed_func::<(), i32>(&store, "abi_version")?
+            .call(&mut store, ())
+            .map_err(wasmi::Error::from)?;
+
+        if version != ABI_VERSION {
+            return Err(StatisticsError::PluginAbiError {
+                version,
+                expected: ABI_VERSION,
+            });
+        }
+
+        let code = instance
+            .get_typed_func::<(), i32>(&store, "run")?
+            .call(&mut store, ())
+            .map_err(wasmi::Error::from)?;
+
+        match code {
+            0 => Ok(std::mem::take(&mut store.data_mut().output)),
+            code => Err(StatisticsError::PluginExitError(code)),
+        }
+    }
+}
+
+/// Get the linker providing the host ABI.
+fn get_linker(engine: &Engine) -> Result<Linker<HostState>> {
+    let mut linker = Linker::new(engine);
+
+    linker
+        .func_wrap(HOST_MODULE, "block_count", |caller: Caller<HostState>| {
+            caller.data().blocks.len() as i32
+        })
+        .map_err(wasmi::Error::from)?
+        .func_wrap(
+            HOST_MODULE,
+            "block_height",
+            |caller: Caller<HostState>, index: i32| {
+                get_block(&caller, index).map_or(-1, |(height, _)| *height as i64)
+            },
+        )
+        .map_err(wasmi::Error::from)?
+        .func_wrap(
+            HOST_MODULE,
+            "block_len",
+            |caller: Caller<HostState>, index: i32| {
+                get_block(&caller, index).map_or(-1, |(_, block)| block.len() as i32)
+            },
+        )
+        .map_err(wasmi::Error::from)?
+        .func_wrap(
+            HOST_MODULE,
+            "read_block",
+            |mut caller: Caller<HostState>, index: i32, ptr: i32| {
+                let block = match get_block(&caller, index) {
+                    Some((_, block)) => block.clone(),
+                    None => return Ok(-1),
+                };
+                get_memory(&caller)?
+                    .write(&mut caller, ptr as u32 as usize, &block)
+                    .map_err(|e| Trap::new(e.to_string()))?;
+                Ok(block.len() as i32)
+            },
+        )
+        .map_err(wasmi::Error::from)?
+        .func_wrap(
+            HOST_MODULE,
+            "output",
+            |mut caller: Caller<HostState>, ptr: i32, len: i32| {
+                let mut text = vec![0; len as u32 as usize];
+                get_memory(&caller)?
+                    .read(&caller, ptr as u32 as usize, &mut text)
+                    .map_err(|e| Trap::new(e.to_string()))?;
+                caller
+                    .data_mut()
+                    .output
+                    .push_str(&String::from_utf8_lossy(&text));
+                Ok(())
+            },
+        )
+        .map_err(wasmi::Error::from)?;
+
+    Ok(linker)
+}
+
+/// Get the block of the sample at the index.
+fn get_block<'a>(caller: &'a Caller<HostState>, index: i32) -> Option<&'a (u64, Vec<u8>)> {
+    usize::try_from(index)
+        .ok()
+        .and_then(|index| caller.data().blocks.get(index))
+}
+
+/// Get the memory exported by the plugin.
+fn get_memory(caller: &Caller<HostState>) -> std::result::Result<Memory, Trap> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Trap::new("The plugin does not export its memory"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::{blockdata::constants::genesis_block, Network};
+
+    /// Plugin writing "block" for every block it can read.
+    const PLUGIN: &str = r#"
+        (module
+            (import "bitcoin_statistics" "block_count" (func $block_count (result i32)))
+            (import "bitcoin_statistics" "block_len" (func $block_len (param i32) (result i32)))
+            (import "bitcoin_statistics" "read_block" (func $read_block (param i32 i32) (result i32)))
+            (import "bitcoin_statistics" "output" (func $output (param i32 i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "block")
+            (func (export "abi_version") (result i32) (i32.const 1))
+            (func (export "run") (result i32) (local $i i32)
+                (block $done
+                    (loop $next
+                        (br_if $done (i32.ge_s (local.get $i) (call $block_count)))
+                        (if (i32.ne
+                                (call $read_block (local.get $i) (i32.const 1024))
+                                (call $block_len (local.get $i)))
+                            (then (return (i32.const 1))))
+                        (call $output (i32.const 0) (i32.const 5))
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br $next)))
+                (i32.const 0)))
+    "#;
+
+    fn plugin(wat: &str) -> WasmPlugin {
+        WasmPlugin::from_bytes(&wat::parse_str(wat).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_plugin_run() {
+        let blocks = vec![
+            (BlockHeight(0), genesis_block(Network::Bitcoin)),
+            (BlockHeight(1), genesis_block(Network::Bitcoin)),
+        ];
+        assert_eq!(plugin(PLUGIN).run(&blocks).unwrap(), "blockblock");
+    }
+
+    #[test]
+    fn test_plugin_errors() {
+        let version = PLUGIN.replace("(result i32) (i32.const 1))", "(result i32) (i32.const 2))");
+        assert!(matches!(
+            plugin(&version).run(&[]),
+            Err(StatisticsError::PluginAbiError {
+                version: 2,
+                expected: 1
+            })
+        ));
+
+        let failing = PLUGIN.replace("(i32.const 0)))", "(i32.const 3)))");
+        assert!(matches!(
+            plugin(&failing).run(&[]),
+            Err(StatisticsError::PluginExitError(3))
+        ));
+        assert!(WasmPlugin::from_bytes(b"not wasm").is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use bitcoincore_rpc::bitcoin::{Address, Block, Script};
+use std::{fs, path::Path, str::FromStr};
+
+use crate::{Result, StatisticsError};
+
+/// Coinbase tags of well known mining pools. Tags are matched without
+/// regard to case against the coinbase scriptSig.
+const POOL_TAGS: &[(&str, &[&str])] = &[
+    ("Foundry USA", &["Foundry USA"]),
+    ("AntPool", &["Mined by AntPool", "AntPool"]),
+    ("F2Pool", &["F2Pool", "七彩神仙鱼"]),
+    ("ViaBTC", &["ViaBTC"]),
+    ("Binance Pool", &["binance"]),
+    ("MARA Pool", &["MARA Pool", "MARA Made in USA"]),
+    ("Braiins Pool", &["/slush/", "Braiins"]),
+    ("Poolin", &["poolin"]),
+    ("BTC.com", &["btcom", "BTC.COM"]),
+    ("Luxor", &["Luxor"]),
+    ("SpiderPool", &["SpiderPool"]),
+    ("SBI Crypto", &["SBICrypto"]),
+    ("OCEAN", &["OCEAN.XYZ"]),
+    ("Huobi Pool", &["HuoBi", "Huobi"]),
+    ("BTC.TOP", &["BTC.TOP"]),
+    ("1THash", &["1THash"]),
+    ("Bitfury", &["Bitfury"]),
+    ("KanoPool", &["Kano"]),
+];
+
+/// Signature used to attribute a block to a mining pool.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PoolSignature {
+    name: String,
+    tags: Vec<String>,
+    /// Scripts of the payout addresses of the pool
+    payouts: Vec<Script>,
+}
+
+impl PoolSignature {
+    /// Create a new signature for the pool using coinbase tags.
+    pub fn new(name: &str, tags: &[&str]) -> Self {
+        PoolSignature {
+            name: name.to_string(),
+            tags: tags.iter().map(|tag| tag.to_lowercase()).collect(),
+            payouts: vec![],
+        }
+    }
+
+    /// Also match the coinbases paying out to the scripts.
+    pub fn with_payouts(mut self, payouts: Vec<Script>) -> Self {
+        self.payouts = payouts;
+        self
+    }
+
+    /// Name of the pool.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check whether the signature matches the coinbase of the block.
+    pub fn matches(&self, block: &Block) -> bool {
+        self.matches_tag(block) || self.matches_payout(block)
+    }
+
+    /// Check whether a tag of the signature is within the coinbase scriptSig
+    /// of the block.
+    pub fn matches_tag(&self, block: &Block) -> bool {
+        let coinbase = match block.coinbase().and_then(|tx| tx.input.first()) {
+            Some(input) => String::from_utf8_lossy(input.script_sig.as_bytes()).to_lowercase(),
+            None => return false,
+        };
+
+        self.tags.iter().any(|tag| coinbase.contains(tag))
+    }
+
+    /// Check whether the coinbase of the block pays out to a payout address
+    /// of the signature.
+    pub fn matches_payout(&self, block: &Block) -> bool {
+        block.coinbase().is_some_and(|tx| {
+            tx.output
+                .iter()
+                .any(|output| self.payouts.contains(&output.script_pubkey))
+        })
+    }
+}
+
+/// Get the bundled list of pool signatures.
+pub fn get_default_pools() -> Vec<PoolSignature> {
+    POOL_TAGS
+        .iter()
+        .map(|(name, tags)| PoolSignature::new(name, tags))
+        .collect()
+}
+
+/// Parse pool signatures of lines of `NAME,tag:TEXT` or
+/// `NAME,address:ADDRESS`, the lines of the same name making up a single
+/// signature. Empty lines and comments starting with `#` are ignored.
+pub fn parse_pools(text: &str) -> Result<Vec<PoolSignature>> {
+    let mut pools: Vec<PoolSignature> = vec![];
+
+    for line in text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+    {
+        let error = || StatisticsError::PoolSignatureError(line.to_string());
+        let (name, signature) = line.split_once(',').ok_or_else(error)?;
+        let name = name.trim();
+        let index = match pools.iter().position(|pool| pool.name == name) {
+            Some(index) => index,
+            None => {
+                pools.push(PoolSignature::new(name, &[]));
+                pools.len() - 1
+            }
+        };
+
+        match signature.trim().split_once(':') {
+            Some(("tag", tag)) if !tag.is_empty() => pools[index].tags.push(tag.to_lowercase()),
+            Some(("address", address)) => {
+                let address = Address::from_str(address.trim()).map_err(|_| error())?;
+                pools[index].payouts.push(address.script_pubkey());
+            }
+            _ => return Err(error()),
+        }
+    }
+
+    Ok(pools)
+}
+
+/// Load the pool signatures of a file, see parse_pools for its lines.
+pub fn load_pools(path: &Path) -> Result<Vec<PoolSignature>> {
+    parse_pools(&fs::read_to_string(path)?)
+}
+
+/// Attribute the block to the first pool with a matching signature.
+pub fn get_pool<'a>(block: &Block, pools: &'a [PoolSignature]) -> Option<&'a str> {
+    pools
+        .iter()
+        .find(|pool| pool.matches(block))
+        .map(|pool| pool.name())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::{
+        blockdata::constants::genesis_block, Network, Script, Transaction, TxIn, TxOut,
+    };
+
+    /// Mainnet address of the payout script of a pool.
+    const PAYOUT: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+    fn block_with_coinbase(script_sig: &[u8]) -> Block {
+        let mut block = genesis_block(Network::Bitcoin);
+        block.txdata = vec![Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![TxIn {
+                script_sig: Script::from(script_sig.to_vec()),
+                ..Default::default()
+            }],
+            output: vec![],
+        }];
+        block
+    }
+
+    #[test]
+    fn test_get_pool() {
+        let pools = get_default_pools();
+        let block = block_with_coinbase(b"\x03\x01\x02\x03Mined by AntPool bj");
+        assert_eq!(get_pool(&block, &pools), Some("AntPool"));
+
+        let block = block_with_coinbase(b"\x03\x01\x02\x03/FOUNDRY USA POOL #dropgold/");
+        assert_eq!(get_pool(&block, &pools), Some("Foundry USA"));
+
+        let block = block_with_coinbase(b"\x03\x01\x02\x03");
+        assert_eq!(get_pool(&block, &pools), None);
+    }
+
+    #[test]
+    fn test_parse_pools() {
+        let text = format!(
+            "# Pools of the study\nNew Pool,tag:/NewPool/\n\nNew Pool,address:{}\n",
+            PAYOUT
+        );
+        let pools = parse_pools(&text).unwrap();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].name(), "New Pool");
+
+        let block = block_with_coinbase(b"\x03\x01\x02\x03/newpool/");
+        assert!(pools[0].matches_tag(&block));
+        assert!(!pools[0].matches_payout(&block));
+
+        let mut block = block_with_coinbase(b"\x03\x01\x02\x03");
+        block.txdata[0].output.push(TxOut {
+            value: 625_000_000,
+            script_pubkey: Address::from_str(PAYOUT).unwrap().script_pubkey(),
+        });
+        assert!(!pools[0].matches_tag(&block));
+        assert_eq!(get_pool(&block, &pools), Some("New Pool"));
+
+        assert!(parse_pools("New Pool").is_err());
+        assert!(parse_pools("New Pool,address:unknown").is_err());
+        assert!(parse_pools("New Pool,script:00").is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Verification of the proof of work of fetched headers, confirming the
+//! source of the blocks does not feed bogus headers before the analysis.
+//!
+//! Every header is hashed anew with the double SHA-256 of sha2, which uses
+//! the SHA extensions of the CPU where available, spread over a thread per
+//! core in chunks of headers.
+
+use bitcoincore_rpc::bitcoin::{
+    consensus::{encode::serialize, Params},
+    util::uint::Uint256,
+    BlockHeader, Network,
+};
+use sha2::{Digest, Sha256};
+use std::thread;
+
+use crate::{BlockHeight, Result, StatisticsError};
+
+/// Fewest headers verified by a thread, smaller samples are not worth
+/// spawning threads for.
+const MIN_CHUNK_SIZE: usize = 1024;
+
+/// Verify the proof of work of the headers, failing with the number of
+/// headers whose hash exceeds the target of their bits or whose target is
+/// easier than the proof of work limit of the network.
+pub fn verify_pow(headers: &[(BlockHeight, BlockHeader)], network: Network) -> Result<()> {
+    let invalid = get_invalid_heights(headers, network);
+
+    match invalid.first() {
+        Some(height) => Err(StatisticsError::ProofOfWorkError {
+            count: invalid.len(),
+            height: *height,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Get the heights of the headers failing their proof of work, lowest
+/// first.
+pub fn get_invalid_heights(
+    headers: &[(BlockHeight, BlockHeader)],
+    network: Network,
+) -> Vec<BlockHeight> {
+    let pow_limit = Params::new(network).pow_limit;
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = headers.len().div_ceil(threads).max(MIN_CHUNK_SIZE);
+
+    let mut invalid = thread::scope(|scope| {
+        let workers = headers
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter(|(_, header)| !is_valid_pow(header, &pow_limit))
+                        .map(|(height, _)| *height)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Proof of work verification panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    invalid.sort_unstable();
+    invalid
+}
+
+/// Check whether the double SHA-256 of the header is within the target of
+/// its bits, the target being no easier than the proof of work limit.
+fn is_valid_pow(header: &BlockHeader, pow_limit: &Uint256) -> bool {
+    let target = header.target();
+
+    if target == Uint256::default() || target > *pow_limit {
+        return false;
+    }
+
+    let hash = Sha256::digest(Sha256::digest(serialize(header)));
+    // The hash is a little endian number like the target
+    let mut words = [0u64; 4];
+    for (word, bytes) in words.iter_mut().zip(hash.chunks_exact(8)) {
+        *word = u64::from_le_bytes(bytes.try_into().expect("Chunks of 8 bytes"));
+    }
+
+    Uint256(words) <= target
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::blockdata::constants::genesis_block;
+
+    #[test]
+    fn test_verify_pow() {
+        let genesis = genesis_block(Network::Bitcoin).header;
+        let headers = (0..3000)
+            .map(|height| (BlockHeight(height), genesis))
+            .collect::<Vec<_>>();
+        assert!(verify_pow(&headers, Network::Bitcoin).is_ok());
+
+        let mut headers = headers;
+        // A tampered nonce no longer meets the target
+        headers[2500].1.nonce += 1;
+        // Nor does a target easier than the limit of the network
+        headers[7].1 = genesis_block(Network::Regtest).header;
+        assert_eq!(
+            get_invalid_heights(&headers, Network::Bitcoin),
+            vec![BlockHeight(7), BlockHeight(2500)]
+        );
+        // The easy target is within the limit of regtest
+        assert_eq!(
+            get_invalid_heights(&headers, Network::Regtest),
+            vec![BlockHeight(2500)]
+        );
+
+        let error = verify_pow(&headers, Network::Bitcoin).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "2 headers fail their proof of work, the first at height 7"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::{collections::BTreeMap, fs::File, io::Read, path::Path};
+
+use crate::utils::parse_date;
+use crate::{Result, StatisticsError};
+
+/// Historical BTC/USD prices keyed by the unix time from which they apply.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct PriceFeed {
+    prices: BTreeMap<u32, f64>,
+}
+
+impl PriceFeed {
+    /// Create the feed from the prices keyed by unix time.
+    pub fn new(prices: BTreeMap<u32, f64>) -> Self {
+        PriceFeed { prices }
+    }
+
+    /// Load the feed from a CSV file, see from_reader.
+    pub fn load(path: &Path) -> Result<Self> {
+        PriceFeed::from_reader(File::open(path)?)
+    }
+
+    /// Fetch the feed as CSV from the URL, see from_reader.
+    #[cfg(feature = "price-feed")]
+    pub fn fetch(url: &str) -> Result<Self> {
+        let response = ureq::get(url)
+            .call()
+            .map_err(|e| StatisticsError::PriceFeedError(format!("{}: {}", url, e)))?;
+
+        PriceFeed::from_reader(response.into_reader())
+    }
+
+    /// Read the feed from CSV with a header and a row of the date and the
+    /// price in USD of every day, e.g. `2022-08-01,23300.5`. The date is
+    /// either a day in UTC or a unix timestamp.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let mut prices = BTreeMap::new();
+
+        for record in csv::Reader::from_reader(reader).records() {
+            let record = record?;
+            let field = |i: usize| record.get(i).unwrap_or_default().trim();
+            let invalid = || StatisticsError::PriceFeedError(format!("Invalid row: {:?}", record));
+
+            let time = parse_date(field(0)).ok_or_else(invalid)?;
+            let price = field(1).parse::<f64>().map_err(|_| invalid())?;
+
+            prices.insert(time, price);
+        }
+
+        Ok(PriceFeed { prices })
+    }
+
+    /// Get the latest price at the unix time, none before the first price.
+    pub fn get_price(&self, time: u32) -> Option<f64> {
+        self.prices
+            .range(..=time)
+            .next_back()
+            .map(|(_, price)| *price)
+    }
+
+    /// Check whether the feed has no prices.
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_price_feed() {
+        let csv = "date,price\n2022-08-01,23300.5\n1659398400,22900\n";
+        let feed = PriceFeed::from_reader(csv.as_bytes()).unwrap();
+        assert_eq!(feed.get_price(1659311999), None);
+        assert_eq!(feed.get_price(1659312000), Some(23300.5));
+        assert_eq!(feed.get_price(1659398399), Some(23300.5));
+        assert_eq!(feed.get_price(1700000000), Some(22900.0));
+        assert!(PriceFeed::from_reader("date,price\nyesterday,1\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use indicatif::ProgressBar;
+use tokio::sync::watch;
+
+use crate::BlockHeight;
+
+/// Progress events emitted while collecting sample data.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FetchProgress {
+    /// The current block height is being fetched
+    FetchingHeight,
+    /// The current block height was fetched
+    Height { height: BlockHeight },
+    /// Fetching of the sampled blocks started
+    Started { total: u64 },
+    /// A block was fetched
+    Fetched { height: BlockHeight },
+    /// A block could not be fetched
+    Failed { height: BlockHeight },
+    /// All sampled blocks were processed
+    Finished { fetched: u64 },
+}
+
+/// Receiver of progress events while collecting sample data. Implemented
+/// for closures and watch channels so library consumers can observe
+/// progress without the terminal progress bar.
+pub trait ProgressSink: Send + Sync {
+    /// Report a progress event.
+    fn report(&self, event: FetchProgress);
+}
+
+/// Report progress on the terminal using a progress bar.
+#[derive(Debug)]
+pub struct ProgressBarSink {
+    progress_bar: ProgressBar,
+}
+
+/// Discard all progress events.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NoProgress;
+
+impl ProgressBarSink {
+    /// Create a new terminal progress bar sink.
+    pub fn new() -> Self {
+        ProgressBarSink {
+            progress_bar: ProgressBar::new_spinner(),
+        }
+    }
+}
+
+impl Default for ProgressBarSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressSink for ProgressBarSink {
+    fn report(&self, event: FetchProgress) {
+        match event {
+            FetchProgress::FetchingHeight => self
+                .progress_bar
+                .println("Fetching current block height..."),
+            FetchProgress::Height { height } => self
+                .progress_bar
+                .println(format!("Success! Block height: {}", height)),
+            FetchProgress::Started { total } => {
+                // Progressive collections fetch in several rounds
+                self.progress_bar.reset();
+                self.progress_bar.set_length(total);
+                self.progress_bar.set_position(0);
+            }
+            FetchProgress::Fetched { height } => {
+                self.progress_bar
+                    .set_message(format!("Fetched block {}", height));
+                self.progress_bar.inc(1);
+            }
+            FetchProgress::Failed { height } => {
+                self.progress_bar
+                    .println(format!("Error retrieving block {}", height));
+                self.progress_bar.inc(1);
+            }
+            FetchProgress::Finished { fetched } => {
+                self.progress_bar.finish_and_clear();
+                eprintln!("Finished fetching {} blocks.", fetched);
+            }
+        }
+    }
+}
+
+impl ProgressSink for NoProgress {
+    fn report(&self, _event: FetchProgress) {}
+}
+
+impl<F> ProgressSink for F
+where
+    F: Fn(FetchProgress) + Send + Sync,
+{
+    fn report(&self, event: FetchProgress) {
+        self(event)
+    }
+}
+
+impl ProgressSink for watch::Sender<FetchProgress> {
+    fn report(&self, event: FetchProgress) {
+        // Having no receivers left is not an error for the collection
+        let _ = self.send(event);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_closure_sink() {
+        let events = Mutex::new(vec![]);
+        let sink = |event| events.lock().unwrap().push(event);
+        sink.report(FetchProgress::Started { total: 1 });
+        sink.report(FetchProgress::Fetched {
+            height: BlockHeight(7),
+        });
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                FetchProgress::Started { total: 1 },
+                FetchProgress::Fetched {
+                    height: BlockHeight(7),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch_sink() {
+        let (sender, receiver) = watch::channel(FetchProgress::FetchingHeight);
+        sender.report(FetchProgress::Finished { fetched: 3 });
+        assert_eq!(*receiver.borrow(), FetchProgress::Finished { fetched: 3 });
+    }
+}
@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io::ErrorKind, path::Path};
+
+use crate::{BlockHeight, Result};
+
+/// The gap between the timestamps of a parent block and its child.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct BlockGap {
+    pub seconds: i64,
+    pub parent_height: BlockHeight,
+    pub child_height: BlockHeight,
+    pub time: u32,
+    pub difficulty: f64,
+}
+
+/// Record book of the longest and shortest gaps observed over every scan.
+/// The book can be persisted to carry the records across runs.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct RecordBook {
+    longest: Vec<BlockGap>,
+    shortest: Vec<BlockGap>,
+}
+
+impl RecordBook {
+    /// Load the record book from a JSON file, an empty book is returned
+    /// when the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(RecordBook::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save the record book as a JSON file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The longest gaps descending.
+    pub fn longest(&self) -> &[BlockGap] {
+        &self.longest
+    }
+
+    /// The shortest gaps ascending.
+    pub fn shortest(&self) -> &[BlockGap] {
+        &self.shortest
+    }
+
+    /// Record the gap if it ranks within the given number of records.
+    pub fn observe(&mut self, gap: BlockGap, count: usize) {
+        insert_record(&mut self.longest, gap.clone(), count, |a, b| {
+            b.seconds.cmp(&a.seconds)
+        });
+        insert_record(&mut self.shortest, gap, count, |a, b| {
+            a.seconds.cmp(&b.seconds)
+        });
+    }
+}
+
+/// Insert the gap into the ordered records keeping the top count entries.
+fn insert_record<F>(records: &mut Vec<BlockGap>, gap: BlockGap, count: usize, order: F)
+where
+    F: Fn(&BlockGap, &BlockGap) -> std::cmp::Ordering,
+{
+    // A gap already in the book is not a new record
+    if records
+        .iter()
+        .any(|record| record.child_height == gap.child_height)
+    {
+        return;
+    }
+
+    let index = records.partition_point(|record| order(record, &gap).is_le());
+
+    if index < count {
+        records.insert(index, gap);
+        records.truncate(count);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gap(seconds: i64, child_height: u64) -> BlockGap {
+        BlockGap {
+            seconds,
+            parent_height: BlockHeight(child_height - 1),
+            child_height: BlockHeight(child_height),
+            time: 0,
+            difficulty: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_observe() {
+        let mut book = RecordBook::default();
+        [600, 7200, -60, 30, 7200, 86400]
+            .iter()
+            .enumerate()
+            .for_each(|(i, seconds)| book.observe(gap(*seconds, i as u64 + 1), 2));
+        book.observe(gap(86400, 6), 2);
+
+        let longest: Vec<_> = book.longest().iter().map(|g| g.seconds).collect();
+        let shortest: Vec<_> = book.shortest().iter().map(|g| g.seconds).collect();
+        assert_eq!(longest, vec![86400, 7200]);
+        assert_eq!(book.longest()[1].child_height, BlockHeight(2));
+        assert_eq!(shortest, vec![-60, 30]);
+    }
+}
@@ -0,0 +1,1278 @@
+use serde::Serialize;
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use tabled::{object::Rows, Footer, Header, Modify, Table, Tabled};
+
+use crate::l10n::{localize, localize_summary};
+use crate::utils::{get_mean, get_rounded_by};
+use crate::{
+    display_hashrate, AutocorrelationReport, BlockSizeReport, BlockTimeDriftReport,
+    ChainTipsReport, CoinbaseRevenueReport, ConfirmationLatencyReport, ConsolidationsReport,
+    CorrelationReport, CumulativeDriftReport, CustomReport, DifficultyEpochsReport,
+    DifficultyTrendReport, EmptyBlocksReport, EntityClustersReport, EpochDurationsReport,
+    ExamplesReport, FeeElasticityReport, FeeStatsReport, FutureTimestampsReport,
+    HalvingEraSpeedReport, HashrateReport, HistogramBucket, HistoryReport, IndexVerifyReport,
+    MinDifficultyBlocks, MonotonicityStreaksReport, NegativeDriftsReport, OutOfOrderReport,
+    PartialSampleTable, PoolLuckReport, PoolsReport, RecordsReport, ReorgRiskReport, Result,
+    RevenueReport, RollingReport, SignetProducersReport, TaprootAdoptionReport, TimeWarpReport,
+    TransactionsReport, VersionBitsReport, WatchReport, WitnessVersionFlowReport,
+};
+
+/// Characters of the bar of the largest bucket of a histogram.
+const HISTOGRAM_BAR: usize = 50;
+
+/// Report analyzed from a partial sample, marked with the achieved sample.
+#[derive(Serialize)]
+pub struct PartialReport<'a, R> {
+    pub partial: &'a PartialSampleTable,
+    pub report: &'a R,
+}
+
+/// Format the reports are rendered in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    /// Tables for reading on the terminal
+    Table,
+    /// A single line of JSON per report for piping into other tools
+    Json,
+    /// The rows of the report with a header for spreadsheets
+    Csv,
+    /// Markdown tables for pasting into issues and notes
+    Markdown,
+}
+
+/// Destination of the rendered results, stdout unless written to a file.
+/// Progress and diagnostics go to stderr, so stdout only carries results.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Destination {
+    path: Option<PathBuf>,
+    append: bool,
+}
+
+/// Report of an analysis that can be rendered in every output format.
+pub trait Report: fmt::Display + Serialize {
+    /// Write the rows of the report as CSV, the summary statistics are only
+    /// part of the other formats.
+    fn write_csv<W: Write>(&self, writer: W) -> Result<()>;
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" => Ok(OutputFormat::Markdown),
+            _ => Err(format!("Unknown output format: {}", s)),
+        }
+    }
+}
+
+impl Destination {
+    /// Create the destination writing to the file, replacing it with every
+    /// output, or to stdout without a file.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Destination {
+            path,
+            append: false,
+        }
+    }
+
+    /// Append every output to the file instead of replacing it, emptying
+    /// the file first.
+    pub fn with_append(mut self) -> Result<Self> {
+        if let Some(path) = &self.path {
+            File::create(path)?;
+        }
+
+        self.append = true;
+        Ok(self)
+    }
+
+    /// Get the file written to, none for stdout.
+    pub fn get_path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Write the rendered output followed by a new line.
+    pub fn write(&self, rendered: &str) -> Result<()> {
+        match &self.path {
+            Some(path) if self.append => {
+                let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+                writeln!(file, "{}", rendered)?;
+            }
+            Some(path) => fs::write(path, format!("{}\n", rendered))?,
+            None => println!("{}", rendered),
+        }
+
+        Ok(())
+    }
+}
+
+/// Render the report in the format.
+pub fn render<R: Report>(report: &R, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(report.to_string()),
+        OutputFormat::Json => Ok(serde_json::to_string(report)?),
+        OutputFormat::Csv => {
+            let mut csv = vec![];
+            report.write_csv(&mut csv)?;
+            Ok(String::from_utf8_lossy(&csv).into_owned())
+        }
+        OutputFormat::Markdown => Ok(get_markdown(&report.to_string())),
+    }
+}
+
+/// Part of a report rendered as tables, see get_blocks.
+pub(crate) enum Block {
+    /// Lines between tables, e.g. warnings or histograms
+    Text(Vec<String>),
+    Table(RenderedTable),
+}
+
+/// Table parsed back from its rendering, the lines of cells spanning
+/// several joined with newlines.
+pub(crate) struct RenderedTable {
+    pub title: Option<String>,
+    /// Rows of cells, the column names first
+    pub rows: Vec<Vec<String>>,
+    /// Comma separated pairs of a label and a value
+    pub footer: Option<String>,
+}
+
+/// Split a report rendered as tables into its tables and the lines between
+/// them.
+pub(crate) fn get_blocks(rendered: &str) -> Vec<Block> {
+    let is_table = |line: &&str| line.starts_with('+') || line.starts_with('|');
+    let mut blocks = vec![];
+    let mut lines = rendered.lines().peekable();
+
+    while lines.peek().is_some() {
+        let table = lines.clone().take_while(is_table).collect::<Vec<_>>();
+
+        if table.is_empty() {
+            let text = lines
+                .clone()
+                .take_while(|line| !is_table(line))
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            lines.nth(text.len() - 1);
+
+            if text.iter().any(|line| !line.trim().is_empty()) {
+                blocks.push(Block::Text(text));
+            }
+        } else {
+            lines.nth(table.len() - 1);
+            blocks.push(Block::Table(get_rendered_table(&table)));
+        }
+    }
+
+    blocks
+}
+
+/// Parse the lines of a table, a row of a single cell first is the header
+/// and last the footer of the table.
+fn get_rendered_table(lines: &[&str]) -> RenderedTable {
+    let mut rows: Vec<Vec<String>> = vec![];
+
+    for group in lines.split(|line| line.starts_with('+')) {
+        let mut row: Vec<String> = vec![];
+
+        for line in group {
+            let cells = line.trim_matches('|').split('|').map(str::trim);
+
+            for (i, cell) in cells.enumerate() {
+                match row.get_mut(i) {
+                    Some(joined) if !cell.is_empty() => {
+                        joined.push('\n');
+                        joined.push_str(cell);
+                    }
+                    Some(_) => {}
+                    None => row.push(cell.to_string()),
+                }
+            }
+        }
+
+        if !row.is_empty() {
+            rows.push(row);
+        }
+    }
+
+    let title = match rows.first() {
+        Some(row) if rows.len() > 1 && row.len() == 1 => Some(rows.remove(0).remove(0)),
+        _ => None,
+    };
+    let footer = match rows.last() {
+        Some(row) if rows.len() > 1 && row.len() == 1 => rows.pop().map(|mut row| row.remove(0)),
+        _ => None,
+    };
+
+    RenderedTable {
+        title,
+        rows,
+        footer,
+    }
+}
+
+/// Convert a report rendered as tables to markdown. The header of every
+/// table becomes a heading, its rows a markdown table and its footer a list
+/// of the pairs of the summary. Lines between tables, e.g. warnings, are
+/// kept as paragraphs, or as a code block when several to keep them
+/// aligned.
+pub fn get_markdown(rendered: &str) -> String {
+    let sections = get_blocks(rendered).into_iter().map(|block| match block {
+        Block::Text(text) if text.len() == 1 => text[0].clone(),
+        Block::Text(text) => format!("```text\n{}\n```", text.join("\n")),
+        Block::Table(table) => get_markdown_table(&table),
+    });
+
+    sections.collect::<Vec<_>>().join("\n\n")
+}
+
+/// Convert a parsed table to markdown, see get_markdown.
+fn get_markdown_table(table: &RenderedTable) -> String {
+    let mut markdown = vec![];
+
+    if let Some(title) = &table.title {
+        markdown.push(format!("### {}", title));
+    }
+
+    if let Some((header, body)) = table.rows.split_first() {
+        let line = |cells: &[String]| format!("| {} |", cells.join(" | ").replace('\n', "<br>"));
+        let mut lines = vec![line(header), format!("|{}", "---|".repeat(header.len()))];
+        lines.extend(body.iter().map(|row| line(row)));
+        markdown.push(lines.join("\n"));
+    }
+
+    if let Some(footer) = &table.footer {
+        let pairs = footer.split(", ").map(|pair| match pair.split_once(": ") {
+            Some((label, value)) => format!("- **{}:** {}", label, value),
+            None => format!("- {}", pair),
+        });
+        markdown.push(pairs.collect::<Vec<_>>().join("\n"));
+    }
+
+    markdown.join("\n\n")
+}
+
+/// Write the rows as CSV with a header of their field names.
+pub fn write_csv<W: Write, T: Serialize>(writer: W, rows: &[T]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+
+    for row in rows {
+        writer.serialize(row)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Implement Report for reports with their rows as entries.
+macro_rules! impl_report {
+    ($($report:ty),*) => {
+        $(
+            impl Report for $report {
+                fn write_csv<W: Write>(&self, writer: W) -> Result<()> {
+                    write_csv(writer, &self.entries)
+                }
+            }
+        )*
+    };
+}
+
+impl_report!(
+    BlockTimeDriftReport,
+    RecordsReport,
+    MonotonicityStreaksReport,
+    NegativeDriftsReport,
+    OutOfOrderReport,
+    FutureTimestampsReport,
+    ReorgRiskReport,
+    ChainTipsReport,
+    EpochDurationsReport,
+    TimeWarpReport,
+    CumulativeDriftReport,
+    HalvingEraSpeedReport,
+    HistoryReport,
+    IndexVerifyReport,
+    ExamplesReport,
+    DifficultyEpochsReport,
+    VersionBitsReport,
+    DifficultyTrendReport,
+    RollingReport,
+    HashrateReport,
+    AutocorrelationReport,
+    SignetProducersReport,
+    CorrelationReport,
+    PoolLuckReport,
+    PoolsReport,
+    EmptyBlocksReport,
+    WitnessVersionFlowReport,
+    EntityClustersReport,
+    CustomReport,
+    FeeElasticityReport,
+    FeeStatsReport,
+    ConsolidationsReport,
+    RevenueReport,
+    BlockSizeReport,
+    TransactionsReport,
+    TaprootAdoptionReport,
+    CoinbaseRevenueReport,
+    ConfirmationLatencyReport
+);
+
+impl Report for WatchReport {
+    /// Write the arrivals, a single row per report.
+    fn write_csv<W: Write>(&self, writer: W) -> Result<()> {
+        write_csv(writer, &[&self.arrivals])
+    }
+}
+
+impl Report for String {
+    /// Write the report of a plugin as is.
+    fn write_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        Ok(writer.write_all(self.as_bytes())?)
+    }
+}
+
+impl<R: Report> Report for PartialReport<'_, R> {
+    /// Write the rows of the report, the partial sample is only part of
+    /// the other formats.
+    fn write_csv<W: Write>(&self, writer: W) -> Result<()> {
+        self.report.write_csv(writer)
+    }
+}
+
+impl<R: Report> fmt::Display for PartialReport<'_, R> {
+    /// Render the achieved sample ahead of the report.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table([self.partial])
+            .with(Header(localize("Partial Sample")))
+            .with(Footer(localize(
+                "The collection stopped early, the report covers the sampled blocks",
+            )));
+
+        write!(f, "{}\n{}", table, self.report)
+    }
+}
+
+impl fmt::Display for BlockTimeDriftReport {
+    /// Render the drifts as a table with the summary statistics as footer.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut footer = format!(
+            "Occurrences: {}, Mean: {} minutes, Mean Confidence Interval: {} - {} minutes, Standard Deviation: {}, Poisson Probability: 1 / {} hours",
+            self.entries.len(),
+            get_rounded_by(self.mean, 2),
+            get_rounded_by(self.mean_low, 2),
+            get_rounded_by(self.mean_high, 2),
+            self.std_deviation,
+            get_rounded_by(self.poisson_probability, 2)
+        );
+
+        if let Some(blocks) = self.min_difficulty_blocks {
+            let action = match self.min_difficulty {
+                MinDifficultyBlocks::Filter => "Filtered",
+                _ => "Tagged",
+            };
+            footer.push_str(&format!(", Min Difficulty Blocks {}: {}", action, blocks));
+        }
+
+        if let Some(fit) = &self.exponential_fit {
+            footer.push_str(&format!(
+                ", Chi-Square: {} ({} df, p-value: {})",
+                get_rounded_by(fit.statistic, 2),
+                fit.degrees,
+                get_rounded_by(fit.p_value, 4)
+            ));
+        }
+
+        if let Some(test) = &self.exponential_ks {
+            footer.push_str(&format!(
+                ", Kolmogorov-Smirnov D: {} (p-value: {})",
+                get_rounded_by(test.statistic, 4),
+                get_rounded_by(test.p_value, 4)
+            ));
+        }
+
+        let table = new_table(&self.entries)
+            .with(Header(localize("Block Times")))
+            .with(Footer(localize_summary(&footer)));
+
+        let target_table =
+            new_table(&self.within_target).with(Header(localize("Mining Times Within Target")));
+        let percentile_table =
+            new_table(&self.percentiles).with(Header(localize("Mining Time Percentiles")));
+
+        write!(f, "{}\n{}\n{}", table, target_table, percentile_table)?;
+
+        if let Some(error) = self.stratified_error {
+            let strata_table = new_table(&self.strata)
+                .with(Header(localize("Mining Times by Stratum")))
+                .with(Footer(localize_summary(&format!(
+                    "Strata: {}, Pooled Mean: {} minutes, Standard Error: {} minutes",
+                    self.strata.len(),
+                    get_rounded_by(self.mean, 2),
+                    get_rounded_by(error, 2)
+                ))));
+            write!(f, "\n{}", strata_table)?;
+        }
+
+        write_warnings(f, &self.warnings)?;
+
+        if !self.histogram.is_empty() {
+            write_histogram(f, &self.histogram)?;
+        }
+
+        if self.windows.is_empty() {
+            return Ok(());
+        }
+
+        let spans = self.windows.iter().map(|row| row.span as f64);
+        let variances = self.windows.iter().map(|row| row.variance);
+        let max_gaps = self.windows.iter().map(|row| row.max_gap as f64);
+        let window_table = new_table(&self.windows)
+            .with(Header(localize("Windows")))
+            .with(Footer(localize_summary(&format!(
+                "Windows: {}, Mean Span: {} minutes, Mean Variance: {}, Mean Max Gap: {} minutes",
+                self.windows.len(),
+                get_rounded_by(get_mean(&spans.collect::<Vec<_>>()) / 60.0, 2),
+                get_rounded_by(get_mean(&variances.collect::<Vec<_>>()), 2),
+                get_rounded_by(get_mean(&max_gaps.collect::<Vec<_>>()), 2)
+            ))));
+
+        write!(f, "\n{}", window_table)
+    }
+}
+
+/// Create the table of the rows with their column headers localized, see
+/// l10n.
+fn new_table<T: Tabled>(rows: impl IntoIterator<Item = T>) -> Table {
+    Table::new(rows).with(Modify::new(Rows::first()).with(|header: &str| localize(header)))
+}
+
+/// Write the warnings of the unstable estimates of a report, one per line.
+fn write_warnings(f: &mut fmt::Formatter, warnings: &[String]) -> fmt::Result {
+    let label = localize("Warning");
+    warnings
+        .iter()
+        .try_for_each(|warning| write!(f, "\n{}: {}", label, warning))
+}
+
+/// Write the histogram of mining times as bars of hashes scaled to the
+/// largest bucket, along with the count expected of exponential mining times.
+fn write_histogram(f: &mut fmt::Formatter, buckets: &[HistogramBucket]) -> fmt::Result {
+    let max = buckets
+        .iter()
+        .map(|bucket| bucket.count)
+        .max()
+        .unwrap_or_default();
+    write!(f, "\n{}", localize("Mining Time Distribution (minutes)"))?;
+    let expected = localize("expected");
+
+    for bucket in buckets {
+        let range = match bucket.end {
+            Some(end) => format!("{} - {}", bucket.start, end),
+            None => format!("{}+", bucket.start),
+        };
+        let bar = "#".repeat(bucket.count * HISTOGRAM_BAR / max.max(1));
+
+        write!(
+            f,
+            "\n{:>13} | {:<width$} {} ({} {})",
+            range,
+            bar,
+            bucket.count,
+            bucket.expected,
+            expected,
+            width = HISTOGRAM_BAR
+        )?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for RecordsReport {
+    /// Render the records as a table with the gaps scanned as footer.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Block Time Records")))
+            .with(Footer(localize_summary(&format!(
+                "Gaps Scanned: {}",
+                self.gaps
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for MonotonicityStreaksReport {
+    /// Render the backward jumps by period followed by the longest streaks.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let jump_table = new_table(&self.entries)
+            .with(Header(localize("Backward Timestamp Jumps")))
+            .with(Footer(localize_summary(&format!(
+                "Pairs: {}, Backward Jumps: {}, Equal Timestamps: {}",
+                self.pairs, self.backward_jumps, self.equal_timestamps
+            ))));
+
+        let streak_table = new_table(&self.streaks)
+            .with(Header(localize("Monotonicity Streaks")))
+            .with(Footer(localize_summary(&format!(
+                "Mean Streak: {} blocks",
+                get_rounded_by(self.mean_streak, 2)
+            ))));
+
+        write!(f, "{}\n{}", jump_table, streak_table)
+    }
+}
+
+impl fmt::Display for NegativeDriftsReport {
+    /// Render the negative drifts as a table with their frequency and
+    /// magnitude as footer.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (low, high) = self.frequency_interval;
+        let table = new_table(&self.entries)
+            .with(Header(localize("Negative Drifts")))
+            .with(Footer(localize_summary(&format!(
+                "Pairs: {}, Negative Drifts: {}, Frequency: {:.2}% ({:.2}% - {:.2}%), Mean: {} s, Median: {} s, Max: {} s",
+                self.pairs,
+                self.entries.len(),
+                self.frequency * 100.0,
+                low * 100.0,
+                high * 100.0,
+                self.mean_magnitude,
+                self.median_magnitude,
+                self.max_magnitude
+            ))));
+
+        write!(f, "{}", table)?;
+        write_warnings(f, &self.warnings)
+    }
+}
+
+impl fmt::Display for OutOfOrderReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (low, high) = self.frequency_interval;
+        let table = new_table(&self.entries)
+            .with(Header(localize("Out of Order Timestamps")))
+            .with(Footer(localize_summary(&format!(
+                "Pairs: {}, Out of Order: {}, Frequency: {:.2}% ({:.2}% - {:.2}%)",
+                self.pairs,
+                self.out_of_order,
+                self.frequency * 100.0,
+                low * 100.0,
+                high * 100.0
+            ))));
+
+        write!(f, "{}", table)?;
+        write_warnings(f, &self.warnings)
+    }
+}
+
+impl fmt::Display for FutureTimestampsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Future Timestamps")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Mean Margin: {} s, Median Margin: {} s, Near: {} s, Near MTP: {}, Near Future Limit: {}",
+                self.blocks,
+                self.mean_margin,
+                self.median_margin,
+                self.near,
+                self.near_past,
+                self.near_future
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for ReorgRiskReport {
+    /// Render the reversal probabilities as a table with the mining times
+    /// and stale rate they were estimated from as footer.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Reorg Risk")))
+            .with(Footer(localize_summary(&format!(
+                "Intervals: {}, Mean: {} s, Dispersion: {}, Stale Rate: {:.4}%, Delay: {} s, Race: {:.4}%",
+                self.intervals,
+                self.mean_interval,
+                self.dispersion,
+                self.stale_rate * 100.0,
+                self.delay,
+                self.race_probability * 100.0
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for ChainTipsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let footer = format!(
+            "Height: {}, Stale: {} ({:.4}%), Recent Blocks: {}, Recent Stale: {} ({:.4}%)",
+            self.height,
+            self.stale,
+            self.stale_rate * 100.0,
+            self.recent,
+            self.recent_stale,
+            self.recent_stale_rate * 100.0
+        );
+        let table = new_table(&self.entries)
+            .with(Header(localize("Chain Tips")))
+            .with(Footer(localize_summary(&footer)));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for HistoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Run History")))
+            .with(Footer(localize_summary(&format!(
+                "Runs: {}",
+                self.entries.len()
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for IndexVerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Header Cache")))
+            .with(Footer(localize_summary(&format!(
+                "Headers: {}, Fetched: {}, Remaining Damage: {}",
+                self.headers, self.fetched, self.remaining
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for ExamplesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Example Studies")))
+            .with(Footer(localize_summary(&format!(
+                "Examples: {}",
+                self.entries.len()
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for EpochDurationsReport {
+    /// Render the epochs as a table with the distribution of their
+    /// durations as footer.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Epoch Durations")))
+            .with(Footer(localize_summary(&format!(
+                "Epochs: {}, Mean: {} days, Median: {} days, Standard Deviation: {} days, Within 14 Days: {:.2}%",
+                self.entries.len(),
+                self.mean_duration,
+                self.median_duration,
+                self.std_deviation,
+                self.faster * 100.0
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for TimeWarpReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Time Warp")))
+            .with(Footer(localize_summary(&format!(
+                "Epochs: {}, Flagged: {}, Maximum Gap: {} seconds, Influence: {:+.4}%",
+                self.entries.len(),
+                self.flagged,
+                self.max_gap,
+                self.influence * 100.0
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for CumulativeDriftReport {
+    /// Render the checkpoints as a table with the drift of the latest block
+    /// as footer.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Cumulative Drift")))
+            .with(Footer(localize_summary(&format!(
+                "Checkpoints: {}, Ahead: {} days ({} blocks), Mean Interval: {} m, Drift Rate: {} days per year",
+                self.entries.len(),
+                self.ahead,
+                self.blocks_ahead,
+                self.mean_interval,
+                self.drift_rate
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for HalvingEraSpeedReport {
+    /// Render the eras side by side as a table with the overall speed as
+    /// footer.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Halving Era Speed")))
+            .with(Footer(localize_summary(&format!(
+                "Eras: {}, Sampled Intervals: {}, Mean Interval: {} m, Significant Changes: {}",
+                self.entries.len(),
+                self.intervals,
+                self.mean_interval,
+                self.significant
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for DifficultyEpochsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tracking_error = match self.tracking_error {
+            Some(error) => format!("{:.2}%", error * 100.0),
+            None => String::from("None"),
+        };
+
+        let table = new_table(&self.entries)
+            .with(Header(localize("Difficulty Epochs")))
+            .with(Footer(localize_summary(&format!(
+                "Epochs: {}, Intervals: {}, Mean Tracking Error: {}",
+                self.entries.len(),
+                self.intervals,
+                tracking_error
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for RollingReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(format!(
+                "Rolling {} over {} Blocks",
+                self.metric, self.blocks
+            )))
+            .with(Footer(localize_summary(&format!(
+                "Points: {}",
+                self.entries.len()
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for HashrateReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(format!("Hashrate over {} Blocks", self.blocks)))
+            .with(Footer(localize_summary(&format!(
+                "Estimates: {}, Mean: {} minutes, Standard Deviation: {}, Sample Hashrate: {}",
+                self.entries.len(),
+                get_rounded_by(self.mean, 2),
+                self.std_deviation,
+                display_hashrate(&self.hashrate)
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for AutocorrelationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Autocorrelation of Mining Times")))
+            .with(Footer(localize_summary(&format!(
+                "Intervals: {}, Mean: {} minutes, Box-Pierce Q: {} ({} df, p-value: {})",
+                self.intervals,
+                get_rounded_by(self.mean, 2),
+                get_rounded_by(self.box_pierce.statistic, 2),
+                self.box_pierce.degrees,
+                get_rounded_by(self.box_pierce.p_value, 4)
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for SignetProducersReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Signet Producers")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Challenge Signers: {}",
+                self.blocks, self.signers
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for CorrelationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Block Size vs Mining Time")))
+            .with(Footer(localize_summary(&format!(
+                "Pairs: {}, Mean Size: {} bytes, Mean Weight: {} WU",
+                self.pairs,
+                get_rounded_by(self.mean_size, 2),
+                get_rounded_by(self.mean_weight, 2)
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for PoolLuckReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Pool Luck")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Z-Score: {}, Pools with Expected Share: {}",
+                self.blocks, self.z_score, self.expected_pools
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for PoolsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Mining Pools")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Attributed: {}, Z-Score: {}",
+                self.blocks, self.attributed, self.z_score
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for EmptyBlocksReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut footer = format!(
+            "Blocks: {}, Empty: {} ({:.2}%), Z-Score: {}",
+            self.blocks,
+            self.empty,
+            self.rate * 100.0,
+            self.z_score
+        );
+
+        if let Some(mean) = self.mean_empty_time {
+            footer.push_str(&format!(", Empty Mean: {} minutes", mean));
+        }
+
+        if let Some(mean) = self.mean_time {
+            footer.push_str(&format!(", Other Mean: {} minutes", mean));
+        }
+
+        let table = new_table(&self.entries)
+            .with(Header(localize("Empty Blocks")))
+            .with(Footer(localize_summary(&footer)));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for WitnessVersionFlowReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let net_flows = self
+            .net_flows
+            .iter()
+            .map(|(script_type, net)| format!("Net {}: {:+}", script_type, net))
+            .collect::<Vec<_>>();
+
+        let table = new_table(&self.entries)
+            .with(Header(localize("Witness Version Flow")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, {}",
+                self.blocks,
+                net_flows.join(", ")
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for EntityClustersReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut footer = format!(
+            "Scripts: {}, Clusters: {}, Largest Cluster: {}, Transactions: {}, CoinJoins Skipped: {}, Inputs Unattributed: {}",
+            self.scripts,
+            self.clusters,
+            self.largest_cluster,
+            self.transactions,
+            self.coinjoins,
+            self.unattributed
+        );
+
+        if let Some(max_scripts) = self.script_limit {
+            footer.push_str(&format!(", Script Limit Reached: {}", max_scripts));
+        }
+
+        let table = new_table(&self.entries)
+            .with(Header(localize("Entity Clusters (Heuristic)")))
+            .with(Footer(localize_summary(&footer)));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for CustomReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Custom Expression")))
+            .with(Footer(localize_summary(&format!(
+                "Evaluated: {}, Values: {}, Skipped: {}",
+                self.evaluated,
+                self.values,
+                self.evaluated - self.values
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for FeeElasticityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Fee Elasticity")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Elasticity: {} (p-value: {}), R²: {}",
+                self.blocks,
+                get_rounded_by(self.elasticity, 4),
+                get_rounded_by(self.p_value, 4),
+                get_rounded_by(self.r_squared, 4)
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for FeeStatsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Fee Statistics")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Total Fees: {} BTC, Z-Score: {}",
+                self.blocks,
+                get_rounded_by(self.total_fees, 8),
+                self.z_score
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for ConsolidationsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Consolidation Waves")))
+            .with(Footer(localize_summary(&format!(
+                "Periods: {}, Mean Ratio: {}, Standard Deviation: {}, Median Fee Rate: {} sat/vB",
+                self.periods,
+                get_rounded_by(self.mean_ratio, 2),
+                self.std_deviation,
+                get_rounded_by(self.median_fee_rate, 2)
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for RevenueReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut footer = format!(
+            "Blocks: {}, Subsidy: {} BTC, Fees: {} BTC",
+            self.blocks, self.total_subsidy, self.total_fees
+        );
+
+        if let Some(usd) = self.total_usd {
+            footer.push_str(&format!(
+                ", Revenue: ${:.2}, Blocks without a Price: {}",
+                usd, self.unpriced
+            ));
+        }
+
+        let table = new_table(&self.entries)
+            .with(Header(localize("Miner Revenue")))
+            .with(Footer(localize_summary(&footer)));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for BlockSizeReport {
+    /// Render the distribution of the metrics and the trend by period as
+    /// consecutive tables.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let metrics_table = new_table(&self.metrics)
+            .with(Header(localize("Block Size")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Full Blocks: {}, Z-Score: {}",
+                self.blocks, self.full_blocks, self.z_score
+            ))));
+        let mut footer = format!("Periods: {}", self.entries.len());
+
+        if let Some(trend) = self.trend {
+            footer.push_str(&format!(
+                ", Fullness Trend: {:+.2}% per Period",
+                trend * 100.0
+            ));
+        }
+
+        let trend_table = new_table(&self.entries)
+            .with(Header(localize("Block Size by Period")))
+            .with(Footer(localize_summary(&footer)));
+
+        write!(f, "{}\n{}", metrics_table, trend_table)
+    }
+}
+
+impl fmt::Display for TransactionsReport {
+    /// Render the distribution of the transactions per block and their
+    /// correlation with the mining time as consecutive tables.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Transactions per Block")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Mean: {} ({} - {}), Standard Deviation: {}, Z-Score: {}",
+                self.blocks,
+                self.mean,
+                self.mean_low,
+                self.mean_high,
+                self.std_deviation,
+                self.z_score
+            ))));
+        let correlation_table = new_table(&self.correlation)
+            .with(Header(localize("Transactions vs Mining Time")))
+            .with(Footer(localize_summary(&format!("Pairs: {}", self.pairs))));
+
+        write!(f, "{}\n{}", table, correlation_table)
+    }
+}
+
+impl fmt::Display for TaprootAdoptionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Taproot Adoption")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Excluded before Activation: {}, P2TR Outputs: {} ({:.2}%), Key Path Spends: {} ({:.2}%), Script Path Spends: {}",
+                self.blocks,
+                self.excluded,
+                self.outputs,
+                self.output_share * 100.0,
+                self.key_path,
+                self.key_path_share * 100.0,
+                self.script_path
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for CoinbaseRevenueReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Coinbase Revenue by Halving Era")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Subsidy: {} BTC, Fees: {} BTC, Fee Share: {:.2}%, Underclaimed Blocks: {}",
+                self.blocks,
+                self.total_subsidy,
+                self.total_fees,
+                self.fee_share * 100.0,
+                self.underclaimed
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for DifficultyTrendReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut footer = format!(
+            "Blocks: {}, Epochs: {}, Retargets: {}",
+            self.blocks,
+            self.entries.len(),
+            self.retargets
+        );
+
+        if let Some(growth) = self.growth {
+            footer.push_str(&format!(", Growth: {:+.2}% per Epoch", growth * 100.0));
+        }
+
+        let table = new_table(&self.entries)
+            .with(Header(localize("Difficulty Trend")))
+            .with(Footer(localize_summary(&footer)));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for VersionBitsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Version Bits Signaling")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Version Bits Blocks: {}, Threshold: {:.2}%, Z-Score: {}",
+                self.blocks,
+                self.versionbits_blocks,
+                self.threshold * 100.0,
+                self.z_score
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for WatchReport {
+    /// Render the arrivals, the time to confirmation and the conflicting
+    /// spends as consecutive tables.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arrival_table = new_table([&self.arrivals])
+            .with(Header(localize("Transaction Arrivals")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Confirmed Unseen: {}",
+                self.blocks, self.unseen
+            ))));
+
+        let confirmation_table = new_table(&self.confirmation_times)
+            .with(Header(localize("Time to Confirmation")))
+            .with(Footer(localize_summary(&format!(
+                "Mean: {} s",
+                get_rounded_by(self.mean_confirmation_time, 2)
+            ))));
+
+        let conflict_table =
+            new_table(&self.conflicts).with(Header(localize("Conflicting Spends")));
+
+        write!(
+            f,
+            "{}\n{}\n{}",
+            arrival_table, confirmation_table, conflict_table
+        )
+    }
+}
+
+impl fmt::Display for ConfirmationLatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = new_table(&self.entries)
+            .with(Header(localize("Confirmation Latency by Fee Rate")))
+            .with(Footer(localize_summary(&format!(
+                "Blocks: {}, Confirmed: {}, Unknown Fee Rate: {}",
+                self.blocks, self.confirmed, self.unknown
+            ))));
+
+        write!(f, "{}", table)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BlockTimeDriftTable;
+
+    #[test]
+    fn test_render() {
+        let report = RecordsReport {
+            entries: vec![],
+            gaps: 3,
+        };
+        assert_eq!(
+            render(&report, OutputFormat::Json).unwrap(),
+            r#"{"entries":[],"gaps":3}"#
+        );
+        assert!(render(&report, OutputFormat::Table)
+            .unwrap()
+            .contains("Gaps Scanned: 3"));
+        assert!("yaml".parse::<OutputFormat>().is_err());
+        assert_eq!(render(&report, OutputFormat::Markdown).unwrap(), "### Block Time Records\n\n| Record | Gap | Parent Height | Child Height | Date (UTC) | Difficulty |\n|---|---|---|---|---|---|\n\n- **Gaps Scanned:** 3");
+    }
+
+    #[test]
+    fn test_get_markdown() {
+        let rendered = "+---+-----+\n| Title   |\n+---+-----+\n| A | B   |\n+---+-----+\n| 1 | y   |\n|   | z   |\n+---+-----+\n| Mean: 1 s, Done |\n+---+-----+\nWarning: unstable";
+
+        assert_eq!(
+            get_markdown(rendered),
+            "### Title\n\n| A | B |\n|---|---|\n| 1 | y<br>z |\n\n- **Mean:** 1 s\n- Done\n\nWarning: unstable"
+        );
+        assert_eq!(get_markdown("a\nb"), "```text\na\nb\n```");
+    }
+
+    #[test]
+    fn test_destination() {
+        let path = std::env::temp_dir().join(format!("output-{}.txt", std::process::id()));
+        fs::write(&path, "stale").unwrap();
+
+        let destination = Destination::new(Some(path.clone()));
+        destination.write("first").unwrap();
+        destination.write("second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+
+        let destination = destination.with_append().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        destination.write("first").unwrap();
+        destination.write("second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        assert_eq!(destination.get_path(), Some(path.as_path()));
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Destination::default().get_path(), None);
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let report = BlockTimeDriftReport {
+            entries: vec![BlockTimeDriftTable::new(
+                40,
+                Default::default(),
+                Default::default(),
+                false,
+            )],
+            mean: 10.0,
+            mean_low: 9.0,
+            mean_high: 11.0,
+            std_deviation: 1.0,
+            poisson_probability: 2.0,
+            within_target: vec![],
+            percentiles: vec![],
+            windows: vec![],
+            min_difficulty: MinDifficultyBlocks::Tag,
+            min_difficulty_blocks: None,
+            histogram: vec![],
+            exponential_fit: None,
+            exponential_ks: None,
+            strata: vec![],
+            stratified_error: None,
+            deltas: vec![],
+            warnings: vec![],
+        };
+        assert_eq!(
+            render(&report, OutputFormat::Csv).unwrap(),
+            format!(
+                "drift,parent_hash,child_hash,min_difficulty\n40,{0},{0},false\n",
+                "0".repeat(64)
+            )
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Backend fetching blocks from the unauthenticated REST interface of
+//! bitcoind, enabled with `-rest`, instead of its RPC.
+//!
+//! Like the Electrum backend, the backend answers the calls of bitcoind
+//! made by the RPC client. The block hashes of a batch are resolved by
+//! fetching the headers of every run of contiguous heights with a single
+//! request of up to 2000 headers, so full population scans need a fraction
+//! of the requests of the RPC. Full blocks are fetched in binary, block
+//! statistics are not served.
+//!
+//! See https://github.com/bitcoin/bitcoin/blob/master/doc/REST-interface.md
+
+use bitcoincore_rpc::{
+    bitcoin::{
+        consensus::encode::{deserialize, serialize_hex},
+        hashes::hex::ToHex,
+        BlockHash, BlockHeader,
+    },
+    jsonrpc::client::Transport,
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, fmt, io};
+
+use crate::electrum::HeaderIndex;
+use crate::transport::{
+    get_rpc_error, Answer, Answered, HttpTransport, RPC_INVALID_ADDRESS_OR_KEY,
+    RPC_INVALID_PARAMETER, RPC_METHOD_NOT_FOUND,
+};
+
+/// Most headers returned by a single request of the REST interface.
+pub const MAX_HEADERS: usize = 2000;
+/// Bytes of a serialized block header.
+const HEADER_SIZE: usize = 80;
+
+/// Backend answering the RPC calls of bitcoind with its REST interface.
+pub struct RestBackend {
+    http: HttpTransport,
+    index: HeaderIndex,
+}
+
+impl RestBackend {
+    /// Create a new backend over the HTTP transport of the endpoint of
+    /// bitcoind, caching the fetched headers to the index.
+    pub fn new(http: HttpTransport, index: HeaderIndex) -> Self {
+        RestBackend { http, index }
+    }
+
+    /// Answer a call other than getblockhash.
+    fn call(&self, method: &str, params: &[Value]) -> io::Result<Answered> {
+        let hash = params.first().and_then(Value::as_str).unwrap_or_default();
+
+        match method {
+            "getblockcount" => Ok(self
+                .get("/rest/chaininfo.json")?
+                .and_then(|info| parse_json(&info))
+                .map(|info| info["blocks"].clone())),
+            "getblockheader" => match params.get(1).and_then(Value::as_bool).unwrap_or(true) {
+                true => Ok(self
+                    .get(&format!("/rest/headers/1/{}.json", hash))?
+                    .and_then(|headers| parse_json(&headers))
+                    .map(|headers| headers[0].clone())),
+                false => {
+                    let cached = hash
+                        .parse::<BlockHash>()
+                        .ok()
+                        .and_then(|hash| self.lock_index().get(&hash).copied());
+
+                    match cached {
+                        Some((_, header)) => Ok(Ok(json!(serialize_hex(&header)))),
+                        None => Ok(self
+                            .get(&format!("/rest/headers/1/{}.bin", hash))?
+                            .map(|header| json!(header.to_hex()))),
+                    }
+                }
+            },
+            // Only raw blocks are served
+            "getblock" if params.get(1).and_then(Value::as_u64) == Some(0) => Ok(self
+                .get(&format!("/rest/block/{}.bin", hash))?
+                .map(|block| json!(block.to_hex()))),
+            method => Ok(Err(get_rpc_error(
+                RPC_METHOD_NOT_FOUND,
+                format!("{} is not served by the REST interface", method),
+            ))),
+        }
+    }
+
+    /// Get the hashes of the heights, fetching the headers of every run of
+    /// contiguous heights from the hash of its first height. Heights beyond
+    /// the tip are left out.
+    fn get_hashes(&self, heights: &[u64]) -> io::Result<HashMap<u64, BlockHash>> {
+        let mut hashes = HashMap::new();
+        let mut runs: Vec<(u64, usize)> = vec![];
+
+        for height in heights {
+            match runs.last_mut() {
+                Some((start, count))
+                    if *start + *count as u64 == *height && *count < MAX_HEADERS =>
+                {
+                    *count += 1;
+                }
+                _ => runs.push((*height, 1)),
+            }
+        }
+
+        for (start, count) in runs {
+            let first = match self.get(&format!("/rest/blockhashbyheight/{}.hex", start))? {
+                Ok(hash) => String::from_utf8_lossy(&hash).trim().to_string(),
+                Err(_) => continue,
+            };
+            let headers = match self.get(&format!("/rest/headers/{}/{}.bin", count, first))? {
+                Ok(headers) => headers,
+                Err(_) => continue,
+            };
+
+            let mut index = self.lock_index();
+            for (i, bytes) in headers.chunks_exact(HEADER_SIZE).enumerate() {
+                let header = deserialize::<BlockHeader>(bytes)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                let height = start + i as u64;
+                hashes.insert(height, header.block_hash());
+                index.insert(header.block_hash(), (height, header));
+            }
+        }
+
+        Ok(hashes)
+    }
+
+    /// Get the resource at the path. An unknown block is an error of the
+    /// RPC while other failures are errors of the transport to be retried,
+    /// e.g. bitcoind still warming up or started without `-rest`.
+    fn get(&self, path: &str) -> io::Result<Answered<Vec<u8>>> {
+        match self.http.get(path)? {
+            (200, body) => Ok(Ok(body)),
+            (400 | 404, body) => Ok(Err(get_rpc_error(
+                RPC_INVALID_ADDRESS_OR_KEY,
+                String::from_utf8_lossy(&body).trim(),
+            ))),
+            (status, _) => Err(io::Error::other(format!(
+                "HTTP error {} from the REST interface at {}",
+                status, path
+            ))),
+        }
+    }
+
+    fn lock_index(&self) -> std::sync::MutexGuard<'_, HashMap<BlockHash, (u64, BlockHeader)>> {
+        self.index.lock().expect("Header index is poisoned")
+    }
+}
+
+impl Answer for RestBackend {
+    fn answer(&self, method: &str, params: &[Value]) -> io::Result<Answered> {
+        Ok(self.answer_batch(&[(method, params.to_vec())])?.remove(0))
+    }
+
+    /// Answer the calls, fetching the hashes of all the heights of the
+    /// batch in runs of contiguous heights first.
+    fn answer_batch(&self, calls: &[(&str, Vec<Value>)]) -> io::Result<Vec<Answered>> {
+        let mut heights = calls
+            .iter()
+            .filter(|(method, _)| *method == "getblockhash")
+            .filter_map(|(_, params)| params.first().and_then(Value::as_u64))
+            .collect::<Vec<_>>();
+        heights.sort_unstable();
+        heights.dedup();
+        let hashes = self.get_hashes(&heights)?;
+
+        calls
+            .iter()
+            .map(|(method, params)| match *method {
+                "getblockhash" => Ok(params
+                    .first()
+                    .and_then(Value::as_u64)
+                    .and_then(|height| hashes.get(&height))
+                    .map(|hash| json!(hash))
+                    .ok_or_else(|| {
+                        get_rpc_error(RPC_INVALID_PARAMETER, "Block height out of range")
+                    })),
+                method => self.call(method, params),
+            })
+            .collect()
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "REST ")?;
+        self.http.fmt_target(f)
+    }
+}
+
+/// Parse a JSON body of the REST interface.
+fn parse_json(body: &[u8]) -> Answered {
+    serde_json::from_slice(body)
+        .map_err(|e| get_rpc_error(RPC_INVALID_ADDRESS_OR_KEY, e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transport::{get_block_headers, AnswerTransport};
+    use crate::BlockHeight;
+    use bitcoincore_rpc::{
+        bitcoin::{blockdata::constants::genesis_block, consensus::serialize, Network},
+        jsonrpc, Auth, Client, RpcApi,
+    };
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    /// Serve a chain of the genesis block and two children, recording the
+    /// requested paths.
+    fn serve(listener: TcpListener, paths: Arc<Mutex<Vec<String>>>) {
+        let genesis = genesis_block(Network::Bitcoin).header;
+        let mut chain = vec![genesis];
+        for nonce in 1..3 {
+            let parent = chain[chain.len() - 1];
+            chain.push(BlockHeader {
+                prev_blockhash: parent.block_hash(),
+                nonce,
+                ..parent
+            });
+        }
+
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let path = line.split(' ').nth(1).unwrap_or_default().to_string();
+            paths.lock().unwrap().push(path.clone());
+
+            let (status, body) = match path.as_str() {
+                "/rest/chaininfo.json" => (200, br#"{"blocks":2}"#.to_vec()),
+                "/rest/blockhashbyheight/0.hex" => {
+                    (200, genesis.block_hash().to_string().into_bytes())
+                }
+                path if path.starts_with("/rest/headers/3/") => {
+                    (200, chain.iter().flat_map(serialize).collect())
+                }
+                _ => (404, b"Not found".to_vec()),
+            };
+
+            write!(
+                stream,
+                "HTTP/1.1 {} OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rest_transport() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let paths = Arc::new(Mutex::new(vec![]));
+        let served = Arc::clone(&paths);
+        thread::spawn(move || serve(listener, served));
+
+        let http = HttpTransport::new(endpoint.parse().unwrap(), Auth::None).unwrap();
+        let transport = AnswerTransport(RestBackend::new(http, HeaderIndex::default()));
+        let client = Client::from_jsonrpc(jsonrpc::Client::with_transport(transport));
+
+        assert_eq!(client.get_block_count().unwrap(), 2);
+        let headers =
+            get_block_headers(&client, &[BlockHeight(0), BlockHeight(1), BlockHeight(2)]).unwrap();
+        assert_eq!(headers[0], genesis_block(Network::Bitcoin).header);
+        assert_eq!(headers[2].prev_blockhash, headers[1].block_hash());
+        assert!(client.get_block_hash(5).is_err());
+        assert!(client.get_block_stats(0).is_err());
+
+        // The hashes and headers of the run come from two requests
+        let paths = paths.lock().unwrap();
+        assert_eq!(paths.len(), 4);
+        assert!(paths[2].starts_with("/rest/headers/3/"));
+    }
+}
@@ -0,0 +1,86 @@
+use schemars::{schema::RootSchema, schema_for};
+use std::collections::BTreeMap;
+
+use crate::{
+    AutocorrelationReport, BlockSizeReport, BlockTimeDriftReport, ChainTipsReport,
+    CoinbaseRevenueReport, ConfirmationLatencyReport, ConsolidationsReport, CorrelationReport,
+    CumulativeDriftReport, CustomReport, DifficultyEpochsReport, DifficultyTrendReport,
+    EmptyBlocksReport, EntityClustersReport, EpochDurationsReport, ExamplesReport,
+    FeeElasticityReport, FeeStatsReport, FutureTimestampsReport, HalvingEraSpeedReport,
+    HashrateReport, HistoryReport, IndexVerifyReport, MonotonicityStreaksReport,
+    NegativeDriftsReport, OutOfOrderReport, PoolLuckReport, PoolsReport, RecordsReport,
+    ReorgRiskReport, RevenueReport, RollingReport, SignetProducersReport, TaprootAdoptionReport,
+    TimeWarpReport, TransactionsReport, VersionBitsReport, WatchReport, WitnessVersionFlowReport,
+};
+
+/// Get the JSON schema of the output of every analysis keyed by the name of
+/// the analysis, the rolling statistics are keyed by "rolling".
+pub fn get_report_schemas() -> BTreeMap<&'static str, RootSchema> {
+    BTreeMap::from([
+        ("autocorrelation", schema_for!(AutocorrelationReport)),
+        ("block-size", schema_for!(BlockSizeReport)),
+        ("block-time-drift", schema_for!(BlockTimeDriftReport)),
+        ("chain-tips", schema_for!(ChainTipsReport)),
+        ("coinbase-revenue", schema_for!(CoinbaseRevenueReport)),
+        (
+            "confirmation-latency",
+            schema_for!(ConfirmationLatencyReport),
+        ),
+        ("consolidations", schema_for!(ConsolidationsReport)),
+        ("cumulative-drift", schema_for!(CumulativeDriftReport)),
+        ("custom", schema_for!(CustomReport)),
+        ("difficulty-epochs", schema_for!(DifficultyEpochsReport)),
+        ("difficulty-trend", schema_for!(DifficultyTrendReport)),
+        ("empty-blocks", schema_for!(EmptyBlocksReport)),
+        ("entity-clusters", schema_for!(EntityClustersReport)),
+        ("epoch-durations", schema_for!(EpochDurationsReport)),
+        ("examples", schema_for!(ExamplesReport)),
+        ("fee-elasticity", schema_for!(FeeElasticityReport)),
+        ("fee-stats", schema_for!(FeeStatsReport)),
+        ("future-timestamps", schema_for!(FutureTimestampsReport)),
+        ("halving-era-speed", schema_for!(HalvingEraSpeedReport)),
+        ("hashrate", schema_for!(HashrateReport)),
+        ("history", schema_for!(HistoryReport)),
+        ("index-verify", schema_for!(IndexVerifyReport)),
+        (
+            "monotonicity-streaks",
+            schema_for!(MonotonicityStreaksReport),
+        ),
+        ("negative-drifts", schema_for!(NegativeDriftsReport)),
+        ("out-of-order", schema_for!(OutOfOrderReport)),
+        ("pool-luck", schema_for!(PoolLuckReport)),
+        ("pools", schema_for!(PoolsReport)),
+        ("records", schema_for!(RecordsReport)),
+        ("reorg-risk", schema_for!(ReorgRiskReport)),
+        ("revenue", schema_for!(RevenueReport)),
+        ("rolling", schema_for!(RollingReport)),
+        ("signet-producers", schema_for!(SignetProducersReport)),
+        ("size-interval-correlation", schema_for!(CorrelationReport)),
+        ("taproot-adoption", schema_for!(TaprootAdoptionReport)),
+        ("time-warp", schema_for!(TimeWarpReport)),
+        ("transactions", schema_for!(TransactionsReport)),
+        ("version-bits", schema_for!(VersionBitsReport)),
+        ("watch", schema_for!(WatchReport)),
+        (
+            "witness-version-flow",
+            schema_for!(WitnessVersionFlowReport),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_report_schemas() {
+        let schemas = get_report_schemas();
+        let drift = serde_json::to_value(&schemas["block-time-drift"]).unwrap();
+        assert_eq!(drift["title"], "BlockTimeDriftReport");
+        assert_eq!(
+            drift["properties"]["entries"]["items"]["$ref"],
+            "#/definitions/BlockTimeDriftTable"
+        );
+        assert!(drift["definitions"]["BlockTimeDriftTable"]["properties"]["drift"].is_object());
+    }
+}
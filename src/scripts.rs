@@ -0,0 +1,271 @@
+use bitcoincore_rpc::bitcoin::{blockdata::script::Instruction, PublicKey, Script, TxIn};
+use std::fmt;
+
+/// Size of a Schnorr signature without and with an explicit sighash type.
+const SCHNORR_SIGNATURE_SIZES: [usize; 2] = [64, 65];
+/// First byte of a taproot annex witness element.
+const TAPROOT_ANNEX_TAG: u8 = 0x50;
+/// Leaf version mask of the first byte of a taproot control block.
+const TAPROOT_LEAF_MASK: u8 = 0xfe;
+/// Leaf version of tapscript.
+const TAPROOT_LEAF_TAPSCRIPT: u8 = 0xc0;
+
+/// Script type generation of an input or output.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum ScriptType {
+    /// Scripts without a witness (P2PK, P2PKH, P2SH, bare multisig...)
+    Legacy,
+    /// SegWit version 0 (P2WPKH, P2WSH and their P2SH wrapped forms)
+    SegwitV0,
+    /// SegWit version 1 (P2TR)
+    Taproot,
+    /// Witness programs of versions not yet defined by consensus
+    OtherWitness,
+}
+
+/// Path of a taproot spend.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TaprootSpend {
+    /// Spent with a signature of the output key
+    KeyPath,
+    /// Spent by revealing a script of the script tree
+    ScriptPath,
+}
+
+impl fmt::Display for ScriptType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ScriptType::Legacy => "Legacy",
+            ScriptType::SegwitV0 => "SegWit v0",
+            ScriptType::Taproot => "Taproot",
+            ScriptType::OtherWitness => "Other Witness",
+        })
+    }
+}
+
+/// Get the script type of an output, None when the output is provably
+/// unspendable (e.g. OP_RETURN) and so does not create spendable coins.
+pub fn get_output_type(script_pubkey: &Script) -> Option<ScriptType> {
+    if script_pubkey.is_provably_unspendable() {
+        None
+    } else if script_pubkey.is_v0_p2wpkh() || script_pubkey.is_v0_p2wsh() {
+        Some(ScriptType::SegwitV0)
+    } else if script_pubkey.is_v1_p2tr() {
+        Some(ScriptType::Taproot)
+    } else if script_pubkey.is_witness_program() {
+        Some(ScriptType::OtherWitness)
+    } else {
+        Some(ScriptType::Legacy)
+    }
+}
+
+/// Infer the script type of the output spent by the input. Blocks do not
+/// carry the spent outputs so the type is inferred from the shape of the
+/// scriptSig and witness.
+///
+/// Note: A P2WSH witness consisting of a single 64 or 65 byte element is
+/// indistinguishable from a taproot key path spend and is counted as
+/// taproot.
+pub fn get_input_type(input: &TxIn) -> ScriptType {
+    if input.witness.is_empty() {
+        ScriptType::Legacy
+    } else if get_taproot_spend(input).is_some() {
+        ScriptType::Taproot
+    } else {
+        ScriptType::SegwitV0
+    }
+}
+
+/// Infer the path of a taproot spend from the shape of the witness of the
+/// input, None when the input does not spend a taproot output. See
+/// get_input_type for the inputs mistaken for taproot.
+pub fn get_taproot_spend(input: &TxIn) -> Option<TaprootSpend> {
+    let witness = input.witness.to_vec();
+
+    // Only P2SH wrapped SegWit v0 has both a scriptSig and a witness
+    if witness.is_empty() || !input.script_sig.is_empty() {
+        return None;
+    }
+
+    // Ignore the annex of taproot spends
+    let elements = match witness.split_last() {
+        Some((last, rest)) if witness.len() > 1 && last.first() == Some(&TAPROOT_ANNEX_TAG) => rest,
+        _ => &witness[..],
+    };
+
+    let is_key_path = elements.len() == 1 && SCHNORR_SIGNATURE_SIZES.contains(&elements[0].len());
+    let is_script_path = elements.len() > 1
+        && matches!(elements.last(), Some(control) if control.len() >= 33
+            && (control.len() - 33) % 32 == 0
+            && control[0] & TAPROOT_LEAF_MASK == TAPROOT_LEAF_TAPSCRIPT);
+
+    if is_key_path {
+        Some(TaprootSpend::KeyPath)
+    } else if is_script_path {
+        Some(TaprootSpend::ScriptPath)
+    } else {
+        None
+    }
+}
+
+/// Derive the script of the output spent by a single key input (P2PKH,
+/// P2WPKH or P2SH wrapped P2WPKH) from the public key it reveals. Inputs
+/// of any other type return None.
+pub fn get_spent_script(input: &TxIn) -> Option<Script> {
+    let pushes = input
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction {
+            Ok(Instruction::PushBytes(bytes)) => Some(bytes),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let witness = input.witness.to_vec();
+
+    match (pushes.as_slice(), witness.as_slice()) {
+        ([_, pubkey], []) => Some(Script::new_p2pkh(
+            &PublicKey::from_slice(pubkey).ok()?.pubkey_hash(),
+        )),
+        ([], [_, pubkey]) => Some(Script::new_v0_p2wpkh(
+            &PublicKey::from_slice(pubkey).ok()?.wpubkey_hash()?,
+        )),
+        ([redeem_script], [_, pubkey]) => {
+            let redeem_script = Script::from(redeem_script.to_vec());
+            let wpubkey_hash = PublicKey::from_slice(pubkey).ok()?.wpubkey_hash()?;
+
+            if redeem_script == Script::new_v0_p2wpkh(&wpubkey_hash) {
+                Some(Script::new_p2sh(&redeem_script.script_hash()))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::{blockdata::script::Builder, Witness};
+    use std::str::FromStr;
+
+    /// Compressed public key of the secp256k1 generator.
+    const PUBKEY: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+    fn input(script_sig: Vec<u8>, witness: Vec<Vec<u8>>) -> TxIn {
+        TxIn {
+            script_sig: Script::from(script_sig),
+            witness: Witness::from_vec(witness),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_get_output_type() {
+        let p2wpkh = Builder::new()
+            .push_int(0)
+            .push_slice(&[0; 20])
+            .into_script();
+        let p2tr = Builder::new()
+            .push_int(1)
+            .push_slice(&[0; 32])
+            .into_script();
+        let v2 = Builder::new()
+            .push_int(2)
+            .push_slice(&[0; 32])
+            .into_script();
+        let p2pkh = Script::from(vec![0x76, 0xa9, 0x14]);
+        let op_return = Script::from(vec![0x6a, 0x01, 0x00]);
+
+        assert_eq!(get_output_type(&p2wpkh), Some(ScriptType::SegwitV0));
+        assert_eq!(get_output_type(&p2tr), Some(ScriptType::Taproot));
+        assert_eq!(get_output_type(&v2), Some(ScriptType::OtherWitness));
+        assert_eq!(get_output_type(&p2pkh), Some(ScriptType::Legacy));
+        assert_eq!(get_output_type(&op_return), None);
+    }
+
+    #[test]
+    fn test_get_input_type() {
+        assert_eq!(
+            get_input_type(&input(vec![0x01, 0x00], vec![])),
+            ScriptType::Legacy
+        );
+        assert_eq!(
+            get_input_type(&input(vec![], vec![vec![0; 71], vec![2; 33]])),
+            ScriptType::SegwitV0
+        );
+        assert_eq!(
+            get_input_type(&input(
+                vec![0x16, 0x00, 0x14],
+                vec![vec![0; 71], vec![2; 33]]
+            )),
+            ScriptType::SegwitV0
+        );
+        assert_eq!(
+            get_input_type(&input(vec![], vec![vec![0; 64]])),
+            ScriptType::Taproot
+        );
+        assert_eq!(
+            get_input_type(&input(vec![], vec![vec![0; 64], vec![0x50, 0x01]])),
+            ScriptType::Taproot
+        );
+
+        let mut control = vec![0xc1];
+        control.extend([0; 64]);
+        assert_eq!(
+            get_input_type(&input(vec![], vec![vec![0; 64], vec![0x51], control])),
+            ScriptType::Taproot
+        );
+    }
+
+    #[test]
+    fn test_get_taproot_spend() {
+        let mut control = vec![0xc0];
+        control.extend([0; 32]);
+
+        assert_eq!(
+            get_taproot_spend(&input(vec![], vec![vec![0; 65]])),
+            Some(TaprootSpend::KeyPath)
+        );
+        assert_eq!(
+            get_taproot_spend(&input(vec![], vec![vec![0x51], control.clone()])),
+            Some(TaprootSpend::ScriptPath)
+        );
+        assert_eq!(
+            get_taproot_spend(&input(vec![0x16], vec![vec![0x51], control])),
+            None
+        );
+        assert_eq!(
+            get_taproot_spend(&input(vec![], vec![vec![0; 71], vec![2; 33]])),
+            None
+        );
+        assert_eq!(get_taproot_spend(&input(vec![0x01, 0x00], vec![])), None);
+    }
+
+    #[test]
+    fn test_get_spent_script() {
+        let pubkey = PublicKey::from_str(PUBKEY).unwrap();
+        let p2pkh = Builder::new()
+            .push_slice(&[0; 71])
+            .push_key(&pubkey)
+            .into_script();
+        assert_eq!(
+            get_spent_script(&input(p2pkh.to_bytes(), vec![])),
+            Some(Script::new_p2pkh(&pubkey.pubkey_hash()))
+        );
+
+        let p2wpkh = Script::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap());
+        let witness = vec![vec![0; 71], pubkey.to_bytes()];
+        assert_eq!(
+            get_spent_script(&input(vec![], witness.clone())),
+            Some(p2wpkh.clone())
+        );
+
+        let nested = Builder::new().push_slice(p2wpkh.as_bytes()).into_script();
+        assert_eq!(
+            get_spent_script(&input(nested.to_bytes(), witness)),
+            Some(Script::new_p2sh(&p2wpkh.script_hash()))
+        );
+        assert_eq!(get_spent_script(&input(vec![], vec![vec![0; 64]])), None);
+    }
+}
@@ -0,0 +1,335 @@
+use bitcoincore_rpc::bitcoin::{
+    blockdata::{
+        opcodes::{all::OP_RETURN, OP_FALSE},
+        script::{Builder, Instruction},
+    },
+    consensus::{encode::serialize, Decodable},
+    hashes::hex::FromHex,
+    secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1},
+    Block, OutPoint, Script, Transaction, TxIn, TxMerkleNode, TxOut, Witness,
+};
+
+use crate::Result;
+
+/// The challenge used by the default public signet.
+pub const DEFAULT_CHALLENGE: &str = "512103ad5e0edad18cb1f0fc0d28a3d4f1f3e445640337489abb10404f2d1e086be430210359ef5021964fe22d6f8e05b2463c9540ce96883fe3b278760f048f5189f2e6c452ae";
+
+/// Marker prefixing the signet solution within the witness commitment.
+/// See https://github.com/bitcoin/bips/blob/master/bip-0325.mediawiki
+const SIGNET_HEADER: [u8; 4] = [0xec, 0xc7, 0xda, 0xa2];
+
+/// Prefix of the coinbase output carrying the segwit witness commitment.
+const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+/// The block challenge of a signet along with the keys able to sign it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SignetChallenge {
+    script: Script,
+    signers: Vec<PublicKey>,
+}
+
+impl SignetChallenge {
+    /// Parse a hex encoded challenge script. Any public keys pushed by the
+    /// script are treated as potential block signers.
+    pub fn from_hex(challenge: &str) -> Result<Self> {
+        let script = Script::from(Vec::<u8>::from_hex(challenge)?);
+        let signers = script
+            .instructions()
+            .filter_map(|instruction| match instruction {
+                Ok(Instruction::PushBytes(bytes)) => PublicKey::from_slice(bytes).ok(),
+                _ => None,
+            })
+            .collect();
+
+        Ok(SignetChallenge { script, signers })
+    }
+
+    /// Public keys found in the challenge script.
+    pub fn signers(&self) -> &[PublicKey] {
+        &self.signers
+    }
+
+    /// Identify which signer of the challenge produced the block by verifying
+    /// the signet solution signatures against each of the challenge keys.
+    ///
+    /// Note: Only legacy (bare multisig / pay-to-pubkey) challenges can be
+    /// attributed. Witness challenges will return None.
+    pub fn get_signer(&self, block: &Block) -> Option<usize> {
+        let (script_sig, witness, merkle_root) = get_signet_solution(block)?;
+        let to_sign = self.get_to_sign(block, merkle_root, script_sig.clone(), witness);
+        let secp = Secp256k1::verification_only();
+
+        for instruction in script_sig.instructions() {
+            let bytes = match instruction {
+                Ok(Instruction::PushBytes(bytes)) if bytes.len() > 1 => bytes,
+                _ => continue,
+            };
+            let (hash_type, der) = bytes.split_last()?;
+            let mut signature = match Signature::from_der(der) {
+                Ok(signature) => signature,
+                Err(_) => continue,
+            };
+            signature.normalize_s();
+
+            let sighash = to_sign.signature_hash(0, &self.script, *hash_type as u32);
+            let message = Message::from_slice(&sighash[..]).ok()?;
+
+            if let Some(signer) = self
+                .signers
+                .iter()
+                .position(|key| secp.verify_ecdsa(&message, &signature, key).is_ok())
+            {
+                return Some(signer);
+            }
+        }
+
+        None
+    }
+
+    /// Reconstruct the virtual transaction described by BIP325 spending the
+    /// challenge with the solution, whose signature hash the signers sign.
+    fn get_to_sign(
+        &self,
+        block: &Block,
+        merkle_root: TxMerkleNode,
+        script_sig: Script,
+        witness: Witness,
+    ) -> Transaction {
+        let mut block_data = serialize(&block.header.version);
+        block_data.extend(serialize(&block.header.prev_blockhash));
+        block_data.extend(serialize(&merkle_root));
+        block_data.extend(serialize(&block.header.time));
+
+        let to_spend = Transaction {
+            version: 0,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new()
+                    .push_opcode(OP_FALSE)
+                    .push_slice(&block_data)
+                    .into_script(),
+                sequence: 0,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: self.script.clone(),
+            }],
+        };
+
+        Transaction {
+            version: 0,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(to_spend.txid(), 0),
+                script_sig,
+                sequence: 0,
+                witness,
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+            }],
+        }
+    }
+}
+
+impl Default for SignetChallenge {
+    fn default() -> Self {
+        SignetChallenge::from_hex(DEFAULT_CHALLENGE).expect("Invalid default challenge")
+    }
+}
+
+/// Extract the signet solution (scriptSig and witness) from the coinbase
+/// witness commitment of the block along with the merkle root the solution
+/// commits to (the merkle root of the block without the solution).
+pub fn get_signet_solution(block: &Block) -> Option<(Script, Witness, TxMerkleNode)> {
+    let coinbase = block.txdata.first()?;
+    let index = coinbase.output.iter().rposition(|output| {
+        output
+            .script_pubkey
+            .as_bytes()
+            .starts_with(&WITNESS_COMMITMENT_HEADER)
+    })?;
+    let (commitment, solution) = get_cleared_commitment(&coinbase.output[index].script_pubkey)?;
+
+    let mut cursor = std::io::Cursor::new(&solution);
+    let script_sig = Script::consensus_decode(&mut cursor).ok()?;
+    let witness = Witness::consensus_decode(&mut cursor).ok()?;
+
+    // Extraneous data is not a valid solution
+    if cursor.position() as usize != solution.len() {
+        return None;
+    }
+
+    let mut modified = block.clone();
+    modified.txdata[0].output[index].script_pubkey = commitment;
+
+    Some((script_sig, witness, modified.compute_merkle_root()?))
+}
+
+/// Remove the signet solution from the witness commitment script returning
+/// the cleared script along with the raw solution bytes.
+fn get_cleared_commitment(commitment: &Script) -> Option<(Script, Vec<u8>)> {
+    let mut builder = Builder::new();
+    let mut solution = None;
+
+    for instruction in commitment.instructions() {
+        match instruction.ok()? {
+            Instruction::PushBytes(bytes) => {
+                if solution.is_none()
+                    && bytes.len() > SIGNET_HEADER.len()
+                    && bytes.starts_with(&SIGNET_HEADER)
+                {
+                    solution = Some(bytes[SIGNET_HEADER.len()..].to_vec());
+                    builder = builder.push_slice(&SIGNET_HEADER);
+                } else {
+                    builder = builder.push_slice(bytes);
+                }
+            }
+            Instruction::Op(opcode) => builder = builder.push_opcode(opcode),
+        }
+    }
+
+    solution.map(|solution| (builder.into_script(), solution))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::{
+        blockdata::opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_1, OP_PUSHNUM_2},
+        consensus::Encodable,
+        hashes::hex::ToHex,
+        secp256k1::SecretKey,
+        BlockHash, BlockHeader, EcdsaSighashType,
+    };
+
+    /// Sign a block of a 1-of-2 multisig signet with the key at the index,
+    /// following BIP325: the solution is committed to the coinbase after
+    /// signing the block with the solution cleared from its commitment.
+    fn get_signed_block(challenge: &SignetChallenge, key: &SecretKey, time: u32) -> Block {
+        let commitment = |solution: &[u8]| {
+            let mut pushed = SIGNET_HEADER.to_vec();
+            pushed.extend(solution);
+            Builder::new()
+                .push_opcode(OP_RETURN)
+                .push_slice(&[WITNESS_COMMITMENT_HEADER[2..].to_vec(), vec![0x11; 32]].concat())
+                .push_slice(&pushed)
+                .into_script()
+        };
+        let coinbase = |script_pubkey: Script| Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new().push_int(1).into_script(),
+                sequence: u32::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 50_0000_0000,
+                script_pubkey,
+            }],
+        };
+        let mut block = Block {
+            header: BlockHeader {
+                version: 0x2000_0000,
+                prev_blockhash: BlockHash::default(),
+                merkle_root: TxMerkleNode::default(),
+                time,
+                bits: 0x1e03_77ae,
+                nonce: 0,
+            },
+            txdata: vec![coinbase(commitment(&[]))],
+        };
+
+        let merkle_root = block.compute_merkle_root().unwrap();
+        let to_sign = challenge.get_to_sign(&block, merkle_root, Script::new(), Witness::new());
+        let sighash = to_sign.signature_hash(0, &challenge.script, EcdsaSighashType::All.to_u32());
+        let message = Message::from_slice(&sighash[..]).unwrap();
+        let mut signature = Secp256k1::new()
+            .sign_ecdsa(&message, key)
+            .serialize_der()
+            .to_vec();
+        signature.push(EcdsaSighashType::All.to_u32() as u8);
+
+        let mut solution = vec![];
+        let script_sig = Builder::new()
+            .push_opcode(OP_FALSE)
+            .push_slice(&signature)
+            .into_script();
+        script_sig.consensus_encode(&mut solution).unwrap();
+        Witness::new().consensus_encode(&mut solution).unwrap();
+
+        block.txdata = vec![coinbase(commitment(&solution))];
+        block.header.merkle_root = block.compute_merkle_root().unwrap();
+        block
+    }
+
+    #[test]
+    fn test_get_signer() {
+        let secp = Secp256k1::new();
+        let keys =
+            [[0x01; 32], [0x02; 32], [0x03; 32]].map(|key| SecretKey::from_slice(&key).unwrap());
+        let challenge = |first: &SecretKey, second: &SecretKey| {
+            let script = Builder::new()
+                .push_opcode(OP_PUSHNUM_1)
+                .push_slice(&PublicKey::from_secret_key(&secp, first).serialize())
+                .push_slice(&PublicKey::from_secret_key(&secp, second).serialize())
+                .push_opcode(OP_PUSHNUM_2)
+                .push_opcode(OP_CHECKMULTISIG)
+                .into_script();
+            SignetChallenge::from_hex(&script.as_bytes().to_hex()).unwrap()
+        };
+        let signet = challenge(&keys[0], &keys[1]);
+
+        let block = get_signed_block(&signet, &keys[1], 1_600_000_000);
+        assert!(get_signet_solution(&block).is_some());
+        assert_eq!(signet.get_signer(&block), Some(1));
+        assert_eq!(
+            signet.get_signer(&get_signed_block(&signet, &keys[0], 1_600_000_000)),
+            Some(0)
+        );
+
+        // Neither a key outside the challenge nor a block changed after
+        // signing is attributed
+        assert_eq!(
+            signet.get_signer(&get_signed_block(&signet, &keys[2], 1_600_000_000)),
+            None
+        );
+        let mut tampered = block.clone();
+        tampered.header.time += 1;
+        assert_eq!(signet.get_signer(&tampered), None);
+        assert_eq!(challenge(&keys[2], &keys[0]).get_signer(&block), None);
+    }
+
+    #[test]
+    fn test_signet_challenge_from_hex() {
+        let challenge = SignetChallenge::default();
+        assert_eq!(challenge.signers().len(), 2);
+        assert!(SignetChallenge::from_hex("zz").is_err());
+    }
+
+    #[test]
+    fn test_get_cleared_commitment() {
+        let mut pushed = SIGNET_HEADER.to_vec();
+        pushed.extend([0x01, 0x02, 0x03]);
+        let script = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice(&[0xaa; 36])
+            .push_slice(&pushed)
+            .into_script();
+        let expected = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice(&[0xaa; 36])
+            .push_slice(&SIGNET_HEADER)
+            .into_script();
+
+        let (cleared, solution) = get_cleared_commitment(&script).unwrap();
+        assert_eq!(cleared, expected);
+        assert_eq!(solution, vec![0x01, 0x02, 0x03]);
+        assert!(get_cleared_commitment(&expected).is_none());
+    }
+}
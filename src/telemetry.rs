@@ -0,0 +1,371 @@
+//! Export of the traces and metrics of a run to an OpenTelemetry collector
+//! over OTLP/HTTP with the OpenTelemetry SDK, see
+//! https://opentelemetry.io/docs/specs/otlp. Spans cover every analysis and
+//! every batch of fetches, metrics the latency and the failures of the RPC
+//! calls by method. The SDK exports from its own threads, the metrics every
+//! EXPORT_INTERVAL so long-running watches are observed too, and flushes
+//! the rest once the guard returned by init is dropped.
+//!
+//! Spans are not tied to a thread or a task: the context of the analysis is
+//! handed to the progress sink, which starts the span of every batch of
+//! fetches as its child.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use bitcoincore_rpc::jsonrpc::{self, client::Transport, Request, Response};
+use opentelemetry::{
+    global::{self, BoxedSpan, BoxedTracer},
+    metrics::{Counter, Histogram},
+    trace::{Span, Status, TraceContextExt, Tracer},
+    Context, KeyValue,
+};
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    metrics::{PeriodicReader, SdkMeterProvider},
+    trace::SdkTracerProvider,
+    Resource,
+};
+
+use crate::progress::{FetchProgress, ProgressSink};
+use crate::{Result, StatisticsError};
+
+/// Interval between exports of the metrics.
+pub const EXPORT_INTERVAL: Duration = Duration::from_secs(10);
+/// Bounds of the buckets of the RPC latency histogram in milliseconds.
+const LATENCY_BOUNDS: [f64; 12] = [
+    1.0, 2.5, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+];
+/// Name of the service and of the instrumentation scope.
+const SERVICE: &str = env!("CARGO_PKG_NAME");
+
+/// Shuts the telemetry of the run down once dropped, exporting what is left.
+#[derive(Debug)]
+pub struct ExportGuard {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+}
+
+/// Transport recording the latency and the failures of its calls.
+pub struct TelemetryTransport<T: Transport> {
+    inner: T,
+    duration: Histogram<f64>,
+    failures: Counter<u64>,
+}
+
+/// Progress sink recording a span of every batch of fetches, from the
+/// start of the batch to its end, before passing the events on.
+pub struct TelemetrySink<S: ProgressSink> {
+    inner: S,
+    tracer: BoxedTracer,
+    /// Context the spans of the batches are children of
+    context: Context,
+    /// Span of the batch being fetched along with its failed fetches
+    batch: Mutex<Option<(BoxedSpan, u64)>>,
+}
+
+/// Start exporting the telemetry of the run to the OTLP/HTTP endpoint of a
+/// collector, e.g. http://localhost:4318.
+pub fn init(endpoint: &str) -> Result<ExportGuard> {
+    let get_error =
+        |e: &dyn std::fmt::Display| StatisticsError::TelemetryError(format!("{}: {}", endpoint, e));
+    url::Url::parse(endpoint).map_err(|e| get_error(&e))?;
+    let endpoint = endpoint.trim_end_matches('/');
+
+    let spans = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint))
+        .build()
+        .map_err(|e| get_error(&e))?;
+    let metrics = MetricExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/metrics", endpoint))
+        .build()
+        .map_err(|e| get_error(&e))?;
+    let resource = Resource::builder().with_service_name(SERVICE).build();
+
+    let tracer_provider = SdkTracerProvider::builder()
+        .with_batch_exporter(spans)
+        .with_resource(resource.clone())
+        .build();
+    let meter_provider = SdkMeterProvider::builder()
+        .with_reader(
+            PeriodicReader::builder(metrics)
+                .with_interval(EXPORT_INTERVAL)
+                .build(),
+        )
+        .with_resource(resource)
+        .build();
+    global::set_tracer_provider(tracer_provider.clone());
+    global::set_meter_provider(meter_provider.clone());
+
+    Ok(ExportGuard {
+        tracer_provider,
+        meter_provider,
+    })
+}
+
+/// Start the span of the analysis, returning the context of its work. The
+/// span ends once the context and its clones are dropped.
+pub fn start_analysis(analysis: &str) -> Context {
+    let tracer = global::tracer(SERVICE);
+    let span = tracer
+        .span_builder("analysis")
+        .with_attributes([KeyValue::new("analysis", analysis.to_string())])
+        .start(&tracer);
+
+    Context::current_with_span(span)
+}
+
+impl Drop for ExportGuard {
+    fn drop(&mut self) {
+        let spans = self.tracer_provider.shutdown();
+        let metrics = self.meter_provider.shutdown();
+
+        if let Some(e) = spans.err().or(metrics.err()) {
+            eprintln!("{}", StatisticsError::TelemetryError(e.to_string()));
+        }
+    }
+}
+
+impl<T: Transport> TelemetryTransport<T> {
+    /// Record the calls of the transport to the meter of the run.
+    pub fn new(inner: T) -> Self {
+        let meter = global::meter(SERVICE);
+
+        TelemetryTransport {
+            inner,
+            duration: meter
+                .f64_histogram("rpc.client.duration")
+                .with_description("Latency of the RPC calls, once per batch of calls")
+                .with_unit("ms")
+                .with_boundaries(LATENCY_BOUNDS.to_vec())
+                .build(),
+            failures: meter
+                .u64_counter("rpc.client.failures")
+                .with_description("Failed RPC calls")
+                .with_unit("{call}")
+                .build(),
+        }
+    }
+
+    fn record(&self, method: &str, start: Instant, failed: bool) {
+        let attributes = [KeyValue::new("rpc.method", method.to_string())];
+        self.duration
+            .record(start.elapsed().as_secs_f64() * 1000.0, &attributes);
+        self.failures.add(failed as u64, &attributes);
+    }
+}
+
+impl<T: Transport> Transport for TelemetryTransport<T> {
+    fn send_request(&self, request: Request) -> std::result::Result<Response, jsonrpc::Error> {
+        let (method, start) = (request.method.to_string(), Instant::now());
+        let response = self.inner.send_request(request);
+
+        let failed = !matches!(&response, Ok(response) if response.error.is_none());
+        self.record(&method, start, failed);
+
+        response
+    }
+
+    fn send_batch(
+        &self,
+        requests: &[Request],
+    ) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+        let start = Instant::now();
+        let responses = self.inner.send_batch(requests);
+
+        if let Some(request) = requests.first() {
+            // The calls of a batch share its latency, so it is recorded once
+            let failed = !matches!(&responses, Ok(responses)
+                if responses.iter().all(|response| response.error.is_none()));
+            self.record(request.method, start, failed);
+        }
+
+        responses
+    }
+
+    fn fmt_target(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.fmt_target(f)
+    }
+}
+
+impl<S: ProgressSink> TelemetrySink<S> {
+    /// Record the batches of fetches reported to the sink as children of
+    /// the context, see start_analysis.
+    pub fn new(inner: S, context: Context) -> Self {
+        TelemetrySink {
+            inner,
+            tracer: global::tracer(SERVICE),
+            context,
+            batch: Mutex::new(None),
+        }
+    }
+}
+
+impl<S: ProgressSink> ProgressSink for TelemetrySink<S> {
+    fn report(&self, event: FetchProgress) {
+        let mut batch = self.batch.lock().unwrap();
+
+        match &event {
+            FetchProgress::Started { total } => {
+                let mut span = self.tracer.start_with_context("fetch batch", &self.context);
+                span.set_attribute(KeyValue::new("blocks", *total as i64));
+                *batch = Some((span, 0));
+            }
+            FetchProgress::Failed { .. } => {
+                if let Some((_, failed)) = batch.as_mut() {
+                    *failed += 1;
+                }
+            }
+            FetchProgress::Finished { fetched } => {
+                if let Some((mut span, failed)) = batch.take() {
+                    span.set_attribute(KeyValue::new("fetched", *fetched as i64));
+                    span.set_attribute(KeyValue::new("failed", failed as i64));
+
+                    if failed > 0 {
+                        span.set_status(Status::error(format!("{} fetches failed", failed)));
+                    }
+
+                    span.end();
+                }
+            }
+            _ => {}
+        }
+
+        drop(batch);
+        self.inner.report(event);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::progress::NoProgress;
+    use crate::transport::{
+        get_rpc_error, Answer, AnswerTransport, Answered, RPC_METHOD_NOT_FOUND,
+    };
+    use crate::BlockHeight;
+    use opentelemetry::trace::SpanId;
+    use opentelemetry_sdk::{
+        metrics::{
+            data::{AggregatedMetrics, MetricData},
+            InMemoryMetricExporter,
+        },
+        trace::InMemorySpanExporter,
+    };
+    use serde_json::{json, Value};
+    use std::{fmt, io};
+
+    /// Backend answering uptime and failing every other call.
+    struct Uptime;
+
+    impl Answer for Uptime {
+        fn answer(&self, method: &str, _params: &[Value]) -> io::Result<Answered> {
+            Ok(match method {
+                "uptime" => Ok(json!(60)),
+                _ => Err(get_rpc_error(RPC_METHOD_NOT_FOUND, "Method not found")),
+            })
+        }
+
+        fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "uptime")
+        }
+    }
+
+    #[test]
+    fn test_spans() {
+        let exporter = InMemorySpanExporter::default();
+        global::set_tracer_provider(
+            SdkTracerProvider::builder()
+                .with_simple_exporter(exporter.clone())
+                .build(),
+        );
+
+        let sink = TelemetrySink::new(NoProgress, start_analysis("fee-stats"));
+        sink.report(FetchProgress::Started { total: 3 });
+        sink.report(FetchProgress::Failed {
+            height: BlockHeight(1),
+        });
+        sink.report(FetchProgress::Finished { fetched: 2 });
+        drop(sink);
+        drop(start_analysis("fee-stats"));
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let (batch, analysis, other) = (&spans[0], &spans[1], &spans[2]);
+        assert_eq!(batch.name, "fetch batch");
+        assert_eq!(
+            batch.span_context.trace_id(),
+            analysis.span_context.trace_id()
+        );
+        assert_eq!(batch.parent_span_id, analysis.span_context.span_id());
+        assert!(batch.attributes.contains(&KeyValue::new("blocks", 3)));
+        assert!(batch.attributes.contains(&KeyValue::new("failed", 1)));
+        assert_eq!(batch.status, Status::error("1 fetches failed"));
+        assert_eq!(analysis.name, "analysis");
+        assert_eq!(analysis.status, Status::Unset);
+        // Every analysis starts a trace of its own
+        assert_eq!(analysis.parent_span_id, SpanId::INVALID);
+        assert_eq!(other.parent_span_id, SpanId::INVALID);
+        assert_ne!(
+            other.span_context.trace_id(),
+            analysis.span_context.trace_id()
+        );
+    }
+
+    #[test]
+    fn test_transport_metrics() {
+        let exporter = InMemoryMetricExporter::default();
+        let provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(exporter.clone())
+            .build();
+        global::set_meter_provider(provider.clone());
+
+        let transport = TelemetryTransport::new(AnswerTransport(Uptime));
+        let client = jsonrpc::Client::with_transport(transport);
+        client.call::<u64>("uptime", &[]).unwrap();
+        client.call::<u64>("uptime", &[]).unwrap();
+        client.call::<u64>("stop", &[]).unwrap_err();
+        provider.force_flush().unwrap();
+
+        let metrics = exporter.get_finished_metrics().unwrap();
+        let metrics = metrics
+            .iter()
+            .flat_map(|metrics| metrics.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .collect::<Vec<_>>();
+        let method = |method: &str| KeyValue::new("rpc.method", method.to_string());
+
+        let duration = metrics
+            .iter()
+            .find(|metric| metric.name() == "rpc.client.duration")
+            .unwrap();
+        let AggregatedMetrics::F64(MetricData::Histogram(duration)) = duration.data() else {
+            panic!("rpc.client.duration is not a histogram");
+        };
+        let uptime = duration
+            .data_points()
+            .find(|point| point.attributes().any(|kv| *kv == method("uptime")))
+            .unwrap();
+        assert_eq!(uptime.count(), 2);
+        assert_eq!(uptime.bounds().collect::<Vec<_>>(), LATENCY_BOUNDS);
+
+        let failures = metrics
+            .iter()
+            .find(|metric| metric.name() == "rpc.client.failures")
+            .unwrap();
+        let AggregatedMetrics::U64(MetricData::Sum(failures)) = failures.data() else {
+            panic!("rpc.client.failures is not a sum");
+        };
+        let failed = |name: &str| {
+            failures
+                .data_points()
+                .find(|point| point.attributes().any(|kv| *kv == method(name)))
+                .map(|point| point.value())
+        };
+        assert_eq!(failed("uptime"), Some(0));
+        assert_eq!(failed("stop"), Some(1));
+    }
+}
@@ -0,0 +1,244 @@
+use bitcoincore_rpc::bitcoin::BlockHeader;
+use chrono::DateTime;
+use std::{
+    fs,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::utils::get_difficulty;
+use crate::{BlockHeight, Result, StatisticsError};
+
+/// Fields of a block available to the placeholders of a template.
+pub const TEMPLATE_FIELDS: [&str; 12] = [
+    "height",
+    "hash",
+    "previous_hash",
+    "time",
+    "date",
+    "version",
+    "version_hex",
+    "merkle_root",
+    "bits",
+    "nonce",
+    "difficulty",
+    "interval",
+];
+
+/// Template of the rows of exported blocks, a header line followed by a row
+/// written for every block with `{{field}}` placeholders, see TEMPLATE_FIELDS.
+/// The built in templates match the block schemas of explorers, so exports
+/// can be joined with datasets downloaded from them.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExportTemplate {
+    header: String,
+    row: String,
+}
+
+impl ExportTemplate {
+    /// Create a template from the header and the row of every block.
+    pub fn new(header: &str, row: &str) -> Result<Self> {
+        for field in get_placeholders(row)? {
+            if !TEMPLATE_FIELDS.contains(&field) {
+                return Err(StatisticsError::TemplateError(format!(
+                    "Unknown field {{{{{}}}}}",
+                    field
+                )));
+            }
+        }
+
+        Ok(ExportTemplate {
+            header: header.to_string(),
+            row: row.to_string(),
+        })
+    }
+
+    /// Columns of the blocks of the mempool.space API.
+    pub fn mempool_space() -> Self {
+        ExportTemplate {
+            header: String::from(
+                "id,height,version,timestamp,bits,nonce,difficulty,merkle_root,previousblockhash",
+            ),
+            row: String::from(
+                "{{hash}},{{height}},{{version}},{{time}},{{bits}},{{nonce}},{{difficulty}},\
+                 {{merkle_root}},{{previous_hash}}",
+            ),
+        }
+    }
+
+    /// Columns of the tab separated block dumps of Blockchair, dated in UTC.
+    pub fn blockchair() -> Self {
+        ExportTemplate {
+            header: String::from(
+                "id\thash\ttime\tversion\tversion_hex\tmerkle_root\tnonce\tbits\tdifficulty",
+            ),
+            row: String::from(
+                "{{height}}\t{{hash}}\t{{date}}\t{{version}}\t{{version_hex}}\t{{merkle_root}}\t\
+                 {{nonce}}\t{{bits}}\t{{difficulty}}",
+            ),
+        }
+    }
+
+    /// Load a template from a file, its first line is the header and its
+    /// second line the row of every block, e.g. `{{height}};{{date}}`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let template = fs::read_to_string(path)?;
+        let mut lines = template.lines();
+
+        match (lines.next(), lines.next()) {
+            (Some(header), Some(row)) => ExportTemplate::new(header, row),
+            _ => Err(StatisticsError::TemplateError(format!(
+                "{} needs a header line and a row line",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Write the header and a row for every block. The interval is the
+    /// seconds since the block before it, empty when not within the sample.
+    pub fn write<W: Write>(
+        &self,
+        writer: W,
+        headers: &[(BlockHeight, &BlockHeader)],
+    ) -> Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{}", self.header)?;
+
+        let mut parent: Option<&(BlockHeight, &BlockHeader)> = None;
+
+        for block in headers {
+            let (height, header) = block;
+            let interval = parent
+                .filter(|(parent_height, _)| *parent_height + 1 == *height)
+                .map(|(_, parent)| ((header.time as i64) - (parent.time as i64)).to_string());
+
+            let row = render_row(&self.row, |field| match field {
+                "height" => height.to_string(),
+                "hash" => header.block_hash().to_string(),
+                "previous_hash" => header.prev_blockhash.to_string(),
+                "time" => header.time.to_string(),
+                "date" => DateTime::from_timestamp(header.time as i64, 0)
+                    .map(|date| date.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default(),
+                "version" => header.version.to_string(),
+                "version_hex" => format!("{:08x}", header.version),
+                "merkle_root" => header.merkle_root.to_string(),
+                "bits" => header.bits.to_string(),
+                "nonce" => header.nonce.to_string(),
+                "difficulty" => get_difficulty(header.bits).to_string(),
+                "interval" => interval.clone().unwrap_or_default(),
+                _ => String::new(),
+            });
+            writeln!(writer, "{}", row)?;
+            parent = Some(block);
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl FromStr for ExportTemplate {
+    type Err = String;
+
+    /// Parse the name of a built in template.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "mempool-space" => Ok(ExportTemplate::mempool_space()),
+            "blockchair" => Ok(ExportTemplate::blockchair()),
+            _ => Err(format!("Unknown export template: {}", s)),
+        }
+    }
+}
+
+/// Get the fields of the placeholders within a row.
+fn get_placeholders(row: &str) -> Result<Vec<&str>> {
+    let mut fields = vec![];
+    let mut rest = row;
+
+    while let Some(start) = rest.find("{{") {
+        match rest[start..].find("}}") {
+            Some(end) => {
+                fields.push(rest[start + 2..start + end].trim());
+                rest = &rest[start + end + 2..];
+            }
+            None => {
+                return Err(StatisticsError::TemplateError(format!(
+                    "Unclosed placeholder in {}",
+                    row
+                )))
+            }
+        }
+    }
+
+    Ok(fields)
+}
+
+/// Render a row replacing its placeholders with the values of their fields.
+fn render_row<F: Fn(&str) -> String>(row: &str, value: F) -> String {
+    let mut rendered = String::with_capacity(row.len());
+    let mut rest = row;
+
+    while let Some(start) = rest.find("{{") {
+        match rest[start..].find("}}") {
+            Some(end) => {
+                rendered.push_str(&rest[..start]);
+                rendered.push_str(&value(rest[start + 2..start + end].trim()));
+                rest = &rest[start + end + 2..];
+            }
+            None => break,
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_export_template() {
+        let header = |time| BlockHeader {
+            version: 0x20000000,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time,
+            bits: 0x1d00ffff,
+            nonce: 7,
+        };
+        let (first, second, third) = (header(1_600_000_000), header(1_600_000_600), header(0));
+        let headers = [
+            (BlockHeight(10), &first),
+            (BlockHeight(11), &second),
+            (BlockHeight(50), &third),
+        ];
+
+        let template =
+            ExportTemplate::new("height,date,interval", "{{height}},{{ date }},{{interval}}");
+        let mut csv = vec![];
+        template.unwrap().write(&mut csv, &headers).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "height,date,interval\n\
+             10,2020-09-13 12:26:40,\n\
+             11,2020-09-13 12:36:40,600\n\
+             50,1970-01-01 00:00:00,\n"
+        );
+
+        let mut tsv = vec![];
+        ExportTemplate::blockchair()
+            .write(&mut tsv, &headers[..1])
+            .unwrap();
+        let tsv = String::from_utf8(tsv).unwrap();
+        assert!(tsv.ends_with("\t7\t486604799\t1\n"));
+        assert!(tsv.contains("\t2020-09-13 12:26:40\t536870912\t20000000\t"));
+
+        assert!("mempool-space".parse::<ExportTemplate>().is_ok());
+        assert!("mempool".parse::<ExportTemplate>().is_err());
+        assert!(ExportTemplate::new("", "{{height}},{{fee}}").is_err());
+        assert!(ExportTemplate::new("", "{{height").is_err());
+    }
+}
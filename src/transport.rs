@@ -0,0 +1,1224 @@
+use bitcoincore_rpc::{
+    bitcoin::{consensus::encode::deserialize, hashes::hex::FromHex, BlockHash, BlockHeader},
+    jsonrpc::{
+        self, client::Transport, error::RpcError, serde_json::value::RawValue,
+        simple_http::SimpleHttpTransport, Request, Response,
+    },
+    Auth, Client,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    fmt,
+    future::Future,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use url::{Host, Url};
+use zeroize::Zeroizing;
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+use crate::{BlockHeight, Result, StatisticsError};
+
+/// Port of the bitcoind RPC server when none is given without a scheme.
+const DEFAULT_PORT: u16 = 8332;
+/// Read and write timeout of a request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Number of requests in flight at once by default.
+pub const DEFAULT_CONCURRENCY: usize = 64;
+/// Number of calls of a batched request by default.
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+/// RPC error code of bitcoind while it is still starting up.
+const RPC_IN_WARMUP: i32 = -28;
+/// RPC error code of bitcoind for an unknown method.
+pub const RPC_METHOD_NOT_FOUND: i32 = -32601;
+/// RPC error code of bitcoind for an unknown block.
+pub const RPC_INVALID_ADDRESS_OR_KEY: i32 = -5;
+/// RPC error code of bitcoind for a height beyond the tip.
+pub const RPC_INVALID_PARAMETER: i32 = -8;
+
+/// Result of a call answered by a backend, the result of bitcoind or its
+/// RPC error.
+pub type Answered<T = Value> = std::result::Result<T, RpcError>;
+
+/// Address of the bitcoind RPC server.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Address {
+    /// A host name, IPv4 or IPv6 address with a port
+    Tcp { host: String, port: u16 },
+    /// A Unix domain socket, e.g. a local proxy in front of bitcoind
+    Unix(PathBuf),
+}
+
+/// Endpoint of the bitcoind RPC server.
+///
+/// Accepted forms are `host`, `host:port`, `[ipv6]:port` and the same with
+/// an `http://` scheme and an optional path (e.g. `/wallet/name`), along
+/// with `unix:/path/to/socket` for Unix domain sockets.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Endpoint {
+    address: Address,
+    path: String,
+}
+
+/// Pool of RPC clients handed out in turn so that concurrent fetches are
+/// spread over separate connections to the rpcthreads of bitcoind. The
+/// number of requests in flight at once is bounded by the permits.
+pub struct ClientPool {
+    clients: Vec<Arc<Client>>,
+    next: AtomicUsize,
+    permits: Arc<Semaphore>,
+    retry: RetryPolicy,
+}
+
+/// Policy of retrying requests failing transiently with an exponential
+/// backoff, randomized by the jitter so retries do not arrive in lockstep.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RetryPolicy {
+    retries: u32,
+    backoff: Duration,
+    jitter: f64,
+    failure: FailurePolicy,
+}
+
+/// Handling of a block still failing once out of retries.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FailurePolicy {
+    /// Leave the block out of the sample
+    Skip,
+    /// Abort the collection with the error
+    Abort,
+}
+
+/// JSON-RPC transport to bitcoind, the simple HTTP transport of jsonrpc
+/// over TCP unless it cannot reach the endpoint.
+#[derive(Debug)]
+pub enum RpcTransport {
+    /// Simple HTTP transport to a host name or IPv4 address
+    Simple(SimpleHttpTransport),
+    /// HTTP transport to a Unix domain socket or an IPv6 address, which the
+    /// simple HTTP transport cannot parse
+    Http(HttpTransport),
+}
+
+/// Backend answering the RPC calls of bitcoind in place of its RPC, e.g.
+/// from an Electrum server, so every analysis runs unchanged. The backend
+/// is the transport of an RPC client wrapped in an AnswerTransport.
+pub trait Answer: Send + Sync + 'static {
+    /// Answer the call of the method with its parameters. Failures of the
+    /// backend are errors of the transport so they are retried.
+    fn answer(&self, method: &str, params: &[Value]) -> io::Result<Answered>;
+
+    /// Answer the calls of a batch in order, one by one unless the backend
+    /// batches them itself.
+    fn answer_batch(&self, calls: &[(&str, Vec<Value>)]) -> io::Result<Vec<Answered>> {
+        calls
+            .iter()
+            .map(|(method, params)| self.answer(method, params))
+            .collect()
+    }
+
+    /// Format the target of the backend, see Transport::fmt_target.
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result;
+}
+
+/// Transport of the RPC client answering the requests with a backend.
+pub struct AnswerTransport<A>(pub A);
+
+/// HTTP transport of JSON-RPC requests to an endpoint, keeping the
+/// connection alive between requests. Also used for the REST interface of
+/// bitcoind, see get.
+#[derive(Debug)]
+pub struct HttpTransport {
+    endpoint: Endpoint,
+    authorization: Option<Zeroizing<String>>,
+    timeout: Duration,
+    /// Connection kept alive for the next request, held for the duration
+    /// of every request
+    connection: Mutex<Option<BufReader<Stream>>>,
+}
+
+/// Connection to the address of an endpoint.
+#[derive(Debug)]
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Endpoint {
+    /// Address of the server.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// HTTP path of the requests.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Value of the Host header of the requests, the port only when not the
+    /// default of HTTP and a placeholder for Unix domain sockets, which
+    /// have no host.
+    pub fn host(&self) -> String {
+        match &self.address {
+            Address::Tcp { host, port } => {
+                let host = match host.contains(':') {
+                    true => format!("[{}]", host),
+                    false => host.clone(),
+                };
+
+                match port {
+                    80 => host,
+                    port => format!("{}:{}", host, port),
+                }
+            }
+            Address::Unix(_) => String::from("localhost"),
+        }
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = StatisticsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let error = |reason: &str| StatisticsError::EndpointError {
+            endpoint: s.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if let Some(path) = s.strip_prefix("unix:") {
+            let path = path.trim_start_matches("//");
+
+            return match path.is_empty() {
+                true => Err(error("missing socket path")),
+                false => Ok(Endpoint {
+                    address: Address::Unix(PathBuf::from(path)),
+                    path: String::from("/"),
+                }),
+            };
+        }
+
+        let has_scheme = s.contains("://");
+        let url = match has_scheme {
+            true => Url::parse(s),
+            false => Url::parse(&format!("http://{}", s)),
+        }
+        .map_err(|e| error(&e.to_string()))?;
+
+        match url.scheme() {
+            "http" => {}
+            "https" => return Err(error("https is not supported, use a TLS terminating proxy")),
+            _ => return Err(error("scheme should be http or unix")),
+        }
+
+        let host = match url.host() {
+            Some(Host::Domain(domain)) => domain.to_string(),
+            Some(Host::Ipv4(ip)) => ip.to_string(),
+            Some(Host::Ipv6(ip)) => ip.to_string(),
+            None => return Err(error("missing host")),
+        };
+        let port = url.port().unwrap_or(match has_scheme {
+            true => 80,
+            false => DEFAULT_PORT,
+        });
+
+        Ok(Endpoint {
+            address: Address::Tcp { host, port },
+            path: url.path().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.address {
+            Address::Tcp { host, port } if host.contains(':') => {
+                write!(f, "http://[{}]:{}{}", host, port, self.path)
+            }
+            Address::Tcp { host, port } => write!(f, "http://{}:{}{}", host, port, self.path),
+            Address::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl ClientPool {
+    /// Create a new pool of the clients.
+    ///
+    /// Panics when no clients are provided.
+    pub fn new(clients: Vec<Client>) -> Self {
+        assert!(!clients.is_empty(), "A client pool requires a client");
+
+        ClientPool {
+            clients: clients.into_iter().map(Arc::new).collect(),
+            next: AtomicUsize::new(0),
+            permits: Arc::new(Semaphore::new(DEFAULT_CONCURRENCY)),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Retry the requests failing transiently with the policy.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Get the policy of retrying failed requests.
+    pub fn retry(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Bound the number of requests in flight at once.
+    ///
+    /// Panics when the limit is zero.
+    pub fn with_concurrency(mut self, limit: usize) -> Self {
+        assert!(
+            limit > 0,
+            "A client pool requires a concurrency of at least one"
+        );
+        self.permits = Arc::new(Semaphore::new(limit));
+        self
+    }
+
+    /// Get the permits bounding the requests in flight, a permit is held
+    /// for the duration of every request.
+    pub fn permits(&self) -> Arc<Semaphore> {
+        Arc::clone(&self.permits)
+    }
+
+    /// Number of clients in the pool.
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// A pool always holds at least one client.
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Get the next client of the pool.
+    pub fn get(&self) -> Arc<Client> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.clients.len();
+        Arc::clone(&self.clients[index])
+    }
+}
+
+impl Clone for ClientPool {
+    /// Clone the pool sharing the clients and the permits.
+    fn clone(&self) -> Self {
+        ClientPool {
+            clients: self.clients.clone(),
+            next: AtomicUsize::new(self.next.load(Ordering::Relaxed)),
+            permits: Arc::clone(&self.permits),
+            retry: self.retry,
+        }
+    }
+}
+
+impl From<Client> for ClientPool {
+    fn from(client: Client) -> Self {
+        ClientPool::new(vec![client])
+    }
+}
+
+impl RetryPolicy {
+    /// Create a new policy retrying a request up to the number of retries,
+    /// doubling the backoff after every retry. The jitter randomizes every
+    /// backoff by up to the fraction in either direction.
+    pub fn new(retries: u32, backoff: Duration, jitter: f64) -> Self {
+        RetryPolicy {
+            retries,
+            backoff,
+            jitter: jitter.clamp(0.0, 1.0),
+            failure: FailurePolicy::Skip,
+        }
+    }
+
+    /// Handle blocks still failing once out of retries with the policy.
+    pub fn with_failure(mut self, failure: FailurePolicy) -> Self {
+        self.failure = failure;
+        self
+    }
+
+    /// Get the handling of blocks still failing once out of retries.
+    pub fn failure(&self) -> FailurePolicy {
+        self.failure
+    }
+
+    /// Get the backoff before the retry, counted from 0, given a random
+    /// number between 0 and 1 for the jitter.
+    pub fn get_backoff(&self, retry: u32, random: f64) -> Duration {
+        let jitter = 1.0 + self.jitter * (2.0 * random - 1.0);
+        self.backoff
+            .mul_f64(2.0_f64.powi(retry.min(16) as i32) * jitter)
+    }
+
+    /// Run the request, retrying it while it fails transiently and retries
+    /// remain. The result of the last attempt is returned.
+    pub async fn run<T, F, R>(&self, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> R,
+        R: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+
+        loop {
+            match request().await {
+                Err(e) if retry < self.retries && is_transient(&e) => {
+                    tokio::time::sleep(self.get_backoff(retry, rand::random())).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl FromStr for FailurePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(FailurePolicy::Skip),
+            "abort" => Ok(FailurePolicy::Abort),
+            _ => Err(format!("Unknown failure policy: {}", s)),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Retry up to 3 times after 500ms, 1s and 2s, give or take half.
+    fn default() -> Self {
+        RetryPolicy::new(3, Duration::from_millis(500), 0.5)
+    }
+}
+
+impl RpcTransport {
+    /// Create a new transport to the endpoint authenticating with the
+    /// credentials when given.
+    pub fn new(endpoint: Endpoint, auth: Auth) -> Result<Self> {
+        match endpoint.address() {
+            Address::Tcp { host, port } if !host.contains(':') => {
+                let url = format!("{}:{}{}", host, port, endpoint.path());
+                let mut builder = SimpleHttpTransport::builder()
+                    .url(&url)
+                    .map_err(|e| StatisticsError::EndpointError {
+                        endpoint: endpoint.to_string(),
+                        reason: e.to_string(),
+                    })?
+                    .timeout(DEFAULT_TIMEOUT);
+
+                if let Some(credentials) = get_credentials(auth)? {
+                    builder = builder.cookie_auth(credentials.as_str());
+                }
+
+                Ok(RpcTransport::Simple(builder.build()))
+            }
+            _ => Ok(RpcTransport::Http(HttpTransport::new(endpoint, auth)?)),
+        }
+    }
+}
+
+impl HttpTransport {
+    /// Create a new transport to the endpoint authenticating with the
+    /// credentials when given.
+    pub fn new(endpoint: Endpoint, auth: Auth) -> Result<Self> {
+        let authorization = get_credentials(auth)?.map(|credentials| {
+            let encoded = Zeroizing::new(base64::encode(credentials.as_bytes()));
+            Zeroizing::new(format!("Basic {}", encoded.as_str()))
+        });
+
+        Ok(HttpTransport {
+            endpoint,
+            authorization,
+            timeout: DEFAULT_TIMEOUT,
+            connection: Mutex::new(None),
+        })
+    }
+
+    /// Post the JSON body to the endpoint and parse the JSON response.
+    fn request<T: Serialize, R: DeserializeOwned>(&self, body: &T) -> io::Result<R> {
+        let body = serde_json::to_vec(body)?;
+        // The request holds the credentials
+        let mut request = Zeroizing::new(
+            format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n",
+                self.endpoint.path,
+                self.endpoint.host(),
+                body.len()
+            )
+            .into_bytes(),
+        );
+
+        if let Some(authorization) = &self.authorization {
+            request.extend(b"Authorization: ");
+            request.extend(authorization.as_bytes());
+            request.extend(b"\r\n");
+        }
+
+        request.extend(b"\r\n");
+        request.extend(body);
+
+        let (status, body) = parse_response(&self.send(&request)?)?;
+
+        // Errors of the RPC are returned as JSON with an error status
+        serde_json::from_slice(&body).map_err(|e| match status {
+            200 => e.into(),
+            status => io::Error::other(format!("HTTP error {} from {}", status, self.endpoint)),
+        })
+    }
+
+    /// Get the resource at the path without authenticating, e.g. of the
+    /// REST interface of bitcoind, returning the status code and body.
+    pub fn get(&self, path: &str) -> io::Result<(u16, Vec<u8>)> {
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n",
+            path,
+            self.endpoint.host()
+        );
+        parse_response(&self.send(request.as_bytes())?)
+    }
+
+    /// Send the request to the endpoint and read its response over the
+    /// connection kept alive by the previous request, or a new connection.
+    /// A kept connection closed by the server while idle is only noticed
+    /// once used, so the request is sent again over a new connection when
+    /// the kept connection is found closed before any response.
+    fn send(&self, request: &[u8]) -> io::Result<Vec<u8>> {
+        let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        let kept = connection
+            .take()
+            .map(|mut stream| (exchange(&mut stream, request), stream));
+
+        let (result, stream) = match kept {
+            Some((Err(e), _)) if is_closed(&e) => {
+                let mut stream = self.connect()?;
+                (exchange(&mut stream, request), stream)
+            }
+            Some(kept) => kept,
+            None => {
+                let mut stream = self.connect()?;
+                (exchange(&mut stream, request), stream)
+            }
+        };
+        let (response, keep_alive) = result?;
+
+        if keep_alive {
+            *connection = Some(stream);
+        }
+
+        Ok(response)
+    }
+
+    /// Open a new connection to the endpoint.
+    fn connect(&self) -> io::Result<BufReader<Stream>> {
+        let stream = match &self.endpoint.address {
+            Address::Tcp { host, port } => {
+                let stream = self.connect_tcp(host, *port)?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                stream.set_nodelay(true)?;
+                Stream::Tcp(stream)
+            }
+            #[cfg(unix)]
+            Address::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                Stream::Unix(stream)
+            }
+            #[cfg(not(unix))]
+            Address::Unix(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "Unix domain sockets are not supported on this platform",
+                ))
+            }
+        };
+
+        Ok(BufReader::new(stream))
+    }
+
+    /// Connect to the first reachable address of the host.
+    fn connect_tcp(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+        let mut error = io::Error::new(io::ErrorKind::NotFound, "No address for the host");
+
+        for address in (host, port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error)
+    }
+}
+
+impl Transport for RpcTransport {
+    fn send_request(&self, request: Request) -> std::result::Result<Response, jsonrpc::Error> {
+        match self {
+            RpcTransport::Simple(transport) => transport.send_request(request),
+            RpcTransport::Http(transport) => transport.send_request(request),
+        }
+    }
+
+    fn send_batch(
+        &self,
+        requests: &[Request],
+    ) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+        match self {
+            RpcTransport::Simple(transport) => transport.send_batch(requests),
+            RpcTransport::Http(transport) => transport.send_batch(requests),
+        }
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcTransport::Simple(transport) => transport.fmt_target(f),
+            RpcTransport::Http(transport) => transport.fmt_target(f),
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send_request(&self, request: Request) -> std::result::Result<Response, jsonrpc::Error> {
+        self.request(&request)
+            .map_err(|e| jsonrpc::Error::Transport(Box::new(e)))
+    }
+
+    fn send_batch(
+        &self,
+        requests: &[Request],
+    ) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+        self.request(&requests)
+            .map_err(|e| jsonrpc::Error::Transport(Box::new(e)))
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.endpoint)
+    }
+}
+
+impl<A: Answer> Transport for AnswerTransport<A> {
+    fn send_request(&self, request: Request) -> std::result::Result<Response, jsonrpc::Error> {
+        self.send_batch(std::slice::from_ref(&request))
+            .map(|mut responses| responses.remove(0))
+    }
+
+    fn send_batch(
+        &self,
+        requests: &[Request],
+    ) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+        let calls = requests
+            .iter()
+            .map(|request| (request.method, get_params(request)))
+            .collect::<Vec<_>>();
+        let answers = self
+            .0
+            .answer_batch(&calls)
+            .map_err(|e| jsonrpc::Error::Transport(Box::new(e)))?;
+
+        Ok(requests
+            .iter()
+            .zip(answers)
+            .map(|(request, answer)| {
+                let (result, error) = match answer {
+                    Ok(result) => (Some(jsonrpc::arg(result)), None),
+                    Err(error) => (None, Some(error)),
+                };
+
+                Response {
+                    result,
+                    error,
+                    id: request.id.clone(),
+                    jsonrpc: request.jsonrpc.map(String::from),
+                }
+            })
+            .collect())
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_target(f)
+    }
+}
+
+/// Get the parameters of a request as JSON values.
+pub fn get_params(request: &Request) -> Vec<Value> {
+    request
+        .params
+        .iter()
+        .map(|param| serde_json::from_str(param.get()).unwrap_or_default())
+        .collect()
+}
+
+/// Get the RPC error of bitcoind with the code and message.
+pub fn get_rpc_error(code: i32, message: impl Into<String>) -> RpcError {
+    RpcError {
+        code,
+        message: message.into(),
+        data: None,
+    }
+}
+
+/// Get the user and password of the credentials as `user:pass`, cleared
+/// once dropped.
+fn get_credentials(auth: Auth) -> Result<Option<Zeroizing<String>>> {
+    let (user, pass) = auth.get_user_pass()?;
+    let (user, pass) = (user.map(Zeroizing::new), pass.map(Zeroizing::new));
+
+    Ok(user.map(|user| {
+        let pass = pass.as_ref().map_or("", |pass| pass.as_str());
+        Zeroizing::new(format!("{}:{}", user.as_str(), pass))
+    }))
+}
+
+/// Call the method once for every set of parameters in a single batched
+/// request, returning the results in the order of the parameters.
+pub fn call_batch<T: DeserializeOwned>(
+    client: &Client,
+    method: &str,
+    params: &[Vec<Box<RawValue>>],
+) -> Result<Vec<T>> {
+    if params.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let client = client.get_jsonrpc_client();
+    let requests = params
+        .iter()
+        .map(|params| client.build_request(method, params))
+        .collect::<Vec<_>>();
+    let mut results = vec![];
+
+    for response in client
+        .send_batch(&requests)
+        .map_err(bitcoincore_rpc::Error::from)?
+    {
+        let response = response.ok_or(jsonrpc::Error::WrongBatchResponseSize);
+        let result = response.and_then(|response| response.result::<T>());
+        results.push(result.map_err(bitcoincore_rpc::Error::from)?);
+    }
+
+    Ok(results)
+}
+
+/// Get the headers of the blocks at the heights with two batched requests,
+/// one for the block hashes and one for the headers.
+pub fn get_block_headers(client: &Client, heights: &[BlockHeight]) -> Result<Vec<BlockHeader>> {
+    let params = heights
+        .iter()
+        .map(|height| vec![jsonrpc::arg(height)])
+        .collect::<Vec<_>>();
+    let hashes = call_batch::<BlockHash>(client, "getblockhash", &params)?;
+
+    let params = hashes
+        .iter()
+        .map(|hash| vec![jsonrpc::arg(hash), jsonrpc::arg(false)])
+        .collect::<Vec<_>>();
+    let mut headers = vec![];
+
+    for header in call_batch::<String>(client, "getblockheader", &params)? {
+        let bytes = Vec::<u8>::from_hex(&header).map_err(bitcoincore_rpc::Error::from)?;
+        headers.push(deserialize(&bytes)?);
+    }
+
+    Ok(headers)
+}
+
+/// Check whether the error may pass when retried, a failure of the
+/// transport such as a timeout or a full work queue, or bitcoind warming up.
+pub fn is_transient(error: &StatisticsError) -> bool {
+    match error {
+        StatisticsError::ClientError(bitcoincore_rpc::Error::JsonRpc(error)) => match error {
+            jsonrpc::Error::Transport(_) => true,
+            jsonrpc::Error::Rpc(error) => error.code == RPC_IN_WARMUP,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Check whether the error of an exchange is the connection found closed by
+/// the server before any response.
+fn is_closed(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+    )
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Write the request to the stream and read a single response, see
+/// read_response. Only a connection found closed before any response fails
+/// with an error of a closed connection, see is_closed.
+fn exchange<S: Read + Write>(
+    stream: &mut BufReader<S>,
+    request: &[u8],
+) -> io::Result<(Vec<u8>, bool)> {
+    stream.get_mut().write_all(request)?;
+    stream.get_mut().flush()?;
+
+    if stream.fill_buf()?.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Connection closed before the response",
+        ));
+    }
+
+    read_response(stream).map_err(|e| match is_closed(&e) {
+        true => io::Error::new(io::ErrorKind::InvalidData, "Incomplete HTTP response"),
+        false => e,
+    })
+}
+
+/// Read a single HTTP response, by its content length or chunks, so the
+/// connection can carry the next request. Returns the response along with
+/// whether the connection is kept alive, a response delimited by closing
+/// the connection never is.
+fn read_response<S: Read>(stream: &mut BufReader<S>) -> io::Result<(Vec<u8>, bool)> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+
+    // Head of the response up to the empty line
+    let mut response = vec![];
+
+    while !response.ends_with(b"\r\n\r\n") {
+        if stream.read_until(b'\n', &mut response)? == 0 {
+            return Err(invalid("Incomplete HTTP response"));
+        }
+    }
+
+    let head = String::from_utf8_lossy(&response).to_lowercase();
+    let header = |name: &str| {
+        head.split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .find(|(header, _)| header.trim() == name)
+            .map(|(_, value)| value.trim().to_string())
+    };
+    let keep_alive =
+        head.starts_with("http/1.1") && header("connection").as_deref() != Some("close");
+
+    if header("transfer-encoding").as_deref() == Some("chunked") {
+        loop {
+            let start = response.len();
+            stream.read_until(b'\n', &mut response)?;
+            let size = String::from_utf8_lossy(&response[start..]).to_string();
+            let size = size.split(';').next().unwrap_or_default().trim();
+            let size =
+                usize::from_str_radix(size, 16).map_err(|_| invalid("Invalid chunk size"))?;
+
+            if size == 0 {
+                // Trailers up to the empty line closing the body
+                while !response.ends_with(b"\r\n\r\n") {
+                    if stream.read_until(b'\n', &mut response)? == 0 {
+                        return Err(invalid("Incomplete chunked body"));
+                    }
+                }
+
+                return Ok((response, keep_alive));
+            }
+
+            let start = response.len();
+            response.resize(start + size + 2, 0);
+            stream.read_exact(&mut response[start..])?;
+        }
+    }
+
+    match header("content-length").map(|length| length.parse::<usize>()) {
+        Some(Ok(length)) => {
+            let start = response.len();
+            response.resize(start + length, 0);
+            stream.read_exact(&mut response[start..])?;
+            Ok((response, keep_alive))
+        }
+        Some(Err(_)) => Err(invalid("Invalid HTTP content length")),
+        None => {
+            stream.read_to_end(&mut response)?;
+            Ok((response, false))
+        }
+    }
+}
+
+/// Parse an HTTP response returning the status code and body.
+fn parse_response(response: &[u8]) -> io::Result<(u16, Vec<u8>)> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+    let split = response
+        .windows(4)
+        .position(|bytes| bytes == b"\r\n\r\n")
+        .ok_or_else(|| invalid("Incomplete HTTP response"))?;
+    let head = String::from_utf8_lossy(&response[..split]).to_lowercase();
+    let body = &response[split + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .filter(|line| line.starts_with("http/1."))
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| invalid("Invalid HTTP status line"))?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .collect::<Vec<_>>();
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(header, _)| *header == name)
+            .map(|(_, value)| *value)
+    };
+
+    if header("transfer-encoding") == Some("chunked") {
+        return Ok((
+            status,
+            get_dechunked(body).ok_or_else(|| invalid("Invalid chunked body"))?,
+        ));
+    }
+
+    match header("content-length").map(str::parse::<usize>) {
+        Some(Ok(length)) if length <= body.len() => Ok((status, body[..length].to_vec())),
+        Some(_) => Err(invalid("Invalid HTTP content length")),
+        None => Ok((status, body.to_vec())),
+    }
+}
+
+/// Decode a chunked HTTP body.
+fn get_dechunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = vec![];
+
+    loop {
+        let line = body.windows(2).position(|bytes| bytes == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line + 2..];
+
+        if size == 0 {
+            return Some(decoded);
+        }
+
+        decoded.extend(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{net::TcpListener, thread};
+
+    fn tcp(host: &str, port: u16, path: &str) -> Endpoint {
+        Endpoint {
+            address: Address::Tcp {
+                host: host.to_string(),
+                port,
+            },
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_endpoint_from_str() {
+        let parse = |s: &str| s.parse::<Endpoint>().unwrap();
+        assert_eq!(parse("127.0.0.1:18443"), tcp("127.0.0.1", 18443, "/"));
+        assert_eq!(parse("localhost"), tcp("localhost", 8332, "/"));
+        assert_eq!(parse("[::1]:8332"), tcp("::1", 8332, "/"));
+        assert_eq!(parse("http://[fe80::1]"), tcp("fe80::1", 80, "/"));
+        assert_eq!(
+            parse("http://node:8332/wallet/cold"),
+            tcp("node", 8332, "/wallet/cold")
+        );
+        assert_eq!(
+            parse("unix:///run/bitcoind.sock").address(),
+            &Address::Unix(PathBuf::from("/run/bitcoind.sock"))
+        );
+        assert_eq!(parse("[::1]:8332").to_string(), "http://[::1]:8332/");
+        assert_eq!(parse("[::1]:8332").host(), "[::1]:8332");
+        assert_eq!(parse("http://[fe80::1]").host(), "[fe80::1]");
+        assert_eq!(parse("node.example:8332").host(), "node.example:8332");
+        assert_eq!(parse("unix:/run/bitcoind.sock").host(), "localhost");
+
+        assert!("https://node".parse::<Endpoint>().is_err());
+        assert!("::1:8332".parse::<Endpoint>().is_err());
+        assert!("node:port".parse::<Endpoint>().is_err());
+        assert!("unix:".parse::<Endpoint>().is_err());
+    }
+
+    #[test]
+    fn test_client_pool() {
+        let client = || Client::new("127.0.0.1:0", Auth::None).unwrap();
+        let pool = ClientPool::new(vec![client(), client(), client()]);
+        let first = pool.get();
+
+        assert_eq!(pool.len(), 3);
+        assert!(!Arc::ptr_eq(&first, &pool.get()));
+        assert!(!Arc::ptr_eq(&first, &pool.get()));
+        assert!(Arc::ptr_eq(&first, &pool.get()));
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy() {
+        let policy = RetryPolicy::new(2, Duration::from_millis(100), 0.5);
+        assert_eq!(policy.get_backoff(0, 0.5), Duration::from_millis(100));
+        assert_eq!(policy.get_backoff(2, 0.5), Duration::from_millis(400));
+        assert_eq!(policy.get_backoff(1, 0.0), Duration::from_millis(100));
+        assert_eq!(policy.get_backoff(1, 1.0), Duration::from_millis(300));
+
+        let transient = || {
+            let error = io::Error::new(io::ErrorKind::TimedOut, "timed out");
+            let error = jsonrpc::Error::Transport(Box::new(error));
+            StatisticsError::ClientError(bitcoincore_rpc::Error::JsonRpc(error))
+        };
+        let policy = RetryPolicy::new(2, Duration::from_millis(1), 0.5);
+        let attempts = AtomicUsize::new(0);
+        let request = || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(transient()),
+                attempt => Ok(attempt),
+            }
+        };
+        assert_eq!(policy.run(request).await.unwrap(), 2);
+
+        attempts.store(0, Ordering::SeqCst);
+        let policy = RetryPolicy::new(1, Duration::from_millis(1), 0.5);
+        assert!(policy.run(request).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        attempts.store(0, Ordering::SeqCst);
+        let request = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(StatisticsError::HeightRangeError(String::new()))
+        };
+        assert!(policy.run(request).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}\n";
+        assert_eq!(parse_response(response).unwrap(), (200, b"{}".to_vec()));
+
+        let response =
+            b"HTTP/1.1 500 Internal Server Error\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n{\"a\r\n2\r\n\"}\r\n0\r\n\r\n";
+        assert_eq!(
+            parse_response(response).unwrap(),
+            (500, b"{\"a\"}".to_vec())
+        );
+
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+        assert!(parse_response(b"SSH-2.0\r\n\r\n").is_err());
+    }
+
+    /// Read a request with a body of its content length from the stream.
+    fn read_request<S: Read>(stream: &mut BufReader<S>) -> Option<String> {
+        let mut head = String::new();
+
+        while !head.ends_with("\r\n\r\n") {
+            if stream.read_line(&mut head).ok()? == 0 {
+                return None;
+            }
+        }
+
+        let length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("Content-Length: "))
+            .map_or(0, |length| length.parse().unwrap());
+        let mut body = vec![0; length];
+        stream.read_exact(&mut body).ok()?;
+        Some(format!("{}{}", head, String::from_utf8_lossy(&body)))
+    }
+
+    #[test]
+    fn test_http_transport() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let mut requests = vec![];
+
+            // Two requests over the kept connection, the third after the
+            // connection was closed while idle over a new one
+            for served in [2, 1] {
+                let (stream, _) = listener.accept().unwrap();
+                let mut stream = BufReader::new(stream);
+
+                for _ in 0..served {
+                    let request = read_request(&mut stream).unwrap();
+                    let json = &request[request.find("\r\n\r\n").unwrap() + 4..];
+                    let id = serde_json::from_str::<serde_json::Value>(json).unwrap()["id"].clone();
+                    let body =
+                        serde_json::json!({"result": 7, "error": null, "id": id}).to_string();
+                    requests.push(request);
+                    write!(
+                        stream.get_mut(),
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                }
+            }
+
+            requests
+        });
+
+        let endpoint = format!("127.0.0.1:{}", port).parse().unwrap();
+        let auth = Auth::UserPass(String::from("user"), String::from("pass"));
+        let client = jsonrpc::Client::with_transport(HttpTransport::new(endpoint, auth).unwrap());
+
+        for _ in 0..3 {
+            let result: u64 = client.call("getblockcount", &[]).unwrap();
+            assert_eq!(result, 7);
+        }
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].starts_with("POST / HTTP/1.1\r\n"));
+        assert!(requests[0].contains(&format!("Host: 127.0.0.1:{}\r\n", port)));
+        assert!(requests[0].contains("Authorization: Basic dXNlcjpwYXNz\r\n"));
+        assert!(!requests[0].contains("Connection: close"));
+    }
+
+    #[test]
+    fn test_get_block_headers() {
+        let header = BlockHeader {
+            version: 1,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time: 1_600_000_000,
+            bits: 0x1d00ffff,
+            nonce: 0,
+        };
+        let header_hex = bitcoincore_rpc::bitcoin::consensus::encode::serialize_hex(&header);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let mut methods = vec![];
+
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 4096];
+                let read = stream.read(&mut request).unwrap();
+                let body = String::from_utf8_lossy(&request[..read]);
+                let body = &body[body.find("\r\n\r\n").unwrap() + 4..];
+                let requests: Vec<serde_json::Value> = serde_json::from_str(body).unwrap();
+                let responses = requests
+                    .iter()
+                    .map(|request| {
+                        let result = match request["method"].as_str() {
+                            Some("getblockhash") => serde_json::json!(BlockHash::default()),
+                            _ => serde_json::json!(header_hex),
+                        };
+                        serde_json::json!({"result": result, "error": null, "id": request["id"]})
+                    })
+                    .collect::<Vec<_>>();
+                let body = serde_json::to_string(&responses).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                methods.push((requests[0]["method"].clone(), requests.len()));
+            }
+
+            methods
+        });
+
+        let endpoint = format!("127.0.0.1:{}", port).parse().unwrap();
+        let transport = HttpTransport::new(endpoint, Auth::None).unwrap();
+        let client = Client::from_jsonrpc(jsonrpc::Client::with_transport(transport));
+
+        assert_eq!(
+            get_block_headers(&client, &[BlockHeight(1), BlockHeight(2), BlockHeight(3)]).unwrap(),
+            vec![header; 3]
+        );
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                (serde_json::json!("getblockhash"), 3),
+                (serde_json::json!("getblockheader"), 3)
+            ]
+        );
+        assert!(get_block_headers(&client, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rpc_transport() {
+        let transport = |endpoint: &str| RpcTransport::new(endpoint.parse().unwrap(), Auth::None);
+
+        assert!(matches!(
+            transport("127.0.0.1:8332").unwrap(),
+            RpcTransport::Simple(_)
+        ));
+        assert!(matches!(
+            transport("http://localhost:8332/wallet/name").unwrap(),
+            RpcTransport::Simple(_)
+        ));
+        assert!(matches!(
+            transport("[::1]:8332").unwrap(),
+            RpcTransport::Http(_)
+        ));
+        assert!(matches!(
+            transport("unix:/run/bitcoind.sock").unwrap(),
+            RpcTransport::Http(_)
+        ));
+    }
+
+    #[test]
+    fn test_cookie_file_auth() {
+        let path = std::env::temp_dir().join("bitcoin-statistics-test.cookie");
+        std::fs::write(&path, "__cookie__:secret").unwrap();
+
+        let endpoint = "127.0.0.1:8332".parse::<Endpoint>().unwrap();
+        let transport = HttpTransport::new(endpoint.clone(), Auth::CookieFile(path.clone()));
+        assert_eq!(
+            transport
+                .unwrap()
+                .authorization
+                .as_deref()
+                .map(String::as_str),
+            Some("Basic X19jb29raWVfXzpzZWNyZXQ=")
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(HttpTransport::new(endpoint, Auth::CookieFile(path)).is_err());
+    }
+}
@@ -1,19 +1,67 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+
 /// Get the mean from vec of numbers.
 pub fn get_mean(nums: &[f64]) -> f64 {
     nums.iter().sum::<f64>() / nums.len() as f64
 }
 
-/// Calculate standard deviation from vec of numbers.
-pub fn get_standard_deviation(nums: &[f64], sample: bool) -> f64 {
+/// Get the median from vec of numbers.
+pub fn get_median(nums: &[f64]) -> f64 {
+    let mut sorted = nums.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+
+    if sorted.is_empty() {
+        f64::NAN
+    } else if sorted.len() % 2 == 1 {
+        sorted[mid]
+    } else {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    }
+}
+
+/// Get the percentile (0 to 100) from vec of numbers using linear
+/// interpolation between the closest ranks.
+pub fn get_percentile(nums: &[f64], percentile: f64) -> f64 {
+    get_percentiles(nums, &[percentile])[0]
+}
+
+/// Get the percentiles (0 to 100) from vec of numbers sorting them once,
+/// see get_percentile.
+pub fn get_percentiles(nums: &[f64], percentiles: &[f64]) -> Vec<f64> {
+    let mut sorted = nums.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    percentiles
+        .iter()
+        .map(|percentile| {
+            if sorted.is_empty() {
+                return f64::NAN;
+            }
+
+            let rank = percentile / 100.0 * (sorted.len() - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+
+            sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+        })
+        .collect()
+}
+
+/// Calculate variance from vec of numbers.
+pub fn get_variance(nums: &[f64], sample: bool) -> f64 {
     let len = nums.len() as f64;
-    let len = if sample { len - 1.0 } else { len as f64 };
+    let len = if sample { len - 1.0 } else { len };
     let mean = get_mean(nums);
     let iter = nums.iter();
     // Sum of Squares
     let sos = iter.fold(0.0, |sum, n| (n - mean).powf(2.0) + sum);
-    let variance = sos / (len) as f64;
 
-    (variance.sqrt() * 100.0).round() / 100.0
+    sos / len
+}
+
+/// Calculate standard deviation from vec of numbers.
+pub fn get_standard_deviation(nums: &[f64], sample: bool) -> f64 {
+    (get_variance(nums, sample).sqrt() * 100.0).round() / 100.0
 }
 
 /// Calculate poisson probability.
@@ -21,11 +69,516 @@ pub fn get_poisson_probability(lambda: f64, interval: f64) -> f64 {
     1.0 / (lambda * std::f64::consts::E.powf(interval * lambda))
 }
 
+/// Calculate the Wilson score confidence interval of a binomial proportion
+/// given the number of successes out of n trials and the z-score.
+pub fn get_wilson_interval(successes: usize, n: usize, z: f64) -> (f64, f64) {
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+
+    let n = n as f64;
+    let p = successes as f64 / n;
+    let z2 = z.powf(2.0);
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = z * (p * (1.0 - p) / n + z2 / (4.0 * n.powf(2.0))).sqrt() / (1.0 + z2 / n);
+
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+/// Calculate the relative standard error of a binomial proportion given the
+/// number of successes out of n trials, infinite without any success.
+pub fn get_relative_standard_error(successes: usize, n: usize) -> f64 {
+    if successes == 0 || n == 0 {
+        return f64::INFINITY;
+    }
+
+    let p = successes as f64 / n as f64;
+    ((1.0 - p) / (n as f64 * p)).sqrt()
+}
+
+/// Calculate the normal confidence interval of the mean of a sample given
+/// the z-score.
+pub fn get_mean_interval(nums: &[f64], z: f64) -> (f64, f64) {
+    let mean = get_mean(nums);
+
+    if nums.len() < 2 {
+        return (mean, mean);
+    }
+
+    let n = nums.len() as f64;
+    let variance = nums.iter().map(|n| (n - mean).powf(2.0)).sum::<f64>() / (n - 1.0);
+    let margin = z * (variance / n).sqrt();
+
+    (mean - margin, mean + margin)
+}
+
+/// Allocate the count to parts in proportion to their sizes, the parts with
+/// the largest remainders rounding up so the allocations sum to the count.
+pub fn get_proportional_allocation(sizes: &[u64], count: u64) -> Vec<u64> {
+    let total = sizes.iter().sum::<u64>();
+
+    if total == 0 {
+        return vec![0; sizes.len()];
+    }
+
+    let mut allocation = sizes
+        .iter()
+        .map(|size| count * size / total)
+        .collect::<Vec<_>>();
+    let mut remainders = sizes
+        .iter()
+        .enumerate()
+        .map(|(i, size)| (count * size % total, i))
+        .collect::<Vec<_>>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let left = count - allocation.iter().sum::<u64>();
+    remainders
+        .iter()
+        .take(left as usize)
+        .for_each(|(_, i)| allocation[*i] += 1);
+
+    allocation
+}
+
+/// Calculate the autocorrelation of runs of numbers at every lag from 1 to
+/// the given lag, pooling the pairs of numbers the lag apart within every
+/// run around the mean and variance of all numbers. Returns the number of
+/// pairs and the autocorrelation of every lag, NaN without pairs.
+pub fn get_autocorrelations(runs: &[Vec<f64>], lags: usize) -> Vec<(usize, f64)> {
+    let nums = runs.concat();
+    let mean = get_mean(&nums);
+    let variance = get_variance(&nums, false);
+
+    (1..=lags)
+        .map(|lag| {
+            let products = runs
+                .iter()
+                .flat_map(|run| run.iter().zip(run.iter().skip(lag)))
+                .map(|(a, b)| (a - mean) * (b - mean))
+                .collect::<Vec<_>>();
+
+            (products.len(), get_mean(&products) / variance)
+        })
+        .collect()
+}
+
+/// Calculate the rolling means and medians over every run of the given
+/// number of consecutive numbers, empty when there are fewer numbers.
+pub fn get_rolling(nums: &[f64], n: usize) -> Vec<(f64, f64)> {
+    nums.windows(n.max(1))
+        .map(|run| (get_mean(run), get_median(run)))
+        .collect()
+}
+
+/// Calculate the inverse of the standard normal cumulative distribution
+/// function (quantile) of the probability using the rational approximation
+/// of Acklam, accurate to about 1e-9.
+/// See https://web.archive.org/web/20151030215612/http://home.online.no/~pjacklam/notes/invnorm/
+pub fn get_inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    } else if p >= 1.0 {
+        return f64::INFINITY;
+    }
+
+    // Evaluate the polynomials of the coefficients by Horner's method
+    let poly = |coefficients: &[f64], x: f64| coefficients.iter().fold(0.0, |sum, c| sum * x + c);
+    let tail = |p: f64| {
+        let q = (-2.0 * p.ln()).sqrt();
+        poly(&C, q) / (poly(&D, q) * q + 1.0)
+    };
+
+    if p < P_LOW {
+        tail(p)
+    } else if p > 1.0 - P_LOW {
+        -tail(1.0 - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        poly(&A, r) * q / (poly(&B, r) * r + 1.0)
+    }
+}
+
+/// Calculate the two-sided z-score of the confidence level, e.g. 1.96 for
+/// a confidence of 0.95.
+pub fn get_z_score(confidence: f64) -> f64 {
+    get_inverse_normal_cdf(1.0 - (1.0 - confidence) / 2.0)
+}
+
+/// Calculate the standard normal cumulative distribution function using the
+/// approximation of the error function by Abramowitz and Stegun (7.1.26),
+/// accurate to about 1e-7.
+pub fn get_normal_cdf(x: f64) -> f64 {
+    const P: f64 = 0.3275911;
+    const A: [f64; 5] = [
+        0.254829592,
+        -0.284496736,
+        1.421413741,
+        -1.453152027,
+        1.061405429,
+    ];
+    let z = x.abs() / 2.0_f64.sqrt();
+    let t = 1.0 / (1.0 + P * z);
+    let polynomial = A.iter().rev().fold(0.0, |sum, a| (sum + a) * t);
+    let erf = 1.0 - polynomial * (-z * z).exp();
+
+    0.5 * (1.0 + erf.copysign(x))
+}
+
+/// Calculate the two-sided confidence level of the z-score, the inverse of
+/// get_z_score, e.g. 0.95 for a z-score of 1.96.
+pub fn get_confidence(z_score: f64) -> f64 {
+    2.0 * get_normal_cdf(z_score) - 1.0
+}
+
+/// Calculate the mining difficulty from the compact target bits of a block.
+pub fn get_difficulty(bits: u32) -> f64 {
+    let target = |bits: u32| {
+        let exponent = (bits >> 24) as i32;
+        (bits & 0x00ffffff) as f64 * 256.0_f64.powi(exponent - 3)
+    };
+
+    // Relative to the proof of work limit target
+    target(0x1d00ffff) / target(bits)
+}
+
+/// Estimate the hashrate in hashes per second from the difficulty and the
+/// mean seconds between blocks, a difficulty of 1 taking 2^32 hashes.
+pub fn get_hashrate(difficulty: f64, mean_interval: f64) -> f64 {
+    difficulty * 2.0_f64.powi(32) / mean_interval.max(1.0)
+}
+
+/// Format a unix timestamp as a UTC date and time.
+pub fn get_date(timestamp: u32) -> String {
+    DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Get the UTC year of a unix timestamp.
+pub fn get_year(timestamp: u32) -> i32 {
+    DateTime::from_timestamp(timestamp as i64, 0).map_or(1970, |date| date.year())
+}
+
+/// Parse a UTC day (e.g. 2023-01-01) or a unix timestamp into a unix
+/// timestamp, the day starting at midnight.
+pub fn parse_date(date: &str) -> Option<u32> {
+    let date = date.trim();
+
+    match date.parse::<u32>() {
+        Ok(timestamp) => Some(timestamp),
+        Err(_) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.and_utc().timestamp() as u32),
+    }
+}
+
+/// Get the current unix timestamp in seconds.
+pub fn get_timestamp() -> u64 {
+    Utc::now().timestamp().max(0) as u64
+}
+
 /// Round a float to the specified precision
 pub fn get_rounded_by(num: f64, precision: u8) -> f64 {
     (num * 10.0_f64.powf(precision as f64)).round() / 10.0_f64.powf(precision as f64)
 }
 
+/// Calculate the Pearson correlation coefficient of paired numbers.
+pub fn get_pearson_correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    let mean_x = get_mean(xs);
+    let mean_y = get_mean(ys);
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+
+    for (x, y) in xs.iter().zip(ys) {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powf(2.0);
+        var_y += (y - mean_y).powf(2.0);
+    }
+
+    cov / (var_x * var_y).sqrt()
+}
+
+/// Fit a least squares line through paired numbers returning the slope,
+/// intercept and coefficient of determination (R²).
+pub fn get_linear_regression(xs: &[f64], ys: &[f64]) -> (f64, f64, f64) {
+    let mean_x = get_mean(xs);
+    let mean_y = get_mean(ys);
+    let (mut cov, mut var_x) = (0.0, 0.0);
+
+    for (x, y) in xs.iter().zip(ys) {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powf(2.0);
+    }
+
+    let slope = cov / var_x;
+    let intercept = mean_y - slope * mean_x;
+
+    (slope, intercept, get_pearson_correlation(xs, ys).powf(2.0))
+}
+
+/// Calculate the Spearman rank correlation coefficient of paired numbers.
+pub fn get_spearman_correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    get_pearson_correlation(&get_ranks(xs), &get_ranks(ys))
+}
+
+/// Get the rank of every number, ties receive the average of their ranks.
+pub fn get_ranks(nums: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..nums.len()).collect();
+    order.sort_by(|a, b| nums[*a].total_cmp(&nums[*b]));
+
+    let mut ranks = vec![0.0; nums.len()];
+    let mut i = 0;
+
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && nums[order[j + 1]] == nums[order[i]] {
+            j += 1;
+        }
+        // Ranks are 1-based
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        order[i..=j].iter().for_each(|index| ranks[*index] = rank);
+        i = j + 1;
+    }
+
+    ranks
+}
+
+/// Calculate the two-tailed p-value of a correlation coefficient over n
+/// pairs using the Student's t-distribution with n - 2 degrees of freedom.
+pub fn get_correlation_p_value(r: f64, n: usize) -> f64 {
+    if n < 3 {
+        return 1.0;
+    }
+
+    let df = (n - 2) as f64;
+    let t2 = r.powf(2.0) * df / (1.0 - r.powf(2.0)).max(f64::EPSILON);
+
+    get_incomplete_beta(df / (df + t2), df / 2.0, 0.5)
+}
+
+/// Calculate the natural log of the gamma function (Lanczos approximation).
+pub fn get_ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |sum, (i, c)| {
+            sum + c / (x + 1.0 + i as f64)
+        });
+
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Calculate the regularized incomplete beta function I_x(a, b).
+pub fn get_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    } else if x >= 1.0 {
+        return 1.0;
+    }
+
+    let ln_front =
+        get_ln_gamma(a + b) - get_ln_gamma(a) - get_ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+
+    // The continued fraction converges quickly on this side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        ln_front.exp() * get_beta_fraction(x, a, b) / a
+    } else {
+        1.0 - ln_front.exp() * get_beta_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Evaluate the continued fraction of the incomplete beta function using
+/// the modified Lentz method.
+fn get_beta_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    d = 1.0 / if d.abs() < TINY { TINY } else { d };
+    let mut h = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+        let mut step = |numerator: f64| {
+            d = 1.0 + numerator * d;
+            d = 1.0 / if d.abs() < TINY { TINY } else { d };
+            c = 1.0 + numerator / c;
+            c = if c.abs() < TINY { TINY } else { c };
+            c * d
+        };
+
+        h *= step(m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)));
+        let delta = step(-(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)));
+        h *= delta;
+
+        if (delta - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+
+    h
+}
+
+/// Calculate the cumulative distribution function of the gamma distribution
+/// of the shape and scale at x, the regularized lower incomplete gamma
+/// function by its series expansion.
+pub fn get_gamma_cdf(x: f64, shape: f64, scale: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    let x = x / scale;
+    let mut term = 1.0 / shape;
+    let mut sum = term;
+
+    for n in 1..1000 {
+        term *= x / (shape + n as f64);
+        sum += term;
+
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+
+    (sum * (shape * x.ln() - x - get_ln_gamma(shape)).exp()).min(1.0)
+}
+
+/// Calculate the p-value of the chi-square statistic of the degrees of
+/// freedom, the upper tail of the chi-square distribution.
+pub fn get_chi_square_p_value(statistic: f64, degrees: usize) -> f64 {
+    1.0 - get_gamma_cdf(statistic, degrees as f64 / 2.0, 2.0)
+}
+
+/// Run the chi-square goodness of fit test of the numbers against the
+/// exponential distribution of their mean. The numbers are binned into up
+/// to max_bins bins of equal expected counts of at least 5, numbers below
+/// zero falling into the first bin. Returns the statistic with its degrees
+/// of freedom and p-value, none without the numbers for 3 bins.
+pub fn get_exponential_chi_square(nums: &[f64], max_bins: usize) -> Option<(f64, usize, f64)> {
+    let bins = (nums.len() / 5).min(max_bins);
+    let mean = get_mean(nums);
+
+    if bins < 3 || mean <= 0.0 {
+        return None;
+    }
+
+    let mut counts = vec![0; bins];
+    nums.iter().for_each(|num| {
+        // Share of exponential numbers below the number
+        let share = 1.0 - (-num.max(0.0) / mean).exp();
+        counts[((share * bins as f64) as usize).min(bins - 1)] += 1;
+    });
+
+    let expected = nums.len() as f64 / bins as f64;
+    let statistic = counts
+        .iter()
+        .map(|count| (*count as f64 - expected).powi(2) / expected)
+        .sum::<f64>();
+    // A degree is lost to the total and another to the estimated mean
+    let degrees = bins - 2;
+
+    Some((
+        statistic,
+        degrees,
+        get_chi_square_p_value(statistic, degrees),
+    ))
+}
+
+/// Calculate the p-value of the Kolmogorov-Smirnov statistic of n numbers,
+/// the upper tail of the Kolmogorov distribution with the correction of
+/// Stephens for small samples.
+pub fn get_kolmogorov_smirnov_p_value(statistic: f64, n: usize) -> f64 {
+    let root = (n as f64).sqrt();
+    let lambda = (root + 0.12 + 0.11 / root) * statistic;
+
+    // The series converges too slowly below, where the tail is 1
+    if lambda < 0.2 {
+        return 1.0;
+    }
+
+    let sum = (1..=100)
+        .map(|j| {
+            let sign = if j % 2 == 1 { 1.0 } else { -1.0 };
+            sign * (-2.0 * (j * j) as f64 * lambda * lambda).exp()
+        })
+        .sum::<f64>();
+
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// Run the Kolmogorov-Smirnov test of the numbers against the exponential
+/// distribution of their mean, the largest distance between the empirical
+/// distribution of the numbers and the exponential one. Returns the
+/// statistic D with its p-value, none without numbers. The mean being
+/// estimated from the numbers, the p-value is conservative.
+pub fn get_exponential_kolmogorov_smirnov(nums: &[f64]) -> Option<(f64, f64)> {
+    let mean = get_mean(nums);
+
+    if nums.is_empty() || mean <= 0.0 {
+        return None;
+    }
+
+    let mut sorted = nums.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let n = sorted.len() as f64;
+    let statistic = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, num)| {
+            let cdf = 1.0 - (-num.max(0.0) / mean).exp();
+            // The empirical distribution steps from i / n to (i + 1) / n
+            ((i + 1) as f64 / n - cdf).max(cdf - i as f64 / n)
+        })
+        .fold(0.0, f64::max);
+
+    Some((
+        statistic,
+        get_kolmogorov_smirnov_p_value(statistic, sorted.len()),
+    ))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -40,6 +593,8 @@ mod test {
     fn test_get_standard_deviation() {
         let data = vec![1.0, 2.0, 3.0, 4.0, 5.0];
         assert_eq!(get_standard_deviation(&data, true), 1.58);
+        assert_eq!(get_variance(&data, true), 2.5);
+        assert_eq!(get_variance(&data, false), 2.0);
         assert_eq!(get_standard_deviation(&data, false), 1.41);
     }
 
@@ -48,9 +603,232 @@ mod test {
         assert_eq!(get_poisson_probability(6.0, -2.0).round(), 27126.0);
     }
 
+    #[test]
+    fn test_get_correlations() {
+        let xs = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let ys = vec![2.0, 4.0, 6.0, 8.0, 11.0];
+        assert_eq!(get_rounded_by(get_pearson_correlation(&xs, &ys), 4), 0.9959);
+        assert_eq!(get_spearman_correlation(&xs, &ys), 1.0);
+        assert_eq!(get_ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+    }
+
+    #[test]
+    fn test_get_median() {
+        assert_eq!(get_median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(get_median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert!(get_median(&[]).is_nan());
+    }
+
+    #[test]
+    fn test_get_percentile() {
+        let nums = [15.0, 20.0, 35.0, 40.0, 50.0];
+        assert_eq!(get_percentile(&nums, 0.0), 15.0);
+        assert_eq!(get_percentile(&nums, 40.0), 29.0);
+        assert_eq!(get_percentile(&nums, 100.0), 50.0);
+        assert!(get_percentile(&[], 50.0).is_nan());
+    }
+
+    #[test]
+    fn test_get_percentiles() {
+        let nums = (0..=200).rev().map(f64::from).collect::<Vec<_>>();
+        assert_eq!(
+            get_percentiles(&nums, &[50.0, 90.0, 95.0, 99.0]),
+            vec![100.0, 180.0, 190.0, 198.0]
+        );
+        assert!(get_percentiles(&[], &[50.0])[0].is_nan());
+    }
+
+    #[test]
+    fn test_get_linear_regression() {
+        let xs = vec![1.0, 2.0, 3.0, 4.0];
+        let ys = vec![3.0, 5.0, 7.0, 9.0];
+        assert_eq!(get_linear_regression(&xs, &ys), (2.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_get_correlation_p_value() {
+        assert_eq!(get_rounded_by(get_correlation_p_value(0.5, 20), 4), 0.0248);
+        assert_eq!(get_rounded_by(get_correlation_p_value(0.0, 20), 4), 1.0);
+        assert_eq!(
+            get_rounded_by(get_incomplete_beta(0.5, 2.0, 3.0), 4),
+            0.6875
+        );
+    }
+
+    #[test]
+    fn test_get_gamma_cdf() {
+        assert_eq!(get_rounded_by(get_gamma_cdf(1.0, 2.0, 1.0), 4), 0.2642);
+        assert_eq!(get_rounded_by(get_gamma_cdf(60.0, 1.0, 600.0), 4), 0.0952);
+        assert_eq!(get_gamma_cdf(0.0, 1.0, 600.0), 0.0);
+    }
+
+    #[test]
+    fn test_get_wilson_interval() {
+        let (low, high) = get_wilson_interval(30, 100, 1.96);
+        assert_eq!(get_rounded_by(low, 4), 0.2189);
+        assert_eq!(get_rounded_by(high, 4), 0.3959);
+        assert_eq!(get_wilson_interval(0, 0, 1.96), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_get_relative_standard_error() {
+        assert_eq!(
+            get_rounded_by(get_relative_standard_error(3, 1000), 4),
+            0.5765
+        );
+        assert_eq!(get_relative_standard_error(10, 10), 0.0);
+        assert!(get_relative_standard_error(0, 1000).is_infinite());
+    }
+
+    #[test]
+    fn test_get_mean_interval() {
+        let (low, high) = get_mean_interval(&[2.0, 4.0, 6.0, 8.0], 1.96);
+        assert_eq!(get_rounded_by(low, 4), 2.4697);
+        assert_eq!(get_rounded_by(high, 4), 7.5303);
+        assert_eq!(get_mean_interval(&[3.0], 1.96), (3.0, 3.0));
+    }
+
+    #[test]
+    fn test_get_rolling() {
+        let nums = [1.0, 5.0, 3.0, 4.0];
+        assert_eq!(get_rolling(&nums, 3), vec![(3.0, 3.0), (4.0, 4.0)]);
+        assert_eq!(get_rolling(&nums, 1)[1], (5.0, 5.0));
+        assert!(get_rolling(&nums, 5).is_empty());
+    }
+
+    #[test]
+    fn test_get_z_score() {
+        assert_eq!(get_rounded_by(get_inverse_normal_cdf(0.5), 6), 0.0);
+        assert_eq!(get_rounded_by(get_inverse_normal_cdf(0.01), 4), -2.3263);
+        assert_eq!(get_rounded_by(get_z_score(0.95), 3), 1.96);
+        assert_eq!(get_rounded_by(get_z_score(0.99), 3), 2.576);
+        assert_eq!(get_rounded_by(get_z_score(0.90), 3), 1.645);
+        assert_eq!(get_rounded_by(get_z_score(0.999), 3), 3.291);
+    }
+
+    #[test]
+    fn test_get_confidence() {
+        assert_eq!(get_rounded_by(get_normal_cdf(0.0), 6), 0.5);
+        assert_eq!(get_rounded_by(get_normal_cdf(-2.3263), 4), 0.01);
+        assert_eq!(get_rounded_by(get_confidence(1.96), 3), 0.95);
+        assert_eq!(get_rounded_by(get_confidence(get_z_score(0.99)), 6), 0.99);
+        assert_eq!(get_rounded_by(get_confidence(0.0), 6), 0.0);
+    }
+
+    #[test]
+    fn test_get_difficulty() {
+        assert_eq!(get_difficulty(0x1d00ffff), 1.0);
+        assert_eq!(get_rounded_by(get_difficulty(0x1b0404cb), 2), 16307.42);
+    }
+
+    #[test]
+    fn test_get_hashrate() {
+        assert_eq!(get_hashrate(1.0, 600.0).round(), 7158279.0);
+        assert_eq!(
+            get_rounded_by(get_hashrate(16307.42, 600.0) / 1e9, 2),
+            116.73
+        );
+    }
+
+    #[test]
+    fn test_get_date() {
+        assert_eq!(get_date(1231006505), "2009-01-03 18:15");
+    }
+
+    #[test]
+    fn test_get_year() {
+        assert_eq!(get_year(1231006505), 2009);
+        assert_eq!(get_year(1704067199), 2023);
+        assert_eq!(get_year(1704067200), 2024);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2009-01-03"), Some(1230940800));
+        assert_eq!(parse_date("1231006505"), Some(1231006505));
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
     #[test]
     fn test_get_rounded_by() {
         assert_eq!(get_rounded_by(10.467864583333325, 2), 10.47);
         assert_eq!(get_rounded_by(10.467864583333325, 5), 10.46786);
     }
+
+    #[test]
+    fn test_get_chi_square_p_value() {
+        assert_eq!(get_rounded_by(get_chi_square_p_value(3.841, 1), 3), 0.05);
+        assert_eq!(get_rounded_by(get_chi_square_p_value(18.307, 10), 3), 0.05);
+        assert_eq!(get_chi_square_p_value(0.0, 3), 1.0);
+    }
+
+    #[test]
+    fn test_get_exponential_chi_square() {
+        // Quantiles of the exponential distribution fit it perfectly
+        let nums = (0..100)
+            .map(|i| -10.0 * (1.0 - (i as f64 + 0.5) / 100.0).ln())
+            .collect::<Vec<_>>();
+        let (statistic, degrees, p_value) = get_exponential_chi_square(&nums, 10).unwrap();
+        assert!(statistic < 1.0);
+        assert_eq!(degrees, 8);
+        assert!(p_value > 0.99);
+
+        // Regular intervals do not
+        let (_, _, p_value) = get_exponential_chi_square(&[10.0; 100], 10).unwrap();
+        assert!(p_value < 1e-6);
+        assert!(get_exponential_chi_square(&[10.0; 14], 10).is_none());
+    }
+
+    #[test]
+    fn test_get_kolmogorov_smirnov_p_value() {
+        // Critical values of the Kolmogorov distribution
+        let scale = 100.0 + 0.12 + 0.0011;
+        let p_value = get_kolmogorov_smirnov_p_value(1.358 / scale, 10_000);
+        assert_eq!(get_rounded_by(p_value, 3), 0.05);
+        let p_value = get_kolmogorov_smirnov_p_value(1.628 / scale, 10_000);
+        assert_eq!(get_rounded_by(p_value, 3), 0.01);
+        assert_eq!(get_kolmogorov_smirnov_p_value(0.0, 100), 1.0);
+    }
+
+    #[test]
+    fn test_get_exponential_kolmogorov_smirnov() {
+        // Quantiles of the exponential distribution fit it closely
+        let nums = (0..100)
+            .map(|i| -10.0 * (1.0 - (i as f64 + 0.5) / 100.0).ln())
+            .collect::<Vec<_>>();
+        let (statistic, p_value) = get_exponential_kolmogorov_smirnov(&nums).unwrap();
+        assert!(statistic < 0.05);
+        assert!(p_value > 0.99);
+
+        // Regular intervals do not
+        let (statistic, p_value) = get_exponential_kolmogorov_smirnov(&[10.0; 100]).unwrap();
+        assert_eq!(get_rounded_by(statistic, 3), 0.632);
+        assert!(p_value < 1e-6);
+        assert!(get_exponential_kolmogorov_smirnov(&[]).is_none());
+    }
+
+    #[test]
+    fn test_get_autocorrelations() {
+        // Alternating numbers are perfectly anticorrelated at odd lags
+        let runs = vec![vec![1.0, 3.0, 1.0, 3.0, 1.0], vec![3.0, 1.0, 3.0]];
+        let autocorrelations = get_autocorrelations(&runs, 5);
+        assert_eq!(autocorrelations[0].0, 6);
+        assert_eq!(get_rounded_by(autocorrelations[0].1, 4), -1.0);
+        assert_eq!(get_rounded_by(autocorrelations[1].1, 4), 1.0);
+        assert_eq!(autocorrelations[3].0, 1);
+        // No run spans 5 lags
+        assert_eq!(autocorrelations[4].0, 0);
+        assert!(autocorrelations[4].1.is_nan());
+    }
+
+    #[test]
+    fn test_get_proportional_allocation() {
+        assert_eq!(
+            get_proportional_allocation(&[2016, 2016, 1008], 10),
+            vec![4, 4, 2]
+        );
+        assert_eq!(get_proportional_allocation(&[1, 1, 1], 4), vec![2, 1, 1]);
+        assert_eq!(get_proportional_allocation(&[3, 0, 7], 0), vec![0, 0, 0]);
+        assert_eq!(get_proportional_allocation(&[0, 0], 5), vec![0, 0]);
+    }
 }
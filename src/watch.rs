@@ -0,0 +1,881 @@
+//! Live observation of the ZMQ notifications of bitcoind.
+//!
+//! bitcoind publishes the transactions entering its mempool on the `rawtx`
+//! topic (`-zmqpubrawtx`) and connected blocks on the `rawblock` topic
+//! (`-zmqpubrawblock`). When a block is connected, every transaction of the
+//! block is announced on `rawtx` again, starting with the coinbase, before
+//! the block itself. Both topics should be published on the same endpoint
+//! so the order of the notifications is preserved. The hash of every
+//! connected block is published on the `hashblock` topic
+//! (`-zmqpubhashblock`), enough to follow the intervals between blocks.
+
+use bitcoincore_rpc::{
+    bitcoin::{consensus::deserialize, Block, BlockHash, BlockHeader, OutPoint, Transaction, Txid},
+    Client, RpcApi,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt, thread,
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::utils::{get_mean, get_percentile, get_rounded_by};
+use crate::{
+    BlockHeight, BlockSampleData, ConfirmationLatencyReport, ConfirmationLatencyTable,
+    ConfirmationTimeTable, ConflictTable, Result, TransactionArrivalTable, WatchReport,
+};
+
+/// Topics subscribed to on the ZMQ endpoints for watching transactions.
+pub const TRANSACTION_TOPICS: [&str; 2] = ["rawtx", "rawblock"];
+/// Topics subscribed to on the ZMQ endpoints for watching blocks.
+pub const BLOCK_TOPICS: [&str; 1] = ["hashblock"];
+/// Milliseconds to wait for a notification before checking cancellation.
+const RECEIVE_TIMEOUT: i32 = 500;
+/// Percentiles of the time to confirmation reported.
+const PERCENTILES: [f64; 5] = [10.0, 25.0, 50.0, 75.0, 90.0];
+/// Lower bounds of the fee rate bands in sat/vB.
+const FEE_RATE_BANDS: [f64; 8] = [0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0];
+/// Sequence numbers below this value signal opt-in replaceability (BIP125).
+const MAX_BIP125_RBF_SEQUENCE: u32 = 0xfffffffd;
+
+/// A notification published by bitcoind.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Notification {
+    /// A transaction entered the mempool or was connected in a block
+    Transaction(Transaction),
+    /// A block was connected
+    Block(Block),
+    /// The hash of a block that was connected
+    BlockHash(BlockHash),
+}
+
+/// Event of the conflict log.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ConflictEvent {
+    /// A transaction in the mempool was replaced by a conflicting spend
+    Replaced {
+        original: Txid,
+        replacement: Txid,
+        signals_rbf: bool,
+    },
+    /// A conflict was resolved by a transaction confirming in a block
+    Resolved {
+        txid: Txid,
+        branch: ConflictBranch,
+        branches: usize,
+    },
+}
+
+/// The branch of a conflict which confirmed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConflictBranch {
+    /// The transaction first seen in the mempool
+    Original,
+    /// A transaction replacing the original in the mempool
+    Replacement,
+    /// A conflicting transaction never seen in the mempool
+    Unseen,
+}
+
+/// Statistics of the transactions observed while watching the mempool.
+///
+/// A pending transaction is dropped when replaced by a transaction spending
+/// the same outputs (RBF), when conflicted by a confirmed transaction or
+/// when it has been pending for longer than the expiry (`-mempoolexpiry`).
+/// Descendants of a dropped transaction are dropped along with it.
+/// Evictions of a full mempool are not announced and count as expired.
+///
+/// Conflicting spends are grouped with the transactions they replaced until
+/// one of the branches confirms, resolving the conflict.
+#[derive(Clone, Debug)]
+pub struct TransactionWatch {
+    started: u64,
+    expiry: u64,
+    pending: HashMap<Txid, PendingTransaction>,
+    spenders: HashMap<OutPoint, Txid>,
+    announcing_block: bool,
+    arrivals: u64,
+    unseen: u64,
+    blocks: u64,
+    confirmations: Vec<Confirmation>,
+    replaced: u64,
+    conflicted: u64,
+    expired: u64,
+    conflicts: Vec<Conflict>,
+    conflict_txids: HashMap<Txid, usize>,
+    conflict_outputs: HashMap<OutPoint, usize>,
+    conflict_log: Option<Vec<ConflictEvent>>,
+}
+
+/// A transaction waiting in the mempool.
+#[derive(Clone, Debug)]
+struct PendingTransaction {
+    first_seen: u64,
+    first_block: u64,
+    fee_rate: Option<f64>,
+    signals_rbf: bool,
+    inputs: Vec<OutPoint>,
+}
+
+/// Conflicting spends in the order they were seen, starting with the
+/// original transaction.
+#[derive(Clone, Debug)]
+struct Conflict {
+    branches: Vec<Txid>,
+    signals_rbf: bool,
+    resolution: Option<ConflictBranch>,
+}
+
+/// Wait of a transaction from first seen to its inclusion in a block.
+#[derive(Clone, Debug)]
+struct Confirmation {
+    seconds: u64,
+    blocks: u64,
+    fee_rate: Option<f64>,
+}
+
+/// Rolling window of the latest blocks connected, followed through the
+/// hashblock notifications to keep the statistics of their intervals live.
+#[derive(Clone, Debug)]
+pub struct BlockWatch {
+    window: usize,
+    blocks: VecDeque<(u64, BlockHeader)>,
+}
+
+impl fmt::Display for ConflictEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConflictEvent::Replaced {
+                original,
+                replacement,
+                signals_rbf,
+            } => write!(
+                f,
+                "Conflict: {} replaced {} ({})",
+                replacement,
+                original,
+                if *signals_rbf {
+                    "opt-in RBF"
+                } else {
+                    "non-RBF"
+                }
+            ),
+            ConflictEvent::Resolved {
+                txid,
+                branch,
+                branches,
+            } => write!(
+                f,
+                "Resolved: {} confirmed, the {} branch of {} conflicting transactions",
+                txid, branch, branches
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ConflictBranch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ConflictBranch::Original => "original",
+            ConflictBranch::Replacement => "replacement",
+            ConflictBranch::Unseen => "unseen",
+        })
+    }
+}
+
+impl Notification {
+    /// Decode the body of a notification, None for unknown topics.
+    pub fn decode(topic: &[u8], body: &[u8]) -> Result<Option<Self>> {
+        match topic {
+            b"rawtx" => Ok(Some(Notification::Transaction(deserialize(body)?))),
+            b"rawblock" => Ok(Some(Notification::Block(deserialize(body)?))),
+            // The hash is published in the byte order it is displayed in
+            b"hashblock" => {
+                let mut hash = body.to_vec();
+                hash.reverse();
+                Ok(Some(Notification::BlockHash(deserialize(&hash)?)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Subscribe to the topics of the ZMQ endpoints. Notifications are received
+/// on a separate thread until the token is cancelled or the receiver is
+/// dropped.
+pub fn subscribe(
+    endpoints: &[String],
+    topics: &[&str],
+    cancel: CancellationToken,
+) -> Result<mpsc::UnboundedReceiver<Result<Notification>>> {
+    let context = zmq::Context::new();
+    let socket = context.socket(zmq::SUB)?;
+    socket.set_rcvtimeo(RECEIVE_TIMEOUT)?;
+
+    for endpoint in endpoints {
+        socket.connect(endpoint)?;
+    }
+
+    for topic in topics {
+        socket.set_subscribe(topic.as_bytes())?;
+    }
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    thread::spawn(move || {
+        while !cancel.is_cancelled() && !sender.is_closed() {
+            // Messages are the topic, body and sequence number
+            let message = match socket.recv_multipart(0) {
+                Ok(message) => message,
+                Err(zmq::Error::EAGAIN) => continue,
+                Err(e) => {
+                    let _ = sender.send(Err(e.into()));
+                    break;
+                }
+            };
+
+            if let [topic, body, ..] = message.as_slice() {
+                if let Some(notification) = Notification::decode(topic, body).transpose() {
+                    let _ = sender.send(notification);
+                }
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+/// Get the fee rate in sat/vB of a transaction in the mempool of the node,
+/// None when the transaction already left the mempool.
+pub fn get_fee_rate(client: &Client, txid: &Txid) -> Option<f64> {
+    let entry = client.get_mempool_entry(txid).ok()?;
+    Some(entry.fees.base.as_sat() as f64 / entry.vsize.max(1) as f64)
+}
+
+impl TransactionWatch {
+    /// Create a new watch started at the unix time, expiring transactions
+    /// pending for longer than the expiry in seconds.
+    pub fn new(started: u64, expiry: u64) -> Self {
+        TransactionWatch {
+            started,
+            expiry,
+            pending: HashMap::new(),
+            spenders: HashMap::new(),
+            announcing_block: false,
+            arrivals: 0,
+            unseen: 0,
+            blocks: 0,
+            confirmations: vec![],
+            replaced: 0,
+            conflicted: 0,
+            expired: 0,
+            conflicts: vec![],
+            conflict_txids: HashMap::new(),
+            conflict_outputs: HashMap::new(),
+            conflict_log: None,
+        }
+    }
+
+    /// Keep a log of the conflicts observed, see take_conflict_log.
+    pub fn with_conflict_log(mut self) -> Self {
+        self.conflict_log = Some(vec![]);
+        self
+    }
+
+    /// Take the conflict events logged since the last call.
+    pub fn take_conflict_log(&mut self) -> Vec<ConflictEvent> {
+        self.conflict_log
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Observe a notification received at the unix time, returning the txid
+    /// of a transaction newly seen in the mempool.
+    pub fn observe(&mut self, notification: &Notification, time: u64) -> Option<Txid> {
+        match notification {
+            Notification::Transaction(tx) => self.observe_transaction(tx, time),
+            Notification::Block(block) => {
+                self.observe_block(block, time);
+                None
+            }
+            Notification::BlockHash(_) => None,
+        }
+    }
+
+    /// Set the fee rate in sat/vB of a pending transaction.
+    pub fn set_fee_rate(&mut self, txid: &Txid, fee_rate: f64) {
+        if let Some(pending) = self.pending.get_mut(txid) {
+            pending.fee_rate = Some(fee_rate);
+        }
+    }
+
+    /// Report the statistics at the unix time.
+    pub fn report(&self, time: u64) -> WatchReport {
+        let elapsed = time.saturating_sub(self.started);
+        let latencies = self
+            .confirmations
+            .iter()
+            .map(|confirmation| confirmation.seconds as f64)
+            .collect::<Vec<_>>();
+        let confirmed = latencies.len() as u64;
+        let dropped = self.replaced + self.conflicted + self.expired;
+        let rate = match elapsed {
+            0 => 0.0,
+            elapsed => self.arrivals as f64 / elapsed as f64 * 60.0,
+        };
+
+        let arrivals = TransactionArrivalTable {
+            elapsed: elapsed as i64,
+            arrivals: self.arrivals,
+            rate: format!("{} tx/m", get_rounded_by(rate, 2)),
+            confirmed,
+            pending: self.pending.len() as u64,
+            replaced: self.replaced,
+            conflicted: self.conflicted,
+            expired: self.expired,
+            drop_rate: match confirmed + dropped {
+                0 => 0.0,
+                resolved => dropped as f64 / resolved as f64,
+            },
+        };
+
+        WatchReport {
+            arrivals,
+            blocks: self.blocks,
+            unseen: self.unseen,
+            confirmation_times: PERCENTILES
+                .iter()
+                .map(|percentile| ConfirmationTimeTable {
+                    percentile: format!("P{}", percentile),
+                    time: get_percentile(&latencies, *percentile) as i64,
+                })
+                .collect(),
+            mean_confirmation_time: get_mean(&latencies),
+            conflicts: self.get_conflicts(),
+        }
+    }
+
+    /// Get the resolution of the conflicts by the replaceability the
+    /// original transaction signaled.
+    fn get_conflicts(&self) -> Vec<ConflictTable> {
+        [(true, "Opt-in RBF"), (false, "Non-RBF")]
+            .iter()
+            .map(|(signals_rbf, signaling)| {
+                let conflicts = self
+                    .conflicts
+                    .iter()
+                    .filter(|conflict| conflict.signals_rbf == *signals_rbf)
+                    .collect::<Vec<_>>();
+                let resolved = |branch| {
+                    conflicts
+                        .iter()
+                        .filter(|conflict| conflict.resolution == Some(branch))
+                        .count() as u64
+                };
+                let replacement = resolved(ConflictBranch::Replacement);
+                let original = resolved(ConflictBranch::Original);
+                let unseen = resolved(ConflictBranch::Unseen);
+
+                ConflictTable {
+                    signaling: signaling.to_string(),
+                    conflicts: conflicts.len() as u64,
+                    replacements: conflicts
+                        .iter()
+                        .map(|conflict| conflict.branches.len() as u64 - 1)
+                        .sum(),
+                    pending: conflicts.len() as u64 - replacement - original - unseen,
+                    replacement,
+                    original,
+                    unseen,
+                    replacement_share: match replacement + original + unseen {
+                        0 => 0.0,
+                        resolved => replacement as f64 / resolved as f64,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Report the wait of the confirmed transactions by fee rate band.
+    /// Transactions without a known fee rate are not banded.
+    pub fn report_latency(&self) -> ConfirmationLatencyReport {
+        let mut bands = vec![vec![]; FEE_RATE_BANDS.len()];
+        let mut unknown = 0;
+
+        for confirmation in self.confirmations.iter() {
+            match confirmation.fee_rate {
+                Some(fee_rate) => {
+                    let band = FEE_RATE_BANDS.partition_point(|lower| *lower <= fee_rate);
+                    bands[band.saturating_sub(1)].push(confirmation);
+                }
+                None => unknown += 1,
+            }
+        }
+
+        let entries = bands
+            .iter()
+            .enumerate()
+            .filter(|(_, confirmations)| !confirmations.is_empty())
+            .map(|(i, confirmations)| {
+                let seconds = confirmations
+                    .iter()
+                    .map(|confirmation| confirmation.seconds as f64)
+                    .collect::<Vec<_>>();
+                let blocks = confirmations
+                    .iter()
+                    .map(|confirmation| confirmation.blocks as f64)
+                    .collect::<Vec<_>>();
+                let next_block = blocks.iter().filter(|blocks| **blocks <= 1.0).count();
+
+                ConfirmationLatencyTable {
+                    band: match FEE_RATE_BANDS.get(i + 1) {
+                        Some(upper) => format!("{}-{} sat/vB", FEE_RATE_BANDS[i], upper),
+                        None => format!("{}+ sat/vB", FEE_RATE_BANDS[i]),
+                    },
+                    transactions: confirmations.len() as u64,
+                    median_blocks: get_percentile(&blocks, 50.0),
+                    p90_blocks: get_rounded_by(get_percentile(&blocks, 90.0), 2),
+                    next_block: next_block as f64 / blocks.len() as f64,
+                    median: get_percentile(&seconds, 50.0) as i64,
+                    p90: get_percentile(&seconds, 90.0) as i64,
+                }
+            })
+            .collect();
+
+        ConfirmationLatencyReport {
+            entries,
+            blocks: self.blocks,
+            confirmed: self.confirmations.len(),
+            unknown,
+        }
+    }
+
+    fn observe_transaction(&mut self, tx: &Transaction, time: u64) -> Option<Txid> {
+        // The coinbase starts the announcement of the transactions of a block
+        if tx.is_coin_base() {
+            self.announcing_block = true;
+            return None;
+        }
+
+        let txid = tx.txid();
+
+        if self.announcing_block || self.pending.contains_key(&txid) {
+            return None;
+        }
+
+        self.arrivals += 1;
+
+        for input in tx.input.iter() {
+            if let Some(spender) = self.spenders.get(&input.previous_output).copied() {
+                self.observe_conflict(spender, tx);
+                self.replaced += self.drop_pending(spender);
+            }
+        }
+
+        let inputs = tx.input.iter().map(|input| input.previous_output);
+        self.spenders
+            .extend(inputs.clone().map(|input| (input, txid)));
+        self.pending.insert(
+            txid,
+            PendingTransaction {
+                first_seen: time,
+                first_block: self.blocks,
+                fee_rate: None,
+                signals_rbf: tx
+                    .input
+                    .iter()
+                    .any(|input| input.sequence <= MAX_BIP125_RBF_SEQUENCE),
+                inputs: inputs.collect(),
+            },
+        );
+
+        Some(txid)
+    }
+
+    fn observe_block(&mut self, block: &Block, time: u64) {
+        self.announcing_block = false;
+        self.blocks += 1;
+
+        for tx in block.txdata.iter().filter(|tx| !tx.is_coin_base()) {
+            let txid = tx.txid();
+
+            match self.remove(&txid) {
+                Some(pending) => self.confirmations.push(Confirmation {
+                    seconds: time.saturating_sub(pending.first_seen),
+                    blocks: self.blocks - pending.first_block,
+                    fee_rate: pending.fee_rate,
+                }),
+                None => self.unseen += 1,
+            }
+
+            for input in tx.input.iter() {
+                if let Some(spender) = self.spenders.get(&input.previous_output).copied() {
+                    self.conflicted += self.drop_pending(spender);
+                }
+
+                if let Some(index) = self.conflict_outputs.remove(&input.previous_output) {
+                    self.resolve_conflict(index, txid);
+                }
+            }
+        }
+
+        let expired = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| time.saturating_sub(pending.first_seen) > self.expiry)
+            .map(|(txid, _)| *txid)
+            .collect::<Vec<_>>();
+
+        for txid in expired {
+            self.expired += self.drop_pending(txid);
+        }
+    }
+
+    /// Group the transaction with the pending transaction it replaces.
+    fn observe_conflict(&mut self, original: Txid, tx: &Transaction) {
+        let pending = match self.pending.get(&original) {
+            Some(pending) => pending,
+            None => return,
+        };
+        let index = match self.conflict_txids.get(&original) {
+            Some(index) => *index,
+            None => {
+                let index = self.conflicts.len();
+                self.conflicts.push(Conflict {
+                    branches: vec![original],
+                    signals_rbf: pending.signals_rbf,
+                    resolution: None,
+                });
+                self.conflict_txids.insert(original, index);
+                self.conflict_outputs
+                    .extend(pending.inputs.iter().map(|input| (*input, index)));
+                index
+            }
+        };
+
+        let txid = tx.txid();
+        let conflict = &mut self.conflicts[index];
+
+        // A replacement can conflict with the original over several inputs
+        if conflict.branches.contains(&txid) {
+            return;
+        }
+
+        conflict.branches.push(txid);
+        self.conflict_txids.insert(txid, index);
+        self.conflict_outputs
+            .extend(tx.input.iter().map(|input| (input.previous_output, index)));
+
+        if let Some(log) = self.conflict_log.as_mut() {
+            log.push(ConflictEvent::Replaced {
+                original,
+                replacement: txid,
+                signals_rbf: conflict.signals_rbf,
+            });
+        }
+    }
+
+    /// Resolve the conflict with the transaction confirmed in a block.
+    fn resolve_conflict(&mut self, index: usize, txid: Txid) {
+        let conflict = &mut self.conflicts[index];
+
+        if conflict.resolution.is_some() {
+            return;
+        }
+
+        let branch = match conflict.branches.iter().position(|branch| *branch == txid) {
+            Some(0) => ConflictBranch::Original,
+            Some(_) => ConflictBranch::Replacement,
+            None => ConflictBranch::Unseen,
+        };
+        conflict.resolution = Some(branch);
+
+        if let Some(log) = self.conflict_log.as_mut() {
+            log.push(ConflictEvent::Resolved {
+                txid,
+                branch,
+                branches: conflict.branches.len(),
+            });
+        }
+    }
+
+    /// Drop the pending transaction along with its descendants returning the
+    /// number of transactions dropped.
+    fn drop_pending(&mut self, txid: Txid) -> u64 {
+        if self.remove(&txid).is_none() {
+            return 0;
+        }
+
+        let descendants = self
+            .spenders
+            .iter()
+            .filter(|(output, _)| output.txid == txid)
+            .map(|(_, spender)| *spender)
+            .collect::<Vec<_>>();
+
+        1 + descendants
+            .into_iter()
+            .map(|descendant| self.drop_pending(descendant))
+            .sum::<u64>()
+    }
+
+    /// Remove the pending transaction.
+    fn remove(&mut self, txid: &Txid) -> Option<PendingTransaction> {
+        let pending = self.pending.remove(txid)?;
+
+        for input in pending.inputs.iter() {
+            self.spenders.remove(input);
+        }
+
+        Some(pending)
+    }
+}
+
+impl BlockWatch {
+    /// Create a new watch of the intervals between the latest blocks, keeping
+    /// one more block than the intervals of the window.
+    pub fn new(window: usize) -> Self {
+        BlockWatch {
+            window: window.max(1),
+            blocks: VecDeque::new(),
+        }
+    }
+
+    /// Height of the block expected next, None before any block connected.
+    pub fn next_height(&self) -> Option<u64> {
+        self.blocks.back().map(|(height, _)| height + 1)
+    }
+
+    /// Connect the block at the height. A block at or below the latest
+    /// height replaces the blocks from its height after a reorg, while a
+    /// block skipping heights restarts the window.
+    pub fn connect(&mut self, height: u64, header: BlockHeader) {
+        match self.next_height() {
+            Some(next) if height > next => self.blocks.clear(),
+            Some(next) if height < next => self.blocks.retain(|(block, _)| *block < height),
+            _ => (),
+        }
+
+        self.blocks.push_back((height, header));
+
+        while self.blocks.len() > self.window + 1 {
+            self.blocks.pop_front();
+        }
+    }
+
+    /// Number of blocks within the window.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Check whether no block connected yet.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Get the blocks of the window as sample data for the analyses.
+    pub fn get_data(&self) -> BlockSampleData {
+        let blocks = self.blocks.iter();
+        BlockSampleData::new(
+            blocks
+                .map(|(height, header)| (BlockHeight(*height), *header))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::{
+        blockdata::constants::genesis_block, consensus::serialize, hashes::Hash, Network, TxIn,
+        TxOut,
+    };
+
+    fn tx(inputs: &[OutPoint], value: u64) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: inputs
+                .iter()
+                .map(|output| TxIn {
+                    previous_output: *output,
+                    ..Default::default()
+                })
+                .collect(),
+            output: vec![TxOut {
+                value,
+                script_pubkey: Default::default(),
+            }],
+        }
+    }
+
+    fn block(txs: &[&Transaction]) -> Block {
+        let mut block = genesis_block(Network::Regtest);
+        block.txdata.extend(txs.iter().map(|tx| (*tx).clone()));
+        block
+    }
+
+    fn output(n: u8) -> OutPoint {
+        OutPoint::new(Txid::from_slice(&[n; 32]).unwrap(), 0)
+    }
+
+    #[test]
+    fn test_notification_decode() {
+        let block = genesis_block(Network::Bitcoin);
+        let tx = block.txdata[0].clone();
+        let hash = block.block_hash();
+
+        assert_eq!(
+            Notification::decode(b"rawtx", &serialize(&tx)).unwrap(),
+            Some(Notification::Transaction(tx))
+        );
+        assert_eq!(
+            Notification::decode(b"rawblock", &serialize(&block)).unwrap(),
+            Some(Notification::Block(block))
+        );
+        let mut body = serialize(&hash);
+        body.reverse();
+        assert_eq!(
+            Notification::decode(b"hashblock", &body).unwrap(),
+            Some(Notification::BlockHash(hash))
+        );
+        assert_eq!(Notification::decode(b"hashtx", &[0; 32]).unwrap(), None);
+        assert!(Notification::decode(b"rawtx", &[0; 3]).is_err());
+    }
+
+    #[test]
+    fn test_transaction_watch_confirmations() {
+        let mut watch = TransactionWatch::new(0, 3600);
+        let first = tx(&[output(1)], 1);
+        let second = tx(&[output(2)], 2);
+        let unseen = tx(&[output(3)], 3);
+        let coinbase = genesis_block(Network::Regtest).txdata[0].clone();
+
+        assert_eq!(watch.observe_transaction(&first, 10), Some(first.txid()));
+        assert_eq!(watch.observe_transaction(&second, 20), Some(second.txid()));
+        assert_eq!(watch.observe_transaction(&first, 30), None);
+        watch.set_fee_rate(&first.txid(), 2.5);
+        // The block announces its transactions before the block itself
+        assert_eq!(watch.observe_transaction(&coinbase, 100), None);
+        assert_eq!(watch.observe_transaction(&first, 100), None);
+        assert_eq!(watch.observe_transaction(&unseen, 100), None);
+        watch.observe_block(&block(&[]), 90);
+        watch.observe_block(&block(&[&first, &unseen]), 100);
+
+        assert_eq!(watch.arrivals, 2);
+        assert_eq!(watch.confirmations.len(), 1);
+        assert_eq!(watch.confirmations[0].seconds, 90);
+        assert_eq!(watch.confirmations[0].blocks, 2);
+        assert_eq!(watch.confirmations[0].fee_rate, Some(2.5));
+        assert_eq!(watch.unseen, 1);
+        assert_eq!(watch.pending.len(), 1);
+
+        watch.observe_transaction(&tx(&[output(4)], 4), 110);
+        assert_eq!(watch.arrivals, 3);
+    }
+
+    #[test]
+    fn test_transaction_watch_drops() {
+        let mut watch = TransactionWatch::new(0, 3600);
+        let original = tx(&[output(1)], 10);
+        let child = tx(&[OutPoint::new(original.txid(), 0)], 9);
+        let replacement = tx(&[output(1)], 8);
+        let conflicted = tx(&[output(2)], 7);
+        let conflicting = tx(&[output(2)], 6);
+        let stale = tx(&[output(3)], 5);
+
+        watch.observe_transaction(&stale, 0);
+        watch.observe_transaction(&original, 10);
+        watch.observe_transaction(&child, 20);
+        watch.observe_transaction(&replacement, 30);
+        assert_eq!(watch.replaced, 2);
+
+        watch.observe_transaction(&conflicted, 40);
+        watch.observe_block(&block(&[&conflicting]), 3620);
+
+        assert_eq!(watch.conflicted, 1);
+        assert_eq!(watch.expired, 1);
+        assert_eq!(watch.pending.len(), 1);
+        assert!(watch.spenders.len() == 1 && watch.spenders.contains_key(&output(1)));
+    }
+
+    #[test]
+    fn test_transaction_watch_conflicts() {
+        let mut watch = TransactionWatch::new(0, 3600).with_conflict_log();
+        let mut original = tx(&[output(1)], 10);
+        original.input[0].sequence = MAX_BIP125_RBF_SEQUENCE;
+        let replacement = tx(&[output(1)], 9);
+        let second = tx(&[output(1)], 8);
+        let non_rbf = tx(&[output(2)], 7);
+        let rival = tx(&[output(2)], 6);
+        let unseen = tx(&[output(2)], 5);
+
+        watch.observe_transaction(&original, 0);
+        watch.observe_transaction(&replacement, 10);
+        watch.observe_transaction(&second, 20);
+        watch.observe_transaction(&non_rbf, 30);
+        watch.observe_transaction(&rival, 40);
+
+        assert_eq!(
+            watch.take_conflict_log()[0],
+            ConflictEvent::Replaced {
+                original: original.txid(),
+                replacement: replacement.txid(),
+                signals_rbf: true,
+            }
+        );
+        assert!(watch.take_conflict_log().is_empty());
+
+        watch.observe_block(&block(&[&original, &unseen]), 60);
+
+        assert_eq!(watch.conflicts.len(), 2);
+        assert_eq!(watch.conflicts[0].branches.len(), 3);
+        assert_eq!(
+            watch.conflicts[0].resolution,
+            Some(ConflictBranch::Original)
+        );
+        assert_eq!(watch.conflicts[1].branches.len(), 2);
+        assert!(!watch.conflicts[1].signals_rbf);
+        assert_eq!(watch.conflicts[1].resolution, Some(ConflictBranch::Unseen));
+        assert_eq!(
+            watch.take_conflict_log()[0],
+            ConflictEvent::Resolved {
+                txid: original.txid(),
+                branch: ConflictBranch::Original,
+                branches: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_block_watch() {
+        let header = |time| BlockHeader {
+            time,
+            ..genesis_block(Network::Regtest).header
+        };
+        let mut watch = BlockWatch::new(2);
+        assert_eq!(watch.next_height(), None);
+
+        (10..14).for_each(|height| watch.connect(height, header(height as u32 * 600)));
+        assert_eq!(watch.len(), 3);
+        assert_eq!(watch.next_height(), Some(14));
+
+        // A reorg replaces the blocks from the height of the new block
+        watch.connect(12, header(12 * 600 + 1));
+        assert_eq!(watch.len(), 2);
+        assert_eq!(
+            watch.get_data(),
+            BlockSampleData::new(vec![
+                (BlockHeight(11), header(6600)),
+                (BlockHeight(12), header(7201))
+            ])
+        );
+
+        watch.connect(20, header(12_000));
+        assert_eq!(watch.len(), 1);
+        assert_eq!(watch.next_height(), Some(21));
+    }
+}
@@ -0,0 +1,993 @@
+//! Snapshots of the rendered analyses over fixed mock data. Changes to the
+//! output format fail these tests until the snapshots are reviewed with
+//! `cargo insta review`.
+
+use bitcoin_statistics::{
+    custom::{Aggregate, CustomExpression, CustomScope},
+    exclusions::HeightRange,
+    get_chain_tips_report,
+    pools::{get_default_pools, parse_pools},
+    prices::PriceFeed,
+    records::RecordBook,
+    render::PartialReport,
+    schema::get_report_schemas,
+    signet::SignetChallenge,
+    watch::{Notification, TransactionWatch},
+    BlockHeight, BlockSample, BlockSampleData, BlockSampleFullData, BlockStatistics,
+    BlockStatsData, BlockStatsStatistics, FullBlockStatistics, MinDifficultyBlocks, Strata,
+};
+use bitcoincore_rpc::bitcoin::hashes::hex::FromHex;
+use bitcoincore_rpc::{
+    bitcoin::{
+        blockdata::constants::genesis_block, blockdata::script::Builder, Amount, Block, BlockHash,
+        BlockHeader, Network, OutPoint, Script, Transaction, TxIn, TxOut, Witness,
+    },
+    json::{
+        FeeRatePercentiles, GetBlockStatsResult, GetChainTipsResultStatus, GetChainTipsResultTip,
+    },
+};
+use std::collections::BTreeMap;
+
+/// Timestamp of the first mock block.
+const START_TIME: u32 = 1_600_000_000;
+/// Seconds between each mock block and its parent.
+const INTERVALS: [u32; 5] = [600, 1500, 60, 2400, 300];
+
+fn headers() -> BlockSampleData {
+    let mut time = START_TIME;
+    let mut headers = vec![(BlockHeight(100), header(time, 0))];
+
+    for (i, interval) in INTERVALS.iter().enumerate() {
+        time += interval;
+        headers.push((BlockHeight(101 + i as u64), header(time, i as u32 + 1)));
+    }
+
+    BlockSampleData::new(headers)
+}
+
+fn backward_headers() -> BlockSampleData {
+    let intervals: [i64; 8] = [600, -300, 900, 0, 1200, -60, 600, 600];
+    let mut time = START_TIME as i64;
+    let mut headers = vec![(BlockHeight(100), header(time as u32, 0))];
+
+    for (i, interval) in intervals.iter().enumerate() {
+        time += interval;
+        headers.push((
+            BlockHeight(101 + i as u64),
+            header(time as u32, i as u32 + 1),
+        ));
+    }
+
+    BlockSampleData::new(headers)
+}
+
+fn exponential_headers() -> BlockSampleData {
+    // Intervals at the quantiles of exponential intervals of 10 minutes
+    let mut time = START_TIME;
+    let mut headers = vec![(BlockHeight(100), header(time, 0))];
+
+    for i in 0..20 {
+        time += (-600.0 * (1.0 - (i as f64 + 0.5) / 20.0).ln()) as u32;
+        headers.push((BlockHeight(101 + i as u64), header(time, i as u32 + 1)));
+    }
+
+    BlockSampleData::new(headers)
+}
+
+fn epoch_headers() -> BlockSampleData {
+    // Windows within three retarget epochs, the last at two thirds of the
+    // difficulty after a slow epoch
+    let windows: [(u64, u32, [u32; 2]); 3] = [
+        (2013, 0x1d00ffff, [600, 600]),
+        (4029, 0x1d00ffff, [900, 840]),
+        (4032, 0x1d017fff, [300, 300]),
+    ];
+    let mut headers = vec![];
+    let mut time = START_TIME;
+
+    for (start, bits, intervals) in windows {
+        headers.push((
+            BlockHeight(start),
+            BlockHeader {
+                bits,
+                ..header(time, 0)
+            },
+        ));
+
+        for (i, interval) in intervals.iter().enumerate() {
+            time += interval;
+            let nonce = i as u32 + 1;
+            headers.push((
+                BlockHeight(start + nonce as u64),
+                BlockHeader {
+                    bits,
+                    ..header(time, nonce)
+                },
+            ));
+        }
+    }
+
+    BlockSampleData::new(headers)
+}
+
+fn checkpoint_headers() -> BlockSampleData {
+    // Timestamps of the genesis block and the first three halvings
+    let checkpoints: [(u64, u32); 4] = [
+        (0, 1_231_006_505),
+        (210_000, 1_354_116_278),
+        (420_000, 1_468_082_773),
+        (630_000, 1_589_225_023),
+    ];
+
+    BlockSampleData::new(
+        checkpoints
+            .iter()
+            .map(|(height, time)| (BlockHeight(*height), header(*time, *height as u32)))
+            .collect(),
+    )
+}
+
+fn era_headers() -> BlockSampleData {
+    // Timestamps of the genesis block and the first three halvings
+    let halvings: [u32; 4] = [1_231_006_505, 1_354_116_278, 1_468_082_773, 1_589_225_023];
+    let mut headers = vec![];
+
+    for (era, times) in halvings.windows(2).enumerate() {
+        let start = era as u64 * 210_000;
+        headers.push((BlockHeight(start), header(times[0], 0)));
+        headers.push((BlockHeight(start + 209_999), header(times[1] - 600, 1)));
+
+        // Sampled pairs with mining times shortening every era
+        for (i, interval) in INTERVALS.iter().enumerate() {
+            let height = start + 1000 * (i as u64 + 1);
+            let time = times[0] + 1000 * 600 * (i as u32 + 1);
+            headers.push((BlockHeight(height), header(time, 2)));
+            headers.push((
+                BlockHeight(height + 1),
+                header(time + interval / (era as u32 + 1), 3),
+            ));
+        }
+    }
+
+    BlockSampleData::new(headers)
+}
+
+fn boundary_headers() -> BlockSampleData {
+    // Boundaries of three epochs, a fast epoch followed by a slow epoch and
+    // an epoch without the block after it
+    let epochs: [(u32, u32); 3] = [
+        (1_100_000, 0x1d00ffff),
+        (1_500_000, 0x1d00d555),
+        (1_209_600, 0x1d0116a0),
+    ];
+    let mut headers = vec![];
+    let mut time = START_TIME;
+
+    for (i, (duration, bits)) in epochs.iter().enumerate() {
+        let start = i as u64 * 2016;
+        headers.push((
+            BlockHeight(start),
+            BlockHeader {
+                bits: *bits,
+                ..header(time, 0)
+            },
+        ));
+        headers.push((
+            BlockHeight(start + 2015),
+            BlockHeader {
+                bits: *bits,
+                ..header(time + duration, 1)
+            },
+        ));
+        time += duration + 600;
+    }
+
+    BlockSampleData::new(headers)
+}
+
+fn header(time: u32, nonce: u32) -> BlockHeader {
+    BlockHeader {
+        version: 1,
+        prev_blockhash: Default::default(),
+        merkle_root: Default::default(),
+        time,
+        bits: 0x1d00ffff,
+        nonce,
+    }
+}
+
+fn blocks() -> BlockSampleFullData {
+    let tags: [&[u8]; 6] = [
+        b"Mined by AntPool",
+        b"/Foundry USA/",
+        b"Mined by AntPool bj",
+        b"",
+        b"/ViaBTC/Mined by a long tail miner/",
+        b"Mined by AntPool",
+    ];
+    let mut time = START_TIME;
+
+    let blocks = tags
+        .iter()
+        .enumerate()
+        .map(|(i, tag)| {
+            if i > 0 {
+                time += INTERVALS[i - 1];
+            }
+            (BlockHeight(100 + i as u64), block(time, tag))
+        })
+        .collect();
+
+    BlockSampleFullData::new(blocks)
+}
+
+fn block(time: u32, tag: &[u8]) -> Block {
+    let mut block = genesis_block(Network::Bitcoin);
+    block.header.time = time;
+    block.txdata = vec![Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![TxIn {
+            script_sig: Script::from(tag.to_vec()),
+            ..Default::default()
+        }],
+        output: vec![],
+    }];
+    block
+}
+
+fn flow_blocks() -> BlockSampleFullData {
+    let p2wpkh = Builder::new()
+        .push_int(0)
+        .push_slice(&[0; 20])
+        .into_script();
+    let p2tr = Builder::new()
+        .push_int(1)
+        .push_slice(&[0; 32])
+        .into_script();
+    let p2pkh = Script::from(vec![0x76, 0xa9, 0x14]);
+    let witnesses = [vec![], vec![vec![0; 71], vec![2; 33]], vec![vec![0; 64]]];
+
+    let blocks = [(2015, &p2pkh, 0), (2016, &p2wpkh, 1), (2017, &p2tr, 2)]
+        .iter()
+        .map(|(height, script_pubkey, spent)| {
+            let mut block = block(START_TIME, b"");
+            block.txdata.push(Transaction {
+                version: 2,
+                lock_time: 0,
+                input: vec![TxIn {
+                    witness: Witness::from_vec(witnesses[*spent].clone()),
+                    ..Default::default()
+                }],
+                output: vec![
+                    TxOut {
+                        value: 1_000,
+                        script_pubkey: (*script_pubkey).clone(),
+                    },
+                    TxOut {
+                        value: 1_000,
+                        script_pubkey: p2tr.clone(),
+                    },
+                ],
+            });
+            (BlockHeight(*height), block)
+        })
+        .collect();
+
+    BlockSampleFullData::new(blocks)
+}
+
+fn taproot_blocks() -> BlockSampleFullData {
+    let p2wpkh = Builder::new()
+        .push_int(0)
+        .push_slice(&[0; 20])
+        .into_script();
+    let p2tr = Builder::new()
+        .push_int(1)
+        .push_slice(&[0; 32])
+        .into_script();
+    let mut control = vec![0xc0];
+    control.extend([0; 32]);
+    let key_path = vec![vec![0; 64]];
+    let script_path = vec![vec![0x51], control];
+    let segwit = vec![vec![0; 71], vec![2; 33]];
+
+    // Witnesses spent and P2TR outputs created, the first block predating
+    // the activation
+    let blocks = [
+        (709_631, vec![&segwit], 0),
+        (709_632, vec![&key_path, &segwit], 1),
+        (709_633, vec![&key_path, &script_path], 2),
+        (711_648, vec![&key_path, &key_path, &segwit], 3),
+    ];
+
+    let blocks = blocks
+        .iter()
+        .map(|(height, witnesses, p2tr_outputs)| {
+            let mut block = block(START_TIME, b"");
+            let mut output = vec![TxOut {
+                value: 1_000,
+                script_pubkey: p2wpkh.clone(),
+            }];
+            output.extend((0..*p2tr_outputs).map(|_| TxOut {
+                value: 1_000,
+                script_pubkey: p2tr.clone(),
+            }));
+            block.txdata.push(Transaction {
+                version: 2,
+                lock_time: 0,
+                input: witnesses
+                    .iter()
+                    .map(|witness| TxIn {
+                        witness: Witness::from_vec((*witness).clone()),
+                        ..Default::default()
+                    })
+                    .collect(),
+                output,
+            });
+            (BlockHeight(*height), block)
+        })
+        .collect();
+
+    BlockSampleFullData::new(blocks)
+}
+
+fn cluster_blocks() -> BlockSampleFullData {
+    // Compressed public keys of the first multiples of the secp256k1 generator
+    let pubkeys = [
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+    ];
+    let key_input = |pubkey: &str| TxIn {
+        witness: Witness::from_vec(vec![vec![0; 71], Vec::<u8>::from_hex(pubkey).unwrap()]),
+        ..Default::default()
+    };
+    let output = |value: u64, tag: u8| TxOut {
+        value,
+        script_pubkey: Builder::new()
+            .push_int(0)
+            .push_slice(&[tag; 20])
+            .into_script(),
+    };
+
+    let funding = Transaction {
+        version: 2,
+        lock_time: 0,
+        input: vec![key_input(pubkeys[0])],
+        output: vec![output(1_000, 1), output(2_000, 2)],
+    };
+    let spending = Transaction {
+        version: 2,
+        lock_time: 0,
+        input: vec![
+            TxIn {
+                previous_output: OutPoint::new(funding.txid(), 0),
+                ..Default::default()
+            },
+            TxIn {
+                previous_output: OutPoint::new(funding.txid(), 1),
+                ..Default::default()
+            },
+            key_input(pubkeys[1]),
+        ],
+        output: vec![output(2_500, 3)],
+    };
+    let coinjoin = Transaction {
+        version: 2,
+        lock_time: 0,
+        input: vec![key_input(pubkeys[0]), key_input(pubkeys[1])],
+        output: vec![output(1_000, 4), output(1_000, 5), output(1_000, 6)],
+    };
+    let unknown = Transaction {
+        version: 2,
+        lock_time: 0,
+        input: vec![TxIn::default()],
+        output: vec![output(500, 7)],
+    };
+
+    let blocks = [vec![funding], vec![spending, coinjoin, unknown]]
+        .into_iter()
+        .enumerate()
+        .map(|(i, txdata)| {
+            let mut block = block(START_TIME, b"");
+            block.txdata.extend(txdata);
+            (BlockHeight(200 + i as u64), block)
+        })
+        .collect();
+
+    BlockSampleFullData::new(blocks)
+}
+
+fn stats() -> BlockStatsData {
+    let stats = [
+        (100, 1, 1_000, 0),
+        (101, 2_500, 1_200_000, 2),
+        (102, 3_000, 2_400_000, 5),
+        (103, 1_800, 3_200_000, 12),
+        (104, 3_500, 3_990_000, 35),
+        (105, 3_200, 3_960_000, 28),
+    ];
+
+    BlockStatsData::new(
+        stats
+            .iter()
+            .map(|(height, txs, weight, fee_rate)| {
+                (
+                    BlockHeight(*height),
+                    block_stats(*height, *txs, *weight, *fee_rate),
+                )
+            })
+            .collect(),
+    )
+}
+
+fn consolidation_stats() -> BlockStatsData {
+    // Inputs, outputs and median fee rate of contiguous blocks
+    let stats = [
+        (2_000, 4_000, 20),
+        (2_100, 4_100, 25),
+        (6_000, 1_500, 2),
+        (5_500, 1_200, 1),
+        (2_200, 4_300, 18),
+        (2_000, 3_900, 30),
+        (7_000, 1_400, 1),
+        (1_900, 4_200, 22),
+        (2_050, 4_000, 21),
+        (2_150, 4_050, 26),
+    ];
+
+    BlockStatsData::new(
+        stats
+            .iter()
+            .enumerate()
+            .map(|(i, (ins, outs, fee_rate))| {
+                let height = 800_000 + i as u64;
+                let mut stats = block_stats(height, 3_000, 3_990_000, *fee_rate);
+                stats.ins = *ins;
+                stats.outs = *outs;
+                stats.time = START_TIME as u64 + i as u64 * 600;
+                stats.total_out = Amount::from_sat(*outs as u64 * 5_000_000);
+                (BlockHeight(height), stats)
+            })
+            .collect(),
+    )
+}
+
+fn block_stats(height: u64, txs: usize, weight: usize, fee_rate: u64) -> GetBlockStatsResult {
+    let sat = Amount::from_sat;
+
+    GetBlockStatsResult {
+        avg_fee: sat(0),
+        avg_fee_rate: sat(fee_rate),
+        avg_tx_size: 0,
+        block_hash: BlockHash::default(),
+        fee_rate_percentiles: FeeRatePercentiles {
+            fr_10th: sat(fee_rate),
+            fr_25th: sat(fee_rate),
+            fr_50th: sat(fee_rate),
+            fr_75th: sat(fee_rate),
+            fr_90th: sat(fee_rate),
+        },
+        height,
+        ins: txs,
+        max_fee: sat(0),
+        max_fee_rate: sat(fee_rate),
+        max_tx_size: 0,
+        median_fee: sat(0),
+        median_time: 0,
+        median_tx_size: 0,
+        min_fee: sat(0),
+        min_fee_rate: sat(fee_rate),
+        min_tx_size: 0,
+        outs: txs,
+        subsidy: sat(625_000_000),
+        sw_total_size: 0,
+        sw_total_weight: 0,
+        sw_txs: 0,
+        time: START_TIME as u64 + height * 600,
+        total_out: sat(0),
+        total_size: weight / 4,
+        total_weight: weight,
+        total_fee: sat(fee_rate * weight as u64 / 4),
+        txs,
+        utxo_increase: 0,
+        utxo_size_inc: 0,
+    }
+}
+
+#[test]
+fn test_block_time_drift() {
+    insta::assert_snapshot!(headers()
+        .fetch_block_time_drift(
+            1200,
+            6,
+            false,
+            Network::Bitcoin,
+            MinDifficultyBlocks::Tag,
+            1.96,
+        )
+        .to_string());
+}
+
+#[test]
+fn test_block_time_drift_exponential_fit() {
+    insta::assert_snapshot!(exponential_headers()
+        .fetch_block_time_drift(
+            3600,
+            21,
+            false,
+            Network::Bitcoin,
+            MinDifficultyBlocks::Tag,
+            1.96,
+        )
+        .to_string());
+}
+
+#[test]
+fn test_block_time_drift_strata() {
+    let data = exponential_headers();
+    let strata = Strata::Ranges(vec![
+        HeightRange {
+            start: 100,
+            end: 111,
+        },
+        HeightRange {
+            start: 111,
+            end: 121,
+        },
+    ]);
+    let intervals = data.get_stratum_intervals(&strata, 21);
+    insta::assert_snapshot!(data
+        .fetch_block_time_drift(
+            3600,
+            21,
+            false,
+            Network::Bitcoin,
+            MinDifficultyBlocks::Tag,
+            1.96,
+        )
+        .with_strata(intervals)
+        .to_string());
+}
+
+#[test]
+fn test_records() {
+    let mut book = RecordBook::default();
+    insta::assert_snapshot!(headers().fetch_records(&mut book, 2, 6).to_string());
+}
+
+#[test]
+fn test_rolling_intervals() {
+    insta::assert_snapshot!(headers().fetch_rolling_intervals(3, 6).to_string());
+}
+
+#[test]
+fn test_hashrate() {
+    insta::assert_snapshot!(headers().fetch_hashrate(3, 6).to_string());
+}
+
+#[test]
+fn test_autocorrelation() {
+    insta::assert_snapshot!(exponential_headers()
+        .fetch_autocorrelation(5, 21, 1.96)
+        .to_string());
+}
+
+#[test]
+fn test_partial_report() {
+    let sample = BlockSample::new(1.96, 0.5, 0.05, false);
+    let partial = sample.get_partial_sample("Timed out", 6, 2000);
+    let report = headers().fetch_hashrate(3, 6);
+    insta::assert_snapshot!(PartialReport {
+        partial: &partial,
+        report: &report
+    }
+    .to_string());
+}
+
+#[test]
+fn test_negative_drifts() {
+    insta::assert_snapshot!(backward_headers()
+        .fetch_negative_drifts(9, 1.96)
+        .to_string());
+}
+
+#[test]
+fn test_block_time_drift_histogram() {
+    insta::assert_snapshot!(backward_headers()
+        .fetch_block_time_drift(
+            1200,
+            9,
+            false,
+            Network::Bitcoin,
+            MinDifficultyBlocks::Tag,
+            1.96,
+        )
+        .with_histogram(5)
+        .to_string());
+}
+
+#[test]
+fn test_out_of_order() {
+    // Windows of five blocks a year apart, the later with more children
+    // timestamped before their parent
+    let windows: [(u64, u32, [i64; 4]); 2] = [
+        (100, START_TIME, [600, -300, 900, 600]),
+        (52_660, START_TIME + 31_536_000, [-120, 1200, -60, -900]),
+    ];
+    let mut headers = vec![];
+
+    for (start, time, intervals) in windows {
+        let mut time = time as i64;
+        headers.push((BlockHeight(start), header(time as u32, 0)));
+
+        for (i, interval) in intervals.iter().enumerate() {
+            time += interval;
+            headers.push((
+                BlockHeight(start + i as u64 + 1),
+                header(time as u32, i as u32 + 1),
+            ));
+        }
+    }
+
+    insta::assert_snapshot!(BlockSampleData::new(headers)
+        .fetch_out_of_order(5, 1.96)
+        .to_string());
+}
+
+#[test]
+fn test_future_timestamps() {
+    // Blocks on target but for one a second after its median time past and
+    // one over two hours ahead
+    let headers = (0..20)
+        .map(|i| {
+            let time = match i {
+                15 => START_TIME + 9 * 600 + 1,
+                18 => START_TIME + 18 * 600 + 9000,
+                _ => START_TIME + i * 600,
+            };
+            (BlockHeight(1000 + i as u64), header(time, i))
+        })
+        .collect();
+
+    insta::assert_snapshot!(BlockSampleData::new(headers)
+        .fetch_future_timestamps(2, 600)
+        .to_string());
+}
+
+#[test]
+fn test_reorg_risk() {
+    insta::assert_snapshot!(headers().fetch_reorg_risk(0.005, 6, 6).to_string());
+}
+
+#[test]
+fn test_chain_tips() {
+    let tips = [
+        (840_000, 0, GetChainTipsResultStatus::Active),
+        (839_990, 1, GetChainTipsResultStatus::ValidFork),
+        (820_111, 2, GetChainTipsResultStatus::ValidHeaders),
+        (780_000, 1, GetChainTipsResultStatus::HeadersOnly),
+        (839_500, 4, GetChainTipsResultStatus::Invalid),
+    ]
+    .iter()
+    .map(|(height, branch_length, status)| GetChainTipsResultTip {
+        height: *height,
+        hash: genesis_block(Network::Regtest).block_hash(),
+        branch_length: *branch_length,
+        status: *status,
+    })
+    .collect::<Vec<_>>();
+
+    insta::assert_snapshot!(get_chain_tips_report(&tips, 2016).to_string());
+}
+
+#[test]
+fn test_monotonicity_streaks() {
+    insta::assert_snapshot!(backward_headers()
+        .fetch_monotonicity_streaks(4, 3, 9)
+        .to_string());
+}
+
+#[test]
+fn test_difficulty_epochs() {
+    insta::assert_snapshot!(epoch_headers().fetch_difficulty_epochs(3).to_string());
+}
+
+#[test]
+fn test_difficulty_trend() {
+    insta::assert_snapshot!(epoch_headers().fetch_difficulty_trend(1).to_string());
+}
+
+#[test]
+fn test_epoch_durations() {
+    insta::assert_snapshot!(boundary_headers().fetch_epoch_durations(1).to_string());
+}
+
+#[test]
+fn test_time_warp() {
+    // Three epochs with the context of the last two, the first block of
+    // the last epoch timestamped a second after its median time past far
+    // behind the block before it
+    let mut times = vec![(0, START_TIME), (2015, START_TIME + 1_209_000)];
+    times.extend(
+        (2005..2015).map(|height| (height, START_TIME + 1_203_000 + (height - 2005) * 600)),
+    );
+    let end = START_TIME + 2_419_200;
+    times.extend([(2016, START_TIME + 1_209_600), (4031, end)]);
+    times.extend((4021..4031).map(|height| (height, end - (4031 - height) * 1800)));
+    times.extend([(4032, end - 8999), (6047, end + 1_209_600)]);
+
+    let headers = times
+        .iter()
+        .map(|(height, time)| (BlockHeight(*height as u64), header(*time, *height)))
+        .collect();
+
+    insta::assert_snapshot!(BlockSampleData::new(headers)
+        .fetch_time_warp(7200)
+        .to_string());
+}
+
+#[test]
+fn test_cumulative_drift() {
+    insta::assert_snapshot!(checkpoint_headers()
+        .fetch_cumulative_drift(Network::Bitcoin)
+        .to_string());
+}
+
+#[test]
+fn test_halving_era_speed() {
+    insta::assert_snapshot!(era_headers().fetch_halving_era_speed(1.96).to_string());
+}
+
+#[test]
+fn test_signet_producers() {
+    insta::assert_snapshot!(blocks()
+        .fetch_signet_producers(&SignetChallenge::default(), 6)
+        .to_string());
+}
+
+#[test]
+fn test_size_interval_correlation() {
+    insta::assert_snapshot!(blocks().fetch_size_interval_correlation(6).to_string());
+}
+
+#[test]
+fn test_pool_luck() {
+    let expected = BTreeMap::from([(String::from("AntPool"), 0.25)]);
+    insta::assert_snapshot!(blocks()
+        .fetch_pool_luck(&get_default_pools(), &expected, 1.96, 6)
+        .to_string());
+}
+
+#[test]
+fn test_empty_blocks() {
+    // Pool and transactions besides the coinbase of contiguous blocks
+    let blocks: [(&[u8], usize); 6] = [
+        (b"Mined by AntPool", 0),
+        (b"/Foundry USA/", 2),
+        (b"Mined by AntPool", 0),
+        (b"/Foundry USA/", 0),
+        (b"/ViaBTC/", 1),
+        (b"Mined by AntPool", 3),
+    ];
+    let mut time = START_TIME;
+    let data = BlockSampleFullData::new(
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(i, (tag, transactions))| {
+                if i > 0 {
+                    time += INTERVALS[i - 1];
+                }
+                let mut block = block(time, tag);
+                let tx = block.txdata[0].clone();
+                block.txdata.extend(vec![tx; *transactions]);
+                (BlockHeight(100 + i as u64), block)
+            })
+            .collect(),
+    );
+
+    insta::assert_snapshot!(data
+        .fetch_empty_blocks(&get_default_pools(), 1.96)
+        .to_string());
+}
+
+#[test]
+fn test_pools() {
+    let mut pools = parse_pools("Long Tail,tag:long tail miner").unwrap();
+    pools.extend(get_default_pools());
+    insta::assert_snapshot!(blocks().fetch_pools(&pools, 1.96).to_string());
+}
+
+#[test]
+fn test_version_bits() {
+    // Versions of blocks over two epochs, signaling bit 1 and rolling bit 13
+    let versions: [u32; 8] = [
+        0x2000_0000,
+        0x2000_0002,
+        0x2000_2002,
+        0x0000_0004,
+        0x2000_0002,
+        0x2000_0002,
+        0x2000_2002,
+        0x2000_0002,
+    ];
+    let headers = versions
+        .iter()
+        .enumerate()
+        .map(|(i, version)| {
+            let mut header = header(START_TIME + i as u32 * 600, i as u32);
+            header.version = *version as i32;
+            (BlockHeight(2014 + i as u64), header)
+        })
+        .collect();
+
+    insta::assert_snapshot!(BlockSampleData::new(headers)
+        .fetch_version_bits(&[], 0.95, 1.96)
+        .to_string());
+}
+
+#[test]
+fn test_witness_version_flow() {
+    insta::assert_snapshot!(flow_blocks().fetch_witness_version_flow(2016).to_string());
+}
+
+#[test]
+fn test_taproot_adoption() {
+    insta::assert_snapshot!(taproot_blocks().fetch_taproot_adoption(709_632).to_string());
+}
+
+#[test]
+fn test_coinbase_revenue() {
+    // Value claimed by the coinbase in satoshis, the last block underclaiming
+    let blocks = [
+        (209_999, 5_010_000_000),
+        (210_000, 2_550_000_000),
+        (630_000, 745_000_000),
+        (630_001, 625_000_000),
+        (840_001, 300_000_000),
+    ];
+    let data = BlockSampleFullData::new(
+        blocks
+            .iter()
+            .map(|(height, value)| {
+                let mut block = block(START_TIME, b"");
+                block.txdata[0].output.push(TxOut {
+                    value: *value,
+                    script_pubkey: Script::new(),
+                });
+                (BlockHeight(*height), block)
+            })
+            .collect(),
+    );
+
+    insta::assert_snapshot!(data.fetch_coinbase_revenue().to_string());
+}
+
+#[test]
+fn test_custom() {
+    let expression = CustomExpression::compile("if interval == () { () } else { interval / 60.0 }");
+    let aggregates = ["count", "sum", "mean", "min", "max", "p90"]
+        .iter()
+        .map(|aggregate| aggregate.parse::<Aggregate>().unwrap())
+        .collect::<Vec<_>>();
+    insta::assert_snapshot!(blocks()
+        .fetch_custom(&expression.unwrap(), CustomScope::Block, &aggregates, 6)
+        .unwrap()
+        .to_string());
+}
+
+#[test]
+fn test_entity_clusters() {
+    insta::assert_snapshot!(cluster_blocks().fetch_entity_clusters(1_000).to_string());
+}
+
+#[test]
+fn test_fee_elasticity() {
+    insta::assert_snapshot!(stats().fetch_fee_elasticity().to_string());
+}
+
+#[test]
+fn test_fee_stats() {
+    insta::assert_snapshot!(stats().fetch_fee_stats(1.96).to_string());
+}
+
+#[test]
+fn test_rolling_fee_rates() {
+    insta::assert_snapshot!(stats().fetch_rolling_fee_rates(4).to_string());
+}
+
+#[test]
+fn test_block_size() {
+    insta::assert_snapshot!(stats().fetch_block_size(3, 1.96).to_string());
+}
+
+#[test]
+fn test_transactions() {
+    // Transactions and mining time of contiguous blocks
+    let stats = [
+        (1, 0),
+        (2_500, 14),
+        (1_200, 6),
+        (3_800, 25),
+        (400, 2),
+        (2_900, 11),
+    ];
+    let mut time = START_TIME as u64;
+    let data = BlockStatsData::new(
+        stats
+            .iter()
+            .enumerate()
+            .map(|(i, (txs, minutes))| {
+                let height = 100 + i as u64;
+                let mut stats = block_stats(height, *txs, 1_000_000, 10);
+                time += minutes * 60;
+                stats.time = time;
+                (BlockHeight(height), stats)
+            })
+            .collect(),
+    );
+
+    insta::assert_snapshot!(data.fetch_transactions(1.96).to_string());
+}
+
+#[test]
+fn test_revenue() {
+    let prices = PriceFeed::new(BTreeMap::from([
+        (START_TIME + 102 * 600, 20_000.0),
+        (START_TIME + 104 * 600, 25_000.0),
+    ]));
+    insta::assert_snapshot!(stats().fetch_revenue(3, Some(&prices)).to_string());
+}
+
+#[test]
+fn test_consolidations() {
+    insta::assert_snapshot!(consolidation_stats()
+        .fetch_consolidations(1, 1.0)
+        .to_string());
+}
+
+fn watch() -> TransactionWatch {
+    let mut watch = TransactionWatch::new(0, 3600);
+    let txs = (0..6u8)
+        .map(|n| Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Default::default(), n as u32 % 5),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: n as u64,
+                script_pubkey: Script::new(),
+            }],
+        })
+        .collect::<Vec<_>>();
+
+    for (i, tx) in txs.iter().enumerate() {
+        watch.observe(&Notification::Transaction(tx.clone()), i as u64 * 30);
+        watch.set_fee_rate(&tx.txid(), [1.5, 3.0, 12.0][i % 3]);
+    }
+
+    let mut block = genesis_block(Network::Regtest);
+    watch.observe(&Notification::Block(block.clone()), 300);
+    block.txdata.extend(txs[1..4].iter().cloned());
+    watch.observe(&Notification::Block(block), 600);
+
+    watch
+}
+
+#[test]
+fn test_watch() {
+    insta::assert_snapshot!(watch().report(900).to_string());
+}
+
+#[test]
+fn test_confirmation_latency() {
+    insta::assert_snapshot!(watch().report_latency().to_string());
+}
+
+#[test]
+fn test_report_schemas() {
+    insta::assert_snapshot!(serde_json::to_string_pretty(&get_report_schemas()).unwrap());
+}